@@ -1,19 +1,212 @@
 use git2::Repository;
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 
 /// Churn data: mapping relative path → commit count in the history window
 pub type ChurnData = HashMap<String, usize>;
 
-/// Analyze churn rate: count commits per file over a history window
-pub fn analyze_churn(workspace_path: &str, history_days: u32) -> Result<ChurnData, String> {
+/// One commit that touched a file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChurnCommit {
+    pub commit_short: String,
+    pub author: String,
+    pub time: i64,
+    pub summary: String,
+}
+
+/// Commits touching a single file in the history window, newest first,
+/// with the churn score they add up to
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileChurnDetail {
+    pub relative_path: String,
+    pub commits: Vec<ChurnCommit>,
+    pub churn_score: f64,
+}
+
+/// Analyze churn rate: count commits per file over a history window,
+/// walking back from `analysis_ref` (HEAD when `None`)
+pub fn analyze_churn(
+    workspace_path: &str,
+    history_days: u32,
+    analysis_ref: Option<&str>,
+) -> Result<ChurnData, String> {
     let repo = Repository::open(workspace_path)
         .map_err(|e| format!("Git error: {}", e))?;
 
     let mut churn: HashMap<String, usize> = HashMap::new();
+    walk_history_window(&repo, history_days, analysis_ref, |commit| {
+        count_commit_changes(&repo, commit, &mut churn);
+    })?;
+
+    Ok(churn)
+}
+
+/// List the commits within the history window that changed `relative_path`,
+/// walking back from `analysis_ref` (HEAD when `None`)
+pub fn analyze_file_churn_detail(
+    workspace_path: &str,
+    relative_path: &str,
+    history_days: u32,
+    analysis_ref: Option<&str>,
+) -> Result<FileChurnDetail, String> {
+    let repo = Repository::open(workspace_path)
+        .map_err(|e| format!("Git error: {}", e))?;
+    let relative_path = relative_path.replace('\\', "/");
+
+    let mut commits = Vec::new();
+    walk_history_window(&repo, history_days, analysis_ref, |commit| {
+        let mut touched = ChurnData::new();
+        count_commit_changes(&repo, commit, &mut touched);
+        if !touched.contains_key(&relative_path) {
+            return;
+        }
+        commits.push(ChurnCommit {
+            commit_short: commit.id().to_string().chars().take(7).collect(),
+            author: commit.author().name().unwrap_or("unknown").to_string(),
+            time: commit.time().seconds(),
+            summary: commit.summary().unwrap_or_default().to_string(),
+        });
+    })?;
+
+    let mut churn = ChurnData::new();
+    churn.insert(relative_path.clone(), commits.len());
+    let churn_score = compute_file_churn(&churn, &relative_path, history_days);
+
+    Ok(FileChurnDetail {
+        relative_path,
+        commits,
+        churn_score,
+    })
+}
+
+/// Commits touching a file in the older and recent halves of the history
+/// window; positive `acceleration` means the file is changing more often
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChurnAcceleration {
+    pub relative_path: String,
+    pub older_commits: usize,
+    pub recent_commits: usize,
+    /// `recent_commits - older_commits`
+    pub acceleration: i64,
+}
+
+/// Files whose commit count in the recent half of the `history_days` window
+/// exceeds the older half, fastest-accelerating first
+pub fn analyze_churn_acceleration(
+    workspace_path: &str,
+    history_days: u32,
+    analysis_ref: Option<&str>,
+) -> Result<Vec<ChurnAcceleration>, String> {
+    let repo = Repository::open(workspace_path)
+        .map_err(|e| format!("Git error: {}", e))?;
+    let midpoint = chrono::Utc::now().timestamp() - (history_days as i64 * 86400) / 2;
+
+    let mut older = ChurnData::new();
+    let mut recent = ChurnData::new();
+    walk_history_window(&repo, history_days, analysis_ref, |commit| {
+        let half = if commit.time().seconds() >= midpoint { &mut recent } else { &mut older };
+        count_commit_changes(&repo, commit, half);
+    })?;
+
+    let mut accelerating: Vec<ChurnAcceleration> = recent
+        .into_iter()
+        .map(|(relative_path, recent_commits)| {
+            let older_commits = older.get(&relative_path).copied().unwrap_or(0);
+            ChurnAcceleration {
+                acceleration: recent_commits as i64 - older_commits as i64,
+                relative_path,
+                older_commits,
+                recent_commits,
+            }
+        })
+        .filter(|file| file.acceleration > 0)
+        .collect();
+    accelerating.sort_by(|a, b| {
+        b.acceleration
+            .cmp(&a.acceleration)
+            .then_with(|| a.relative_path.cmp(&b.relative_path))
+    });
+    Ok(accelerating)
+}
+
+/// A file whose path changed within the history window
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileRelocation {
+    pub old_path: String,
+    pub new_path: String,
+    /// Commit of the most recent move
+    pub commit_id: String,
+    pub timestamp: i64,
+}
+
+/// Renames detected by git's similarity matching in commits within the
+/// `history_days` window, newest first. A file moved more than once is
+/// reported once, from its oldest path in the window to its latest.
+pub fn analyze_relocations(
+    workspace_path: &str,
+    history_days: u32,
+    analysis_ref: Option<&str>,
+) -> Result<Vec<FileRelocation>, String> {
+    let repo = Repository::open(workspace_path)
+        .map_err(|e| format!("Git error: {}", e))?;
+
+    let mut relocations: Vec<FileRelocation> = Vec::new();
+    walk_history_window(&repo, history_days, analysis_ref, |commit| {
+        let Ok(tree) = commit.tree() else {
+            return;
+        };
+        let parent_tree = commit.parent(0).ok().and_then(|p| p.tree().ok());
+        let Ok(mut diff) = repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), None) else {
+            return;
+        };
+        let mut find = git2::DiffFindOptions::new();
+        find.renames(true);
+        if diff.find_similar(Some(&mut find)).is_err() {
+            return;
+        }
+        for delta in diff.deltas() {
+            if delta.status() != git2::Delta::Renamed {
+                continue;
+            }
+            let (Some(old), Some(new)) = (delta.old_file().path(), delta.new_file().path()) else {
+                continue;
+            };
+            let old_path = old.to_string_lossy().replace('\\', "/");
+            let new_path = new.to_string_lossy().replace('\\', "/");
+            // Walking newest-first: an older move into a path a newer move
+            // starts from extends that relocation back to its origin.
+            if let Some(later) = relocations.iter_mut().find(|r| r.old_path == new_path) {
+                later.old_path = old_path;
+            } else {
+                relocations.push(FileRelocation {
+                    old_path,
+                    new_path,
+                    commit_id: commit.id().to_string(),
+                    timestamp: commit.time().seconds(),
+                });
+            }
+        }
+    })?;
+
+    relocations.retain(|r| r.old_path != r.new_path);
+    Ok(relocations)
+}
+
+/// Visit commits newest-first from `analysis_ref` (HEAD when `None`) until
+/// one falls outside the `history_days` window
+fn walk_history_window(
+    repo: &Repository,
+    history_days: u32,
+    analysis_ref: Option<&str>,
+    mut visit: impl FnMut(&git2::Commit<'_>),
+) -> Result<(), String> {
+    let start = crate::commands::git::resolve_analysis_ref(repo, analysis_ref)?;
 
     let mut revwalk = repo.revwalk()
         .map_err(|e| format!("Revwalk error: {}", e))?;
-    revwalk.push_head().ok();
+    if let Some(oid) = start {
+        revwalk.push(oid).ok();
+    }
     revwalk.set_sorting(git2::Sort::TIME).ok();
 
     let cutoff = chrono::Utc::now().timestamp() - (history_days as i64 * 86400);
@@ -28,39 +221,140 @@ pub fn analyze_churn(workspace_path: &str, history_days: u32) -> Result<ChurnDat
             break;
         }
 
-        let tree = match commit.tree() {
-            Ok(t) => t,
-            Err(_) => continue,
+        visit(&commit);
+    }
+
+    Ok(())
+}
+
+/// Timestamp of the newest commit that changed each of `paths`, found in one
+/// walk back from `analysis_ref` (HEAD when `None`) that stops once every
+/// path has been seen. Paths never committed are absent.
+pub fn last_change_times(
+    workspace_path: &str,
+    paths: &HashSet<String>,
+    analysis_ref: Option<&str>,
+) -> Result<HashMap<String, i64>, String> {
+    let repo = Repository::open(workspace_path)
+        .map_err(|e| format!("Git error: {}", e))?;
+    let mut found = HashMap::new();
+    let Some(start) = crate::commands::git::resolve_analysis_ref(&repo, analysis_ref)? else {
+        return Ok(found);
+    };
+
+    let mut revwalk = repo.revwalk()
+        .map_err(|e| format!("Revwalk error: {}", e))?;
+    revwalk.push(start).ok();
+    revwalk.set_sorting(git2::Sort::TIME).ok();
+
+    let blob_at = |tree: Option<&git2::Tree<'_>>, path: &str| {
+        tree.and_then(|tree| tree.get_path(std::path::Path::new(path)).ok())
+            .map(|entry| entry.id())
+    };
+    let mut pending: Vec<&String> = paths.iter().collect();
+    for oid in revwalk.flatten() {
+        if pending.is_empty() {
+            break;
+        }
+        let Ok(commit) = repo.find_commit(oid) else {
+            continue;
         };
+        let tree = commit.tree().ok();
+        let parent_tree = commit.parent(0).ok().and_then(|parent| parent.tree().ok());
+        pending.retain(|path| {
+            let blob = blob_at(tree.as_ref(), path);
+            if blob.is_some() && blob != blob_at(parent_tree.as_ref(), path) {
+                found.insert((*path).clone(), commit.time().seconds());
+                return false;
+            }
+            true
+        });
+    }
+
+    Ok(found)
+}
 
-        // Get parent tree for diff
-        let parent_tree = commit.parent(0)
-            .ok()
-            .and_then(|p| p.tree().ok());
-
-        let diff = repo.diff_tree_to_tree(
-            parent_tree.as_ref(),
-            Some(&tree),
-            None,
-        );
-
-        if let Ok(diff) = diff {
-            diff.foreach(
-                &mut |delta, _| {
-                    if let Some(path) = delta.new_file().path() {
-                        let path_str = path.to_string_lossy().to_string();
-                        *churn.entry(path_str).or_insert(0) += 1;
-                    }
-                    true
-                },
-                None, None, None,
-            ).ok();
+/// Count commits per file in the range `from_ref..to_ref`: commits reachable
+/// from `to_ref` but not from `from_ref`, regardless of commit dates
+pub fn analyze_churn_range(workspace_path: &str, from_ref: &str, to_ref: &str) -> Result<ChurnData, String> {
+    let repo = Repository::open(workspace_path)
+        .map_err(|e| format!("Git error: {}", e))?;
+    let resolve = |spec: &str| {
+        crate::commands::git::resolve_analysis_ref(&repo, Some(spec))?
+            .ok_or_else(|| format!("INVALID_REF: {spec} must not be empty"))
+    };
+    let from = resolve(from_ref)?;
+    let to = resolve(to_ref)?;
+
+    let mut revwalk = repo.revwalk()
+        .map_err(|e| format!("Revwalk error: {}", e))?;
+    revwalk.push(to).map_err(|e| format!("Revwalk error: {}", e))?;
+    revwalk.hide(from).map_err(|e| format!("Revwalk error: {}", e))?;
+
+    let mut churn: HashMap<String, usize> = HashMap::new();
+    for oid in revwalk.flatten() {
+        if let Ok(commit) = repo.find_commit(oid) {
+            count_commit_changes(&repo, &commit, &mut churn);
         }
     }
 
     Ok(churn)
 }
 
+/// Bump the count of every file a commit changed relative to its first parent
+fn count_commit_changes(repo: &Repository, commit: &git2::Commit<'_>, churn: &mut ChurnData) {
+    let tree = match commit.tree() {
+        Ok(t) => t,
+        Err(_) => return,
+    };
+
+    // Get parent tree for diff
+    let parent_tree = commit.parent(0)
+        .ok()
+        .and_then(|p| p.tree().ok());
+
+    let diff = repo.diff_tree_to_tree(
+        parent_tree.as_ref(),
+        Some(&tree),
+        None,
+    );
+
+    if let Ok(diff) = diff {
+        diff.foreach(
+            &mut |delta, _| {
+                if let Some(path) = delta.new_file().path() {
+                    let path_str = path.to_string_lossy().to_string();
+                    *churn.entry(path_str).or_insert(0) += 1;
+                }
+                true
+            },
+            None, None, None,
+        ).ok();
+    }
+}
+
+/// Count commits reachable from `analysis_ref` (HEAD when `None`) made
+/// within the last `days` days
+pub fn count_recent_commits(workspace_path: &str, days: u32, analysis_ref: Option<&str>) -> Result<usize, String> {
+    let repo = Repository::open(workspace_path)
+        .map_err(|e| format!("Git error: {}", e))?;
+    let Some(start) = crate::commands::git::resolve_analysis_ref(&repo, analysis_ref)? else {
+        return Ok(0);
+    };
+
+    let mut revwalk = repo.revwalk()
+        .map_err(|e| format!("Revwalk error: {}", e))?;
+    revwalk.push(start).ok();
+    revwalk.set_sorting(git2::Sort::TIME).ok();
+
+    let cutoff = chrono::Utc::now().timestamp() - (days as i64 * 86400);
+    Ok(revwalk
+        .flatten()
+        .filter_map(|oid| repo.find_commit(oid).ok())
+        .take_while(|commit| commit.time().seconds() >= cutoff)
+        .count())
+}
+
 /// Compute churn score for a single file (0–100)
 pub fn compute_file_churn(churn_data: &ChurnData, relative_path: &str, history_days: u32) -> f64 {
     let count = *churn_data.get(relative_path).unwrap_or(&0) as f64;
@@ -1,11 +1,14 @@
 use crate::commands::ast::FileComplexity;
 use crate::commands::ast::FunctionComplexity;
+use crate::commands::ast::HEURISTIC_CONFIDENCE;
+use serde::{Deserialize, Serialize};
 
 /// Analyze cyclomatic complexity using line-based heuristics
 /// Full implementation would use tree-sitter AST node counting
 pub fn analyze_complexity(source: &str, language: &str) -> FileComplexity {
     let mut functions: Vec<FunctionComplexity> = Vec::new();
     let mut current_func_name = String::new();
+    let mut current_start_line = 0usize;
     let mut current_complexity = 1usize; // Base complexity
     let mut in_function = false;
     let mut brace_depth = 0i32;
@@ -17,12 +20,13 @@ pub fn analyze_complexity(source: &str, language: &str) -> FileComplexity {
         _ => vec!["if ", "else if ", "for ", "while ", "switch ", "case ", "catch ", "|| ", "&& ", "? "],
     };
 
-    for line in source.lines() {
+    for (index, line) in source.lines().enumerate() {
         let trimmed = line.trim();
 
         // Detect function start
         if is_function_declaration(trimmed, language) && !in_function {
             current_func_name = extract_function_name(trimmed, language);
+            current_start_line = index + 1;
             current_complexity = 1;
             in_function = true;
             func_start_depth = brace_depth;
@@ -53,6 +57,7 @@ pub fn analyze_complexity(source: &str, language: &str) -> FileComplexity {
                 functions.push(FunctionComplexity {
                     name: current_func_name.clone(),
                     complexity: current_complexity,
+                    start_line: current_start_line,
                 });
                 in_function = false;
             }
@@ -64,6 +69,7 @@ pub fn analyze_complexity(source: &str, language: &str) -> FileComplexity {
         functions.push(FunctionComplexity {
             name: current_func_name,
             complexity: current_complexity,
+            start_line: current_start_line,
         });
     }
 
@@ -73,7 +79,156 @@ pub fn analyze_complexity(source: &str, language: &str) -> FileComplexity {
         functions.iter().map(|f| f.complexity as f64).sum::<f64>() / functions.len() as f64
     };
 
-    FileComplexity { functions, average }
+    FileComplexity {
+        functions,
+        average,
+        confidence: HEURISTIC_CONFIDENCE.to_string(),
+    }
+}
+
+/// Inclusive lower bounds of the function complexity buckets; the last is open
+pub const COMPLEXITY_BUCKETS: [usize; 4] = [1, 6, 11, 21];
+
+/// Functions whose complexity falls in `min..=max` (`max` is `None` for the
+/// open-ended top bucket)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComplexityBucket {
+    pub label: String,
+    pub min: usize,
+    pub max: Option<usize>,
+    pub count: usize,
+}
+
+/// The most complex function in the workspace
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MostComplexFunction {
+    pub relative_path: String,
+    pub name: String,
+    pub complexity: usize,
+    pub start_line: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComplexityDistribution {
+    pub total_functions: usize,
+    pub buckets: Vec<ComplexityBucket>,
+    pub max_function: Option<MostComplexFunction>,
+}
+
+/// Bucket every function of every `(relative_path, functions)` file. Ties for
+/// the maximum go to the first file and function seen.
+pub fn complexity_distribution<'a>(
+    files: impl IntoIterator<Item = (&'a str, &'a [FunctionComplexity])>,
+) -> ComplexityDistribution {
+    let mut buckets: Vec<ComplexityBucket> = COMPLEXITY_BUCKETS
+        .iter()
+        .enumerate()
+        .map(|(index, &min)| {
+            let max = COMPLEXITY_BUCKETS.get(index + 1).map(|next| next - 1);
+            ComplexityBucket {
+                label: match max {
+                    Some(max) => format!("{min}-{max}"),
+                    None => format!("{min}+"),
+                },
+                min,
+                max,
+                count: 0,
+            }
+        })
+        .collect();
+
+    let mut total_functions = 0;
+    let mut max_function: Option<MostComplexFunction> = None;
+    for (relative_path, functions) in files {
+        for function in functions {
+            total_functions += 1;
+            if let Some(bucket) = buckets.iter_mut().rev().find(|bucket| function.complexity >= bucket.min) {
+                bucket.count += 1;
+            } else {
+                buckets[0].count += 1;
+            }
+            if max_function.as_ref().is_none_or(|max| function.complexity > max.complexity) {
+                max_function = Some(MostComplexFunction {
+                    relative_path: relative_path.to_string(),
+                    name: function.name.clone(),
+                    complexity: function.complexity,
+                    start_line: function.start_line,
+                });
+            }
+        }
+    }
+
+    ComplexityDistribution {
+        total_functions,
+        buckets,
+        max_function,
+    }
+}
+
+/// Maximum points a long file can add on top of its function complexity
+const FILE_LENGTH_PENALTY_CAP: f64 = 30.0;
+
+/// Bounded penalty for files longer than `threshold` LOC (0 disables it).
+/// Every `threshold` lines past the limit adds 10 points, capped at 30.
+pub fn file_length_penalty(loc: usize, threshold: usize) -> f64 {
+    if threshold == 0 || loc <= threshold {
+        return 0.0;
+    }
+    let excess = (loc - threshold) as f64 / threshold as f64;
+    (excess * 10.0).min(FILE_LENGTH_PENALTY_CAP)
+}
+
+/// Cyclomatic component score (0–100): average function complexity normalized
+/// so that 20 = 100, plus the file length penalty.
+pub fn size_adjusted_complexity(average: f64, loc: usize, threshold: usize) -> f64 {
+    let base = (average / 20.0 * 100.0).min(100.0);
+    (base + file_length_penalty(loc, threshold)).min(100.0)
+}
+
+/// A run of consecutive functions that could move to its own file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SplitSuggestion {
+    pub start_line: usize,
+    pub end_line: usize,
+    pub functions: Vec<String>,
+    pub total_complexity: usize,
+}
+
+/// For a file longer than `threshold` lines, group its functions in source
+/// order into consecutive runs of at most `threshold` lines each (a single
+/// longer function stays whole). Each function spans from its declaration to
+/// the next one; leading lines before the first function stay with the
+/// first group. Returns nothing for short files or when one group would do.
+pub fn suggest_splits(functions: &[FunctionComplexity], loc: usize, threshold: usize) -> Vec<SplitSuggestion> {
+    if threshold == 0 || loc <= threshold || functions.len() < 2 {
+        return Vec::new();
+    }
+
+    let mut groups: Vec<SplitSuggestion> = Vec::new();
+    for (index, function) in functions.iter().enumerate() {
+        let end_line = functions
+            .get(index + 1)
+            .map_or(loc, |next| next.start_line.saturating_sub(1))
+            .max(function.start_line);
+        match groups.last_mut() {
+            Some(group) if end_line - group.start_line < threshold => {
+                group.end_line = end_line;
+                group.functions.push(function.name.clone());
+                group.total_complexity += function.complexity;
+            }
+            _ => groups.push(SplitSuggestion {
+                start_line: if groups.is_empty() { 1 } else { function.start_line },
+                end_line,
+                functions: vec![function.name.clone()],
+                total_complexity: function.complexity,
+            }),
+        }
+    }
+
+    if groups.len() < 2 {
+        return Vec::new();
+    }
+    groups
 }
 
 fn is_function_declaration(line: &str, language: &str) -> bool {
@@ -172,5 +327,36 @@ mod tests {
         assert_eq!(result.functions.len(), 1);
         assert!(result.functions[0].complexity >= 2);
     }
-}
 
+    #[test]
+    fn long_file_scores_higher_than_short_file_with_same_average() {
+        let short = size_adjusted_complexity(4.0, 200, 500);
+        let long = size_adjusted_complexity(4.0, 3000, 500);
+        assert_eq!(short, 20.0);
+        assert!(long > short, "Expected long file to score higher: {long} vs {short}");
+        assert!(long <= short + FILE_LENGTH_PENALTY_CAP);
+    }
+
+    #[test]
+    fn long_file_gets_split_suggestions_at_function_boundaries() {
+        let source: String = (0..6)
+            .map(|i| format!("fn f{i}() {{\n{}}}\n", "    if x {}\n".repeat(38)))
+            .collect();
+        let loc = source.lines().count();
+        let functions = analyze_complexity(&source, "rust").functions;
+        assert_eq!(functions[1].start_line, 41);
+
+        let splits = suggest_splits(&functions, loc, 100);
+
+        assert_eq!(splits.len(), 3);
+        assert_eq!(splits[0].functions, vec!["f0".to_string(), "f1".to_string()]);
+        assert_eq!((splits[0].start_line, splits[0].end_line), (1, 80));
+        assert_eq!((splits[2].start_line, splits[2].end_line), (161, 240));
+        assert!(suggest_splits(&functions, loc, 500).is_empty());
+    }
+
+    #[test]
+    fn zero_threshold_disables_length_penalty() {
+        assert_eq!(file_length_penalty(10_000, 0), 0.0);
+    }
+}
@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use git2::Repository;
 
 /// Extended co-change analysis result
@@ -13,18 +13,30 @@ pub struct CoChangeResult {
 /// Legacy alias for backwards compatibility
 pub type CoChangeData = Vec<(String, String, usize)>;
 
+/// Default for `maxFilesPerCommitForCoupling`
+pub const DEFAULT_MAX_FILES_PER_COMMIT: usize = 50;
+
 /// Analyze co-changes: find file pairs that changed together in commits
 /// Also tracks per-file change counts needed for proper coupling ratio.
-pub fn analyze_co_changes(workspace_path: &str, history_days: u32) -> Result<CoChangeResult, String> {
+/// History is walked from `analysis_ref` (HEAD when `None`).
+pub fn analyze_co_changes(
+    workspace_path: &str,
+    history_days: u32,
+    analysis_ref: Option<&str>,
+    max_files_per_commit: usize,
+) -> Result<CoChangeResult, String> {
     let repo = Repository::open(workspace_path)
         .map_err(|e| format!("Git error: {}", e))?;
 
-    let mut pair_counts: HashMap<(String, String), usize> = HashMap::new();
-    let mut file_change_counts: HashMap<String, usize> = HashMap::new();
+    let mut commits: Vec<Vec<String>> = Vec::new();
+
+    let start = crate::commands::git::resolve_analysis_ref(&repo, analysis_ref)?;
 
     let mut revwalk = repo.revwalk()
         .map_err(|e| format!("Revwalk error: {}", e))?;
-    revwalk.push_head().ok();
+    if let Some(oid) = start {
+        revwalk.push(oid).ok();
+    }
     revwalk.set_sorting(git2::Sort::TIME).ok();
 
     let cutoff = chrono::Utc::now().timestamp() - (history_days as i64 * 86400);
@@ -63,32 +75,203 @@ pub fn analyze_co_changes(workspace_path: &str, history_days: u32) -> Result<CoC
                 None, None, None,
             ).ok();
 
-            // Track per-file change counts
-            for file in &changed_files {
-                *file_change_counts.entry(file.clone()).or_insert(0) += 1;
+            commits.push(changed_files);
+        }
+    }
+
+    Ok(accumulate_co_changes(&commits, max_files_per_commit))
+}
+
+/// A file's place in the co-change graph: how many distinct files it changes
+/// with and how many co-changes those links add up to
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct CouplingCentrality {
+    pub relative_path: String,
+    pub degree: usize,
+    pub total_weight: usize,
+}
+
+/// Degree centrality over co-change pairs seen at least `min_co_changes`
+/// times, most connected first (ties broken by total weight, then path)
+pub fn coupling_centrality(co_changes: &CoChangeResult, min_co_changes: usize) -> Vec<CouplingCentrality> {
+    let mut nodes: HashMap<&str, (usize, usize)> = HashMap::new();
+    for (a, b, count) in &co_changes.pairs {
+        if *count < min_co_changes {
+            continue;
+        }
+        for file in [a, b] {
+            let node = nodes.entry(file.as_str()).or_insert((0, 0));
+            node.0 += 1;
+            node.1 += count;
+        }
+    }
+
+    let mut ranked: Vec<CouplingCentrality> = nodes
+        .into_iter()
+        .map(|(path, (degree, total_weight))| CouplingCentrality {
+            relative_path: path.to_string(),
+            degree,
+            total_weight,
+        })
+        .collect();
+    ranked.sort_by(|a, b| {
+        b.degree
+            .cmp(&a.degree)
+            .then_with(|| b.total_weight.cmp(&a.total_weight))
+            .then_with(|| a.relative_path.cmp(&b.relative_path))
+    });
+    ranked
+}
+
+/// Coupling ratio at which a co-change peer counts toward a blast radius
+pub const STRONG_COUPLING_RATIO: f64 = 0.3;
+
+/// A file likely to need changes alongside the blast radius target
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct BlastRadiusFile {
+    pub relative_path: String,
+    /// Imports the target directly
+    pub is_importer: bool,
+    pub co_change_count: usize,
+    /// co_changes / min(changes of either file), 0–1
+    pub coupling_ratio: f64,
+    /// 0–100: an import link alone is 50, a co-change ratio of 1 alone is
+    /// 100, combined as independent chances
+    pub risk: f64,
+}
+
+/// Files affected by changing `relative_path`
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct BlastRadius {
+    pub relative_path: String,
+    pub files: Vec<BlastRadiusFile>,
+    /// Sum of the files' risks as fractions: the expected number of other
+    /// files a change will touch
+    pub risk_score: f64,
+}
+
+/// Direct importers of `target` plus co-change peers seen at least twice with
+/// a ratio of `STRONG_COUPLING_RATIO` or more, riskiest first
+pub fn blast_radius(target: &str, importers: &BTreeSet<String>, co_changes: &CoChangeResult) -> BlastRadius {
+    let mut files: BTreeMap<String, BlastRadiusFile> = BTreeMap::new();
+    let entry = |files: &mut BTreeMap<String, BlastRadiusFile>, path: &str| {
+        files.entry(path.to_string()).or_insert_with(|| BlastRadiusFile {
+            relative_path: path.to_string(),
+            is_importer: false,
+            co_change_count: 0,
+            coupling_ratio: 0.0,
+            risk: 0.0,
+        });
+    };
+
+    for importer in importers.iter().filter(|path| path.as_str() != target) {
+        entry(&mut files, importer);
+        if let Some(file) = files.get_mut(importer) {
+            file.is_importer = true;
+        }
+    }
+
+    let target_key = normalize_path_key(target);
+    for (a, b, count) in &co_changes.pairs {
+        let peer = if normalize_path_key(a) == target_key {
+            b
+        } else if normalize_path_key(b) == target_key {
+            a
+        } else {
+            continue;
+        };
+        let changes = |path: &String| co_changes.file_change_counts.get(path).copied().unwrap_or(1);
+        let ratio = (*count as f64 / changes(a).min(changes(b)).max(1) as f64).min(1.0);
+        if *count < 2 || ratio < STRONG_COUPLING_RATIO {
+            continue;
+        }
+        entry(&mut files, peer);
+        if let Some(file) = files.get_mut(peer.as_str()) {
+            file.co_change_count = *count;
+            file.coupling_ratio = ratio;
+        }
+    }
+
+    let mut files: Vec<BlastRadiusFile> = files
+        .into_values()
+        .map(|mut file| {
+            let import_chance = if file.is_importer { 0.5 } else { 0.0 };
+            file.risk = (1.0 - (1.0 - import_chance) * (1.0 - file.coupling_ratio)) * 100.0;
+            file
+        })
+        .collect();
+    files.sort_by(|a, b| {
+        b.risk
+            .partial_cmp(&a.risk)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| a.relative_path.cmp(&b.relative_path))
+    });
+
+    BlastRadius {
+        relative_path: target.to_string(),
+        risk_score: files.iter().map(|file| file.risk / 100.0).sum(),
+        files,
+    }
+}
+
+/// Case- and separator-insensitive key for a workspace-relative path, so that
+/// `Src\Foo.rs` and `src/foo.rs` are treated as the same file.
+pub fn normalize_path_key(path: &str) -> String {
+    path.replace('\\', "/").to_lowercase()
+}
+
+/// Build pair and per-file change counts from the changed-file lists of each commit.
+/// Paths are merged by `normalize_path_key`; the first spelling seen is reported.
+/// Commits touching more than `max_files_per_commit` files (mass reformats,
+/// renames) still count as changes but generate no pairs.
+pub fn accumulate_co_changes(commits: &[Vec<String>], max_files_per_commit: usize) -> CoChangeResult {
+    let mut display: HashMap<String, String> = HashMap::new();
+    let mut pair_counts: HashMap<(String, String), usize> = HashMap::new();
+    let mut change_counts: HashMap<String, usize> = HashMap::new();
+
+    for changed_files in commits {
+        let mut keys: Vec<String> = Vec::with_capacity(changed_files.len());
+        for file in changed_files {
+            let key = normalize_path_key(file);
+            display.entry(key.clone()).or_insert_with(|| file.clone());
+            if !keys.contains(&key) {
+                keys.push(key);
             }
+        }
 
-            // Record all pairs from this commit
-            for i in 0..changed_files.len() {
-                for j in (i + 1)..changed_files.len() {
-                    let a = &changed_files[i];
-                    let b = &changed_files[j];
-                    let key = if a < b {
-                        (a.clone(), b.clone())
-                    } else {
-                        (b.clone(), a.clone())
-                    };
-                    *pair_counts.entry(key).or_insert(0) += 1;
-                }
+        // Track per-file change counts
+        for key in &keys {
+            *change_counts.entry(key.clone()).or_insert(0) += 1;
+        }
+
+        if keys.len() > max_files_per_commit {
+            continue;
+        }
+
+        // Record all pairs from this commit
+        for i in 0..keys.len() {
+            for j in (i + 1)..keys.len() {
+                let a = &keys[i];
+                let b = &keys[j];
+                let key = if a < b {
+                    (a.clone(), b.clone())
+                } else {
+                    (b.clone(), a.clone())
+                };
+                *pair_counts.entry(key).or_insert(0) += 1;
             }
         }
     }
 
     let pairs: Vec<_> = pair_counts.into_iter()
-        .map(|((a, b), count)| (a, b, count))
+        .map(|((a, b), count)| (display[&a].clone(), display[&b].clone(), count))
+        .collect();
+    let file_change_counts = change_counts
+        .into_iter()
+        .map(|(key, count)| (display[&key].clone(), count))
         .collect();
 
-    Ok(CoChangeResult { pairs, file_change_counts })
+    CoChangeResult { pairs, file_change_counts }
 }
 
 /// Compute change coupling score for a single file (0–100)
@@ -96,9 +279,10 @@ pub fn analyze_co_changes(workspace_path: &str, history_days: u32) -> Result<CoC
 /// then averages the top-5 peer ratios.
 pub fn compute_change_coupling(relative_path: &str, co_change_result: &CoChangeResult) -> f64 {
     let mut ratios: Vec<f64> = Vec::new();
+    let target = normalize_path_key(relative_path);
 
     for (a, b, co_count) in &co_change_result.pairs {
-        if a == relative_path || b == relative_path {
+        if normalize_path_key(a) == target || normalize_path_key(b) == target {
             let changes_a = co_change_result.file_change_counts.get(a).copied().unwrap_or(1);
             let changes_b = co_change_result.file_change_counts.get(b).copied().unwrap_or(1);
             let min_changes = changes_a.min(changes_b).max(1) as f64;
@@ -119,82 +303,249 @@ pub fn compute_change_coupling(relative_path: &str, co_change_result: &CoChangeR
     (avg * 100.0).min(100.0)
 }
 
+/// In- plus out-degree of every file in the internal import graph, built
+/// once per analysis from `import_edges`. Self-imports don't count.
+#[derive(Debug, Clone, Default)]
+pub struct ImportDegrees {
+    /// Keyed by `normalize_path_key`
+    degrees: HashMap<String, usize>,
+    max_degree: usize,
+}
+
+impl ImportDegrees {
+    pub fn from_edges(edges: &BTreeMap<String, BTreeSet<String>>) -> Self {
+        let mut degrees: HashMap<String, usize> = HashMap::new();
+        for (from, targets) in edges {
+            let from_key = normalize_path_key(from);
+            for target_key in targets.iter().map(|target| normalize_path_key(target)) {
+                if target_key != from_key {
+                    *degrees.entry(from_key.clone()).or_insert(0) += 1;
+                    *degrees.entry(target_key).or_insert(0) += 1;
+                }
+            }
+        }
+        let max_degree = degrees.values().copied().max().unwrap_or(0);
+        Self { degrees, max_degree }
+    }
+}
+
 /// Compute coupling index based on import relationships (0–100)
 /// Formula: (in_degree + out_degree) / (2 * max_degree) * 100
 /// where max_degree is the highest (in + out) across all files.
-pub fn compute_coupling_index(
-    relative_path: &str,
-    workspace_path: &str,
-) -> f64 {
-    // Build workspace-wide import graph
+pub fn compute_coupling_index(relative_path: &str, degrees: &ImportDegrees) -> f64 {
+    if degrees.max_degree == 0 {
+        return 0.0;
+    }
+    let degree = degrees.degrees.get(&normalize_path_key(relative_path)).copied().unwrap_or(0);
+    (degree as f64 / (2.0 * degrees.max_degree as f64) * 100.0).min(100.0)
+}
+
+/// Internal import edges between workspace files, keyed by workspace-relative
+/// path with forward slashes. Imports are resolved by path (see
+/// `resolve_import_path`), so unresolvable imports are dropped.
+pub fn internal_import_edges(workspace_path: &str) -> BTreeMap<String, BTreeSet<String>> {
     let files = crate::commands::git::walkdir(workspace_path);
-    let mut out_degree: HashMap<String, usize> = HashMap::new();
-    let mut in_degree: HashMap<String, usize> = HashMap::new();
+    let local_roots = workspace_import_roots(workspace_path, &files);
+    import_edges(workspace_path, &files, &local_roots)
+}
 
-    for file_path in &files {
-        let source = match std::fs::read_to_string(file_path) {
-            Ok(s) => s,
-            Err(_) => continue,
+/// `internal_import_edges` over an already walked file list. A file that
+/// names itself (`import './self'`) keeps its self-edge; an import that only
+/// reaches the file after dropping item segments (`use self::Type`) does not.
+pub fn import_edges(
+    workspace_path: &str,
+    files: &[String],
+    local_roots: &HashSet<String>,
+) -> BTreeMap<String, BTreeSet<String>> {
+    let relative: Vec<String> = files
+        .iter()
+        .map(|file| workspace_relative(workspace_path, file))
+        .collect();
+    let keys: Vec<String> = relative.iter().map(|rel| normalize_path_key(rel)).collect();
+    let modules = module_paths(&keys);
+    let mut edges: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+
+    for (index, file_path) in files.iter().enumerate() {
+        let Ok(source) = crate::commands::git::read_workspace_file(workspace_path, file_path) else {
+            continue;
         };
         let lang = detect_language_for_coupling(file_path);
-        let imports = extract_imports(&source, &lang);
-        let rel = file_path
-            .strip_prefix(workspace_path)
-            .unwrap_or(file_path)
-            .trim_start_matches('/')
-            .to_string();
+        let (imports, _) = split_imports(&extract_imports(&source, &lang), &lang, local_roots);
+        let targets = edges.entry(relative[index].clone()).or_default();
+        for import_path in &imports {
+            match resolve_import_path(import_path, &lang, &keys[index], &modules) {
+                Some((target, exact)) if target != index || exact => {
+                    targets.insert(relative[target].clone());
+                }
+                _ => {}
+            }
+        }
+    }
 
-        out_degree.insert(rel.clone(), imports.len());
+    edges
+}
 
-        // For each import, try to resolve to a workspace file and bump in_degree
-        for import_path in &imports {
-            let basename = std::path::Path::new(import_path)
+/// Module paths each file can be imported as, mapped to its index in `keys`:
+/// the key without its extension, plus the directory for files that stand
+/// for it (`index`, `__init__`, Rust `mod`/`lib`/`main`, and any Go file,
+/// since a Go package is a directory). The first file claiming a path wins.
+fn module_paths(keys: &[String]) -> HashMap<String, usize> {
+    let mut modules = HashMap::new();
+    for (index, key) in keys.iter().enumerate() {
+        let path = std::path::Path::new(key);
+        let stem = path.file_stem().map(|s| s.to_string_lossy()).unwrap_or_default();
+        let dir = parent_dir(key);
+        let module = if dir.is_empty() { stem.to_string() } else { format!("{dir}/{stem}") };
+        modules.entry(module).or_insert(index);
+
+        let stands_for_dir = match detect_language_for_coupling(key).as_str() {
+            "rust" => matches!(stem.as_ref(), "mod" | "lib" | "main"),
+            "go" => true,
+            _ => matches!(stem.as_ref(), "index" | "__init__"),
+        };
+        if stands_for_dir {
+            modules.entry(dir.to_string()).or_insert(index);
+        }
+    }
+    modules
+}
+
+/// Resolve an internal import to the workspace file it names, by path:
+/// relative imports from the importer's directory, Rust `crate::`/`self::`/
+/// `super::` from the crate root or module, and bare module paths from the
+/// importer's ancestor directories. Trailing segments that name items rather
+/// than modules (`crate::a::b::Type`, `pkg.mod.func`) are dropped until a
+/// module matches. `from_key` and `modules` are `normalize_path_key`s.
+/// Also says whether the whole import named the module (`true`) or trailing
+/// item segments had to be dropped (`false`).
+fn resolve_import_path(
+    import: &str,
+    language: &str,
+    from_key: &str,
+    modules: &HashMap<String, usize>,
+) -> Option<(usize, bool)> {
+    let import = import.trim().to_lowercase();
+    let from_dir = parent_dir(from_key);
+    let lookup = |base: &str, segments: &[&str], shrink: bool| {
+        let shortest = if shrink { segments.len().min(1) } else { segments.len() };
+        (shortest..=segments.len()).rev().find_map(|len| {
+            let path = join_module_path(base, &segments[..len])?;
+            modules.get(&path).map(|index| (*index, len == segments.len()))
+        })
+    };
+
+    match language {
+        "rust" => {
+            let segments: Vec<&str> = import
+                .split("::")
+                .filter_map(|segment| segment.split_whitespace().next())
+                .filter(|segment| *segment != "*")
+                .collect();
+            let parts: Vec<&str> = from_key.split('/').collect();
+            let crate_root = parts
+                .iter()
+                .rposition(|part| *part == "src")
+                .map(|pos| parts[..=pos].join("/"))
+                .unwrap_or_default();
+            let stem = std::path::Path::new(from_key)
                 .file_stem()
                 .map(|s| s.to_string_lossy().to_string())
                 .unwrap_or_default();
-            // Match any workspace file whose stem matches the import
-            for other_file in &files {
-                let other_rel = other_file
-                    .strip_prefix(workspace_path)
-                    .unwrap_or(other_file)
-                    .trim_start_matches('/');
-                let other_stem = std::path::Path::new(other_rel)
-                    .file_stem()
-                    .map(|s| s.to_string_lossy().to_string())
-                    .unwrap_or_default();
-                if other_stem == basename && other_rel != rel {
-                    *in_degree.entry(other_rel.to_string()).or_insert(0) += 1;
-                    break;
+            let own_module = if matches!(stem.as_str(), "mod" | "lib" | "main") {
+                from_dir.to_string()
+            } else {
+                join_module_path(from_dir, &[stem.as_str()])?
+            };
+            match segments.first().copied() {
+                Some("crate") => lookup(&crate_root, &segments[1..], true),
+                Some("self") => lookup(&own_module, &segments[1..], true),
+                Some("super") => {
+                    let supers = segments.iter().take_while(|segment| **segment == "super").count();
+                    let parents = vec![".."; supers];
+                    let base = join_module_path(&own_module, &parents)?;
+                    lookup(&base, &segments[supers..], true)
                 }
+                _ => lookup(&own_module, &segments, true).or_else(|| lookup(&crate_root, &segments, true)),
+            }
+        }
+        "python" => {
+            let dots = import.chars().take_while(|c| *c == '.').count();
+            let segments: Vec<&str> = import[dots..].split('.').filter(|s| !s.is_empty()).collect();
+            if dots > 0 {
+                let parents = vec![".."; dots - 1];
+                let base = join_module_path(from_dir, &parents)?;
+                return lookup(&base, &segments, true);
+            }
+            ancestor_dirs(from_dir).find_map(|base| lookup(base, &segments, true))
+        }
+        "go" => {
+            // The module path prefix isn't part of the directory layout, so
+            // try ever shorter suffixes from the workspace root.
+            let segments: Vec<&str> = import.split('/').filter(|s| !s.is_empty()).collect();
+            (0..segments.len()).find_map(|start| modules.get(&segments[start..].join("/")).map(|index| (*index, true)))
+        }
+        _ => {
+            let without_ext = strip_source_extension(&import);
+            if without_ext.starts_with('.') {
+                let segments: Vec<&str> = without_ext.split('/').collect();
+                lookup(from_dir, &segments, false)
+            } else if let Some(rooted) = without_ext.strip_prefix('/') {
+                lookup("", &rooted.split('/').collect::<Vec<_>>(), false)
+            } else {
+                // `@/` and `~/` aliases point at a source root above the importer.
+                let aliased = without_ext.trim_start_matches("@/").trim_start_matches("~/");
+                let segments: Vec<&str> = aliased.split('/').collect();
+                ancestor_dirs(from_dir).find_map(|base| lookup(base, &segments, false))
             }
         }
     }
+}
+
+/// Directory part of a workspace-relative key; empty at the root
+fn parent_dir(key: &str) -> &str {
+    key.rsplit_once('/').map_or("", |(dir, _)| dir)
+}
 
-    // Find max degree across all files
-    let mut max_degree: usize = 0;
-    let all_files: std::collections::HashSet<&String> = out_degree.keys().chain(in_degree.keys()).collect();
-    for f in &all_files {
-        let total = out_degree.get(*f).copied().unwrap_or(0) + in_degree.get(*f).copied().unwrap_or(0);
-        if total > max_degree {
-            max_degree = total;
+/// `dir` and each of its ancestors, deepest first, ending at the root ("")
+fn ancestor_dirs(dir: &str) -> impl Iterator<Item = &str> {
+    std::iter::successors(Some(dir), |dir| (!dir.is_empty()).then(|| parent_dir(dir)))
+}
+
+/// Join module segments onto a base directory, applying `.` and `..`.
+/// `None` when `..` climbs above the workspace root.
+fn join_module_path(base: &str, segments: &[&str]) -> Option<String> {
+    let mut parts: Vec<&str> = base.split('/').filter(|part| !part.is_empty()).collect();
+    for segment in segments {
+        match *segment {
+            "" | "." => {}
+            ".." => {
+                parts.pop()?;
+            }
+            segment => parts.push(segment),
         }
     }
+    Some(parts.join("/"))
+}
 
-    if max_degree == 0 {
-        return 0.0;
+fn strip_source_extension(import: &str) -> &str {
+    match import.rsplit_once('.') {
+        Some((path, ext)) if !ext.contains('/') && is_source_file(import) => path,
+        _ => import,
     }
+}
 
-    let file_in = in_degree.get(relative_path).copied().unwrap_or(0);
-    let file_out = out_degree.get(relative_path).copied().unwrap_or(0);
-    let score = (file_in + file_out) as f64 / (2.0 * max_degree as f64) * 100.0;
-
-    score.min(100.0)
+fn workspace_relative(workspace_path: &str, file_path: &str) -> String {
+    file_path
+        .strip_prefix(workspace_path)
+        .unwrap_or(file_path)
+        .trim_start_matches(['/', '\\'])
+        .replace('\\', "/")
 }
 
 fn detect_language_for_coupling(path: &str) -> String {
     match std::path::Path::new(path).extension().and_then(|e| e.to_str()) {
         Some("ts") | Some("tsx") => "typescript".to_string(),
-        Some("js") | Some("jsx") => "javascript".to_string(),
+        Some("js") | Some("jsx") | Some("mjs") | Some("cjs") => "javascript".to_string(),
         Some("py") => "python".to_string(),
         Some("go") => "go".to_string(),
         Some("rs") => "rust".to_string(),
@@ -203,6 +554,87 @@ fn detect_language_for_coupling(path: &str) -> String {
     }
 }
 
+/// Names a bare (non-relative) import can use to refer to workspace code:
+/// every directory name and file stem in the workspace, plus the Go module
+/// path from `go.mod`. Lowercased.
+pub fn workspace_import_roots(workspace_path: &str, files: &[String]) -> HashSet<String> {
+    let mut roots = HashSet::new();
+    for file in files {
+        let relative = normalize_path_key(
+            file.strip_prefix(workspace_path)
+                .unwrap_or(file)
+                .trim_start_matches(['/', '\\']),
+        );
+        let path = std::path::Path::new(&relative);
+        if let Some(stem) = path.file_stem() {
+            roots.insert(stem.to_string_lossy().to_string());
+        }
+        if let Some(parent) = path.parent() {
+            for component in parent.components() {
+                roots.insert(component.as_os_str().to_string_lossy().to_string());
+            }
+        }
+    }
+
+    let go_mod = std::path::Path::new(workspace_path).join("go.mod");
+    if let Ok(content) = std::fs::read_to_string(go_mod) {
+        if let Some(module) = content
+            .lines()
+            .find_map(|line| line.trim().strip_prefix("module "))
+        {
+            roots.insert(module.trim().to_lowercase());
+        }
+    }
+
+    roots
+}
+
+/// Whether an import refers to workspace code rather than a third-party or
+/// standard-library package. Relative paths and `crate::`/`self::`/`super::`
+/// are always internal; bare names are internal when their first segment is
+/// one of `local_roots` (see `workspace_import_roots`).
+pub fn is_internal_import(import: &str, language: &str, local_roots: &HashSet<String>) -> bool {
+    let import = import.trim();
+    match language {
+        "rust" => {
+            let first = import.split("::").next().unwrap_or("");
+            match first {
+                "crate" | "self" | "super" => true,
+                "std" | "core" | "alloc" => false,
+                _ => local_roots.contains(&first.to_lowercase()),
+            }
+        }
+        "python" => {
+            import.starts_with('.')
+                || local_roots.contains(&import.split('.').next().unwrap_or("").to_lowercase())
+        }
+        "go" => {
+            let lowered = import.to_lowercase();
+            local_roots
+                .iter()
+                .any(|root| root.contains('/') && (lowered == *root || lowered.starts_with(&format!("{root}/"))))
+        }
+        _ => {
+            import.starts_with('.')
+                || import.starts_with('/')
+                || import.starts_with("@/")
+                || import.starts_with("~/")
+        }
+    }
+}
+
+/// Split imports into (internal, external)
+pub fn split_imports(
+    imports: &[String],
+    language: &str,
+    local_roots: &HashSet<String>,
+) -> (Vec<String>, Vec<String>) {
+    imports
+        .iter()
+        .cloned()
+        .partition(|import| is_internal_import(import, language, local_roots))
+}
+
 fn extract_import_path(line: &str) -> Option<String> {
     // Extract from: import ... from 'path' or require('path')
     if let Some(pos) = line.rfind('\'') {
@@ -237,7 +669,8 @@ fn extract_python_import(line: &str) -> Option<String> {
 fn is_source_file(path: &str) -> bool {
     matches!(
         std::path::Path::new(path).extension().and_then(|e| e.to_str()),
-        Some("ts") | Some("tsx") | Some("js") | Some("jsx") | Some("py") | Some("go") | Some("rs") | Some("java")
+        Some("ts") | Some("tsx") | Some("js") | Some("jsx") | Some("mjs") | Some("cjs") | Some("py") | Some("go")
+            | Some("rs") | Some("java")
     )
 }
 
@@ -362,4 +795,124 @@ mod tests {
         assert_eq!(js_imports, vec!["./x".to_string(), "./y".to_string()]);
         assert_eq!(rust_imports, vec!["crate::module::Type".to_string()]);
     }
+
+    #[test]
+    fn merges_mixed_case_and_separator_duplicates_into_one_pair() {
+        let commits = vec![
+            vec!["src/Foo.rs".to_string(), "src/bar.rs".to_string()],
+            vec!["Src\\foo.rs".to_string(), "SRC/Bar.rs".to_string()],
+            vec!["src/foo.rs".to_string(), "src/bar.rs".to_string()],
+        ];
+
+        let result = accumulate_co_changes(&commits, DEFAULT_MAX_FILES_PER_COMMIT);
+        assert_eq!(result.pairs.len(), 1, "Expected a single merged pair: {:?}", result.pairs);
+
+        let (a, b, count) = &result.pairs[0];
+        assert_eq!(*count, 3);
+        assert!(normalize_path_key(a) < normalize_path_key(b));
+        assert_eq!(result.file_change_counts.len(), 2);
+        assert!(result.file_change_counts.values().all(|count| *count == 3));
+        assert!((compute_change_coupling("SRC/FOO.rs", &result) - 100.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn commits_over_the_file_cap_count_as_changes_but_not_pairs() {
+        let mass_reformat: Vec<String> = (0..100).map(|i| format!("src/file{i}.rs")).collect();
+        let commits = vec![
+            mass_reformat,
+            vec!["src/file0.rs".to_string(), "src/file1.rs".to_string()],
+        ];
+
+        let capped = accumulate_co_changes(&commits[..1], DEFAULT_MAX_FILES_PER_COMMIT);
+        assert!(capped.pairs.is_empty());
+        assert_eq!(capped.file_change_counts.len(), 100);
+
+        let result = accumulate_co_changes(&commits, DEFAULT_MAX_FILES_PER_COMMIT);
+        assert_eq!(result.pairs.len(), 1);
+        assert_eq!(result.pairs[0].2, 1);
+        assert_eq!(result.file_change_counts["src/file0.rs"], 2);
+    }
+
+    #[test]
+    fn classifies_internal_and_external_imports() {
+        let roots: HashSet<String> = ["utils".to_string()].into_iter().collect();
+
+        assert!(!is_internal_import("std::fmt", "rust", &roots));
+        assert!(!is_internal_import("serde::Serialize", "rust", &roots));
+        assert!(is_internal_import("crate::foo", "rust", &roots));
+        assert!(is_internal_import("utils::parse", "rust", &roots));
+        assert!(is_internal_import("./x", "typescript", &roots));
+        assert!(!is_internal_import("react", "typescript", &roots));
+        assert!(is_internal_import(".models", "python", &roots));
+        assert!(!is_internal_import("requests", "python", &roots));
+
+        let imports = vec!["std::fmt".to_string(), "crate::foo".to_string()];
+        let (internal, external) = split_imports(&imports, "rust", &roots);
+        assert_eq!(internal, vec!["crate::foo".to_string()]);
+        assert_eq!(external, vec!["std::fmt".to_string()]);
+    }
+
+    #[test]
+    fn imports_resolve_by_path_not_stem() {
+        let keys: Vec<String> = [
+            "src/lib.rs",
+            "src/api/mod.rs",
+            "src/db/mod.rs",
+            "src/db/pool.rs",
+            "web/src/utils.ts",
+            "web/src/admin/utils.ts",
+            "web/src/admin/page.ts",
+            "app/pkg/__init__.py",
+            "app/pkg/models.py",
+        ]
+        .iter()
+        .map(|key| key.to_string())
+        .collect();
+        let modules = module_paths(&keys);
+        let resolve = |import: &str, language: &str, from: &str| {
+            resolve_import_path(import, language, from, &modules).map(|(index, _)| keys[index].as_str())
+        };
+
+        assert_eq!(resolve("crate::db::pool::Pool", "rust", "src/api/mod.rs"), Some("src/db/pool.rs"));
+        assert_eq!(resolve("crate::db", "rust", "src/api/mod.rs"), Some("src/db/mod.rs"));
+        assert_eq!(resolve("super::api", "rust", "src/db/mod.rs"), Some("src/api/mod.rs"));
+        assert_eq!(resolve("self::pool", "rust", "src/db/mod.rs"), Some("src/db/pool.rs"));
+        assert_eq!(resolve("./utils", "typescript", "web/src/admin/page.ts"), Some("web/src/admin/utils.ts"));
+        assert_eq!(resolve("../utils.js", "typescript", "web/src/admin/page.ts"), Some("web/src/utils.ts"));
+        assert_eq!(resolve(".models", "python", "app/pkg/__init__.py"), Some("app/pkg/models.py"));
+        assert_eq!(resolve("pkg.models.User", "python", "app/main.py"), Some("app/pkg/models.py"));
+        assert_eq!(resolve("./missing", "typescript", "web/src/admin/page.ts"), None);
+    }
+
+    #[test]
+    fn coupling_index_uses_path_resolved_edges_and_keeps_self_imports() {
+        let workspace = tempfile::tempdir().expect("temp workspace");
+        let root = workspace.path();
+        for dir in ["web/a", "web/b", "src"] {
+            std::fs::create_dir_all(root.join(dir)).expect("create dir");
+        }
+        std::fs::write(root.join("web/a/utils.ts"), "export const a = 1;\n").expect("write a utils");
+        std::fs::write(root.join("web/b/utils.ts"), "export const b = 1;\n").expect("write b utils");
+        std::fs::write(root.join("web/a/page.ts"), "import { a } from './utils';\n").expect("write page");
+        std::fs::write(root.join("web/b/self.ts"), "import { me } from './self';\nexport const me = 1;\n")
+            .expect("write self");
+        std::fs::write(root.join("src/lib.rs"), "use crate::Thing;\npub struct Thing;\n").expect("write lib");
+
+        let workspace_path = root.to_string_lossy().to_string();
+        let edges = internal_import_edges(&workspace_path);
+        assert!(edges["web/b/self.ts"].contains("web/b/self.ts"));
+        // `crate::Thing` names an item of lib.rs, not lib.rs importing itself
+        assert!(edges["src/lib.rs"].is_empty());
+        assert_eq!(
+            crate::analysis::cycles::find_import_cycles(&edges),
+            vec![vec!["web/b/self.ts".to_string()]]
+        );
+
+        let degrees = ImportDegrees::from_edges(&edges);
+        assert_eq!(compute_coupling_index("web/a/utils.ts", &degrees), 50.0);
+        assert_eq!(compute_coupling_index("web/a/page.ts", &degrees), 50.0);
+        // Same stem, different directory: not imported by page.ts
+        assert_eq!(compute_coupling_index("web/b/utils.ts", &degrees), 0.0);
+        assert_eq!(compute_coupling_index("web/b/self.ts", &degrees), 0.0);
+    }
 }
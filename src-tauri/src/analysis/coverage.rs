@@ -1,27 +1,365 @@
-use std::path::Path;
+use crate::commands::settings::SkipDirectories;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Upper bound on how many coverage reports are merged for one workspace.
+pub const MAX_COVERAGE_REPORTS: usize = 32;
+
+/// `(lines found, lines hit)` for one source file
+type LineCounts = (u64, u64);
+
+fn line_percent((found, hit): LineCounts) -> f64 {
+    hit as f64 / found as f64 * 100.0
+}
+
+/// The workspace's coverage reports, discovered and merged once per analysis
+#[derive(Debug, Clone, Default)]
+pub struct CoverageIndex {
+    report_count: usize,
+    /// Line counts per workspace-relative path (lcov, cobertura)
+    files: HashMap<String, LineCounts>,
+    /// Jacoco `package/File.java` entries keyed by file name. They omit the
+    /// source root, so they match by path suffix.
+    jacoco: HashMap<String, Vec<(String, LineCounts)>>,
+}
+
+impl CoverageIndex {
+    /// `jacoco_report_path` is the resolved `jacocoReportPath` setting
+    pub fn load(workspace_path: &str, skip: &SkipDirectories, jacoco_report_path: Option<&Path>) -> Self {
+        let reports = discover_coverage_reports(workspace_path, skip, jacoco_report_path);
+        merge_coverage_reports(workspace_path, &reports)
+    }
+
+    /// Coverage for `relative_path`: an exact entry, else the best Jacoco
+    /// entry that is a path suffix of it
+    fn lookup(&self, relative_path: &str) -> Option<f64> {
+        if let Some(counts) = self.files.get(relative_path) {
+            return Some(line_percent(*counts));
+        }
+        let name = relative_path.rsplit('/').next().unwrap_or(relative_path);
+        self.jacoco
+            .get(name)?
+            .iter()
+            .filter(|(key, _)| relative_path == key || relative_path.ends_with(&format!("/{key}")))
+            .map(|(_, counts)| line_percent(*counts))
+            .reduce(f64::max)
+    }
+
+    /// Lines hit over lines found across every file the reports list
+    pub fn summary(&self) -> WorkspaceCoverage {
+        let counts: Vec<LineCounts> = self
+            .files
+            .values()
+            .copied()
+            .chain(self.jacoco.values().flatten().map(|(_, counts)| *counts))
+            .collect();
+        let (found, hit) = counts
+            .iter()
+            .fold((0, 0), |(found, hit), (file_found, file_hit)| (found + file_found, hit + file_hit));
+        WorkspaceCoverage {
+            report_count: self.report_count,
+            file_count: counts.len(),
+            coverage_percent: (found > 0).then(|| line_percent((found, hit))),
+        }
+    }
+}
 
 /// Compute test coverage gap score (0–100)
 /// Uses heuristic: check for co-located test files
-pub fn compute_coverage_gap(relative_path: &str, workspace_path: &str) -> f64 {
+pub fn compute_coverage_gap(relative_path: &str, workspace_path: &str, coverage: &CoverageIndex) -> f64 {
     // First check for coverage reports
-    let lcov_path = Path::new(workspace_path).join("coverage/lcov.info");
-    let cobertura_path = Path::new(workspace_path).join("coverage.xml");
+    if coverage.report_count > 0 {
+        return match coverage.lookup(&relative_path.replace('\\', "/")) {
+            Some(percent) => (100.0 - percent).clamp(0.0, 100.0),
+            None => 80.0,
+        };
+    }
+
+    // Heuristic: check for test file co-location
+    let test_patterns = test_file_candidates(relative_path);
+
+    let workspace = Path::new(workspace_path);
+    for pattern in &test_patterns {
+        if workspace.join(pattern).exists() {
+            return 30.0; // Has tests but coverage unknown → moderate gap
+        }
+    }
 
-    if lcov_path.exists() {
-        return parse_lcov_coverage(&lcov_path, relative_path);
+    80.0 // No test file found → high gap
+}
+
+/// Line coverage across the workspace's coverage reports
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkspaceCoverage {
+    pub report_count: usize,
+    /// Files listed by at least one report
+    pub file_count: usize,
+    /// Lines hit over lines found across those files; `None` without reports
+    pub coverage_percent: Option<f64>,
+}
+
+/// Discover and merge every report into one workspace-wide percentage, for
+/// callers without an analysis' `CoverageIndex` at hand
+pub fn workspace_coverage(workspace_path: &str) -> WorkspaceCoverage {
+    let skip = crate::commands::settings::load_skip_directories(workspace_path);
+    let jacoco_report_path = crate::commands::settings::load_jacoco_report_path(workspace_path);
+    CoverageIndex::load(workspace_path, &skip, jacoco_report_path.as_deref()).summary()
+}
+
+/// lcov (`lcov.info`, `*.lcov`), cobertura (`coverage.xml`, `cobertura*.xml`)
+/// and Jacoco (`jacoco.xml`, or the configured `jacocoReportPath`) reports
+/// anywhere under the workspace, sorted and capped at `MAX_COVERAGE_REPORTS`.
+/// Hidden directories and `skipDirectories` are skipped, except that a
+/// skipped `coverage/` folder or Maven's `target/site/jacoco` is still
+/// searched, since that is where the tools write their reports.
+pub fn discover_coverage_reports(
+    workspace_path: &str,
+    skip: &SkipDirectories,
+    jacoco_report_path: Option<&Path>,
+) -> Vec<PathBuf> {
+    fn walk(dir: &Path, relative_dir: &str, skip: &SkipDirectories, reports: &mut Vec<PathBuf>) {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let name = entry.file_name().to_string_lossy().to_string();
+            if path.is_dir() {
+                if name.starts_with('.') {
+                    continue;
+                }
+                let relative = if relative_dir.is_empty() {
+                    name.clone()
+                } else {
+                    format!("{relative_dir}/{name}")
+                };
+                if !skip.matches(&relative) || name == "coverage" {
+                    walk(&path, &relative, skip, reports);
+                } else if path.join("site/jacoco").is_dir() {
+                    walk(&path.join("site/jacoco"), &format!("{relative}/site/jacoco"), skip, reports);
+                }
+            } else if is_coverage_report(&name) {
+                reports.push(path);
+            }
+        }
     }
-    if cobertura_path.exists() {
-        // Would parse cobertura XML — for MVP, fall through to heuristic
+
+    let mut reports = Vec::new();
+    walk(Path::new(workspace_path), "", skip, &mut reports);
+    if let Some(configured) = jacoco_report_path {
+        if configured.is_file() && !reports.iter().any(|report| report == configured) {
+            reports.push(configured.to_path_buf());
+        }
     }
+    reports.sort();
+    reports.truncate(MAX_COVERAGE_REPORTS);
+    reports
+}
 
-    // Heuristic: check for test file co-location
+fn is_coverage_report(name: &str) -> bool {
+    name == "lcov.info"
+        || name.ends_with(".lcov")
+        || name == "coverage.xml"
+        || (name.starts_with("cobertura") && name.ends_with(".xml"))
+        || name == "jacoco.xml"
+}
+
+/// Line counts per source across all `reports`. A file listed by several
+/// reports keeps the counts with its highest coverage.
+fn merge_coverage_reports(workspace_path: &str, reports: &[PathBuf]) -> CoverageIndex {
+    let mut index = CoverageIndex {
+        report_count: reports.len(),
+        ..Default::default()
+    };
+    let mut jacoco: HashMap<String, LineCounts> = HashMap::new();
+    for report in reports {
+        let Ok(content) = std::fs::read_to_string(report) else {
+            continue;
+        };
+        let is_xml = report.extension().is_some_and(|ext| ext == "xml");
+        if is_xml && content.contains("<sourcefile ") {
+            for (key, counts) in parse_jacoco_lines(&content) {
+                if counts.0 > 0 {
+                    keep_highest(&mut jacoco, key, counts);
+                }
+            }
+            continue;
+        }
+        let lines = if is_xml {
+            parse_cobertura_lines(&content)
+        } else {
+            parse_lcov_lines(&content)
+        };
+        let package_root = report_package_root(report);
+        for (source, counts) in lines {
+            if counts.0 == 0 {
+                continue;
+            }
+            let path = resolve_report_path(workspace_path, &package_root, &source);
+            keep_highest(&mut index.files, path, counts);
+        }
+    }
+    for (key, counts) in jacoco {
+        let name = key.rsplit('/').next().unwrap_or(&key).to_string();
+        index.jacoco.entry(name).or_default().push((key, counts));
+    }
+    index
+}
+
+fn keep_highest(merged: &mut HashMap<String, LineCounts>, path: String, counts: LineCounts) {
+    let entry = merged.entry(path).or_insert(counts);
+    if line_percent(counts) > line_percent(*entry) {
+        *entry = counts;
+    }
+}
+
+/// Directory report paths are relative to: the report's own directory, or
+/// its parent when the report lives in a `coverage/` folder.
+fn report_package_root(report: &Path) -> PathBuf {
+    let dir = report.parent().unwrap_or(Path::new(""));
+    if dir.file_name().is_some_and(|name| name == "coverage") {
+        dir.parent().unwrap_or(dir).to_path_buf()
+    } else {
+        dir.to_path_buf()
+    }
+}
+
+/// Map a report's source path to a workspace-relative path, trying the
+/// report's package root before the workspace root.
+fn resolve_report_path(workspace_path: &str, package_root: &Path, source: &str) -> String {
+    let workspace = Path::new(workspace_path);
+    let source_path = Path::new(source);
+    let resolved = if source_path.is_absolute() {
+        source_path.to_path_buf()
+    } else if package_root.join(source_path).exists() {
+        package_root.join(source_path)
+    } else {
+        workspace.join(source_path)
+    };
+    // Fold `..` so `packages/b/../a/x.ts` matches `packages/a/x.ts`.
+    let mut normalized = PathBuf::new();
+    for component in resolved.components() {
+        match component {
+            std::path::Component::ParentDir => {
+                normalized.pop();
+            }
+            std::path::Component::CurDir => {}
+            other => normalized.push(other),
+        }
+    }
+    normalized
+        .strip_prefix(workspace)
+        .map(|relative| relative.to_string_lossy().to_string())
+        .unwrap_or_else(|_| source.to_string())
+        .replace('\\', "/")
+        .trim_start_matches("./")
+        .to_string()
+}
+
+/// `(lines found, lines hit)` per `SF:` record. Uses `LF`/`LH` when present,
+/// otherwise counts `DA` entries.
+fn parse_lcov_lines(content: &str) -> HashMap<String, (u64, u64)> {
+    let mut files = HashMap::new();
+    let mut current: Option<String> = None;
+    let (mut found, mut hit, mut da_found, mut da_hit) = (None, None, 0u64, 0u64);
+
+    for line in content.lines() {
+        let line = line.trim();
+        if let Some(source) = line.strip_prefix("SF:") {
+            current = Some(source.trim().to_string());
+            (found, hit, da_found, da_hit) = (None, None, 0, 0);
+        } else if let Some(value) = line.strip_prefix("LF:") {
+            found = value.trim().parse::<u64>().ok();
+        } else if let Some(value) = line.strip_prefix("LH:") {
+            hit = value.trim().parse::<u64>().ok();
+        } else if let Some(value) = line.strip_prefix("DA:") {
+            da_found += 1;
+            let hits = value.split(',').nth(1).and_then(|h| h.trim().parse::<u64>().ok());
+            if hits.is_some_and(|h| h > 0) {
+                da_hit += 1;
+            }
+        } else if line == "end_of_record" {
+            if let Some(source) = current.take() {
+                files.insert(source, (found.unwrap_or(da_found), hit.unwrap_or(da_hit)));
+            }
+        }
+    }
+
+    files
+}
+
+/// `(lines found, lines hit)` per `<class filename>` in a cobertura report,
+/// counted from its `<line hits>` elements.
+fn parse_cobertura_lines(content: &str) -> HashMap<String, (u64, u64)> {
+    let mut files: HashMap<String, (u64, u64)> = HashMap::new();
+    let mut current: Option<String> = None;
+
+    for tag in content.split('<') {
+        if tag.starts_with("class ") {
+            current = xml_attribute(tag, "filename");
+        } else if tag.starts_with("line ") {
+            let (Some(file), Some(hits)) = (current.as_ref(), xml_attribute(tag, "hits")) else {
+                continue;
+            };
+            let entry = files.entry(file.clone()).or_insert((0, 0));
+            entry.0 += 1;
+            if hits.parse::<u64>().is_ok_and(|h| h > 0) {
+                entry.1 += 1;
+            }
+        }
+    }
+
+    files
+}
+
+/// `(lines found, lines hit)` per `<package name>/<sourcefile name>` in a
+/// Jacoco report, from each source file's `LINE` counter.
+fn parse_jacoco_lines(content: &str) -> HashMap<String, (u64, u64)> {
+    let mut files = HashMap::new();
+    let mut package = String::new();
+    let mut current: Option<String> = None;
+
+    for tag in content.split('<') {
+        if tag.starts_with("package ") {
+            package = xml_attribute(tag, "name").unwrap_or_default();
+        } else if tag.starts_with("sourcefile ") {
+            current = xml_attribute(tag, "name").map(|name| {
+                if package.is_empty() {
+                    name
+                } else {
+                    format!("{package}/{name}")
+                }
+            });
+        } else if tag.starts_with("/sourcefile") {
+            current = None;
+        } else if tag.starts_with("counter ") && xml_attribute(tag, "type").as_deref() == Some("LINE") {
+            let Some(file) = current.as_ref() else {
+                continue;
+            };
+            let count = |name: &str| xml_attribute(tag, name).and_then(|v| v.parse::<u64>().ok()).unwrap_or(0);
+            let (missed, covered) = (count("missed"), count("covered"));
+            files.insert(file.clone(), (missed + covered, covered));
+        }
+    }
+
+    files
+}
+
+fn xml_attribute(tag: &str, name: &str) -> Option<String> {
+    let start = tag.find(&format!(" {name}=\""))? + name.len() + 3;
+    let end = tag[start..].find('"')?;
+    Some(tag[start..start + end].to_string())
+}
+
+/// Common test file locations for a source file
+fn test_file_candidates(relative_path: &str) -> Vec<PathBuf> {
     let path = Path::new(relative_path);
     let stem = path.file_stem().unwrap_or_default().to_string_lossy();
     let ext = path.extension().unwrap_or_default().to_string_lossy();
     let parent = path.parent().unwrap_or(Path::new(""));
 
-    // Common test file patterns
-    let test_patterns = vec![
+    vec![
         parent.join(format!("{}.test.{}", stem, ext)),
         parent.join(format!("{}.spec.{}", stem, ext)),
         parent.join(format!("test_{}.{}", stem, ext)),
@@ -29,25 +367,156 @@ pub fn compute_coverage_gap(relative_path: &str, workspace_path: &str) -> f64 {
         Path::new("tests").join(format!("test_{}.{}", stem, ext)),
         Path::new("test").join(format!("{}_test.{}", stem, ext)),
         parent.join("__tests__").join(format!("{}.test.{}", stem, ext)),
-    ];
+    ]
+}
 
-    let workspace = Path::new(workspace_path);
-    for pattern in &test_patterns {
-        if workspace.join(pattern).exists() {
-            return 30.0; // Has tests but coverage unknown → moderate gap
+/// Whether a path itself follows one of the test file conventions above
+pub fn is_test_file(relative_path: &str) -> bool {
+    let normalized = relative_path.replace('\\', "/");
+    let path = Path::new(&normalized);
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+
+    let in_test_dir = path
+        .parent()
+        .map(|parent| {
+            parent
+                .components()
+                .any(|c| matches!(c.as_os_str().to_str(), Some("tests" | "test" | "__tests__")))
+        })
+        .unwrap_or(false);
+
+    in_test_dir
+        || stem.ends_with(".test")
+        || stem.ends_with(".spec")
+        || stem.starts_with("test_")
+        || stem.ends_with("_test")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recognizes_test_file_conventions() {
+        assert!(is_test_file("src/foo.test.ts"));
+        assert!(is_test_file("src/foo.spec.js"));
+        assert!(is_test_file("pkg/test_utils.py"));
+        assert!(is_test_file("pkg/server_test.go"));
+        assert!(is_test_file("tests/ipc_contracts.rs"));
+        assert!(!is_test_file("src/main.rs"));
+        assert!(!is_test_file("src/contest.rs"));
+    }
+
+    #[test]
+    fn merges_lcov_reports_from_multiple_packages() {
+        let workspace = tempfile::tempdir().expect("temp workspace");
+        let root = workspace.path();
+        for (dir, file) in [("packages/a", "src/index.ts"), ("packages/b", "src/util.ts")] {
+            std::fs::create_dir_all(root.join(dir).join("src")).expect("create package src");
+            std::fs::create_dir_all(root.join(dir).join("coverage")).expect("create coverage dir");
+            std::fs::write(root.join(dir).join(file), "export {};\n").expect("write source");
         }
+        std::fs::write(
+            root.join("packages/a/coverage/lcov.info"),
+            "SF:src/index.ts\nLF:10\nLH:8\nend_of_record\n",
+        )
+        .expect("write lcov a");
+        std::fs::write(
+            root.join("packages/b/coverage/lcov.info"),
+            "SF:src/util.ts\nDA:1,1\nDA:2,0\nDA:3,0\nDA:4,0\nend_of_record\n\
+             SF:../a/src/index.ts\nLF:10\nLH:5\nend_of_record\n",
+        )
+        .expect("write lcov b");
+
+        let workspace_path = root.to_string_lossy().to_string();
+        let skip = crate::commands::settings::load_skip_directories(&workspace_path);
+        assert_eq!(discover_coverage_reports(&workspace_path, &skip, None).len(), 2);
+        let coverage = CoverageIndex::load(&workspace_path, &skip, None);
+        assert_eq!(compute_coverage_gap("packages/a/src/index.ts", &workspace_path, &coverage), 20.0);
+        assert_eq!(compute_coverage_gap("packages/b/src/util.ts", &workspace_path, &coverage), 75.0);
+        assert_eq!(compute_coverage_gap("packages/c/src/missing.ts", &workspace_path, &coverage), 80.0);
+
+        // (8 + 1) of (10 + 4) lines, not the mean of 80% and 25%
+        let summary = coverage.summary();
+        assert_eq!(summary.file_count, 2);
+        assert!((summary.coverage_percent.expect("coverage") - 9.0 / 14.0 * 100.0).abs() < 1e-9);
     }
 
-    80.0 // No test file found → high gap
-}
+    #[test]
+    fn jacoco_line_counters_set_the_gap_for_java_sources() {
+        let workspace = tempfile::tempdir().expect("temp workspace");
+        let root = workspace.path();
+        std::fs::create_dir_all(root.join("target/site/jacoco")).expect("create jacoco dir");
+        std::fs::write(
+            root.join("target/site/jacoco/jacoco.xml"),
+            r#"<?xml version="1.0" encoding="UTF-8"?><report name="app">
+<package name="com/example">
+  <class name="com/example/Parser" sourcefilename="Parser.java">
+    <counter type="LINE" missed="9" covered="1"/>
+  </class>
+  <sourcefile name="Parser.java">
+    <line nr="3" mi="0" ci="2" mb="0" cb="0"/>
+    <counter type="INSTRUCTION" missed="2" covered="8"/>
+    <counter type="LINE" missed="1" covered="4"/>
+  </sourcefile>
+  <counter type="LINE" missed="50" covered="0"/>
+</package>
+</report>"#,
+        )
+        .expect("write jacoco report");
 
-fn parse_lcov_coverage(lcov_path: &Path, _relative_path: &str) -> f64 {
-    // Simplified LCOV parsing — real implementation would fully parse
-    // For MVP, just check if the file exists in the coverage report
-    if let Ok(content) = std::fs::read_to_string(lcov_path) {
-        if content.contains(_relative_path) {
-            return 30.0; // File found in coverage → assume moderate
+        let workspace_path = root.to_string_lossy().to_string();
+        let skip = crate::commands::settings::load_skip_directories(&workspace_path);
+        let coverage = CoverageIndex::load(&workspace_path, &skip, None);
+        let gap = compute_coverage_gap("src/main/java/com/example/Parser.java", &workspace_path, &coverage);
+        assert!((gap - 20.0).abs() < 1e-9);
+        assert_eq!(
+            compute_coverage_gap("src/main/java/com/example/Other.java", &workspace_path, &coverage),
+            80.0
+        );
+    }
+
+    #[test]
+    fn only_jacoco_entries_match_by_path_suffix() {
+        let workspace = tempfile::tempdir().expect("temp workspace");
+        let root = workspace.path();
+        std::fs::create_dir_all(root.join("reports")).expect("create reports dir");
+        std::fs::write(root.join("lcov.info"), "SF:util.ts\nLF:4\nLH:4\nend_of_record\n").expect("write lcov");
+        std::fs::write(
+            root.join("reports/app.xml"),
+            r#"<report><package name="com/example"><sourcefile name="Util.java">
+<counter type="LINE" missed="3" covered="1"/></sourcefile></package></report>"#,
+        )
+        .expect("write jacoco report");
+
+        let workspace_path = root.to_string_lossy().to_string();
+        let skip = crate::commands::settings::load_skip_directories(&workspace_path);
+        let configured = root.join("reports/app.xml");
+        let coverage = CoverageIndex::load(&workspace_path, &skip, Some(configured.as_path()));
+        assert_eq!(coverage.report_count, 2);
+        assert_eq!(compute_coverage_gap("util.ts", &workspace_path, &coverage), 0.0);
+        // An lcov entry is a workspace path; it never matches a nested namesake.
+        assert_eq!(compute_coverage_gap("packages/a/util.ts", &workspace_path, &coverage), 80.0);
+        assert_eq!(compute_coverage_gap("src/main/java/com/example/Util.java", &workspace_path, &coverage), 75.0);
+        assert_eq!(compute_coverage_gap("src/main/java/org/example/Util.java", &workspace_path, &coverage), 80.0);
+    }
+
+    #[test]
+    fn report_discovery_honours_skip_directories() {
+        let workspace = tempfile::tempdir().expect("temp workspace");
+        let root = workspace.path();
+        for dir in ["coverage", "node_modules/dep/coverage", "vendored/lib", "target/debug"] {
+            std::fs::create_dir_all(root.join(dir)).expect("create report dir");
+            std::fs::write(root.join(dir).join("lcov.info"), "SF:a.ts\nLF:1\nLH:1\nend_of_record\n")
+                .expect("write report");
         }
+        std::fs::create_dir_all(root.join(".debtengine")).expect("create settings dir");
+        std::fs::write(root.join(".debtengine/settings.json"), r#"{"skipDirectories":["vendored"]}"#)
+            .expect("write settings");
+
+        let workspace_path = root.to_string_lossy().to_string();
+        let skip = crate::commands::settings::load_skip_directories(&workspace_path);
+        let reports = discover_coverage_reports(&workspace_path, &skip, None);
+        assert_eq!(reports, vec![root.join("coverage/lcov.info")]);
     }
-    80.0
 }
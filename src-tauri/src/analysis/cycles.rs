@@ -0,0 +1,149 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// Import cycles in a directed graph: every strongly connected component
+/// with more than one file, plus files that import themselves. Each cycle's
+/// files are sorted, and cycles are ordered largest first.
+pub fn find_import_cycles(edges: &BTreeMap<String, BTreeSet<String>>) -> Vec<Vec<String>> {
+    let mut tarjan = Tarjan {
+        edges,
+        index: HashMap::new(),
+        lowlink: HashMap::new(),
+        stack: Vec::new(),
+        on_stack: HashMap::new(),
+        next_index: 0,
+        components: Vec::new(),
+    };
+    for node in edges.keys() {
+        if !tarjan.index.contains_key(node.as_str()) {
+            tarjan.visit(node);
+        }
+    }
+
+    let mut cycles: Vec<Vec<String>> = tarjan
+        .components
+        .into_iter()
+        .filter(|component| {
+            component.len() > 1
+                || edges
+                    .get(component[0])
+                    .is_some_and(|targets| targets.contains(component[0]))
+        })
+        .map(|component| {
+            let mut files: Vec<String> = component.into_iter().map(str::to_string).collect();
+            files.sort();
+            files
+        })
+        .collect();
+    cycles.sort_by(|a, b| b.len().cmp(&a.len()).then_with(|| a.cmp(b)));
+    cycles
+}
+
+/// Tarjan's strongly connected components, walked with an explicit stack so
+/// that long import chains can't overflow the call stack.
+struct Tarjan<'a> {
+    edges: &'a BTreeMap<String, BTreeSet<String>>,
+    index: HashMap<&'a str, usize>,
+    lowlink: HashMap<&'a str, usize>,
+    stack: Vec<&'a str>,
+    on_stack: HashMap<&'a str, bool>,
+    next_index: usize,
+    components: Vec<Vec<&'a str>>,
+}
+
+impl<'a> Tarjan<'a> {
+    fn visit(&mut self, root: &'a str) {
+        // Each frame is a node being visited and its targets not yet followed.
+        let mut frames = vec![self.enter(root)];
+        while let Some((node, targets)) = frames.last_mut() {
+            let node = *node;
+            if let Some(target) = targets.next() {
+                let target = target.as_str();
+                if !self.index.contains_key(target) {
+                    frames.push(self.enter(target));
+                } else if self.on_stack.get(target).copied().unwrap_or(false) {
+                    let low = self.lowlink[node].min(self.index[target]);
+                    self.lowlink.insert(node, low);
+                }
+                continue;
+            }
+
+            frames.pop();
+            if let Some((parent, _)) = frames.last() {
+                let low = self.lowlink[*parent].min(self.lowlink[node]);
+                self.lowlink.insert(*parent, low);
+            }
+            if self.lowlink[node] == self.index[node] {
+                let mut component = Vec::new();
+                while let Some(member) = self.stack.pop() {
+                    self.on_stack.insert(member, false);
+                    component.push(member);
+                    if member == node {
+                        break;
+                    }
+                }
+                self.components.push(component);
+            }
+        }
+    }
+
+    fn enter(&mut self, node: &'a str) -> (&'a str, std::collections::btree_set::Iter<'a, String>) {
+        self.index.insert(node, self.next_index);
+        self.lowlink.insert(node, self.next_index);
+        self.next_index += 1;
+        self.stack.push(node);
+        self.on_stack.insert(node, true);
+        (node, self.edges.get(node).map(|targets| targets.iter()).unwrap_or_default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn graph(edges: &[(&str, &str)]) -> BTreeMap<String, BTreeSet<String>> {
+        let mut graph: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+        for (from, to) in edges {
+            graph.entry(from.to_string()).or_default().insert(to.to_string());
+            graph.entry(to.to_string()).or_default();
+        }
+        graph
+    }
+
+    #[test]
+    fn detects_two_file_cycle_and_self_import() {
+        let edges = graph(&[
+            ("src/a.ts", "src/b.ts"),
+            ("src/b.ts", "src/a.ts"),
+            ("src/b.ts", "src/c.ts"),
+            ("src/d.ts", "src/d.ts"),
+        ]);
+
+        let cycles = find_import_cycles(&edges);
+
+        assert_eq!(
+            cycles,
+            vec![
+                vec!["src/a.ts".to_string(), "src/b.ts".to_string()],
+                vec!["src/d.ts".to_string()],
+            ]
+        );
+    }
+
+    #[test]
+    fn acyclic_graph_has_no_cycles() {
+        let edges = graph(&[("src/a.ts", "src/b.ts"), ("src/b.ts", "src/c.ts"), ("src/a.ts", "src/c.ts")]);
+        assert!(find_import_cycles(&edges).is_empty());
+    }
+
+    #[test]
+    fn long_import_chain_does_not_overflow() {
+        let names: Vec<String> = (0..100_000).map(|i| format!("src/m{i}.ts")).collect();
+        let mut chain: Vec<(&str, &str)> = names.windows(2).map(|pair| (pair[0].as_str(), pair[1].as_str())).collect();
+        chain.push((names[names.len() - 1].as_str(), names[0].as_str()));
+
+        let cycles = find_import_cycles(&graph(&chain));
+
+        assert_eq!(cycles.len(), 1);
+        assert_eq!(cycles[0].len(), names.len());
+    }
+}
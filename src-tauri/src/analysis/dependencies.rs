@@ -0,0 +1,218 @@
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Dependency summary for one manifest at the workspace root
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManifestDependencies {
+    /// Manifest file name, e.g. `package.json`
+    pub manifest: String,
+    pub ecosystem: String,
+    pub dependency_count: usize,
+    pub dev_dependency_count: usize,
+    /// Lockfile found next to the manifest, if any
+    pub lockfile: Option<String>,
+    /// Dependencies pinned to one exact version, which never pick up
+    /// updates. Only reported when a lockfile is present.
+    pub pinned_count: Option<usize>,
+}
+
+#[derive(Debug, Default)]
+struct ParsedManifest {
+    dependencies: Vec<(String, String)>,
+    dev_dependencies: Vec<(String, String)>,
+}
+
+/// (manifest, ecosystem, lockfiles, parser)
+type ManifestKind = (&'static str, &'static str, &'static [&'static str], fn(&str) -> ParsedManifest);
+
+/// Read every supported manifest at the workspace root
+pub fn analyze_dependencies(workspace_path: &str) -> Vec<ManifestDependencies> {
+    let root = Path::new(workspace_path);
+    let manifests: [ManifestKind; 4] = [
+        ("Cargo.toml", "cargo", &["Cargo.lock"], parse_cargo_toml),
+        (
+            "package.json",
+            "npm",
+            &["package-lock.json", "yarn.lock", "pnpm-lock.yaml"],
+            parse_package_json,
+        ),
+        ("go.mod", "go", &["go.sum"], parse_go_mod),
+        ("requirements.txt", "pip", &[], parse_requirements_txt),
+    ];
+
+    manifests
+        .iter()
+        .filter_map(|(manifest, ecosystem, lockfiles, parse)| {
+            let content = std::fs::read_to_string(root.join(manifest)).ok()?;
+            let parsed = parse(&content);
+            let lockfile = lockfiles
+                .iter()
+                .find(|lockfile| root.join(lockfile).exists())
+                .map(|lockfile| lockfile.to_string());
+            let pinned_count = lockfile.as_ref().map(|_| {
+                parsed
+                    .dependencies
+                    .iter()
+                    .chain(&parsed.dev_dependencies)
+                    .filter(|(_, version)| is_exact_pin(version, ecosystem))
+                    .count()
+            });
+
+            Some(ManifestDependencies {
+                manifest: manifest.to_string(),
+                ecosystem: ecosystem.to_string(),
+                dependency_count: parsed.dependencies.len(),
+                dev_dependency_count: parsed.dev_dependencies.len(),
+                lockfile,
+                pinned_count,
+            })
+        })
+        .collect()
+}
+
+fn parse_cargo_toml(content: &str) -> ParsedManifest {
+    let mut parsed = ParsedManifest::default();
+    let mut section = "";
+
+    for line in content.lines() {
+        let trimmed = line.split('#').next().unwrap_or("").trim();
+        if trimmed.starts_with('[') {
+            section = match trimmed {
+                "[dependencies]" | "[build-dependencies]" => "deps",
+                "[dev-dependencies]" => "dev",
+                _ => "",
+            };
+            continue;
+        }
+        let Some((name, spec)) = trimmed.split_once('=') else {
+            continue;
+        };
+        let entry = (name.trim().to_string(), cargo_version(spec.trim()));
+        match section {
+            "deps" => parsed.dependencies.push(entry),
+            "dev" => parsed.dev_dependencies.push(entry),
+            _ => {}
+        }
+    }
+
+    parsed
+}
+
+/// `"1.0"` or `{ version = "=1.0", ... }` → the version requirement
+fn cargo_version(spec: &str) -> String {
+    let spec = match spec.find("version") {
+        Some(index) => spec[index..].split_once('=').map(|(_, rest)| rest).unwrap_or(""),
+        None => spec,
+    };
+    spec.split('"').nth(1).unwrap_or("").to_string()
+}
+
+fn parse_package_json(content: &str) -> ParsedManifest {
+    let Ok(json) = serde_json::from_str::<serde_json::Value>(content) else {
+        return ParsedManifest::default();
+    };
+    let section = |key: &str| -> Vec<(String, String)> {
+        json.get(key)
+            .and_then(serde_json::Value::as_object)
+            .map(|deps| {
+                deps.iter()
+                    .map(|(name, version)| (name.clone(), version.as_str().unwrap_or("").to_string()))
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+
+    ParsedManifest {
+        dependencies: section("dependencies"),
+        dev_dependencies: section("devDependencies"),
+    }
+}
+
+fn parse_go_mod(content: &str) -> ParsedManifest {
+    let mut parsed = ParsedManifest::default();
+    let mut in_require_block = false;
+
+    for line in content.lines() {
+        let trimmed = line.split("//").next().unwrap_or("").trim();
+        let requirement = if in_require_block {
+            if trimmed == ")" {
+                in_require_block = false;
+                continue;
+            }
+            trimmed
+        } else if trimmed == "require (" {
+            in_require_block = true;
+            continue;
+        } else if let Some(rest) = trimmed.strip_prefix("require ") {
+            rest
+        } else {
+            continue;
+        };
+
+        let mut parts = requirement.split_whitespace();
+        if let (Some(module), Some(version)) = (parts.next(), parts.next()) {
+            parsed.dependencies.push((module.to_string(), version.to_string()));
+        }
+    }
+
+    parsed
+}
+
+fn parse_requirements_txt(content: &str) -> ParsedManifest {
+    let dependencies = content
+        .lines()
+        .map(|line| line.split('#').next().unwrap_or("").trim())
+        .filter(|line| !line.is_empty() && !line.starts_with('-'))
+        .map(|line| {
+            let name_end = line
+                .find(|c: char| "=<>!~;[ ".contains(c))
+                .unwrap_or(line.len());
+            (line[..name_end].to_string(), line[name_end..].trim().to_string())
+        })
+        .collect();
+
+    ParsedManifest {
+        dependencies,
+        dev_dependencies: Vec::new(),
+    }
+}
+
+fn is_exact_pin(version: &str, ecosystem: &str) -> bool {
+    let version = version.trim();
+    match ecosystem {
+        "cargo" => version.starts_with('='),
+        "npm" => version.chars().next().is_some_and(|c| c.is_ascii_digit()) && !version.contains(['x', '*', ' ']),
+        // go.mod always records an exact minimum version
+        "go" => false,
+        _ => version.starts_with("=="),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_package_json_dependencies() {
+        let workspace = tempfile::tempdir().expect("temp workspace");
+        std::fs::write(
+            workspace.path().join("package.json"),
+            r#"{
+                "name": "app",
+                "dependencies": { "react": "^18.2.0", "lodash": "4.17.21" },
+                "devDependencies": { "vitest": "^1.0.0" }
+            }"#,
+        )
+        .expect("write package.json");
+        std::fs::write(workspace.path().join("package-lock.json"), "{}").expect("write lockfile");
+
+        let reports = analyze_dependencies(&workspace.path().to_string_lossy());
+
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0].manifest, "package.json");
+        assert_eq!(reports[0].dependency_count, 2);
+        assert_eq!(reports[0].dev_dependency_count, 1);
+        assert_eq!(reports[0].lockfile.as_deref(), Some("package-lock.json"));
+        assert_eq!(reports[0].pinned_count, Some(1));
+    }
+}
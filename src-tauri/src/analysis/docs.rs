@@ -0,0 +1,153 @@
+use serde::{Deserialize, Serialize};
+
+/// Share of a file's public declarations that carry a doc comment
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DocCoverage {
+    pub path: String,
+    pub documented: usize,
+    pub public_items: usize,
+    pub doc_coverage: f64,
+}
+
+/// Count `(documented, public)` declarations in `source`. Public means `pub`
+/// in Rust, `export` in TS/JS, `public` in Java, a capitalized name in Go and
+/// a name without a leading underscore in Python. Docs are `///` or `/** */`
+/// above the item, a `//` comment in Go and a docstring inside the body in
+/// Python. Returns `None` for languages without a detector.
+pub fn count_documented(source: &str, language: &str) -> Option<(usize, usize)> {
+    let is_public: fn(&str) -> bool = match language {
+        "rust" => is_rust_public,
+        "typescript" | "javascript" => is_js_public,
+        "python" => is_python_public,
+        "go" => is_go_public,
+        "java" => is_java_public,
+        _ => return None,
+    };
+
+    let lines: Vec<&str> = source.lines().collect();
+    let mut documented = 0;
+    let mut total = 0;
+    for (index, line) in lines.iter().enumerate() {
+        if !is_public(line.trim_start()) {
+            continue;
+        }
+        total += 1;
+        let has_doc = if language == "python" {
+            has_docstring(&lines, index)
+        } else {
+            has_preceding_doc(&lines, index, language)
+        };
+        if has_doc {
+            documented += 1;
+        }
+    }
+    Some((documented, total))
+}
+
+/// Doc coverage for one file, or `None` when the language has no detector or
+/// the file has no public declarations.
+pub fn compute_doc_coverage(path: &str, source: &str, language: &str) -> Option<DocCoverage> {
+    let (documented, public_items) = count_documented(source, language)?;
+    if public_items == 0 {
+        return None;
+    }
+    Some(DocCoverage {
+        path: path.to_string(),
+        documented,
+        public_items,
+        doc_coverage: documented as f64 / public_items as f64 * 100.0,
+    })
+}
+
+fn is_rust_public(line: &str) -> bool {
+    line.strip_prefix("pub ").is_some_and(|rest| {
+        [
+            "fn ", "async fn ", "const fn ", "unsafe fn ", "struct ", "enum ", "trait ", "type ", "const ",
+            "static ", "mod ", "union ",
+        ]
+        .iter()
+        .any(|kw| rest.starts_with(kw))
+    })
+}
+
+fn is_js_public(line: &str) -> bool {
+    line.strip_prefix("export ").is_some_and(|rest| {
+        [
+            "function ", "async function ", "class ", "abstract class ", "const ", "let ", "interface ",
+            "type ", "enum ", "default ",
+        ]
+        .iter()
+        .any(|kw| rest.starts_with(kw))
+    })
+}
+
+fn is_python_public(line: &str) -> bool {
+    let name = line
+        .strip_prefix("async def ")
+        .or_else(|| line.strip_prefix("def "))
+        .or_else(|| line.strip_prefix("class "));
+    name.is_some_and(|name| !name.starts_with('_'))
+}
+
+fn is_go_public(line: &str) -> bool {
+    let name = if let Some(rest) = line.strip_prefix("func ") {
+        // Methods: `func (r *T) Name(...)`
+        match rest.strip_prefix('(') {
+            Some(receiver) => receiver.split_once(')').map(|(_, name)| name.trim_start()),
+            None => Some(rest),
+        }
+    } else {
+        line.strip_prefix("type ")
+    };
+    name.and_then(|name| name.chars().next())
+        .is_some_and(char::is_uppercase)
+}
+
+fn is_java_public(line: &str) -> bool {
+    line.starts_with("public ")
+}
+
+/// A doc comment directly above `index`, skipping attributes, decorators and
+/// annotations in between.
+fn has_preceding_doc(lines: &[&str], index: usize, language: &str) -> bool {
+    for line in lines[..index].iter().rev() {
+        let trimmed = line.trim();
+        if trimmed.starts_with("#[") || trimmed.starts_with('@') {
+            continue;
+        }
+        return match language {
+            "rust" => trimmed.starts_with("///") || trimmed.ends_with("*/"),
+            "go" => trimmed.starts_with("//"),
+            _ => trimmed.ends_with("*/"),
+        };
+    }
+    false
+}
+
+/// A `"""`/`'''` string as the first statement after the signature that
+/// starts at `index`.
+fn has_docstring(lines: &[&str], index: usize) -> bool {
+    let Some(signature_end) = (index..lines.len()).find(|&i| lines[i].trim_end().ends_with(':')) else {
+        return false;
+    };
+    lines[signature_end + 1..]
+        .iter()
+        .map(|line| line.trim())
+        .find(|line| !line.is_empty())
+        .is_some_and(|line| line.starts_with("\"\"\"") || line.starts_with("'''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn half_documented_rust_file_has_fifty_percent_coverage() {
+        let source = "/// Adds.\npub fn add() {}\n\npub fn sub() {}\n\n/// Multiplies.\n#[inline]\npub fn mul() {}\n\n#[inline]\npub fn div() {}\n\nfn private() {}\n";
+        let coverage = compute_doc_coverage("src/math.rs", source, "rust").expect("rust coverage");
+
+        assert_eq!(coverage.public_items, 4);
+        assert_eq!(coverage.documented, 2);
+        assert!((coverage.doc_coverage - 50.0).abs() < 1e-9);
+    }
+}
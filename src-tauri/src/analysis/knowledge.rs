@@ -1,20 +1,67 @@
 use git2::Repository;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 /// Blame data: file → author → line count
 pub type BlameData = HashMap<String, HashMap<String, usize>>;
 
-/// Analyze knowledge concentration via git blame
-pub fn analyze_knowledge(workspace_path: &str) -> Result<BlameData, String> {
+/// Author activity: author → timestamp of their most recent commit
+pub type AuthorActivity = HashMap<String, i64>;
+
+/// Ownership fraction at which a file counts as single-owner
+pub const SOLE_OWNER_FRACTION: f64 = 0.8;
+
+/// Ownership fraction at which an author counts among a file's dominant authors
+pub const DOMINANT_AUTHOR_FRACTION: f64 = 0.3;
+
+/// Another file owned by the same dominant author(s)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OwnershipCoupling {
+    pub relative_path: String,
+    /// Authors dominant in both files
+    pub shared_authors: Vec<String>,
+    /// Σ over authors of the smaller ownership share in either file (0–1)
+    pub overlap: f64,
+}
+
+/// Debt sitting in files an author is the dominant (largest blame share)
+/// owner of. Dominant-owner attribution is a heuristic for who to talk to,
+/// not a record of who introduced the debt.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuthorDebt {
+    pub author: String,
+    /// Sum of composite scores of the author's dominant-owned files
+    pub total_score: f64,
+    pub file_count: usize,
+    pub files: Vec<String>,
+}
+
+/// A file owned by one author who has gone quiet in the repository
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KnowledgeRisk {
+    pub relative_path: String,
+    pub owner: String,
+    pub ownership: f64,
+    pub last_commit_at: Option<i64>,
+    pub days_inactive: Option<i64>,
+}
+
+/// Analyze knowledge concentration via git blame at `analysis_ref`
+/// (HEAD when `None`)
+pub fn analyze_knowledge(workspace_path: &str, analysis_ref: Option<&str>) -> Result<BlameData, String> {
     let repo = Repository::open(workspace_path)
         .map_err(|e| format!("Git error: {}", e))?;
 
     let mut blame_data = BlameData::new();
 
+    // A repo with no commits yet has nothing to blame
+    let Some(start) = crate::commands::git::resolve_analysis_ref(&repo, analysis_ref)? else {
+        return Ok(blame_data);
+    };
+
     // Walk tracked files and blame each one
-    let head = repo.head()
-        .map_err(|e| format!("Head error: {}", e))?;
-    let tree = head.peel_to_tree()
+    let tree = repo.find_commit(start)
+        .and_then(|commit| commit.tree())
         .map_err(|e| format!("Tree error: {}", e))?;
 
     tree.walk(git2::TreeWalkMode::PreOrder, |root, entry| {
@@ -27,16 +74,7 @@ pub fn analyze_knowledge(workspace_path: &str) -> Result<BlameData, String> {
             };
 
             if is_source_file(&path) {
-                if let Ok(blame) = repo.blame_file(std::path::Path::new(&path), None) {
-                    let mut authors: HashMap<String, usize> = HashMap::new();
-                    for i in 0..blame.len() {
-                        if let Some(hunk) = blame.get_index(i) {
-                            let sig = hunk.final_signature();
-                            let author = sig.name().unwrap_or("unknown").to_string();
-                            let lines = hunk.lines_in_hunk();
-                            *authors.entry(author).or_insert(0) += lines;
-                        }
-                    }
+                if let Some(authors) = blame_authors(&repo, &path, Some(start)) {
                     blame_data.insert(path, authors);
                 }
             }
@@ -47,6 +85,201 @@ pub fn analyze_knowledge(workspace_path: &str) -> Result<BlameData, String> {
     Ok(blame_data)
 }
 
+/// Lines per author for one file, blamed as of `newest` (HEAD when `None`)
+pub fn blame_authors(
+    repo: &Repository,
+    relative_path: &str,
+    newest: Option<git2::Oid>,
+) -> Option<HashMap<String, usize>> {
+    let mut options = git2::BlameOptions::new();
+    if let Some(oid) = newest {
+        options.newest_commit(oid);
+    }
+    let blame = repo.blame_file(std::path::Path::new(relative_path), Some(&mut options)).ok()?;
+
+    let mut authors: HashMap<String, usize> = HashMap::new();
+    for i in 0..blame.len() {
+        if let Some(hunk) = blame.get_index(i) {
+            let sig = hunk.final_signature();
+            let author = sig.name().unwrap_or("unknown").to_string();
+            let lines = hunk.lines_in_hunk();
+            *authors.entry(author).or_insert(0) += lines;
+        }
+    }
+    Some(authors)
+}
+
+/// Record the most recent commit time of every author reachable from HEAD
+pub fn analyze_author_activity(workspace_path: &str) -> Result<AuthorActivity, String> {
+    let repo = Repository::open(workspace_path)
+        .map_err(|e| format!("Git error: {}", e))?;
+
+    let mut revwalk = repo.revwalk()
+        .map_err(|e| format!("Revwalk error: {}", e))?;
+    revwalk.push_head().ok();
+
+    let mut activity = AuthorActivity::new();
+    for oid in revwalk.flatten() {
+        let commit = match repo.find_commit(oid) {
+            Ok(c) => c,
+            Err(_) => continue,
+        };
+        let author = commit.author().name().unwrap_or("unknown").to_string();
+        let time = commit.time().seconds();
+        let latest = activity.entry(author).or_insert(time);
+        *latest = (*latest).max(time);
+    }
+
+    Ok(activity)
+}
+
+/// Files whose top author owns at least `SOLE_OWNER_FRACTION` of the lines
+/// and has not committed anywhere in the repo within `inactive_days`.
+/// Sorted by ownership, then longest inactivity first.
+pub fn compute_knowledge_risk(
+    blame_data: &BlameData,
+    activity: &AuthorActivity,
+    inactive_days: u32,
+    now: i64,
+) -> Vec<KnowledgeRisk> {
+    let cutoff = now - inactive_days as i64 * 86400;
+    let mut risks: Vec<KnowledgeRisk> = blame_data
+        .iter()
+        .filter_map(|(path, authors)| {
+            let total_lines: usize = authors.values().sum();
+            if total_lines == 0 {
+                return None;
+            }
+            let (owner, lines) = authors
+                .iter()
+                .max_by(|a, b| a.1.cmp(b.1).then_with(|| b.0.cmp(a.0)))?;
+            let ownership = *lines as f64 / total_lines as f64;
+            if ownership < SOLE_OWNER_FRACTION {
+                return None;
+            }
+            let last_commit_at = activity.get(owner).copied();
+            if last_commit_at.is_some_and(|t| t >= cutoff) {
+                return None;
+            }
+            Some(KnowledgeRisk {
+                relative_path: path.clone(),
+                owner: owner.clone(),
+                ownership,
+                last_commit_at,
+                days_inactive: last_commit_at.map(|t| (now - t) / 86400),
+            })
+        })
+        .collect();
+
+    risks.sort_by(|a, b| {
+        b.ownership
+            .total_cmp(&a.ownership)
+            .then_with(|| b.days_inactive.unwrap_or(i64::MAX).cmp(&a.days_inactive.unwrap_or(i64::MAX)))
+            .then_with(|| a.relative_path.cmp(&b.relative_path))
+    });
+    risks
+}
+
+/// Files sharing at least one dominant author with `relative_path`, ranked
+/// by how closely their ownership matches
+pub fn compute_ownership_coupling(blame_data: &BlameData, relative_path: &str) -> Vec<OwnershipCoupling> {
+    let Some(target) = blame_data.get(relative_path).map(ownership_shares) else {
+        return Vec::new();
+    };
+
+    let mut coupled: Vec<OwnershipCoupling> = blame_data
+        .iter()
+        .filter(|(path, _)| path.as_str() != relative_path)
+        .filter_map(|(path, authors)| {
+            let shares = ownership_shares(authors);
+            let mut shared_authors: Vec<String> = target
+                .iter()
+                .filter(|(author, share)| {
+                    **share >= DOMINANT_AUTHOR_FRACTION
+                        && shares.get(*author).is_some_and(|other| *other >= DOMINANT_AUTHOR_FRACTION)
+                })
+                .map(|(author, _)| author.clone())
+                .collect();
+            if shared_authors.is_empty() {
+                return None;
+            }
+            shared_authors.sort();
+            let overlap = target
+                .iter()
+                .filter_map(|(author, share)| shares.get(author).map(|other| share.min(*other)))
+                .sum();
+            Some(OwnershipCoupling {
+                relative_path: path.clone(),
+                shared_authors,
+                overlap,
+            })
+        })
+        .collect();
+
+    coupled.sort_by(|a, b| {
+        b.overlap
+            .total_cmp(&a.overlap)
+            .then_with(|| a.relative_path.cmp(&b.relative_path))
+    });
+    coupled
+}
+
+/// Per-author sums of `scores` (relative path → composite) over the files
+/// each author dominates in blame, highest total first. Files without blame
+/// data are left out; equal line counts go to the alphabetically first author.
+pub fn compute_author_debt<'a>(
+    blame_data: &BlameData,
+    scores: impl IntoIterator<Item = (&'a str, f64)>,
+) -> Vec<AuthorDebt> {
+    let mut by_author: HashMap<String, AuthorDebt> = HashMap::new();
+    for (relative_path, score) in scores {
+        let Some(owner) = blame_data.get(relative_path).and_then(dominant_owner) else {
+            continue;
+        };
+        let entry = by_author.entry(owner.clone()).or_insert_with(|| AuthorDebt {
+            author: owner,
+            total_score: 0.0,
+            file_count: 0,
+            files: Vec::new(),
+        });
+        entry.total_score += score;
+        entry.file_count += 1;
+        entry.files.push(relative_path.to_string());
+    }
+
+    let mut debts: Vec<AuthorDebt> = by_author.into_values().collect();
+    for debt in &mut debts {
+        debt.files.sort();
+    }
+    debts.sort_by(|a, b| {
+        b.total_score
+            .total_cmp(&a.total_score)
+            .then_with(|| a.author.cmp(&b.author))
+    });
+    debts
+}
+
+/// Author with the most blamed lines in a file
+fn dominant_owner(authors: &HashMap<String, usize>) -> Option<String> {
+    authors
+        .iter()
+        .filter(|(_, lines)| **lines > 0)
+        .max_by(|a, b| a.1.cmp(b.1).then_with(|| b.0.cmp(a.0)))
+        .map(|(author, _)| author.clone())
+}
+
+/// Each author's fraction of a file's blamed lines
+fn ownership_shares(authors: &HashMap<String, usize>) -> HashMap<String, f64> {
+    let total_lines: usize = authors.values().sum();
+    if total_lines == 0 {
+        return HashMap::new();
+    }
+    authors
+        .iter()
+        .map(|(author, lines)| (author.clone(), *lines as f64 / total_lines as f64))
+        .collect()
+}
+
 /// Compute knowledge concentration score for a single file (0–100)
 /// Score = max(0, (concentration - 0.5) / 0.5 * 100)
 /// Only triggers when top author concentration > 50%
@@ -74,7 +307,8 @@ pub fn compute_knowledge_concentration(blame_data: &BlameData, relative_path: &s
 fn is_source_file(path: &str) -> bool {
     matches!(
         std::path::Path::new(path).extension().and_then(|e| e.to_str()),
-        Some("ts") | Some("tsx") | Some("js") | Some("jsx") | Some("py") | Some("go") | Some("rs") | Some("java")
+        Some("ts") | Some("tsx") | Some("js") | Some("jsx") | Some("mjs") | Some("cjs") | Some("py") | Some("go")
+            | Some("rs") | Some("java")
     )
 }
 
@@ -121,5 +355,61 @@ mod tests {
         let score = compute_knowledge_concentration(&blame, "lib.rs");
         assert!((score - 50.0).abs() < 1e-6, "Expected 50.0, got {score}");
     }
-}
 
+    #[test]
+    fn inactive_sole_owner_is_risk_and_active_sole_owner_is_not() {
+        let now = 1_700_000_000;
+        let mut blame = BlameData::new();
+        blame.insert("active.rs".to_string(), HashMap::from([("Alice".to_string(), 40)]));
+        blame.insert("inactive.rs".to_string(), HashMap::from([("Bob".to_string(), 40)]));
+        let activity = AuthorActivity::from([
+            ("Alice".to_string(), now - 5 * 86400),
+            ("Bob".to_string(), now - 200 * 86400),
+        ]);
+
+        let risks = compute_knowledge_risk(&blame, &activity, 90, now);
+
+        assert_eq!(risks.len(), 1);
+        assert_eq!(risks[0].relative_path, "inactive.rs");
+        assert_eq!(risks[0].owner, "Bob");
+        assert_eq!(risks[0].days_inactive, Some(200));
+    }
+
+    #[test]
+    fn files_dominated_by_the_same_author_are_ownership_coupled() {
+        let mut blame = BlameData::new();
+        blame.insert(
+            "parser.rs".to_string(),
+            HashMap::from([("Alice".to_string(), 90), ("Bob".to_string(), 10)]),
+        );
+        blame.insert("lexer.rs".to_string(), HashMap::from([("Alice".to_string(), 40)]));
+        blame.insert(
+            "ast.rs".to_string(),
+            HashMap::from([("Alice".to_string(), 30), ("Carol".to_string(), 70)]),
+        );
+        blame.insert("ui.rs".to_string(), HashMap::from([("Carol".to_string(), 50)]));
+
+        let coupled = compute_ownership_coupling(&blame, "parser.rs");
+
+        let paths: Vec<&str> = coupled.iter().map(|c| c.relative_path.as_str()).collect();
+        assert_eq!(paths, vec!["lexer.rs", "ast.rs"]);
+        assert_eq!(coupled[0].shared_authors, vec!["Alice".to_string()]);
+        assert!((coupled[0].overlap - 0.9).abs() < 1e-9);
+        assert!((coupled[1].overlap - 0.3).abs() < 1e-9);
+        assert!(compute_ownership_coupling(&blame, "missing.rs").is_empty());
+    }
+
+    #[test]
+    fn sole_committer_owns_all_file_debt() {
+        let mut blame = BlameData::new();
+        blame.insert("main.rs".to_string(), HashMap::from([("Alice".to_string(), 40)]));
+        blame.insert("lib.rs".to_string(), HashMap::from([("Alice".to_string(), 25)]));
+
+        let debts = compute_author_debt(&blame, [("main.rs", 30.0), ("lib.rs", 50.0), ("new.rs", 10.0)]);
+
+        assert_eq!(debts.len(), 1);
+        assert_eq!(debts[0].author, "Alice");
+        assert!((debts[0].total_score - 80.0).abs() < 1e-9);
+        assert_eq!(debts[0].files, vec!["lib.rs".to_string(), "main.rs".to_string()]);
+    }
+}
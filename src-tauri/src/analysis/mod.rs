@@ -5,3 +5,7 @@ pub mod coverage;
 pub mod knowledge;
 pub mod complexity;
 pub mod staleness;
+pub mod roles;
+pub mod dependencies;
+pub mod cycles;
+pub mod docs;
@@ -0,0 +1,86 @@
+use std::path::Path;
+
+/// Heuristically tag a file with its role from its path and content.
+/// Checks run from most to least specific; anything unmatched is `source`.
+pub fn classify_file(relative_path: &str, source: &str) -> &'static str {
+    let normalized = relative_path.replace('\\', "/").to_lowercase();
+    let path = Path::new(&normalized);
+    let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+
+    if crate::analysis::coverage::is_test_file(relative_path) {
+        return "test";
+    }
+
+    if is_migration(&normalized, &file_name) {
+        return "migration";
+    }
+
+    if is_config(&file_name, &stem) {
+        return "config";
+    }
+
+    if is_entrypoint(&file_name, source) {
+        return "entrypoint";
+    }
+
+    "source"
+}
+
+fn is_migration(normalized: &str, file_name: &str) -> bool {
+    let in_migration_dir = normalized
+        .split('/')
+        .any(|segment| segment == "migrations" || segment == "migrate")
+        || normalized.contains("alembic/versions/");
+
+    // Timestamped/numbered files like `0001_init.py` or `V2__add_column.java`
+    let numbered = file_name.chars().take_while(|c| c.is_ascii_digit()).count() >= 3
+        || (file_name.starts_with('v') && file_name.contains("__"));
+
+    in_migration_dir || (numbered && normalized.contains("migrat"))
+}
+
+fn is_config(file_name: &str, stem: &str) -> bool {
+    stem.ends_with(".config")
+        || matches!(stem, "config" | "settings" | "conf" | "configuration" | "setup" | "build")
+        || file_name.starts_with('.')
+}
+
+fn is_entrypoint(file_name: &str, source: &str) -> bool {
+    if matches!(
+        file_name,
+        "main.rs"
+            | "main.go"
+            | "main.py"
+            | "__main__.py"
+            | "manage.py"
+            | "main.ts"
+            | "main.tsx"
+            | "main.js"
+            | "index.ts"
+            | "index.tsx"
+            | "index.js"
+            | "index.jsx"
+            | "main.java"
+    ) {
+        return true;
+    }
+
+    source.contains("if __name__ == \"__main__\"")
+        || source.contains("public static void main(")
+        || (source.contains("package main") && source.contains("func main()"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_entrypoints_tests_and_plain_sources() {
+        assert_eq!(classify_file("src/main.rs", "fn main() {}"), "entrypoint");
+        assert_eq!(classify_file("src/foo.test.ts", "it('works', () => {})"), "test");
+        assert_eq!(classify_file("db/migrations/0001_init.py", ""), "migration");
+        assert_eq!(classify_file("vite.config.ts", "export default {}"), "config");
+        assert_eq!(classify_file("src/utils.rs", "pub fn helper() {}"), "source");
+    }
+}
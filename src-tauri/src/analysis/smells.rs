@@ -1,9 +1,18 @@
-use crate::commands::ast::FileSmells;
+use crate::commands::ast::{FileSmells, HEURISTIC_CONFIDENCE};
+use std::collections::HashMap;
 
 /// Simple code smell detection using line-by-line heuristics.
 /// For a production version, this would use tree-sitter AST traversal.
-pub fn detect_smells(source: &str, language: &str, loc: usize) -> FileSmells {
+/// `magic_number_allowlist` maps languages to numbers allowed on top of
+/// `MAGIC_NUMBER_ALLOWLIST` (the `magicNumberAllowlist` setting).
+pub fn detect_smells(
+    source: &str,
+    language: &str,
+    loc: usize,
+    magic_number_allowlist: &HashMap<String, Vec<f64>>,
+) -> FileSmells {
     let lines: Vec<&str> = source.lines().collect();
+    let language_allowed = magic_number_allowlist.get(language).map_or(&[][..], Vec::as_slice);
     let mut smells = FileSmells {
         god_function: 0,
         deep_nesting: 0,
@@ -13,10 +22,17 @@ pub fn detect_smells(source: &str, language: &str, loc: usize) -> FileSmells {
         magic_number: 0,
         empty_catch: 0,
         todo_fixme: 0,
+        ignored_error: 0,
         total: 0,
         loc,
+        confidence: HEURISTIC_CONFIDENCE.to_string(),
     };
 
+    // Items under `#[cfg(test)]` may unwrap freely. The attribute arms the
+    // next item; its region ends once braces return to the item's depth.
+    let mut cfg_test_pending = false;
+    let mut cfg_test_depth: Option<i32> = None;
+
     // Track function/method bodies for god function detection
     let mut current_func_lines = 0;
     let mut in_function = false;
@@ -34,6 +50,17 @@ pub fn detect_smells(source: &str, language: &str, loc: usize) -> FileSmells {
             }
         }
 
+        // Swallowed errors
+        if language == "rust" && trimmed.starts_with("#[cfg(test)]") {
+            cfg_test_pending = true;
+        } else if cfg_test_pending && !trimmed.is_empty() && !trimmed.starts_with("#[") && !is_comment(trimmed, language) {
+            cfg_test_pending = false;
+            cfg_test_depth = Some(brace_depth);
+        }
+        if !cfg_test_pending && cfg_test_depth.is_none() && !is_comment(trimmed, language) {
+            smells.ignored_error += count_ignored_errors(strip_trailing_comment(trimmed), language);
+        }
+
         // Track brace depth for nesting and function detection
         let opens = line.matches('{').count() as i32;
         let closes = line.matches('}').count() as i32;
@@ -47,6 +74,11 @@ pub fn detect_smells(source: &str, language: &str, loc: usize) -> FileSmells {
 
         brace_depth += opens - closes;
 
+        let item_ended = closes > 0 || trimmed.ends_with(';');
+        if cfg_test_depth.is_some_and(|depth| brace_depth <= depth && item_ended) {
+            cfg_test_depth = None;
+        }
+
         if in_function {
             current_func_lines += 1;
 
@@ -78,7 +110,7 @@ pub fn detect_smells(source: &str, language: &str, loc: usize) -> FileSmells {
         if !trimmed.starts_with("const ") && !trimmed.starts_with("let ") 
             && !trimmed.starts_with("var ") && !is_comment(trimmed, language) 
         {
-            let magic_count = count_magic_numbers(trimmed);
+            let magic_count = count_magic_numbers(trimmed, language_allowed);
             smells.magic_number += magic_count;
         }
 
@@ -107,11 +139,107 @@ pub fn detect_smells(source: &str, language: &str, loc: usize) -> FileSmells {
 
     smells.total = smells.god_function + smells.deep_nesting + smells.long_param_list
         + smells.duplicate_block + smells.dead_import + smells.magic_number
-        + smells.empty_catch + smells.todo_fixme;
+        + smells.empty_catch + smells.todo_fixme + smells.ignored_error;
 
     smells
 }
 
+/// `detect_smells` for a known path: test files are expected to unwrap and
+/// discard errors, so `ignored_error` is not counted for them.
+pub fn detect_smells_in_file(
+    relative_path: &str,
+    source: &str,
+    language: &str,
+    loc: usize,
+    magic_number_allowlist: &HashMap<String, Vec<f64>>,
+) -> FileSmells {
+    let mut smells = detect_smells(source, language, loc, magic_number_allowlist);
+    if crate::analysis::coverage::is_test_file(relative_path) {
+        smells.total -= smells.ignored_error;
+        smells.ignored_error = 0;
+    }
+    smells
+}
+
+/// Smell kinds tracked in snapshot smell totals, plus `total`
+pub const SMELL_TYPES: [&str; 10] = [
+    "god_function",
+    "deep_nesting",
+    "long_param_list",
+    "duplicate_block",
+    "dead_import",
+    "magic_number",
+    "empty_catch",
+    "todo_fixme",
+    "ignored_error",
+    "total",
+];
+
+/// Per-kind counts keyed by the names in `SMELL_TYPES`
+pub fn smell_counts(smells: &FileSmells) -> std::collections::BTreeMap<String, usize> {
+    [
+        smells.god_function,
+        smells.deep_nesting,
+        smells.long_param_list,
+        smells.duplicate_block,
+        smells.dead_import,
+        smells.magic_number,
+        smells.empty_catch,
+        smells.todo_fixme,
+        smells.ignored_error,
+        smells.total,
+    ]
+    .into_iter()
+    .zip(SMELL_TYPES)
+    .map(|(count, name)| (name.to_string(), count))
+    .collect()
+}
+
+/// Cuts a line at its `//` comment, ignoring `//` inside `"…"` or Go's
+/// backtick strings so `"https://…".parse().unwrap()` keeps its unwrap.
+/// Single quotes are not tracked: in Rust they also start lifetimes.
+fn strip_trailing_comment(line: &str) -> &str {
+    let mut quote = None;
+    let mut escaped = false;
+    let mut chars = line.char_indices().peekable();
+    while let Some((index, c)) = chars.next() {
+        match quote {
+            Some(open) => {
+                if escaped {
+                    escaped = false;
+                } else if c == '\\' && open == '"' {
+                    escaped = true;
+                } else if c == open {
+                    quote = None;
+                }
+            }
+            None if c == '"' || c == '`' => quote = Some(c),
+            None if c == '/' && chars.peek().is_some_and(|&(_, next)| next == '/') => {
+                return &line[..index];
+            }
+            None => {}
+        }
+    }
+    line
+}
+
+fn count_ignored_errors(line: &str, language: &str) -> usize {
+    match language {
+        "rust" => {
+            let discarded = usize::from(line.starts_with("let _ ="));
+            discarded + line.matches(".unwrap()").count() + line.matches(".expect(").count()
+        }
+        "go" => usize::from(
+            line.starts_with("_ =")
+                || line.starts_with("_, _ =")
+                || line.starts_with("_, _ :=")
+                || line.contains(", _ =")
+                || line.contains(", _ :="),
+        ),
+        _ => 0,
+    }
+}
+
 fn is_comment(line: &str, language: &str) -> bool {
     match language {
         "python" => line.starts_with('#'),
@@ -157,20 +285,88 @@ fn count_parameters(line: &str) -> usize {
     0
 }
 
-fn count_magic_numbers(line: &str) -> usize {
-    let allowed = [0.0f64, 1.0, -1.0, 2.0, 100.0];
+/// Numbers never flagged in any language
+const MAGIC_NUMBER_ALLOWLIST: [f64; 5] = [0.0, 1.0, -1.0, 2.0, 100.0];
+
+/// Identifier words whose literal arguments are conventional, not magic
+/// (`res.status(404)`, `port: 8080`, `os.Exit(3)`)
+const MAGIC_NUMBER_CONTEXT_WORDS: [&str; 5] = ["status", "port", "http", "exit", "code"];
+
+/// Default `magicNumberAllowlist`: extra per-language allowances on top of
+/// the global allowlist
+pub fn default_magic_number_allowlist() -> HashMap<String, Vec<f64>> {
+    // Millisecond timeouts and seconds-per-minute conversions
+    let web = vec![10.0, 60.0, 1000.0];
+    // Bit sizes passed to strconv and friends
+    let bit_sizes = vec![8.0, 16.0, 32.0, 64.0];
+    HashMap::from([
+        ("typescript".to_string(), web.clone()),
+        ("javascript".to_string(), web),
+        ("go".to_string(), vec![8.0, 16.0, 32.0, 64.0, 10.0]),
+        ("rust".to_string(), bit_sizes.clone()),
+        ("c".to_string(), bit_sizes.clone()),
+        ("cpp".to_string(), bit_sizes),
+        ("python".to_string(), vec![10.0, 60.0]),
+    ])
+}
+
+fn count_magic_numbers(line: &str, language_allowed: &[f64]) -> usize {
+    let is_separator = |c: char| !c.is_ascii_digit() && c != '.' && c != '-';
     let mut count = 0;
+    let mut start = 0;
 
-    for word in line.split(|c: char| !c.is_ascii_digit() && c != '.' && c != '-') {
-        if let Ok(num) = word.parse::<f64>() {
-            if !allowed.contains(&num) && word.len() > 0 && word != "0" {
-                count += 1;
-            }
+    for (end, c) in line.char_indices().chain(std::iter::once((line.len(), ' '))) {
+        if !is_separator(c) {
+            continue;
+        }
+        let word = &line[start..end];
+        let word_start = start;
+        start = end + c.len_utf8();
+
+        let Ok(num) = word.parse::<f64>() else { continue };
+        if word.is_empty()
+            || word == "0"
+            || MAGIC_NUMBER_ALLOWLIST.contains(&num)
+            || language_allowed.contains(&num)
+            || has_exempt_context(&line[..word_start])
+        {
+            continue;
         }
+        count += 1;
     }
     count
 }
 
+/// Whether the identifier just before a number names a status/port/exit-code
+/// context. Splits camelCase and snake_case so `statusCode` and `listen_port`
+/// match but `report` does not.
+fn has_exempt_context(prefix: &str) -> bool {
+    let is_ident = |c: char| c.is_ascii_alphanumeric() || c == '_';
+    let before = prefix.trim_end_matches(|c: char| !is_ident(c));
+    let ident_start = before
+        .char_indices()
+        .rev()
+        .take_while(|(_, c)| is_ident(*c))
+        .last()
+        .map(|(i, _)| i)
+        .unwrap_or(before.len());
+    let ident = &before[ident_start..];
+
+    let mut words = Vec::new();
+    let mut current = String::new();
+    for c in ident.chars() {
+        if c == '_' || (c.is_ascii_uppercase() && !current.is_empty()) {
+            words.push(std::mem::take(&mut current));
+        }
+        if c != '_' {
+            current.push(c.to_ascii_lowercase());
+        }
+    }
+    words.push(current);
+
+    words.iter().any(|w| MAGIC_NUMBER_CONTEXT_WORDS.contains(&w.as_str()))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -178,7 +374,7 @@ mod tests {
     #[test]
     fn detects_todo_comment() {
         let source = "// TODO: fix this later\nlet x = 1;\n";
-        let smells = detect_smells(source, "typescript", 2);
+        let smells = detect_smells(source, "typescript", 2, &default_magic_number_allowlist());
         assert_eq!(smells.todo_fixme, 1);
     }
 
@@ -192,28 +388,28 @@ mod tests {
         lines.push("}".to_string());
         let source = lines.join("\n");
 
-        let smells = detect_smells(&source, "typescript", lines.len());
+        let smells = detect_smells(&source, "typescript", lines.len(), &default_magic_number_allowlist());
         assert_eq!(smells.god_function, 1, "Should detect one god function");
     }
 
     #[test]
     fn detects_long_param_list() {
         let source = "function foo(a, b, c, d, e, f) {\n  return a;\n}\n";
-        let smells = detect_smells(source, "typescript", 3);
+        let smells = detect_smells(source, "typescript", 3, &default_magic_number_allowlist());
         assert!(smells.long_param_list >= 1, "Should detect long param list");
     }
 
     #[test]
     fn detects_empty_catch_block() {
         let source = "try {\n  foo();\n} catch(e) {\n}\n";
-        let smells = detect_smells(source, "typescript", 4);
+        let smells = detect_smells(source, "typescript", 4, &default_magic_number_allowlist());
         assert_eq!(smells.empty_catch, 1);
     }
 
     #[test]
     fn total_equals_sum_of_all_smells() {
         let source = "// TODO: fix\nfunction foo(a, b, c, d, e, f) { return 42; }\n";
-        let smells = detect_smells(source, "typescript", 2);
+        let smells = detect_smells(source, "typescript", 2, &default_magic_number_allowlist());
         let expected = smells.god_function + smells.deep_nesting + smells.long_param_list
             + smells.duplicate_block + smells.dead_import + smells.magic_number
             + smells.empty_catch + smells.todo_fixme;
@@ -223,7 +419,7 @@ mod tests {
     #[test]
     fn zero_smells_for_clean_code() {
         let source = "const x = 1;\n";
-        let smells = detect_smells(source, "typescript", 1);
+        let smells = detect_smells(source, "typescript", 1, &default_magic_number_allowlist());
         assert_eq!(smells.todo_fixme, 0);
         assert_eq!(smells.god_function, 0);
         assert_eq!(smells.empty_catch, 0);
@@ -234,5 +430,58 @@ mod tests {
         assert_eq!(count_parameters("function foo(a, b, c)"), 3);
         assert_eq!(count_parameters("function foo()"), 0);
     }
-}
 
+    fn allowed(language: &str) -> Vec<f64> {
+        default_magic_number_allowlist().remove(language).unwrap_or_default()
+    }
+
+    #[test]
+    fn status_codes_and_ports_are_not_magic_numbers() {
+        assert_eq!(count_magic_numbers("res.status(404);", &allowed("javascript")), 0);
+        assert_eq!(count_magic_numbers("server.listen({ port: 8080 });", &allowed("typescript")), 0);
+        assert_eq!(count_magic_numbers("os.Exit(3)", &allowed("go")), 0);
+        assert_eq!(count_magic_numbers("x = 404;", &allowed("javascript")), 1);
+        assert_eq!(count_magic_numbers("report(404);", &allowed("javascript")), 1);
+        assert_eq!(count_magic_numbers("setTimeout(fn, 1000);", &allowed("typescript")), 0);
+        assert_eq!(count_magic_numbers("sleep(1000)", &allowed("python")), 1);
+    }
+
+    #[test]
+    fn counts_rust_unwrap_as_ignored_error() {
+        let source = "fn load() -> Config {\n    let raw = read().unwrap(); // config must exist\n    parse(&raw)\n}\n";
+        let smells = detect_smells(source, "rust", 4, &default_magic_number_allowlist());
+        assert_eq!(smells.ignored_error, 1);
+    }
+
+    #[test]
+    fn comment_markers_inside_strings_do_not_hide_unwraps() {
+        let source = "fn endpoint() -> Url {\n    \"https://api.example.com\".parse().unwrap() // always valid\n}\n";
+        assert_eq!(detect_smells(source, "rust", 3, &default_magic_number_allowlist()).ignored_error, 1);
+
+        assert_eq!(strip_trailing_comment(r#"let s = "a \" // b"; // note"#), r#"let s = "a \" // b"; "#);
+        assert_eq!(strip_trailing_comment("_ = get(`http://x`) // ok"), "_ = get(`http://x`) ");
+    }
+
+    #[test]
+    fn counts_go_blank_error_assignment() {
+        let source = "func run() {\n\t_, _ = foo()\n}\n";
+        let smells = detect_smells(source, "go", 3, &default_magic_number_allowlist());
+        assert_eq!(smells.ignored_error, 1);
+    }
+
+    #[test]
+    fn propagated_errors_and_test_code_are_not_counted() {
+        let source = "fn load() -> Result<Config> {\n    let raw = read()?;\n    Ok(parse(&raw)?)\n}\n";
+        assert_eq!(detect_smells(source, "rust", 4, &default_magic_number_allowlist()).ignored_error, 0);
+
+        let with_tests = "fn ok() {}\n#[cfg(test)]\nmod tests {\n    fn t() { load().unwrap(); }\n}\n";
+        assert_eq!(detect_smells(with_tests, "rust", 5, &default_magic_number_allowlist()).ignored_error, 0);
+
+        let after_test_items = "#[cfg(test)]\nuse mock::Client;\n#[cfg(test)]\nfn fixture() -> Config {\n    load().unwrap()\n}\nfn run() {\n    load().unwrap();\n}\n#[cfg(test)]\nmod tests {\n    #[test]\n    fn t() {\n        load().unwrap();\n    }\n}\nfn after() { load().unwrap(); }\n";
+        assert_eq!(detect_smells(after_test_items, "rust", 17, &default_magic_number_allowlist()).ignored_error, 2);
+
+        let unwrap = "fn t() { load().unwrap(); }\n";
+        assert_eq!(detect_smells_in_file("tests/load.rs", unwrap, "rust", 1, &default_magic_number_allowlist()).ignored_error, 0);
+        assert_eq!(detect_smells_in_file("src/load.rs", unwrap, "rust", 1, &default_magic_number_allowlist()).ignored_error, 1);
+    }
+}
@@ -1,10 +1,72 @@
-use std::path::Path;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
 
 /// Compute decision staleness score (0–100)
-/// - Files with linked ADRs not reviewed in >180 days = 100
+/// - Files with linked ADRs not reviewed in more than `stale_days` = 100
 /// - No ADR for complex files (smell > 30) = 50
 /// - ADR reviewed <30 days ago = 0
-pub fn compute_staleness(relative_path: &str, workspace_path: &str, smell_score: f64) -> f64 {
+pub fn compute_staleness(relative_path: &str, workspace_path: &str, smell_score: f64, stale_days: i64) -> f64 {
+    if let Some(adr_path) = find_adr_for(relative_path, workspace_path) {
+        // Parse ADR for last_reviewed_at date
+        if let Some(days_since_review) = adr_review_age_days(&adr_path) {
+            return staleness_for_age(days_since_review, stale_days);
+        }
+
+        // ADR exists but no review date — moderate staleness
+        return 50.0;
+    }
+
+    // No ADR found: penalty only for complex files
+    if smell_score > 30.0 {
+        return 50.0;
+    }
+
+    0.0
+}
+
+/// Review age after which an ADR counts as stale (default `adrStaleDays`)
+pub const DEFAULT_ADR_STALE_DAYS: i64 = 180;
+
+/// 0 when reviewed within 30 days, 100 after `stale_days`, linear in between
+fn staleness_for_age(days_since_review: i64, stale_days: i64) -> f64 {
+    if days_since_review < 30 {
+        0.0 // Recently reviewed
+    } else if days_since_review > stale_days {
+        100.0 // Stale
+    } else {
+        // Linear interpolation between 30 and `stale_days`
+        ((days_since_review - 30) as f64 / (stale_days - 30).max(1) as f64 * 100.0).min(100.0)
+    }
+}
+
+/// "Frozen complexity" penalty: complex files nobody has committed to in the
+/// history window, where knowledge of the code erodes. Returns `penalty` when
+/// `complexity_raw` reaches `threshold` and the file has no commits; a zero
+/// threshold disables the signal.
+pub fn frozen_complexity_penalty(complexity_raw: f64, commit_count: usize, threshold: f64, penalty: f64) -> f64 {
+    if threshold <= 0.0 || commit_count > 0 || complexity_raw < threshold {
+        return 0.0;
+    }
+    penalty
+}
+
+/// Staleness of a documentation file (`trackDocs`), treating the time of its
+/// last commit (see `churn::last_change_times`) as its review date.
+/// Uncommitted docs count as fresh.
+pub fn compute_doc_staleness(last_changed_at: Option<i64>, stale_days: i64) -> f64 {
+    last_changed_at
+        .map(|time| staleness_for_age((chrono::Utc::now().timestamp() - time) / 86400, stale_days))
+        .unwrap_or(0.0)
+}
+
+/// Locate the ADR linked to a file: `.debtengine/adrs/{stem}.adr.md`,
+/// `.debtengine/adrs/{stem}.md`, or `{stem}.adr.md` next to the file.
+pub fn find_adr_for(relative_path: &str, workspace_path: &str) -> Option<PathBuf> {
+    find_adrs_for(relative_path, workspace_path).into_iter().next()
+}
+
+/// Every existing ADR candidate for a file, in lookup order
+pub fn find_adrs_for(relative_path: &str, workspace_path: &str) -> Vec<PathBuf> {
     let workspace = Path::new(workspace_path);
 
     // Check for ADR in .debtengine/adrs/ directory
@@ -27,36 +89,26 @@ pub fn compute_staleness(relative_path: &str, workspace_path: &str, smell_score:
     );
     let inline_adr = file_dir.join(format!("{}.adr.md", file_stem));
 
-    for adr_path in adr_patterns.iter().chain(std::iter::once(&inline_adr)) {
-        if adr_path.exists() {
-            // Parse ADR for last_reviewed_at date
-            if let Ok(content) = std::fs::read_to_string(adr_path) {
-                if let Some(days_since_review) = parse_review_date(&content) {
-                    if days_since_review < 30 {
-                        return 0.0; // Recently reviewed
-                    } else if days_since_review > 180 {
-                        return 100.0; // Stale
-                    } else {
-                        // Linear interpolation between 30-180 days
-                        return ((days_since_review - 30) as f64 / 150.0 * 100.0).min(100.0);
-                    }
-                }
-            }
-
-            // ADR exists but no review date — moderate staleness
-            return 50.0;
-        }
-    }
-
-    // No ADR found: penalty only for complex files
-    if smell_score > 30.0 {
-        return 50.0;
-    }
+    adr_patterns
+        .into_iter()
+        .chain(std::iter::once(inline_adr))
+        .filter(|adr_path| adr_path.exists())
+        .collect()
+}
 
-    0.0
+/// Days since an ADR's recorded review date, if it has one
+pub fn adr_review_age_days(adr_path: &Path) -> Option<i64> {
+    let content = std::fs::read_to_string(adr_path).ok()?;
+    parse_review_date(&content)
 }
 
 fn parse_review_date(content: &str) -> Option<i64> {
+    let date = parse_review_naive_date(content)?;
+    let today = chrono::Utc::now().date_naive();
+    Some(today.signed_duration_since(date).num_days())
+}
+
+fn parse_review_naive_date(content: &str) -> Option<chrono::NaiveDate> {
     // Look for frontmatter or inline date patterns
     // Patterns: last_reviewed_at: YYYY-MM-DD, reviewed: YYYY-MM-DD
     for line in content.lines() {
@@ -64,11 +116,195 @@ fn parse_review_date(content: &str) -> Option<i64> {
         if trimmed.starts_with("last_reviewed_at:") || trimmed.starts_with("reviewed:") || trimmed.starts_with("last-reviewed:") {
             let date_str = line.split(':').skip(1).collect::<Vec<&str>>().join(":").trim().to_string();
             if let Ok(date) = chrono::NaiveDate::parse_from_str(date_str.trim(), "%Y-%m-%d") {
-                let today = chrono::Utc::now().date_naive();
-                let diff = today.signed_duration_since(date);
-                return Some(diff.num_days());
+                return Some(date);
             }
         }
     }
     None
 }
+
+/// An ADR linked to a file, with its review status
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AdrInfo {
+    pub path: String,
+    /// `YYYY-MM-DD` from the ADR's review field, if present
+    pub review_date: Option<String>,
+    pub days_since_review: Option<i64>,
+    /// "fresh" (<30 days), "aging", "stale" (past `adrStaleDays`), or "unreviewed"
+    pub staleness: String,
+}
+
+/// All ADRs linked to a file, in lookup order, described as of `today`;
+/// reviews older than `stale_days` are labelled "stale"
+pub fn describe_adrs_for(
+    relative_path: &str,
+    workspace_path: &str,
+    today: chrono::NaiveDate,
+    stale_days: i64,
+) -> Vec<AdrInfo> {
+    find_adrs_for(relative_path, workspace_path)
+        .into_iter()
+        .map(|adr_path| {
+            let review_date = std::fs::read_to_string(&adr_path)
+                .ok()
+                .and_then(|content| parse_review_naive_date(&content));
+            let days_since_review = review_date.map(|date| today.signed_duration_since(date).num_days());
+            let staleness = match days_since_review {
+                None => "unreviewed",
+                Some(days) if days < 30 => "fresh",
+                Some(days) if days > stale_days => "stale",
+                Some(_) => "aging",
+            };
+            AdrInfo {
+                path: adr_path.to_string_lossy().to_string(),
+                review_date: review_date.map(|date| date.format("%Y-%m-%d").to_string()),
+                days_since_review,
+                staleness: staleness.to_string(),
+            }
+        })
+        .collect()
+}
+
+/// An ADR whose last review is older than the stale threshold
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StaleAdr {
+    /// Workspace-relative path of the ADR
+    pub path: String,
+    pub review_date: String,
+    pub days_since_review: i64,
+    pub days_overdue: i64,
+    /// Workspace files the ADR documents, matched by file stem
+    pub documented_files: Vec<String>,
+}
+
+/// ADRs reviewed more than `stale_days` ago, most overdue first: those in
+/// `.debtengine/adrs/` and inline `{stem}.adr.md` files next to `files`.
+/// ADRs without a review date are left to the staleness score.
+/// `files` are workspace-relative paths checked for a matching stem.
+pub fn find_stale_adrs(workspace_path: &str, files: &[String], stale_days: i64) -> Vec<StaleAdr> {
+    let workspace = Path::new(workspace_path);
+    let today = chrono::Utc::now().date_naive();
+    let stem_of = |path: &str| {
+        Path::new(path)
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_default()
+    };
+    let parent_of = |path: &str| Path::new(path).parent().map(Path::to_path_buf).unwrap_or_default();
+
+    // (workspace-relative ADR path, documented stem, directory the documented files must sit in)
+    let mut candidates: Vec<(String, String, Option<PathBuf>)> = Vec::new();
+    if let Ok(entries) = std::fs::read_dir(workspace.join(".debtengine/adrs")) {
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if let Some(stem) = name.strip_suffix(".adr.md").or_else(|| name.strip_suffix(".md")) {
+                candidates.push((format!(".debtengine/adrs/{name}"), stem.to_string(), None));
+            }
+        }
+    }
+    let inline: std::collections::BTreeSet<(String, String, PathBuf)> = files
+        .iter()
+        .filter(|file| !file.ends_with(".adr.md"))
+        .map(|file| {
+            let stem = stem_of(file);
+            let dir = parent_of(file);
+            let adr = dir.join(format!("{stem}.adr.md")).to_string_lossy().replace('\\', "/");
+            (adr, stem, dir)
+        })
+        .filter(|(adr, _, _)| workspace.join(adr).is_file())
+        .collect();
+    candidates.extend(inline.into_iter().map(|(adr, stem, dir)| (adr, stem, Some(dir))));
+
+    let mut stale: Vec<StaleAdr> = candidates
+        .into_iter()
+        .filter_map(|(path, stem, dir)| {
+            let content = std::fs::read_to_string(workspace.join(&path)).ok()?;
+            let review_date = parse_review_naive_date(&content)?;
+            let days_since_review = today.signed_duration_since(review_date).num_days();
+            if days_since_review <= stale_days {
+                return None;
+            }
+            let documented_files = files
+                .iter()
+                .filter(|file| stem_of(file) == stem && !file.ends_with(".adr.md"))
+                .filter(|file| dir.as_ref().is_none_or(|dir| parent_of(file) == *dir))
+                .cloned()
+                .collect();
+            Some(StaleAdr {
+                path,
+                review_date: review_date.format("%Y-%m-%d").to_string(),
+                days_since_review,
+                days_overdue: days_since_review - stale_days,
+                documented_files,
+            })
+        })
+        .collect();
+    stale.sort_by(|a, b| b.days_overdue.cmp(&a.days_overdue).then_with(|| a.path.cmp(&b.path)));
+    stale
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn describes_matching_adr_with_parsed_review_date() {
+        let workspace = tempfile::tempdir().expect("temp workspace");
+        let adrs_dir = workspace.path().join(".debtengine/adrs");
+        std::fs::create_dir_all(&adrs_dir).expect("create adrs dir");
+        let adr_path = adrs_dir.join("parser.adr.md");
+        std::fs::write(&adr_path, "# Parser\nlast_reviewed_at: 2026-01-10\n").expect("write adr");
+
+        let workspace_path = workspace.path().to_string_lossy().to_string();
+        let today = chrono::NaiveDate::from_ymd_opt(2026, 3, 1).expect("date");
+        let adrs = describe_adrs_for("src/parser.rs", &workspace_path, today, DEFAULT_ADR_STALE_DAYS);
+
+        assert_eq!(adrs.len(), 1);
+        assert_eq!(adrs[0].path, adr_path.to_string_lossy());
+        assert_eq!(adrs[0].review_date.as_deref(), Some("2026-01-10"));
+        assert_eq!(adrs[0].days_since_review, Some(50));
+        assert_eq!(adrs[0].staleness, "aging");
+
+        assert!(describe_adrs_for("src/lexer.rs", &workspace_path, today, DEFAULT_ADR_STALE_DAYS).is_empty());
+    }
+
+    #[test]
+    fn frozen_complex_file_scores_above_maintained_one() {
+        let frozen = frozen_complexity_penalty(80.0, 0, 50.0, 75.0);
+        let maintained = frozen_complexity_penalty(80.0, 12, 50.0, 75.0);
+
+        assert_eq!(frozen, 75.0);
+        assert_eq!(maintained, 0.0);
+        assert_eq!(frozen_complexity_penalty(30.0, 0, 50.0, 75.0), 0.0);
+        assert_eq!(frozen_complexity_penalty(80.0, 0, 0.0, 75.0), 0.0);
+    }
+
+    #[test]
+    fn stale_adrs_include_inline_adrs_and_honour_stale_days() {
+        let workspace = tempfile::tempdir().expect("temp workspace");
+        let src = workspace.path().join("src");
+        std::fs::create_dir_all(&src).expect("create src dir");
+        let reviewed = (chrono::Utc::now().date_naive() - chrono::Duration::days(100)).format("%Y-%m-%d");
+        std::fs::write(src.join("parser.adr.md"), format!("# Parser\nlast_reviewed_at: {reviewed}\n"))
+            .expect("write adr");
+
+        let workspace_path = workspace.path().to_string_lossy().to_string();
+        let files = vec!["src/parser.rs".to_string(), "lib/parser.rs".to_string()];
+
+        let stale = find_stale_adrs(&workspace_path, &files, 60);
+        assert_eq!(stale.len(), 1);
+        assert_eq!(stale[0].path, "src/parser.adr.md");
+        assert_eq!(stale[0].days_overdue, 40);
+        assert_eq!(stale[0].documented_files, vec!["src/parser.rs".to_string()]);
+        assert!(find_stale_adrs(&workspace_path, &files, DEFAULT_ADR_STALE_DAYS).is_empty());
+
+        let today = chrono::Utc::now().date_naive();
+        assert_eq!(describe_adrs_for("src/parser.rs", &workspace_path, today, 60)[0].staleness, "stale");
+        assert_eq!(
+            describe_adrs_for("src/parser.rs", &workspace_path, today, DEFAULT_ADR_STALE_DAYS)[0].staleness,
+            "aging"
+        );
+        assert_eq!(staleness_for_age(100, 60), 100.0);
+        assert_eq!(staleness_for_age(45, 60), 50.0);
+    }
+}
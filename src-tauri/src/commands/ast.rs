@@ -8,6 +8,13 @@ pub struct AstAnalysisData {
     pub imports: HashMap<String, FileImports>,
 }
 
+/// Confidence of the line-based analyzers; an AST-based path would report "high"
+pub const HEURISTIC_CONFIDENCE: &str = "heuristic";
+
+fn heuristic_confidence() -> String {
+    HEURISTIC_CONFIDENCE.to_string()
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FileSmells {
     pub god_function: usize,
@@ -18,43 +25,70 @@ pub struct FileSmells {
     pub magic_number: usize,
     pub empty_catch: usize,
     pub todo_fixme: usize,
+    /// Swallowed errors: `let _ =`, `.unwrap()`/`.expect()` (Rust), `_ = err` (Go)
+    #[serde(default)]
+    pub ignored_error: usize,
     pub total: usize,
     pub loc: usize,
+    /// "heuristic" or "high"
+    #[serde(default = "heuristic_confidence")]
+    pub confidence: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FunctionComplexity {
     pub name: String,
     pub complexity: usize,
+    /// 1-based line of the declaration
+    #[serde(default)]
+    pub start_line: usize,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FileComplexity {
     pub functions: Vec<FunctionComplexity>,
     pub average: f64,
+    /// "heuristic" or "high"
+    #[serde(default = "heuristic_confidence")]
+    pub confidence: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FileImports {
     pub imports: Vec<String>,
     pub imported_by: Vec<String>,
+    /// Imports resolving to workspace code; the coupling graph uses only these
+    #[serde(default)]
+    pub internal: Vec<String>,
+    /// Third-party / standard-library imports
+    #[serde(default)]
+    pub external: Vec<String>,
 }
 
+/// Smells, complexity and imports of `file_paths` (absolute or relative to
+/// `workspace_path`). Internal imports are told apart using the workspace's
+/// own files, as in scoring.
 #[tauri::command]
-pub async fn run_ast_analysis(file_paths: Vec<String>) -> Result<AstAnalysisData, String> {
+pub async fn run_ast_analysis(workspace_path: String, file_paths: Vec<String>) -> Result<AstAnalysisData, String> {
     let mut smells_map = HashMap::new();
     let mut complexity_map = HashMap::new();
     let mut imports_map = HashMap::new();
+    let magic_number_allowlist =
+        crate::commands::settings::load_effective_analysis_settings(&workspace_path)?.magic_number_allowlist;
+    let local_roots = crate::analysis::coupling::workspace_import_roots(
+        &workspace_path,
+        &crate::commands::git::walkdir(&workspace_path),
+    );
 
     for file_path in &file_paths {
-        let source = std::fs::read_to_string(file_path)
+        let source = std::fs::read_to_string(std::path::Path::new(&workspace_path).join(file_path))
             .map_err(|e| format!("Failed to read {}: {}", file_path, e))?;
 
         let lang = detect_language(file_path);
         let loc = source.lines().count();
 
         // Analyze smells
-        let file_smells = crate::analysis::smells::detect_smells(&source, &lang, loc);
+        let file_smells = crate::analysis::smells::detect_smells_in_file(file_path, &source, &lang, loc, &magic_number_allowlist);
         smells_map.insert(file_path.clone(), file_smells);
 
         // Analyze complexity
@@ -63,9 +97,13 @@ pub async fn run_ast_analysis(file_paths: Vec<String>) -> Result<AstAnalysisData
 
         // Analyze imports
         let file_imports = crate::analysis::coupling::extract_imports(&source, &lang);
+        let (internal, external) =
+            crate::analysis::coupling::split_imports(&file_imports, &lang, &local_roots);
         imports_map.insert(file_path.clone(), FileImports {
             imports: file_imports,
             imported_by: Vec::new(), // Populated during full analysis
+            internal,
+            external,
         });
     }
 
@@ -76,10 +114,44 @@ pub async fn run_ast_analysis(file_paths: Vec<String>) -> Result<AstAnalysisData
     })
 }
 
+/// Dependency counts (and exact pins, when locked) for each manifest at the
+/// workspace root: Cargo.toml, package.json, go.mod, requirements.txt
+#[tauri::command]
+pub async fn get_dependency_debt(
+    workspace_path: String,
+) -> Result<Vec<crate::analysis::dependencies::ManifestDependencies>, String> {
+    Ok(crate::analysis::dependencies::analyze_dependencies(&workspace_path))
+}
+
+/// Circular internal imports, each as the list of files in the cycle
+#[tauri::command]
+pub async fn get_import_cycles(workspace_path: String) -> Result<Vec<Vec<String>>, String> {
+    let edges = crate::analysis::coupling::internal_import_edges(&workspace_path);
+    Ok(crate::analysis::cycles::find_import_cycles(&edges))
+}
+
+/// Tag every source file as test/config/entrypoint/migration/source
+#[tauri::command]
+pub async fn classify_files(workspace_path: String) -> Result<HashMap<String, String>, String> {
+    let mut roles = HashMap::new();
+    for file_path in crate::commands::git::walkdir(&workspace_path) {
+        let source = crate::commands::git::read_workspace_file(&workspace_path, &file_path)
+            .unwrap_or_default();
+        let relative = file_path
+            .strip_prefix(&workspace_path)
+            .unwrap_or(&file_path)
+            .trim_start_matches(['/', '\\'])
+            .replace('\\', "/");
+        let role = crate::analysis::roles::classify_file(&relative, &source);
+        roles.insert(relative, role.to_string());
+    }
+    Ok(roles)
+}
+
 fn detect_language(path: &str) -> String {
     match std::path::Path::new(path).extension().and_then(|e| e.to_str()) {
         Some("ts") | Some("tsx") => "typescript".to_string(),
-        Some("js") | Some("jsx") => "javascript".to_string(),
+        Some("js") | Some("jsx") | Some("mjs") | Some("cjs") => "javascript".to_string(),
         Some("py") => "python".to_string(),
         Some("go") => "go".to_string(),
         Some("rs") => "rust".to_string(),
@@ -1,10 +1,19 @@
 use crate::models::budget::DebtBudget;
 use crate::models::file_score::{ComponentScore, FileScore, ScoreComponents};
-use crate::models::register::RegisterItem;
-use crate::models::snapshot::DebtSnapshot;
-use rusqlite::{params, Connection, OptionalExtension, Result};
-
-const DB_SCHEMA_VERSION: i64 = 3;
+use crate::models::register::{
+    find_duplicate_clusters, priority_score, severity_weight, validate_register_item, DuplicateCluster,
+    RegisterActivity, RegisterImportReport, RegisterImportRow, RegisterItem, RegisterPriority,
+};
+use crate::models::snapshot::{
+    coverage_trend, debt_halflife, debt_velocity, embed_coverage, embed_files, embed_smell_counts, nearest_snapshot, regression_alert,
+    smell_trend, smooth_snapshots, CoverageTrendPoint, DebtHalfLife, DebtSnapshot, DebtVelocity, RegressionAlert,
+    SmellTrendPoint, SmoothedSnapshot, DEFAULT_REGRESSION_ALERT_DELTA,
+};
+use crate::models::workspace::WorkspaceIssue;
+use rusqlite::{params, Connection, OptionalExtension, Result, Row};
+use tauri::Emitter;
+
+pub const DB_SCHEMA_VERSION: i64 = 6;
 
 pub fn initialize_schema(conn: &Connection) -> Result<()> {
     conn.execute_batch(
@@ -33,6 +42,24 @@ pub fn initialize_schema(conn: &Connection) -> Result<()> {
         conn.pragma_update(None, "user_version", version)?;
     }
 
+    if version < 4 {
+        apply_migration_4(conn)?;
+        version = 4;
+        conn.pragma_update(None, "user_version", version)?;
+    }
+
+    if version < 5 {
+        apply_migration_5(conn)?;
+        version = 5;
+        conn.pragma_update(None, "user_version", version)?;
+    }
+
+    if version < 6 {
+        apply_migration_6(conn)?;
+        version = 6;
+        conn.pragma_update(None, "user_version", version)?;
+    }
+
     if version > DB_SCHEMA_VERSION {
         // Future schema; do not fail reads/writes for forward-compatible changes.
         conn.pragma_update(None, "user_version", version)?;
@@ -141,6 +168,21 @@ fn apply_migration_3(conn: &Connection) -> Result<()> {
     )
 }
 
+fn apply_migration_4(conn: &Connection) -> Result<()> {
+    add_column_if_missing(conn, "debt_budgets", "warning_score REAL")
+}
+
+fn apply_migration_5(conn: &Connection) -> Result<()> {
+    add_column_if_missing(conn, "file_scores", "content_hash TEXT")
+}
+
+fn apply_migration_6(conn: &Connection) -> Result<()> {
+    add_column_if_missing(conn, "file_scores", "first_seen_at INTEGER")?;
+    // Files scored before the column existed are not new.
+    conn.execute("UPDATE file_scores SET first_seen_at = 0 WHERE first_seen_at IS NULL", [])?;
+    Ok(())
+}
+
 fn add_column_if_missing(conn: &Connection, table: &str, column_def: &str) -> Result<()> {
     let column_name = column_def
         .split_whitespace()
@@ -168,10 +210,166 @@ pub fn get_db_connection(workspace_path: &str) -> Result<Connection> {
     Ok(conn)
 }
 
-pub fn upsert_file_scores(conn: &Connection, files: &[FileScore]) -> Result<()> {
+/// `user_version` of `state.db` as stored on disk, read without migrating;
+/// `None` when the database is missing or unreadable
+pub fn stored_db_version(workspace_path: &str) -> Option<i64> {
+    let db_path = std::path::Path::new(workspace_path)
+        .join(".debtengine")
+        .join("state.db");
+    if !db_path.exists() {
+        return None;
+    }
+    Connection::open(&db_path)
+        .and_then(|conn| conn.pragma_query_value(None, "user_version", |row| row.get(0)))
+        .ok()
+}
+
+/// Check `state.db` without migrating it: it must open, pass
+/// `PRAGMA integrity_check`, and not be newer than this build's schema.
+pub fn check_database(workspace_path: &str) -> Vec<WorkspaceIssue> {
+    let issue = |message: String, repairable: bool| WorkspaceIssue {
+        area: "database".to_string(),
+        message,
+        repairable,
+    };
+
+    let db_path = std::path::Path::new(workspace_path)
+        .join(".debtengine")
+        .join("state.db");
+    if !db_path.exists() {
+        return vec![issue("state.db is missing".to_string(), true)];
+    }
+
+    let conn = match Connection::open(&db_path) {
+        Ok(conn) => conn,
+        Err(e) => return vec![issue(format!("state.db cannot be opened: {e}"), false)],
+    };
+
+    let mut issues = Vec::new();
+    let integrity: Result<Vec<String>> = conn
+        .prepare("PRAGMA integrity_check")
+        .and_then(|mut stmt| {
+            stmt.query_map([], |row| row.get::<_, String>(0))?
+                .collect()
+        });
+    match integrity {
+        Ok(rows) if rows.len() == 1 && rows[0] == "ok" => {}
+        Ok(rows) => {
+            issues.push(issue(format!("integrity check failed: {}", rows.join("; ")), false));
+        }
+        Err(e) => {
+            return vec![issue(format!("integrity check failed: {e}"), false)];
+        }
+    }
+
+    match conn.pragma_query_value(None, "user_version", |row| row.get::<_, i64>(0)) {
+        Ok(version) if version > DB_SCHEMA_VERSION => issues.push(issue(
+            format!("schema version {version} is newer than supported version {DB_SCHEMA_VERSION}"),
+            false,
+        )),
+        Ok(version) if version < DB_SCHEMA_VERSION => issues.push(issue(
+            format!("schema version {version} needs migration to {DB_SCHEMA_VERSION}"),
+            true,
+        )),
+        Ok(_) => {}
+        Err(e) => issues.push(issue(format!("schema version unreadable: {e}"), false)),
+    }
+
+    issues
+}
+
+/// Delete coupling pairs where either side is not in `current_files`.
+/// Returns the number of rows removed.
+pub fn prune_coupling_pairs_with_conn(
+    conn: &Connection,
+    current_files: &std::collections::HashSet<String>,
+) -> Result<usize> {
+    let pairs: Vec<(String, String)> = {
+        let mut stmt = conn.prepare("SELECT file_a, file_b FROM coupling_pairs")?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        rows.collect::<Result<Vec<_>>>()?
+    };
+
+    let tx = conn.unchecked_transaction()?;
+    let mut removed = 0;
+    for (file_a, file_b) in pairs {
+        if !current_files.contains(&file_a) || !current_files.contains(&file_b) {
+            removed += tx.execute(
+                "DELETE FROM coupling_pairs WHERE file_a = ?1 AND file_b = ?2",
+                params![file_a, file_b],
+            )?;
+        }
+    }
+    tx.commit()?;
+    Ok(removed)
+}
+
+/// Replace every coupling pair with `pairs`
+pub fn replace_coupling_pairs_with_conn(
+    conn: &Connection,
+    pairs: &[crate::commands::scoring::CouplingPair],
+) -> Result<()> {
+    let tx = conn.unchecked_transaction()?;
+    tx.execute("DELETE FROM coupling_pairs", [])?;
+    for pair in pairs {
+        tx.execute(
+            "INSERT INTO coupling_pairs (file_a, file_b, co_change_count, coupling_ratio, has_import_link) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                pair.file_a,
+                pair.file_b,
+                pair.co_change_count as i64,
+                pair.coupling_ratio,
+                pair.has_import_link as i64
+            ],
+        )?;
+    }
+    tx.commit()
+}
+
+/// Set `has_import_link` on every coupling pair to whether `import_links`
+/// contains it. `import_links` holds `normalize_path_key` pairs with the
+/// lesser key first. Returns the number of rows whose flag changed.
+pub fn reconcile_coupling_links_with_conn(
+    conn: &Connection,
+    import_links: &std::collections::HashSet<(String, String)>,
+) -> Result<usize> {
+    let pairs: Vec<(String, String, bool)> = {
+        let mut stmt = conn.prepare("SELECT file_a, file_b, has_import_link FROM coupling_pairs")?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get::<_, i64>(2)? != 0)))?;
+        rows.collect::<Result<Vec<_>>>()?
+    };
+
+    let tx = conn.unchecked_transaction()?;
+    let mut corrected = 0;
+    for (file_a, file_b, has_link) in pairs {
+        let key_a = crate::analysis::coupling::normalize_path_key(&file_a);
+        let key_b = crate::analysis::coupling::normalize_path_key(&file_b);
+        let key = if key_a <= key_b { (key_a, key_b) } else { (key_b, key_a) };
+        let linked = import_links.contains(&key);
+        if linked != has_link {
+            corrected += tx.execute(
+                "UPDATE coupling_pairs SET has_import_link = ?3 WHERE file_a = ?1 AND file_b = ?2",
+                params![file_a, file_b, linked as i64],
+            )?;
+        }
+    }
+    tx.commit()?;
+    Ok(corrected)
+}
+
+/// Upsert scores in one transaction, recording each file's entry in
+/// `content_hashes` (keyed by absolute path) as the hash it was scored from
+pub fn upsert_file_scores(
+    conn: &Connection,
+    files: &[FileScore],
+    content_hashes: &std::collections::HashMap<String, String>,
+) -> Result<()> {
     let tx = conn.unchecked_transaction()?;
     for file in files {
         upsert_file_score_with_conn(&tx, file)?;
+        if let Some(hash) = content_hashes.get(&file.path) {
+            store_content_hash(&tx, &file.path, hash)?;
+        }
     }
     tx.commit()
 }
@@ -180,8 +378,25 @@ pub fn upsert_file_score(conn: &Connection, file: &FileScore) -> Result<()> {
     upsert_file_score_with_conn(conn, file)
 }
 
+/// `score_data_json` layout: the components at the top level, as older rows
+/// store them, plus the per-function complexity list
+#[derive(serde::Serialize, serde::Deserialize)]
+struct ScoreData {
+    #[serde(flatten)]
+    components: ScoreComponents,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    functions: Vec<crate::commands::ast::FunctionComplexity>,
+    #[serde(default, skip_serializing_if = "std::collections::BTreeMap::is_empty")]
+    smell_counts: std::collections::BTreeMap<String, usize>,
+}
+
 fn upsert_file_score_with_conn(conn: &Connection, file: &FileScore) -> Result<()> {
-    let components_json = serde_json::to_string(&file.components).unwrap_or_else(|_| "{}".to_string());
+    let score_data = ScoreData {
+        components: file.components.clone(),
+        functions: file.functions.clone(),
+        smell_counts: file.smell_counts.clone(),
+    };
+    let score_data_json = serde_json::to_string(&score_data).unwrap_or_else(|_| "{}".to_string());
     let now = chrono::Utc::now().timestamp();
 
     conn.execute(
@@ -196,8 +411,9 @@ fn upsert_file_score_with_conn(conn: &Connection, file: &FileScore) -> Result<()
             supervision_status,
             mtime_cached,
             score_data_json,
-            updated_at
-        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
+            updated_at,
+            first_seen_at
+        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?10)
         ON CONFLICT(path) DO UPDATE SET
             relative_path = excluded.relative_path,
             composite_score = excluded.composite_score,
@@ -207,7 +423,8 @@ fn upsert_file_score_with_conn(conn: &Connection, file: &FileScore) -> Result<()
             supervision_status = excluded.supervision_status,
             mtime_cached = excluded.mtime_cached,
             score_data_json = excluded.score_data_json,
-            updated_at = excluded.updated_at
+            updated_at = excluded.updated_at,
+            content_hash = NULL
         ",
         params![
             file.path,
@@ -218,7 +435,7 @@ fn upsert_file_score_with_conn(conn: &Connection, file: &FileScore) -> Result<()
             file.last_modified,
             file.supervision_status,
             file.last_modified,
-            components_json,
+            score_data_json,
             now,
         ],
     )?;
@@ -226,6 +443,21 @@ fn upsert_file_score_with_conn(conn: &Connection, file: &FileScore) -> Result<()
     Ok(())
 }
 
+/// When each persisted file was first scored, keyed by relative path; 0 for
+/// files that predate tracking or the workspace's first analysis
+pub fn load_first_seen(conn: &Connection) -> Result<std::collections::HashMap<String, i64>> {
+    let mut stmt = conn.prepare("SELECT relative_path, COALESCE(first_seen_at, 0) FROM file_scores")?;
+    let rows = stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?)))?;
+    rows.collect()
+}
+
+/// Mark every persisted file as known from the start, so the files of a
+/// workspace's first analysis never count as new
+pub fn backdate_first_seen(conn: &Connection) -> Result<()> {
+    conn.execute("UPDATE file_scores SET first_seen_at = 0", [])?;
+    Ok(())
+}
+
 pub fn load_cached_file_mtime(conn: &Connection, file_path: &str) -> Result<Option<i64>> {
     conn.query_row(
         "SELECT mtime_cached FROM file_scores WHERE path = ?1",
@@ -235,36 +467,81 @@ pub fn load_cached_file_mtime(conn: &Connection, file_path: &str) -> Result<Opti
     .optional()
 }
 
+/// Hash of the content a file's persisted score was computed from, if recorded
+pub fn load_cached_content_hash(conn: &Connection, file_path: &str) -> Result<Option<String>> {
+    conn.query_row(
+        "SELECT content_hash FROM file_scores WHERE path = ?1",
+        params![file_path],
+        |row| row.get(0),
+    )
+    .optional()
+    .map(Option::flatten)
+}
+
+/// Record the content hash for a persisted score. Upserting a score clears
+/// it, so call this after `upsert_file_score`.
+pub fn store_content_hash(conn: &Connection, file_path: &str, content_hash: &str) -> Result<()> {
+    conn.execute(
+        "UPDATE file_scores SET content_hash = ?2 WHERE path = ?1",
+        params![file_path, content_hash],
+    )?;
+    Ok(())
+}
+
+const FILE_SCORE_COLUMNS: &str =
+    "path, relative_path, composite_score, loc, language, last_modified, supervision_status, score_data_json";
+
+fn file_score_from_row(row: &Row<'_>) -> Result<FileScore> {
+    let score_data_json: String = row.get(7)?;
+    let ScoreData { components, functions, smell_counts } = serde_json::from_str::<ScoreData>(&score_data_json)
+        .unwrap_or_else(|_| ScoreData {
+            components: empty_components(),
+            functions: Vec::new(),
+            smell_counts: Default::default(),
+        });
+
+    Ok(FileScore {
+        path: row.get(0)?,
+        relative_path: row.get(1)?,
+        composite_score: row.get(2)?,
+        components,
+        loc: row.get::<_, i64>(3)? as usize,
+        language: row.get(4)?,
+        last_modified: row.get(5)?,
+        supervision_status: row.get::<_, String>(6)?,
+        functions,
+        smell_counts,
+    })
+}
+
 pub fn load_cached_file_score(conn: &Connection, file_path: &str) -> Result<Option<FileScore>> {
     conn.query_row(
-        "SELECT path, relative_path, composite_score, loc, language, last_modified, supervision_status, score_data_json FROM file_scores WHERE path = ?1",
+        &format!("SELECT {FILE_SCORE_COLUMNS} FROM file_scores WHERE path = ?1"),
         params![file_path],
-        |row| {
-            let score_data_json: String = row.get(7)?;
-            let components = serde_json::from_str::<ScoreComponents>(&score_data_json)
-                .unwrap_or_else(|_| empty_components());
-
-            Ok(FileScore {
-                path: row.get(0)?,
-                relative_path: row.get(1)?,
-                composite_score: row.get(2)?,
-                components,
-                loc: row.get::<_, i64>(3)? as usize,
-                language: row.get(4)?,
-                last_modified: row.get(5)?,
-                supervision_status: row.get::<_, String>(6)?,
-            })
-        },
+        file_score_from_row,
     )
     .optional()
 }
 
+/// Every persisted file score, ordered by relative path
+pub fn load_all_file_scores(conn: &Connection) -> Result<Vec<FileScore>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {FILE_SCORE_COLUMNS} FROM file_scores ORDER BY relative_path"
+    ))?;
+    let files = stmt
+        .query_map([], file_score_from_row)?
+        .filter_map(|r| r.ok())
+        .collect();
+    Ok(files)
+}
+
 fn empty_components() -> ScoreComponents {
     let zero = ComponentScore {
         raw_score: 0.0,
         weight: 0.0,
         contribution: 0.0,
         details: Vec::new(),
+        confidence: None,
     };
 
     ScoreComponents {
@@ -287,40 +564,153 @@ pub async fn take_snapshot(
     high_debt_count: usize,
     commit_count_week: usize,
     metadata_json: Option<String>,
+    cache: tauri::State<'_, std::sync::Arc<std::sync::Mutex<crate::models::file_score::AnalysisCache>>>,
+    app: tauri::AppHandle,
 ) -> Result<DebtSnapshot, String> {
-    let conn = get_db_connection(&workspace_path)
+    let snapshot = DebtSnapshot {
+        id: 0,
+        timestamp: chrono::Utc::now().timestamp(),
+        composite_score,
+        file_count,
+        high_debt_count,
+        commit_count_week,
+        snapshot_metadata: metadata_json,
+    };
+    take_snapshot_internal(&workspace_path, snapshot, cache.inner(), |alert| {
+        let _ = app.emit("debt_regression_alert", alert);
+    })
+}
+
+/// Insert `snapshot` and call `on_alert` when it scores more than
+/// `regressionAlertDelta` above the previous snapshot. When `cache` holds
+/// this workspace's analysis, its full file list and smell totals replace
+/// whatever metadata the caller supplied, and its merged coverage is reused.
+pub fn take_snapshot_internal<F>(
+    workspace_path: &str,
+    mut snapshot: DebtSnapshot,
+    cache: &std::sync::Arc<std::sync::Mutex<crate::models::file_score::AnalysisCache>>,
+    on_alert: F,
+) -> Result<DebtSnapshot, String>
+where
+    F: FnOnce(RegressionAlert),
+{
+    crate::commands::scoring::ensure_full_analysis(cache)?;
+    let mut metadata_json = snapshot.snapshot_metadata.take();
+    if let Some(files) = crate::commands::scoring::cached_file_summaries(workspace_path, cache) {
+        metadata_json = embed_files(metadata_json, files);
+    }
+    if let Some(smells) = crate::commands::scoring::cached_smell_counts(workspace_path, cache) {
+        metadata_json = embed_smell_counts(metadata_json, smells);
+    }
+    let coverage = crate::commands::scoring::cached_workspace_coverage(workspace_path, cache)
+        .unwrap_or_else(|| crate::analysis::coverage::workspace_coverage(workspace_path));
+    if let Some(percent) = coverage.coverage_percent {
+        metadata_json = embed_coverage(metadata_json, percent);
+    }
+    snapshot.snapshot_metadata = metadata_json;
+
+    let conn = get_db_connection(workspace_path)
         .map_err(|e| format!("DB error: {e}"))?;
+    let previous = load_snapshots(&conn)
+        .map_err(|e| format!("Query error: {e}"))?
+        .pop();
+    // Read before inserting so a settings error can't fail an already stored snapshot.
+    let min_delta = match previous {
+        Some(_) => crate::commands::settings::load_settings_from_disk(workspace_path)?
+            .get("regressionAlertDelta")
+            .and_then(serde_json::Value::as_f64)
+            .unwrap_or(DEFAULT_REGRESSION_ALERT_DELTA),
+        None => DEFAULT_REGRESSION_ALERT_DELTA,
+    };
+    let snapshot = insert_snapshot(&conn, snapshot).map_err(|e| format!("Insert error: {e}"))?;
 
-    let now = chrono::Utc::now().timestamp();
+    if let Some(alert) = previous.and_then(|previous| regression_alert(&previous, &snapshot, min_delta)) {
+        on_alert(alert);
+    }
+
+    Ok(snapshot)
+}
 
+/// Insert a snapshot, returning it with its assigned id
+pub fn insert_snapshot(conn: &Connection, snapshot: DebtSnapshot) -> Result<DebtSnapshot> {
     conn.execute(
         "INSERT INTO debt_snapshots (timestamp, composite_score, file_count, high_debt_count, commit_count_week, snapshot_metadata) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
-        params![now, composite_score, file_count as i64, high_debt_count as i64, commit_count_week as i64, metadata_json],
-    ).map_err(|e| format!("Insert error: {e}"))?;
-
-    let id = conn.last_insert_rowid();
+        params![
+            snapshot.timestamp,
+            snapshot.composite_score,
+            snapshot.file_count as i64,
+            snapshot.high_debt_count as i64,
+            snapshot.commit_count_week as i64,
+            snapshot.snapshot_metadata
+        ],
+    )?;
 
     Ok(DebtSnapshot {
-        id,
-        timestamp: now,
-        composite_score,
-        file_count,
-        high_debt_count,
-        commit_count_week,
-        snapshot_metadata: metadata_json,
+        id: conn.last_insert_rowid(),
+        ..snapshot
     })
 }
 
+/// Number of persisted file scores and when the newest was written
+pub fn file_score_summary(conn: &Connection) -> Result<(usize, Option<i64>)> {
+    conn.query_row(
+        "SELECT COUNT(*), MAX(NULLIF(updated_at, 0)) FROM file_scores",
+        [],
+        |row| Ok((row.get::<_, i64>(0)? as usize, row.get(1)?)),
+    )
+}
+
+pub fn latest_snapshot_timestamp(conn: &Connection) -> Result<Option<i64>> {
+    conn.query_row("SELECT MAX(timestamp) FROM debt_snapshots", [], |row| row.get(0))
+}
+
 #[tauri::command]
 pub async fn get_debt_snapshots(workspace_path: String) -> Result<Vec<DebtSnapshot>, String> {
     let conn = get_db_connection(&workspace_path)
         .map_err(|e| format!("DB error: {e}"))?;
+    load_snapshots(&conn).map_err(|e| format!("Query error: {e}"))
+}
 
-    let mut stmt = conn
-        .prepare(
-            "SELECT id, timestamp, composite_score, file_count, high_debt_count, commit_count_week, snapshot_metadata FROM debt_snapshots ORDER BY timestamp ASC",
-        )
-        .map_err(|e| format!("Query error: {e}"))?;
+/// Total of one smell kind (e.g. `todo_fixme`) per snapshot, oldest first.
+/// Snapshots taken before smell totals were recorded are left out.
+#[tauri::command]
+pub async fn get_smell_trend(
+    workspace_path: String,
+    smell_type: String,
+) -> Result<Vec<SmellTrendPoint>, String> {
+    if !crate::analysis::smells::SMELL_TYPES.contains(&smell_type.as_str()) {
+        return Err(format!("Unknown smell type: {smell_type}"));
+    }
+    let conn = get_db_connection(&workspace_path)
+        .map_err(|e| format!("DB error: {e}"))?;
+    let snapshots = load_snapshots(&conn).map_err(|e| format!("Query error: {e}"))?;
+    Ok(smell_trend(&snapshots, &smell_type))
+}
+
+/// Workspace coverage recorded by each snapshot, oldest first
+#[tauri::command]
+pub async fn get_coverage_trend(workspace_path: String) -> Result<Vec<CoverageTrendPoint>, String> {
+    let conn = get_db_connection(&workspace_path)
+        .map_err(|e| format!("DB error: {e}"))?;
+    let snapshots = load_snapshots(&conn).map_err(|e| format!("Query error: {e}"))?;
+    Ok(coverage_trend(&snapshots))
+}
+
+/// The snapshot taken closest to `timestamp` (unix seconds), or `None` when
+/// there are no snapshots
+#[tauri::command]
+pub async fn get_snapshot_near(workspace_path: String, timestamp: i64) -> Result<Option<DebtSnapshot>, String> {
+    let conn = get_db_connection(&workspace_path)
+        .map_err(|e| format!("DB error: {e}"))?;
+    let snapshots = load_snapshots(&conn).map_err(|e| format!("Query error: {e}"))?;
+    Ok(nearest_snapshot(&snapshots, timestamp).cloned())
+}
+
+/// All snapshots, oldest first
+pub fn load_snapshots(conn: &Connection) -> Result<Vec<DebtSnapshot>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, timestamp, composite_score, file_count, high_debt_count, commit_count_week, snapshot_metadata FROM debt_snapshots ORDER BY timestamp ASC",
+    )?;
 
     let snapshots = stmt
         .query_map([], |row| {
@@ -333,20 +723,301 @@ pub async fn get_debt_snapshots(workspace_path: String) -> Result<Vec<DebtSnapsh
                 commit_count_week: row.get::<_, i64>(5)? as usize,
                 snapshot_metadata: row.get(6)?,
             })
-        })
-        .map_err(|e| format!("Map error: {e}"))?
+        })?
         .filter_map(|r| r.ok())
         .collect();
 
     Ok(snapshots)
 }
 
+/// Weeks for the workspace score to halve at the fitted snapshot trend, or a
+/// divergence warning when debt is rising
+#[tauri::command]
+pub async fn get_debt_halflife(workspace_path: String) -> std::result::Result<DebtHalfLife, String> {
+    let conn = get_db_connection(&workspace_path)
+        .map_err(|e| format!("DB error: {e}"))?;
+    let snapshots = load_snapshots(&conn).map_err(|e| format!("Query error: {e}"))?;
+    Ok(debt_halflife(&snapshots))
+}
+
+/// Fitted points-per-week trend over the last `weeks` of snapshots, with a
+/// projection of when it reaches `target_score` (default `warningThreshold`)
+#[tauri::command]
+pub async fn get_debt_velocity(
+    workspace_path: String,
+    weeks: u32,
+    target_score: Option<f64>,
+) -> std::result::Result<DebtVelocity, String> {
+    if weeks == 0 {
+        return Err("INVALID_WINDOW: weeks must be at least 1".to_string());
+    }
+    let target_score = match target_score {
+        Some(target) => target,
+        None => crate::commands::settings::load_settings_from_disk(&workspace_path)?
+            .get("warningThreshold")
+            .and_then(serde_json::Value::as_f64)
+            .unwrap_or(65.0),
+    };
+    let conn = get_db_connection(&workspace_path)
+        .map_err(|e| format!("DB error: {e}"))?;
+    let mut snapshots = load_snapshots(&conn).map_err(|e| format!("Query error: {e}"))?;
+    if let Some(latest) = snapshots.iter().map(|s| s.timestamp).max() {
+        let since = latest - i64::from(weeks) * 7 * 86400;
+        snapshots.retain(|s| s.timestamp >= since);
+    }
+    Ok(debt_velocity(&snapshots, target_score))
+}
+
+/// Snapshots with a trailing moving average of `window` points
+#[tauri::command]
+pub async fn get_smoothed_trend(
+    workspace_path: String,
+    window: usize,
+) -> std::result::Result<Vec<SmoothedSnapshot>, String> {
+    if window == 0 {
+        return Err("INVALID_WINDOW: window must be at least 1".to_string());
+    }
+    let conn = get_db_connection(&workspace_path)
+        .map_err(|e| format!("DB error: {e}"))?;
+    let snapshots = load_snapshots(&conn).map_err(|e| format!("Query error: {e}"))?;
+    Ok(smooth_snapshots(snapshots, window))
+}
+
+const REGISTER_COLUMNS: &str = "id, created_at, updated_at, title, description, file_path, severity, item_type, owner, target_sprint, estimated_hours, actual_hours, status, tags, linked_commit, notes";
+
+fn register_item_from_row(row: &Row<'_>) -> Result<RegisterItem> {
+    let tags_str: String = row.get(13)?;
+    let tags: Vec<String> = serde_json::from_str(&tags_str).unwrap_or_default();
+    Ok(RegisterItem {
+        id: row.get(0)?,
+        created_at: row.get(1)?,
+        updated_at: row.get(2)?,
+        title: row.get(3)?,
+        description: row.get(4)?,
+        file_path: row.get(5)?,
+        severity: row.get(6)?,
+        item_type: row.get(7)?,
+        owner: row.get(8)?,
+        target_sprint: row.get(9)?,
+        estimated_hours: row.get(10)?,
+        actual_hours: row.get(11)?,
+        status: row.get(12)?,
+        tags,
+        linked_commit: row.get(14)?,
+        notes: row.get(15)?,
+    })
+}
+
+pub fn insert_register_item(conn: &Connection, item: &RegisterItem) -> Result<()> {
+    let tags_json = serde_json::to_string(&item.tags).unwrap_or_else(|_| "[]".to_string());
+    conn.execute(
+        "INSERT INTO debt_register (id, created_at, updated_at, title, description, file_path, severity, item_type, owner, target_sprint, estimated_hours, actual_hours, status, tags, linked_commit, notes) VALUES (?1,?2,?3,?4,?5,?6,?7,?8,?9,?10,?11,?12,?13,?14,?15,?16)",
+        params![&item.id, item.created_at, item.updated_at, &item.title, &item.description, item.file_path.as_deref(), &item.severity, &item.item_type, item.owner.as_deref(), item.target_sprint.as_deref(), item.estimated_hours, item.actual_hours, &item.status, tags_json, item.linked_commit.as_deref(), item.notes.as_deref()],
+    )?;
+    Ok(())
+}
+
+/// Fill defaults for a partially specified register item: a generated id,
+/// current timestamps, empty description/tags and `open` status.
+fn register_item_from_import(value: serde_json::Value, now: i64) -> std::result::Result<RegisterItem, String> {
+    let serde_json::Value::Object(mut obj) = value else {
+        return Err("row must be a JSON object".to_string());
+    };
+    let missing = |obj: &serde_json::Map<String, serde_json::Value>, key: &str| {
+        obj.get(key).is_none_or(|v| v.is_null() || v.as_str() == Some(""))
+    };
+
+    if missing(&obj, "id") {
+        obj.insert("id".to_string(), serde_json::json!(uuid::Uuid::new_v4().to_string()));
+    }
+    for key in ["created_at", "updated_at"] {
+        if missing(&obj, key) {
+            obj.insert(key.to_string(), serde_json::json!(now));
+        }
+    }
+    if missing(&obj, "description") {
+        obj.insert("description".to_string(), serde_json::json!(""));
+    }
+    if missing(&obj, "status") {
+        obj.insert("status".to_string(), serde_json::json!("open"));
+    }
+    if missing(&obj, "tags") {
+        obj.insert("tags".to_string(), serde_json::json!([]));
+    }
+
+    serde_json::from_value::<RegisterItem>(serde_json::Value::Object(obj))
+        .map_err(|e| format!("invalid item: {e}"))
+}
+
+/// Validate and insert a JSON array of register items in one transaction.
+/// Invalid rows are reported and skipped; valid rows are still inserted.
+pub fn import_register_items_with_conn(
+    conn: &Connection,
+    items_json: &str,
+) -> std::result::Result<RegisterImportReport, String> {
+    let rows: Vec<serde_json::Value> = serde_json::from_str(items_json)
+        .map_err(|e| format!("Invalid JSON: expected an array of register items: {e}"))?;
+    let now = chrono::Utc::now().timestamp();
+
+    let tx = conn
+        .unchecked_transaction()
+        .map_err(|e| format!("DB error: {e}"))?;
+    let mut results = Vec::with_capacity(rows.len());
+    for (index, value) in rows.into_iter().enumerate() {
+        let outcome = register_item_from_import(value, now).and_then(|item| {
+            validate_register_item(&item)?;
+            insert_register_item(&tx, &item).map_err(|e| format!("Insert error: {e}"))?;
+            Ok(item.id)
+        });
+        results.push(match outcome {
+            Ok(id) => RegisterImportRow { index, id: Some(id), success: true, error: None },
+            Err(error) => RegisterImportRow { index, id: None, success: false, error: Some(error) },
+        });
+    }
+    tx.commit().map_err(|e| format!("Commit error: {e}"))?;
+
+    let imported = results.iter().filter(|row| row.success).count();
+    Ok(RegisterImportReport {
+        imported,
+        failed: results.len() - imported,
+        rows: results,
+    })
+}
+
+#[tauri::command]
+pub async fn import_register_items(
+    workspace_path: String,
+    items_json: String,
+) -> std::result::Result<RegisterImportReport, String> {
+    let conn = get_db_connection(&workspace_path)
+        .map_err(|e| format!("DB error: {e}"))?;
+    import_register_items_with_conn(&conn, &items_json)
+}
+
+pub fn load_register_items(conn: &Connection) -> Result<Vec<RegisterItem>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {REGISTER_COLUMNS} FROM debt_register ORDER BY created_at DESC"
+    ))?;
+    let items = stmt
+        .query_map([], register_item_from_row)?
+        .filter_map(|r| r.ok())
+        .collect();
+    Ok(items)
+}
+
+/// The `limit` most recently created or updated register items, newest first
+pub fn load_recent_register_activity(conn: &Connection, limit: usize) -> Result<Vec<RegisterActivity>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {REGISTER_COLUMNS} FROM debt_register ORDER BY MAX(created_at, updated_at) DESC, id LIMIT ?1"
+    ))?;
+    let items = stmt
+        .query_map(params![limit as i64], register_item_from_row)?
+        .filter_map(|r| r.ok())
+        .map(RegisterActivity::from_item)
+        .collect();
+    Ok(items)
+}
+
+#[tauri::command]
+pub async fn get_recent_register_activity(
+    workspace_path: String,
+    limit: usize,
+) -> Result<Vec<RegisterActivity>, String> {
+    let conn = get_db_connection(&workspace_path)
+        .map_err(|e| format!("DB error: {e}"))?;
+    load_recent_register_activity(&conn, limit).map_err(|e| format!("Query error: {e}"))
+}
+
+/// Latest persisted composite score for a file, matched by relative or absolute path.
+pub fn load_composite_score(conn: &Connection, file_path: &str) -> Result<Option<f64>> {
+    conn.query_row(
+        "SELECT composite_score FROM file_scores WHERE relative_path = ?1 OR path = ?1 LIMIT 1",
+        params![file_path],
+        |row| row.get(0),
+    )
+    .optional()
+}
+
+/// Persisted composite scores for every file, keyed by relative path
+pub fn load_composite_scores(conn: &Connection) -> Result<std::collections::BTreeMap<String, f64>> {
+    let mut stmt = conn.prepare("SELECT relative_path, composite_score FROM file_scores")?;
+    let scores = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .filter_map(|r| r.ok())
+        .collect();
+    Ok(scores)
+}
+
+/// Rank open and in-progress register items by severity weight scaled by
+/// the linked file's debt score.
+pub fn compute_register_priorities(conn: &Connection) -> Result<Vec<RegisterPriority>> {
+    let mut priorities = Vec::new();
+    let open_items = load_register_items(conn)?
+        .into_iter()
+        .filter(|item| item.status == "open" || item.status == "in_progress");
+    for item in open_items {
+        let file_score = match item.file_path.as_deref() {
+            Some(path) => load_composite_score(conn, path)?,
+            None => None,
+        };
+        priorities.push(RegisterPriority {
+            severity_weight: severity_weight(&item.severity),
+            file_score,
+            priority_score: priority_score(&item.severity, file_score),
+            item,
+        });
+    }
+
+    priorities.sort_by(|a, b| {
+        b.priority_score
+            .partial_cmp(&a.priority_score)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    Ok(priorities)
+}
+
+#[tauri::command]
+pub async fn get_register_priority_score(workspace_path: String) -> Result<Vec<RegisterPriority>, String> {
+    let conn = get_db_connection(&workspace_path)
+        .map_err(|e| format!("DB error: {e}"))?;
+    compute_register_priorities(&conn).map_err(|e| format!("Query error: {e}"))
+}
+
+/// Clusters of register items with near-identical titles (token overlap of at
+/// least `similarity`, 0–1) or the same file, for merging
+#[tauri::command]
+pub async fn find_duplicate_register_items(
+    workspace_path: String,
+    similarity: f64,
+) -> Result<Vec<DuplicateCluster>, String> {
+    if !(0.0..=1.0).contains(&similarity) {
+        return Err("INVALID_SIMILARITY: similarity must be between 0 and 1".to_string());
+    }
+    let conn = get_db_connection(&workspace_path)
+        .map_err(|e| format!("DB error: {e}"))?;
+    let items = load_register_items(&conn).map_err(|e| format!("Query error: {e}"))?;
+    Ok(find_duplicate_clusters(items, similarity))
+}
+
+/// Mark every open or in-progress register item on `file_path` as resolved in
+/// one transaction, returning how many items changed.
+pub fn resolve_register_items_for_file(conn: &Connection, file_path: &str) -> Result<usize> {
+    let tx = conn.unchecked_transaction()?;
+    let updated = tx.execute(
+        "UPDATE debt_register SET status = 'resolved', updated_at = ?2 WHERE file_path = ?1 AND status IN ('open', 'in_progress')",
+        params![file_path, chrono::Utc::now().timestamp()],
+    )?;
+    tx.commit()?;
+    Ok(updated)
+}
+
 #[tauri::command]
 pub async fn register_crud(
     workspace_path: String,
     operation: String,
     item: Option<RegisterItem>,
     id: Option<String>,
+    file_path: Option<String>,
 ) -> Result<serde_json::Value, String> {
     let conn = get_db_connection(&workspace_path)
         .map_err(|e| format!("DB error: {e}"))?;
@@ -354,12 +1025,7 @@ pub async fn register_crud(
     match operation.as_str() {
         "create" => {
             let item = item.ok_or("Item required for create")?;
-            let tags_json = serde_json::to_string(&item.tags).unwrap_or_else(|_| "[]".to_string());
-            conn.execute(
-                "INSERT INTO debt_register (id, created_at, updated_at, title, description, file_path, severity, item_type, owner, target_sprint, estimated_hours, actual_hours, status, tags, linked_commit, notes) VALUES (?1,?2,?3,?4,?5,?6,?7,?8,?9,?10,?11,?12,?13,?14,?15,?16)",
-                params![&item.id, item.created_at, item.updated_at, &item.title, &item.description, item.file_path.as_deref(), &item.severity, &item.item_type, item.owner.as_deref(), item.target_sprint.as_deref(), item.estimated_hours, item.actual_hours, &item.status, tags_json, item.linked_commit.as_deref(), item.notes.as_deref()],
-            )
-            .map_err(|e| format!("Insert error: {e}"))?;
+            insert_register_item(&conn, &item).map_err(|e| format!("Insert error: {e}"))?;
             Ok(serde_json::json!({"status": "created", "id": item.id}))
         }
         "update" => {
@@ -375,69 +1041,18 @@ pub async fn register_crud(
         "read" => {
             let id = id.ok_or("ID required for read")?;
             let mut stmt = conn
-                .prepare("SELECT id, created_at, updated_at, title, description, file_path, severity, item_type, owner, target_sprint, estimated_hours, actual_hours, status, tags, linked_commit, notes FROM debt_register WHERE id = ?1")
+                .prepare(&format!("SELECT {REGISTER_COLUMNS} FROM debt_register WHERE id = ?1"))
                 .map_err(|e| format!("Query error: {e}"))?;
 
             let item: Option<RegisterItem> = stmt
-                .query_row(params![id], |row| {
-                    let tags_str: String = row.get(13)?;
-                    let tags: Vec<String> = serde_json::from_str(&tags_str).unwrap_or_default();
-                    Ok(RegisterItem {
-                        id: row.get(0)?,
-                        created_at: row.get(1)?,
-                        updated_at: row.get(2)?,
-                        title: row.get(3)?,
-                        description: row.get(4)?,
-                        file_path: row.get(5)?,
-                        severity: row.get(6)?,
-                        item_type: row.get(7)?,
-                        owner: row.get(8)?,
-                        target_sprint: row.get(9)?,
-                        estimated_hours: row.get(10)?,
-                        actual_hours: row.get(11)?,
-                        status: row.get(12)?,
-                        tags,
-                        linked_commit: row.get(14)?,
-                        notes: row.get(15)?,
-                    })
-                })
+                .query_row(params![id], register_item_from_row)
                 .optional()
                 .map_err(|e| format!("Read error: {e}"))?;
 
             Ok(serde_json::to_value(item).unwrap_or(serde_json::Value::Null))
         }
         "list" => {
-            let mut stmt = conn
-                .prepare("SELECT id, created_at, updated_at, title, description, file_path, severity, item_type, owner, target_sprint, estimated_hours, actual_hours, status, tags, linked_commit, notes FROM debt_register ORDER BY created_at DESC")
-                .map_err(|e| format!("Query error: {e}"))?;
-
-            let items: Vec<RegisterItem> = stmt
-                .query_map([], |row| {
-                    let tags_str: String = row.get(13)?;
-                    let tags: Vec<String> = serde_json::from_str(&tags_str).unwrap_or_default();
-                    Ok(RegisterItem {
-                        id: row.get(0)?,
-                        created_at: row.get(1)?,
-                        updated_at: row.get(2)?,
-                        title: row.get(3)?,
-                        description: row.get(4)?,
-                        file_path: row.get(5)?,
-                        severity: row.get(6)?,
-                        item_type: row.get(7)?,
-                        owner: row.get(8)?,
-                        target_sprint: row.get(9)?,
-                        estimated_hours: row.get(10)?,
-                        actual_hours: row.get(11)?,
-                        status: row.get(12)?,
-                        tags,
-                        linked_commit: row.get(14)?,
-                        notes: row.get(15)?,
-                    })
-                })
-                .map_err(|e| format!("Map error: {e}"))?
-                .filter_map(|r| r.ok())
-                .collect();
-
+            let items = load_register_items(&conn).map_err(|e| format!("Query error: {e}"))?;
             Ok(serde_json::to_value(items).unwrap_or_default())
         }
         "delete" => {
@@ -446,10 +1061,44 @@ pub async fn register_crud(
                 .map_err(|e| format!("Delete error: {e}"))?;
             Ok(serde_json::json!({"status": "deleted"}))
         }
+        "resolve_by_file" => {
+            let fp = file_path.ok_or("file_path required for resolve_by_file")?;
+            let updated = resolve_register_items_for_file(&conn, &fp)
+                .map_err(|e| format!("Update error: {e}"))?;
+            Ok(serde_json::json!({"status": "resolved", "updated": updated}))
+        }
         _ => Err(format!("Unknown operation: {operation}")),
     }
 }
 
+const BUDGET_COLUMNS: &str = "id, pattern, label, max_score, created_at, notify_on_breach, warning_score";
+
+fn budget_from_row(row: &Row<'_>) -> Result<DebtBudget> {
+    Ok(DebtBudget {
+        id: row.get(0)?,
+        pattern: row.get(1)?,
+        label: row.get(2)?,
+        max_score: row.get(3)?,
+        created_at: row.get(4)?,
+        notify_on_breach: row.get::<_, i32>(5)? != 0,
+        warning_score: row.get(6)?,
+    })
+}
+
+/// All budgets, newest first
+pub fn load_budgets(conn: &Connection) -> Result<Vec<DebtBudget>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {BUDGET_COLUMNS} FROM debt_budgets ORDER BY created_at DESC"
+    ))?;
+
+    let items = stmt
+        .query_map([], budget_from_row)?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    Ok(items)
+}
+
 #[tauri::command]
 pub async fn budget_crud(
     workspace_path: String,
@@ -464,8 +1113,8 @@ pub async fn budget_crud(
         "create" => {
             let item = item.ok_or("Item required for create")?;
             conn.execute(
-                "INSERT INTO debt_budgets (id, pattern, label, max_score, created_at, notify_on_breach) VALUES (?1,?2,?3,?4,?5,?6)",
-                params![&item.id, &item.pattern, &item.label, item.max_score, item.created_at, item.notify_on_breach as i32],
+                "INSERT INTO debt_budgets (id, pattern, label, max_score, created_at, notify_on_breach, warning_score) VALUES (?1,?2,?3,?4,?5,?6,?7)",
+                params![&item.id, &item.pattern, &item.label, item.max_score, item.created_at, item.notify_on_breach as i32, item.warning_score],
             )
             .map_err(|e| format!("Insert error: {e}"))?;
             Ok(serde_json::json!({"status": "created", "id": item.id}))
@@ -473,8 +1122,8 @@ pub async fn budget_crud(
         "update" => {
             let item = item.ok_or("Item required for update")?;
             conn.execute(
-                "UPDATE debt_budgets SET pattern=?2, label=?3, max_score=?4, notify_on_breach=?5 WHERE id=?1",
-                params![&item.id, &item.pattern, &item.label, item.max_score, item.notify_on_breach as i32],
+                "UPDATE debt_budgets SET pattern=?2, label=?3, max_score=?4, notify_on_breach=?5, warning_score=?6 WHERE id=?1",
+                params![&item.id, &item.pattern, &item.label, item.max_score, item.notify_on_breach as i32, item.warning_score],
             )
             .map_err(|e| format!("Update error: {e}"))?;
             Ok(serde_json::json!({"status": "updated", "id": item.id}))
@@ -482,45 +1131,18 @@ pub async fn budget_crud(
         "read" => {
             let id = id.ok_or("ID required for read")?;
             let mut stmt = conn
-                .prepare("SELECT id, pattern, label, max_score, created_at, notify_on_breach FROM debt_budgets WHERE id = ?1")
+                .prepare(&format!("SELECT {BUDGET_COLUMNS} FROM debt_budgets WHERE id = ?1"))
                 .map_err(|e| format!("Query error: {e}"))?;
 
             let item: Option<DebtBudget> = stmt
-                .query_row(params![id], |row| {
-                    Ok(DebtBudget {
-                        id: row.get(0)?,
-                        pattern: row.get(1)?,
-                        label: row.get(2)?,
-                        max_score: row.get(3)?,
-                        created_at: row.get(4)?,
-                        notify_on_breach: row.get::<_, i32>(5)? != 0,
-                    })
-                })
+                .query_row(params![id], budget_from_row)
                 .optional()
                 .map_err(|e| format!("Read error: {e}"))?;
 
             Ok(serde_json::to_value(item).unwrap_or(serde_json::Value::Null))
         }
         "list" => {
-            let mut stmt = conn
-                .prepare("SELECT id, pattern, label, max_score, created_at, notify_on_breach FROM debt_budgets ORDER BY created_at DESC")
-                .map_err(|e| format!("Query error: {e}"))?;
-
-            let items: Vec<DebtBudget> = stmt
-                .query_map([], |row| {
-                    Ok(DebtBudget {
-                        id: row.get(0)?,
-                        pattern: row.get(1)?,
-                        label: row.get(2)?,
-                        max_score: row.get(3)?,
-                        created_at: row.get(4)?,
-                        notify_on_breach: row.get::<_, i32>(5)? != 0,
-                    })
-                })
-                .map_err(|e| format!("Map error: {e}"))?
-                .filter_map(|r| r.ok())
-                .collect();
-
+            let items = load_budgets(&conn).map_err(|e| format!("Query error: {e}"))?;
             Ok(serde_json::to_value(items).unwrap_or_default())
         }
         "delete" => {
@@ -622,6 +1244,8 @@ mod tests {
             language: "rust".to_string(),
             last_modified: 123,
             supervision_status: "none".to_string(),
+            functions: Vec::new(),
+            smell_counts: Default::default(),
         };
 
         upsert_file_score(&conn, &score).expect("upsert file score");
@@ -633,4 +1257,129 @@ mod tests {
         assert_eq!(loaded.composite_score, score.composite_score);
         assert_eq!(loaded.loc, score.loc);
     }
+
+    #[test]
+    fn critical_item_on_high_debt_file_outranks_low_item() {
+        let conn = Connection::open_in_memory().expect("in-memory db");
+        initialize_schema(&conn).expect("schema init");
+
+        upsert_file_score(
+            &conn,
+            &FileScore {
+                path: "/tmp/repo/src/hot.rs".to_string(),
+                relative_path: "src/hot.rs".to_string(),
+                composite_score: 85.0,
+                components: empty_components(),
+                loc: 400,
+                language: "rust".to_string(),
+                last_modified: 0,
+                supervision_status: "none".to_string(),
+                functions: Vec::new(),
+                smell_counts: Default::default(),
+            },
+        )
+        .expect("upsert file score");
+
+        let item = |id: &str, severity: &str, file_path: Option<&str>| RegisterItem {
+            id: id.to_string(),
+            created_at: 0,
+            updated_at: 0,
+            title: id.to_string(),
+            description: String::new(),
+            file_path: file_path.map(str::to_string),
+            severity: severity.to_string(),
+            item_type: "code".to_string(),
+            owner: None,
+            target_sprint: None,
+            estimated_hours: None,
+            actual_hours: None,
+            status: "open".to_string(),
+            tags: vec![],
+            linked_commit: None,
+            notes: None,
+        };
+        insert_register_item(&conn, &item("low-1", "low", None)).expect("insert low");
+        insert_register_item(&conn, &item("crit-1", "critical", Some("src/hot.rs"))).expect("insert critical");
+        let resolved = RegisterItem { status: "resolved".to_string(), ..item("crit-2", "critical", Some("src/hot.rs")) };
+        insert_register_item(&conn, &resolved).expect("insert resolved");
+        let wontfix = RegisterItem { status: "wontfix".to_string(), ..item("high-1", "high", None) };
+        insert_register_item(&conn, &wontfix).expect("insert wontfix");
+
+        let ranked = compute_register_priorities(&conn).expect("priorities");
+        assert_eq!(ranked.len(), 2);
+        assert_eq!(ranked[0].item.id, "crit-1");
+        assert_eq!(ranked[0].file_score, Some(85.0));
+        assert!(ranked[0].priority_score > ranked[1].priority_score);
+    }
+
+    #[test]
+    fn prune_removes_pairs_referencing_deleted_files() {
+        let conn = Connection::open_in_memory().expect("in-memory db");
+        initialize_schema(&conn).expect("schema");
+        conn.execute_batch(
+            "INSERT INTO coupling_pairs (file_a, file_b, co_change_count) VALUES ('src/a.rs', 'src/b.rs', 3);
+             INSERT INTO coupling_pairs (file_a, file_b, co_change_count) VALUES ('src/a.rs', 'src/deleted.rs', 2);",
+        )
+        .expect("insert pairs");
+
+        let current: std::collections::HashSet<String> =
+            ["src/a.rs".to_string(), "src/b.rs".to_string()].into_iter().collect();
+        let removed = prune_coupling_pairs_with_conn(&conn, &current).expect("prune");
+
+        assert_eq!(removed, 1);
+        let remaining: Vec<String> = conn
+            .prepare("SELECT file_b FROM coupling_pairs")
+            .expect("prepare")
+            .query_map([], |row| row.get(0))
+            .expect("query")
+            .collect::<Result<_>>()
+            .expect("rows");
+        assert_eq!(remaining, vec!["src/b.rs".to_string()]);
+    }
+
+    #[test]
+    fn import_reports_invalid_rows_and_inserts_the_rest() {
+        let conn = Connection::open_in_memory().expect("in-memory db");
+        initialize_schema(&conn).expect("schema");
+
+        let items_json = r#"[
+            {"title": "Split parser", "severity": "high", "item_type": "code", "file_path": "src/parser.rs"},
+            {"id": "dep-1", "title": "Bump serde", "severity": "low", "item_type": "dependency", "status": "in_progress"},
+            {"title": "Typo severity", "severity": "urgent", "item_type": "code"}
+        ]"#;
+        let report = import_register_items_with_conn(&conn, items_json).expect("import");
+
+        assert_eq!(report.imported, 2);
+        assert_eq!(report.failed, 1);
+        assert!(report.rows[0].success && report.rows[0].id.is_some());
+        assert_eq!(report.rows[1].id.as_deref(), Some("dep-1"));
+        assert!(!report.rows[2].success);
+        assert!(report.rows[2].error.as_deref().unwrap_or("").contains("severity 'urgent'"));
+
+        let items = load_register_items(&conn).expect("load items");
+        assert_eq!(items.len(), 2);
+        assert!(items.iter().all(|item| item.status == "open" || item.id == "dep-1"));
+    }
+
+    #[test]
+    fn resolve_by_file_closes_open_items_on_that_file_only() {
+        let conn = Connection::open_in_memory().expect("in-memory db");
+        initialize_schema(&conn).expect("schema");
+
+        let items_json = r#"[
+            {"id": "a", "title": "Split parser", "severity": "high", "item_type": "code", "file_path": "src/parser.rs"},
+            {"id": "b", "title": "Test parser", "severity": "low", "item_type": "test", "file_path": "src/parser.rs"},
+            {"id": "c", "title": "Split lexer", "severity": "low", "item_type": "code", "file_path": "src/lexer.rs"}
+        ]"#;
+        import_register_items_with_conn(&conn, items_json).expect("import");
+
+        let updated = resolve_register_items_for_file(&conn, "src/parser.rs").expect("resolve");
+        assert_eq!(updated, 2);
+
+        let items = load_register_items(&conn).expect("load items");
+        let status = |id: &str| items.iter().find(|item| item.id == id).map(|item| item.status.as_str());
+        assert_eq!(status("a"), Some("resolved"));
+        assert_eq!(status("b"), Some("resolved"));
+        assert_eq!(status("c"), Some("open"));
+    }
 }
@@ -1,4 +1,5 @@
-use crate::models::workspace::WorkspaceMeta;
+use crate::commands::settings::SkipDirectories;
+use crate::models::workspace::{VersionInfo, WorkspaceIssue, WorkspaceMeta, WorkspaceValidation};
 use git2::Repository;
 use serde::{Deserialize, Serialize};
 use std::path::Path;
@@ -15,15 +16,29 @@ pub struct GitAnalysisData {
 
 #[tauri::command]
 pub async fn run_git_analysis(workspace_path: String, history_days: u32) -> Result<GitAnalysisData, String> {
-    let churn = crate::analysis::churn::analyze_churn(&workspace_path, history_days)
+    let settings = crate::commands::settings::load_effective_analysis_settings(&workspace_path).ok();
+    let max_files_per_commit = settings
+        .as_ref()
+        .map_or(crate::analysis::coupling::DEFAULT_MAX_FILES_PER_COMMIT, |s| {
+            s.max_files_per_commit_for_coupling
+        });
+    let analysis_ref = settings.and_then(|settings| settings.analysis_ref);
+    let analysis_ref = analysis_ref.as_deref();
+
+    let churn = crate::analysis::churn::analyze_churn(&workspace_path, history_days, analysis_ref)
         .unwrap_or_default();
 
-    let blame = crate::analysis::knowledge::analyze_knowledge(&workspace_path)
+    let blame = crate::analysis::knowledge::analyze_knowledge(&workspace_path, analysis_ref)
         .unwrap_or_default();
 
-    let co_changes = crate::analysis::coupling::analyze_co_changes(&workspace_path, history_days)
-        .unwrap_or_default()
-        .pairs;
+    let co_changes = crate::analysis::coupling::analyze_co_changes(
+        &workspace_path,
+        history_days,
+        analysis_ref,
+        max_files_per_commit,
+    )
+    .unwrap_or_default()
+    .pairs;
 
     // Compute summary stats
     let commit_count: usize = churn.values().sum();
@@ -53,6 +68,272 @@ pub async fn run_git_analysis(workspace_path: String, history_days: u32) -> Resu
     })
 }
 
+/// Check `.debtengine` integrity; with `repair`, re-run settings migration
+/// and DB schema setup when every issue found is repairable.
+#[tauri::command]
+pub async fn validate_workspace(
+    workspace_path: String,
+    repair: Option<bool>,
+) -> Result<WorkspaceValidation, String> {
+    let debtengine_dir = Path::new(&workspace_path).join(".debtengine");
+    if !debtengine_dir.is_dir() {
+        return Err("NOT_INITIALIZED: .debtengine directory does not exist".to_string());
+    }
+
+    let issues: Vec<WorkspaceIssue> = crate::commands::db::check_database(&workspace_path)
+        .into_iter()
+        .chain(crate::commands::settings::check_settings_file(&workspace_path))
+        .collect();
+    let can_auto_repair = issues.iter().all(|issue| issue.repairable);
+
+    let mut repaired = false;
+    if repair.unwrap_or(false) && !issues.is_empty() && can_auto_repair {
+        crate::commands::settings::load_settings_from_disk(&workspace_path)?;
+        crate::commands::db::get_db_connection(&workspace_path)
+            .map_err(|e| format!("Database error: {e}"))?;
+        repaired = true;
+    }
+
+    Ok(WorkspaceValidation {
+        valid: issues.is_empty(),
+        issues,
+        can_auto_repair,
+        repaired,
+    })
+}
+
+/// Stored schema versions of `state.db` and settings.json against the ones
+/// this build expects, read without migrating either file
+#[tauri::command]
+pub async fn get_version_info(workspace_path: String) -> Result<VersionInfo, String> {
+    let db_version = crate::commands::db::stored_db_version(&workspace_path);
+    let settings_version = crate::commands::settings::stored_settings_version(&workspace_path);
+    let expected_db_version = crate::commands::db::DB_SCHEMA_VERSION;
+    let expected_settings_version = crate::commands::settings::SETTINGS_SCHEMA_VERSION;
+
+    let stored = [(db_version, expected_db_version), (settings_version, expected_settings_version)];
+    Ok(VersionInfo {
+        db_version,
+        expected_db_version,
+        settings_version,
+        expected_settings_version,
+        needs_migration: stored
+            .iter()
+            .any(|(version, expected)| version.is_some_and(|v| v < *expected)),
+        is_future: stored
+            .iter()
+            .any(|(version, expected)| version.is_some_and(|v| v > *expected)),
+    })
+}
+
+/// Per-file commit counts between two refs (e.g. release tags), independent
+/// of the `gitHistoryDays` window
+#[tauri::command]
+pub async fn analyze_churn_range(
+    workspace_path: String,
+    from_ref: String,
+    to_ref: String,
+) -> Result<crate::analysis::churn::ChurnData, String> {
+    crate::analysis::churn::analyze_churn_range(&workspace_path, &from_ref, &to_ref)
+}
+
+/// Files changing more often in the recent half of the `gitHistoryDays`
+/// window than in the older half
+#[tauri::command]
+pub async fn get_churn_acceleration(
+    workspace_path: String,
+) -> Result<Vec<crate::analysis::churn::ChurnAcceleration>, String> {
+    let settings = crate::commands::settings::load_effective_analysis_settings(&workspace_path)?;
+    crate::analysis::churn::analyze_churn_acceleration(
+        &workspace_path,
+        settings.history_days,
+        settings.analysis_ref.as_deref(),
+    )
+}
+
+/// A moved file and the review context still filed under its old path
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RelocatedFile {
+    #[serde(flatten)]
+    pub relocation: crate::analysis::churn::FileRelocation,
+    /// Ids of register items whose `file_path` is still the old path
+    pub stale_register_items: Vec<String>,
+    /// An ADR still sits next to the old path or names it
+    pub stale_adr: bool,
+}
+
+/// Files renamed or moved within the `gitHistoryDays` window, flagging
+/// register items and ADRs that still point at the old path
+#[tauri::command]
+pub async fn get_relocated_files(workspace_path: String) -> Result<Vec<RelocatedFile>, String> {
+    get_relocated_files_internal(&workspace_path)
+}
+
+pub fn get_relocated_files_internal(workspace_path: &str) -> Result<Vec<RelocatedFile>, String> {
+    let settings = crate::commands::settings::load_effective_analysis_settings(workspace_path)?;
+    let relocations = crate::analysis::churn::analyze_relocations(
+        workspace_path,
+        settings.history_days,
+        settings.analysis_ref.as_deref(),
+    )?;
+    if relocations.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let conn = crate::commands::db::get_db_connection(workspace_path)
+        .map_err(|e| format!("DB error: {e}"))?;
+    let register_items = crate::commands::db::load_register_items(&conn)
+        .map_err(|e| format!("Query error: {e}"))?;
+    let adr_contents: Vec<String> = fs::read_dir(Path::new(workspace_path).join(".debtengine/adrs"))
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter_map(|entry| fs::read_to_string(entry.path()).ok())
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Ok(relocations
+        .into_iter()
+        .map(|relocation| {
+            let stale_register_items = register_items
+                .iter()
+                .filter(|item| item.file_path.as_deref() == Some(relocation.old_path.as_str()))
+                .map(|item| item.id.clone())
+                .collect();
+            // Central ADRs match by stem, so only one left beside the old
+            // file or naming the old path is out of date.
+            let old_inline_adr = crate::analysis::staleness::find_adrs_for(&relocation.old_path, workspace_path)
+                .into_iter()
+                .any(|adr| !adr.starts_with(Path::new(workspace_path).join(".debtengine")));
+            let stale_adr =
+                old_inline_adr || adr_contents.iter().any(|content| mentions_path(content, &relocation.old_path));
+            RelocatedFile {
+                relocation,
+                stale_register_items,
+                stale_adr,
+            }
+        })
+        .collect())
+}
+
+/// Whether `content` names `path` as a whole token, so `src/main.rs` does not
+/// match `app/src/main.rs` or `src/main.rs.bak`. Trailing sentence dots and a
+/// leading `./` are ignored.
+fn mentions_path(content: &str, path: &str) -> bool {
+    let is_path_char = |c: char| c.is_alphanumeric() || matches!(c, '/' | '\\' | '.' | '_' | '-');
+    content
+        .split(|c: char| !is_path_char(c))
+        .map(|token| token.trim_end_matches('.').trim_start_matches("./"))
+        .any(|token| token == path)
+}
+
+/// Commits touching one file within the `history_days` window, for the
+/// file detail view
+#[tauri::command]
+pub async fn get_file_churn_detail(
+    workspace_path: String,
+    relative_path: String,
+    history_days: u32,
+) -> Result<crate::analysis::churn::FileChurnDetail, String> {
+    let analysis_ref = crate::commands::settings::load_effective_analysis_settings(&workspace_path)
+        .ok()
+        .and_then(|settings| settings.analysis_ref);
+    crate::analysis::churn::analyze_file_churn_detail(
+        &workspace_path,
+        &relative_path,
+        history_days,
+        analysis_ref.as_deref(),
+    )
+}
+
+/// Files sharing dominant blame authors with `relative_path`
+/// (social coupling), strongest ownership overlap first
+#[tauri::command]
+pub async fn get_ownership_coupling(
+    workspace_path: String,
+    relative_path: String,
+) -> Result<Vec<crate::analysis::knowledge::OwnershipCoupling>, String> {
+    let analysis_ref = crate::commands::settings::load_effective_analysis_settings(&workspace_path)
+        .ok()
+        .and_then(|settings| settings.analysis_ref);
+    let blame = crate::analysis::knowledge::analyze_knowledge(&workspace_path, analysis_ref.as_deref())?;
+    Ok(crate::analysis::knowledge::compute_ownership_coupling(
+        &blame,
+        &relative_path.replace('\\', "/"),
+    ))
+}
+
+/// Bus-factor emergencies: single-owner files whose owner is inactive
+#[tauri::command]
+pub async fn get_knowledge_risk(
+    workspace_path: String,
+    inactive_days: u32,
+) -> Result<Vec<crate::analysis::knowledge::KnowledgeRisk>, String> {
+    let analysis_ref = crate::commands::settings::load_effective_analysis_settings(&workspace_path)
+        .ok()
+        .and_then(|settings| settings.analysis_ref);
+    let blame = crate::analysis::knowledge::analyze_knowledge(&workspace_path, analysis_ref.as_deref())?;
+    let activity = crate::analysis::knowledge::analyze_author_activity(&workspace_path)?;
+    Ok(crate::analysis::knowledge::compute_knowledge_risk(
+        &blame,
+        &activity,
+        inactive_days,
+        chrono::Utc::now().timestamp(),
+    ))
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LineBlame {
+    pub line: usize,
+    pub author: String,
+    pub commit_short: String,
+    pub time: i64,
+}
+
+/// Per-line blame for `start_line..=end_line` (1-based), clamped to the file length.
+#[tauri::command]
+pub async fn get_line_blame(
+    workspace_path: String,
+    relative_path: String,
+    start_line: usize,
+    end_line: usize,
+) -> Result<Vec<LineBlame>, String> {
+    let absolute_path = Path::new(&workspace_path).join(&relative_path);
+    let source = fs::read_to_string(&absolute_path)
+        .map_err(|e| format!("FILE_NOT_FOUND: Could not read {relative_path}: {e}"))?;
+
+    let line_count = source.lines().count();
+    if line_count == 0 {
+        return Ok(Vec::new());
+    }
+    let start = start_line.clamp(1, line_count);
+    let end = end_line.clamp(start, line_count);
+
+    let repo = Repository::open(&workspace_path)
+        .map_err(|e| format!("Git error: {e}"))?;
+    let blame = repo
+        .blame_file(Path::new(&relative_path), None)
+        .map_err(|e| format!("Blame error: {e}"))?;
+
+    let mut lines = Vec::with_capacity(end - start + 1);
+    for line in start..=end {
+        // Lines added after the last commit have no blame entry.
+        let Some(hunk) = blame.get_line(line) else {
+            continue;
+        };
+        let signature = hunk.final_signature();
+        lines.push(LineBlame {
+            line,
+            author: signature.name().unwrap_or("unknown").to_string(),
+            commit_short: hunk.final_commit_id().to_string().chars().take(7).collect(),
+            time: signature.when().seconds(),
+        });
+    }
+
+    Ok(lines)
+}
+
 #[tauri::command]
 pub async fn open_workspace(path: String) -> Result<WorkspaceMeta, String> {
     let workspace_path = Path::new(&path);
@@ -105,38 +386,226 @@ pub async fn open_workspace(path: String) -> Result<WorkspaceMeta, String> {
 }
 
 pub(crate) fn walkdir(root: &str) -> Vec<String> {
+    walkdir_with(root, &crate::commands::settings::load_skip_directories(root))
+}
+
+/// `walkdir` with `skipDirectories` loaded once by the caller
+pub(crate) fn walkdir_with(root: &str, skip: &SkipDirectories) -> Vec<String> {
+    walk_workspace(root, skip, is_source_file)
+}
+
+/// Markdown, reStructuredText and plain-text files (`trackDocs`)
+pub(crate) fn doc_files(root: &str, skip: &SkipDirectories) -> Vec<String> {
+    walk_workspace(root, skip, is_doc_file)
+}
+
+fn walk_workspace(root: &str, skip: &SkipDirectories, include: fn(&Path) -> bool) -> Vec<String> {
+    if let Some(files) = bare_repo_files(root, skip, include) {
+        return files;
+    }
+
     let mut files = Vec::new();
     let root_path = Path::new(root);
 
-    fn walk_recursive(dir: &Path, files: &mut Vec<String>) {
+    fn walk_recursive(
+        root: &Path,
+        dir: &Path,
+        skip: &SkipDirectories,
+        include: fn(&Path) -> bool,
+        files: &mut Vec<String>,
+    ) {
         if let Ok(entries) = fs::read_dir(dir) {
             for entry in entries.flatten() {
                 let path = entry.path();
-                let name = path.file_name().unwrap_or_default().to_string_lossy();
-
-                // Skip hidden directories and common non-source directories
-                if name.starts_with('.') || name == "node_modules" || name == "target"
-                    || name == "__pycache__" || name == "vendor" || name == "dist" || name == "build"
-                {
-                    continue;
-                }
 
                 if path.is_dir() {
-                    walk_recursive(&path, files);
-                } else if is_source_file(&path) {
+                    let relative = path.strip_prefix(root).unwrap_or(&path).to_string_lossy().replace('\\', "/");
+                    if !is_skipped_dir(&relative, skip) {
+                        walk_recursive(root, &path, skip, include, files);
+                    }
+                } else if !is_hidden_file(&path) && include(&path) {
                     files.push(path.to_string_lossy().to_string());
                 }
             }
         }
     }
 
-    walk_recursive(root_path, &mut files);
+    walk_recursive(root_path, root_path, skip, include, &mut files);
     files
 }
 
+/// Resolve the commit that history analysis walks from: `analysis_ref` when
+/// set (any revspec, e.g. `main` or `origin/main`), otherwise HEAD.
+/// An unborn HEAD (no commits yet) yields `None`, which the analyzers treat
+/// as empty history; an unknown explicit ref is an error.
+pub(crate) fn resolve_analysis_ref(
+    repo: &Repository,
+    analysis_ref: Option<&str>,
+) -> Result<Option<git2::Oid>, String> {
+    match analysis_ref.map(str::trim).filter(|r| !r.is_empty()) {
+        Some(spec) => repo
+            .revparse_single(spec)
+            .and_then(|object| object.peel_to_commit())
+            .map(|commit| Some(commit.id()))
+            .map_err(|e| format!("INVALID_REF: {spec} does not resolve to a commit: {e}")),
+        None => Ok(repo
+            .head()
+            .ok()
+            .and_then(|head| head.peel_to_commit().ok())
+            .map(|commit| commit.id())),
+    }
+}
+
+/// Relative paths of files in the git index, or `None` if the index can't
+/// be read (e.g. not a repository). Bare repos are listed from HEAD already.
+pub(crate) fn tracked_files(workspace_path: &str) -> Option<std::collections::HashSet<String>> {
+    let repo = Repository::open(workspace_path).ok()?;
+    if repo.is_bare() {
+        return None;
+    }
+    let index = repo.index().ok()?;
+    Some(
+        index
+            .iter()
+            .map(|entry| String::from_utf8_lossy(&entry.path).replace('\\', "/"))
+            .collect(),
+    )
+}
+
+/// Workspace-relative paths with uncommitted additions or modifications,
+/// staged or not. Deleted files are left out since there is nothing to score.
+pub(crate) fn dirty_files(workspace_path: &str) -> Result<std::collections::HashSet<String>, String> {
+    let repo = Repository::open(workspace_path).map_err(|e| format!("Git error: {e}"))?;
+    let mut options = git2::StatusOptions::new();
+    options.include_untracked(true).recurse_untracked_dirs(true);
+    let statuses = repo
+        .statuses(Some(&mut options))
+        .map_err(|e| format!("Git status error: {e}"))?;
+
+    let changed = git2::Status::WT_NEW
+        | git2::Status::WT_MODIFIED
+        | git2::Status::WT_RENAMED
+        | git2::Status::WT_TYPECHANGE
+        | git2::Status::INDEX_NEW
+        | git2::Status::INDEX_MODIFIED
+        | git2::Status::INDEX_RENAMED
+        | git2::Status::INDEX_TYPECHANGE;
+    let deleted = git2::Status::WT_DELETED | git2::Status::INDEX_DELETED;
+
+    Ok(statuses
+        .iter()
+        .filter(|entry| entry.status().intersects(changed) && !entry.status().intersects(deleted))
+        .filter_map(|entry| entry.path().map(|path| path.replace('\\', "/")))
+        .collect())
+}
+
+/// Skip hidden directories and the configured `skipDirectories`
+fn is_skipped_dir(relative_dir: &str, skip: &SkipDirectories) -> bool {
+    let name = relative_dir.rsplit('/').next().unwrap_or(relative_dir);
+    name.starts_with('.') || skip.matches(relative_dir)
+}
+
+/// Skip dot-files such as `.eslintrc.js`; they configure tools rather than
+/// hold the project's code
+fn is_hidden_file(path: &Path) -> bool {
+    path.file_name().is_some_and(|name| name.to_string_lossy().starts_with('.'))
+}
+
+/// Open `root` as a bare repository, if it is one. A quick layout check
+/// avoids opening the repo for every ordinary working-tree walk.
+pub(crate) fn open_bare_repo(root: &str) -> Option<Repository> {
+    let root_path = Path::new(root);
+    if root_path.join(".git").exists() || !root_path.join("HEAD").is_file() {
+        return None;
+    }
+    Repository::open(root).ok().filter(|repo| repo.is_bare())
+}
+
+/// List source files from the HEAD tree of a bare repository. Paths are
+/// joined onto `root` so they relativize the same way as working-tree paths.
+fn bare_repo_files(root: &str, skip: &SkipDirectories, include: fn(&Path) -> bool) -> Option<Vec<String>> {
+    let repo = open_bare_repo(root)?;
+    let mut files = Vec::new();
+    let tree = match repo.head().and_then(|head| head.peel_to_tree()) {
+        Ok(tree) => tree,
+        Err(_) => return Some(files),
+    };
+
+    let _ = tree.walk(git2::TreeWalkMode::PreOrder, |dir, entry| {
+        let name = entry.name().unwrap_or_default();
+        if entry.kind() == Some(git2::ObjectType::Tree) {
+            return if is_skipped_dir(&format!("{dir}{name}"), skip) {
+                git2::TreeWalkResult::Skip
+            } else {
+                git2::TreeWalkResult::Ok
+            };
+        }
+        let relative = format!("{dir}{name}");
+        if entry.kind() == Some(git2::ObjectType::Blob)
+            && !is_hidden_file(Path::new(&relative))
+            && include(Path::new(&relative))
+        {
+            files.push(format!("{}/{relative}", root.trim_end_matches(['/', '\\'])));
+        }
+        git2::TreeWalkResult::Ok
+    });
+
+    Some(files)
+}
+
+/// Read a workspace file, falling back to the HEAD tree blob when the
+/// workspace is a bare clone without a working tree.
+pub(crate) fn read_workspace_file(workspace_path: &str, file_path: &str) -> Result<String, String> {
+    read_workspace_file_with(workspace_path, file_path, None)
+}
+
+/// `read_workspace_file` reusing a bare repository opened once by the caller
+/// (see `open_bare_repo`) instead of reopening it for every file
+pub(crate) fn read_workspace_file_with(
+    workspace_path: &str,
+    file_path: &str,
+    bare_repo: Option<&std::sync::Mutex<Repository>>,
+) -> Result<String, String> {
+    let e = match fs::read_to_string(file_path) {
+        Ok(content) => return Ok(content),
+        Err(e) => e,
+    };
+    let relative = file_path
+        .strip_prefix(workspace_path)
+        .unwrap_or(file_path)
+        .trim_start_matches(['/', '\\']);
+    match bare_repo {
+        Some(repo) => {
+            let repo = repo.lock().map_err(|_| "Git repository lock error".to_string())?;
+            read_head_blob(&repo, relative, file_path)
+        }
+        None => {
+            let repo = open_bare_repo(workspace_path)
+                .ok_or_else(|| format!("Failed to read {file_path}: {e}"))?;
+            read_head_blob(&repo, relative, file_path)
+        }
+    }
+}
+
+fn read_head_blob(repo: &Repository, relative: &str, file_path: &str) -> Result<String, String> {
+    let tree = repo
+        .head()
+        .and_then(|head| head.peel_to_tree())
+        .map_err(|e| format!("Git error: {e}"))?;
+    let entry = tree
+        .get_path(Path::new(relative))
+        .map_err(|e| format!("Failed to read {file_path}: {e}"))?;
+    let blob = repo
+        .find_blob(entry.id())
+        .map_err(|e| format!("Git error: {e}"))?;
+    String::from_utf8(blob.content().to_vec())
+        .map_err(|e| format!("Failed to read {file_path}: {e}"))
+}
+
 fn is_source_file(path: &Path) -> bool {
     match path.extension().and_then(|e| e.to_str()) {
         Some("ts") | Some("tsx") | Some("js") | Some("jsx") => true,
+        Some("mjs") | Some("cjs") => true,
         Some("py") => true,
         Some("go") => true,
         Some("rs") => true,
@@ -145,6 +614,41 @@ fn is_source_file(path: &Path) -> bool {
     }
 }
 
+pub(crate) fn is_doc_file(path: &Path) -> bool {
+    matches!(
+        path.extension().and_then(|e| e.to_str()),
+        Some("md") | Some("rst") | Some("txt")
+    )
+}
+
+/// File name endings of protobuf/gRPC stubs and OpenAPI/codegen output
+pub const GENERATED_CODE_SUFFIXES: [&str; 12] = [
+    ".pb.go",
+    ".pb.gw.go",
+    "_pb2.py",
+    "_pb2_grpc.py",
+    "_pb.js",
+    "_pb.ts",
+    "_grpc_pb.js",
+    "_grpc_pb.ts",
+    ".pb.rs",
+    ".gen.go",
+    ".gen.ts",
+    ".generated.ts",
+];
+
+/// Machine-generated code recognized by its file name (`GENERATED_CODE_SUFFIXES`)
+pub(crate) fn is_generated_code(path: &str) -> bool {
+    let name = path.rsplit(['/', '\\']).next().unwrap_or(path);
+    GENERATED_CODE_SUFFIXES.iter().any(|suffix| name.ends_with(suffix))
+}
+
+/// TypeScript declaration files (`.d.ts`, `.d.mts`, `.d.cts`)
+pub(crate) fn is_type_declaration(path: &str) -> bool {
+    let name = path.rsplit(['/', '\\']).next().unwrap_or(path);
+    name.ends_with(".d.ts") || name.ends_with(".d.mts") || name.ends_with(".d.cts")
+}
+
 fn get_last_analysis_time(conn: &rusqlite::Connection) -> Option<i64> {
     conn.query_row(
         "SELECT MAX(timestamp) FROM debt_snapshots",
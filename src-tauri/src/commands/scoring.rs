@@ -2,57 +2,422 @@ use crate::models::file_score::*;
 use std::sync::{Arc, Mutex};
 use tauri::Emitter;
 
+/// Composite score above which a file counts as high debt
+const HIGH_DEBT_THRESHOLD: f64 = 65.0;
+
+/// Lower bound of the "medium" severity band, matching the heatmap colors
+const MEDIUM_SEVERITY_THRESHOLD: f64 = 35.0;
+
+/// Leading lines scanned for `debtlens: weight` directives
+const WEIGHT_DIRECTIVE_LINES: usize = 10;
+
 struct AnalysisInputs {
     history_days: u32,
     weights: std::collections::HashMap<String, f64>,
+    raw_score_caps: std::collections::HashMap<String, f64>,
+    file_length_threshold: usize,
+    smell_density_scale: f64,
+    magic_number_allowlist: std::collections::HashMap<String, Vec<f64>>,
+    composite_formula: CompositeFormula,
+    relative_blend: f64,
+    tracked_files_only: bool,
+    include_type_declarations: bool,
+    include_generated_code: bool,
+    include_languages: Vec<String>,
+    exclude_languages: Vec<String>,
+    frozen_complexity_threshold: f64,
+    frozen_complexity_penalty: f64,
+    accepted_patterns: Vec<glob::Pattern>,
+    /// `includePatterns`; empty scores every file
+    include_patterns: Vec<glob::Pattern>,
+    /// `excludePatterns`, applied after `include_patterns`
+    exclude_patterns: Vec<glob::Pattern>,
+    new_file_grace_days: u32,
+    /// `adrStaleDays`: review age at which ADR staleness saturates
+    adr_stale_days: i64,
+    max_threads: usize,
+    /// Commit history analysis walked from, if any
+    history_head: Option<String>,
+    /// Source files in the workspace, walked once
+    workspace_files: Vec<String>,
+    /// Documentation files, when `trackDocs` is on
+    doc_files: Vec<String>,
+    /// Last commit time of each documentation file, from one history walk
+    doc_last_changed: std::collections::HashMap<String, i64>,
+    /// Bare import prefixes that refer to workspace code
+    import_roots: std::collections::HashSet<String>,
+    /// Internal import graph degrees; empty unless `load_analysis_inputs` built them
+    import_degrees: crate::analysis::coupling::ImportDegrees,
+    /// `skipDirectories`, loaded once for every walk of this analysis
+    skip_directories: crate::commands::settings::SkipDirectories,
+    /// `jacocoReportPath` resolved against the workspace
+    jacoco_report_path: Option<std::path::PathBuf>,
+    /// Coverage reports merged once; empty unless `load_analysis_inputs` loaded them
+    coverage: crate::analysis::coverage::CoverageIndex,
     churn: crate::analysis::churn::ChurnData,
+    /// False for quick scans and when churn failed to load
+    churn_available: bool,
     blame: crate::analysis::knowledge::BlameData,
     co_changes: crate::analysis::coupling::CoChangeResult,
+    /// Opened once when the workspace is a bare clone, for reading sources
+    /// from HEAD
+    bare_repo: Option<Mutex<git2::Repository>>,
+    /// Git inputs that failed to load and why; they score as zero
+    degraded: Vec<String>,
 }
 
+/// Environment variable holding a JSON weights override for CI runs
+pub const WEIGHTS_OVERRIDE_ENV: &str = "DEBTLENS_WEIGHTS";
+
+/// Score the workspace. With `quick_scan`, git history is never read: churn,
+/// knowledge and change coupling stay at zero, and the result is marked
+/// `partial` and not persisted; reanalysis, staleness refreshes and snapshots
+/// are refused until a full analysis replaces it. `weights_override` (or
+/// `DEBTLENS_WEIGHTS`) replaces the saved weights for this run only: its
+/// scores are cached but kept out of the score history, and snapshots and
+/// reanalysis are refused the same way.
 #[tauri::command]
 pub async fn run_full_analysis(
     workspace_path: String,
+    quick_scan: Option<bool>,
+    weights_override: Option<serde_json::Value>,
     cache: tauri::State<'_, Arc<Mutex<AnalysisCache>>>,
     app: tauri::AppHandle,
 ) -> Result<AnalysisResult, String> {
-    run_full_analysis_internal(&workspace_path, cache.inner(), |progress| {
-        let _ = app.emit("analysis_progress", progress);
-    })
+    let weights_override = match weights_override {
+        Some(weights) => Some(weights),
+        None => weights_override_from_env()?,
+    };
+    run_analysis(
+        &workspace_path,
+        cache.inner(),
+        quick_scan.unwrap_or(false),
+        weights_override.as_ref(),
+        |progress| {
+            let _ = app.emit("analysis_progress", progress);
+        },
+        |summary| {
+            let _ = app.emit("analysis_complete", summary);
+        },
+    )
 }
 
 pub fn run_full_analysis_internal<F>(
     workspace_path: &str,
     cache: &Arc<Mutex<AnalysisCache>>,
+    emit_progress: F,
+) -> Result<AnalysisResult, String>
+where
+    F: FnMut(AnalysisProgress),
+{
+    run_full_analysis_with_events(workspace_path, cache, emit_progress, |_| {})
+}
+
+/// Full analysis scored with `weights_override` in place of the saved weights
+pub fn run_full_analysis_with_weights_internal<F>(
+    workspace_path: &str,
+    cache: &Arc<Mutex<AnalysisCache>>,
+    weights_override: &serde_json::Value,
+    emit_progress: F,
+) -> Result<AnalysisResult, String>
+where
+    F: FnMut(AnalysisProgress),
+{
+    run_analysis(workspace_path, cache, false, Some(weights_override), emit_progress, |_| {})
+}
+
+/// Weights override from `DEBTLENS_WEIGHTS`, if set
+fn weights_override_from_env() -> Result<Option<serde_json::Value>, String> {
+    parse_weights_override(std::env::var(WEIGHTS_OVERRIDE_ENV).ok().as_deref())
+}
+
+/// Parse a raw `DEBTLENS_WEIGHTS` value; unset or blank means no override
+fn parse_weights_override(raw: Option<&str>) -> Result<Option<serde_json::Value>, String> {
+    match raw {
+        Some(raw) if !raw.trim().is_empty() => serde_json::from_str(raw)
+            .map(Some)
+            .map_err(|e| format!("INVALID_WEIGHTS_OVERRIDE: {WEIGHTS_OVERRIDE_ENV} is not valid JSON: {e}")),
+        _ => Ok(None),
+    }
+}
+
+/// Content-only analysis that skips the churn, blame and co-change scans
+pub fn run_quick_scan_internal<F>(
+    workspace_path: &str,
+    cache: &Arc<Mutex<AnalysisCache>>,
+    emit_progress: F,
+) -> Result<AnalysisResult, String>
+where
+    F: FnMut(AnalysisProgress),
+{
+    run_analysis(workspace_path, cache, true, None, emit_progress, |_| {})
+}
+
+/// Full analysis that also reports a compact summary once it completes
+pub fn run_full_analysis_with_events<F, G>(
+    workspace_path: &str,
+    cache: &Arc<Mutex<AnalysisCache>>,
+    emit_progress: F,
+    emit_complete: G,
+) -> Result<AnalysisResult, String>
+where
+    F: FnMut(AnalysisProgress),
+    G: FnOnce(AnalysisSummary),
+{
+    run_analysis(workspace_path, cache, false, None, emit_progress, emit_complete)
+}
+
+fn run_analysis<F, G>(
+    workspace_path: &str,
+    cache: &Arc<Mutex<AnalysisCache>>,
+    quick_scan: bool,
+    weights_override: Option<&serde_json::Value>,
     mut emit_progress: F,
+    emit_complete: G,
 ) -> Result<AnalysisResult, String>
 where
     F: FnMut(AnalysisProgress),
+    G: FnOnce(AnalysisSummary),
 {
     let start = std::time::Instant::now();
-    let files = crate::commands::git::walkdir(workspace_path);
+    let inputs = load_analysis_inputs(workspace_path, quick_scan, weights_override)?;
+    let mut files = inputs.workspace_files.clone();
+    files.extend(inputs.doc_files.iter().cloned());
+    if inputs.tracked_files_only {
+        if let Some(tracked) = crate::commands::git::tracked_files(workspace_path) {
+            files.retain(|file| tracked.contains(&to_relative_path(workspace_path, file)));
+        }
+    }
+    if !inputs.include_type_declarations {
+        files.retain(|file| !crate::commands::git::is_type_declaration(file));
+    }
+    if !inputs.include_generated_code {
+        files.retain(|file| !crate::commands::git::is_generated_code(file));
+    }
+    files.retain(|file| language_selected(file, &inputs));
+    files.retain(|file| path_included(workspace_path, file, &inputs));
+    let (mut scored_files, errors) = score_files(workspace_path, &files, &inputs, &mut emit_progress);
+
+    let component_ranges = (inputs.relative_blend > 0.0).then(|| {
+        normalize_components_relative(
+            &mut scored_files,
+            &inputs.composite_formula,
+            &inputs.raw_score_caps,
+            inputs.relative_blend,
+        )
+    });
+
+    let first_seen = load_first_seen(workspace_path)?;
+    flag_new_files(&mut scored_files, &first_seen, chrono::Utc::now().timestamp(), inputs.new_file_grace_days);
+
+    let mut result = build_analysis_result(scored_files, start.elapsed().as_millis() as u64);
+    result.errors = errors;
+    result.partial = quick_scan;
+    result.degraded = inputs.degraded.clone();
+    // A partial result would overwrite real git-derived scores with zeros,
+    // and a weights override would leave one-off scores in the history.
+    if !quick_scan && weights_override.is_none() {
+        result.score_changes = score_changes_since_last_run(workspace_path, &result.files)?;
+        persist_result(workspace_path, &result)?;
+        persist_coupling_pairs(workspace_path, &inputs.co_changes)?;
+        if first_seen.is_empty() {
+            backdate_first_seen(workspace_path)?;
+        }
+        prune_stale_coupling_pairs(workspace_path, &result.files)?;
+    }
+    update_cache(
+        cache,
+        workspace_path.to_string(),
+        result.clone(),
+        Some(&inputs),
+        component_ranges,
+        weights_override.is_some(),
+    );
+    // The analysis is already persisted; a summary that can't be built
+    // (unreadable settings, DB hiccup) must not turn it into an error.
+    if let Ok(summary) = analysis_summary(workspace_path, &result) {
+        emit_complete(summary);
+    }
+
+    Ok(result)
+}
+
+/// Whether `includeLanguages`/`excludeLanguages` let a code file through.
+/// Docs are governed by `trackDocs` alone.
+fn language_selected(file: &str, inputs: &AnalysisInputs) -> bool {
+    if crate::commands::git::is_doc_file(std::path::Path::new(file)) {
+        return true;
+    }
+    let language = detect_language(file);
+    (inputs.include_languages.is_empty() || inputs.include_languages.contains(&language))
+        && !inputs.exclude_languages.contains(&language)
+}
+
+/// Whether the file matches an `includePatterns` glob, when any are set, and
+/// no `excludePatterns` glob. Skipped directories and the other filters
+/// still apply on top.
+fn path_included(workspace_path: &str, file: &str, inputs: &AnalysisInputs) -> bool {
+    let relative_path = to_relative_path(workspace_path, file);
+    (inputs.include_patterns.is_empty()
+        || inputs.include_patterns.iter().any(|pattern| pattern.matches(&relative_path)))
+        && !inputs.exclude_patterns.iter().any(|pattern| pattern.matches(&relative_path))
+}
+
+fn analysis_summary(workspace_path: &str, result: &AnalysisResult) -> Result<AnalysisSummary, String> {
+    let settings = crate::commands::settings::load_effective_analysis_settings(workspace_path)?;
+    let conn = crate::commands::db::get_db_connection(workspace_path)
+        .map_err(|e| format!("DB error: {e}"))?;
+    let budgets = crate::commands::db::load_budgets(&conn)
+        .map_err(|e| format!("DB read error: {e}"))?;
+
+    Ok(AnalysisSummary {
+        workspace_score: result.workspace_score,
+        file_count: result.file_count,
+        severity_counts: count_severities(&result.files, settings.warning_threshold, settings.critical_threshold),
+        duration_ms: result.duration_ms,
+        budget_breached: any_budget_breached(&result.files, &budgets),
+    })
+}
+
+/// Severity band of a score. Like `counts_as_high_debt`, a file is high or
+/// critical only when it scores above the threshold.
+fn severity_label(score: f64, warning: f64, critical: f64) -> &'static str {
+    if score > critical {
+        "critical"
+    } else if score > warning {
+        "high"
+    } else if score >= MEDIUM_SEVERITY_THRESHOLD {
+        "medium"
+    } else {
+        "low"
+    }
+}
+
+/// Severity buckets (`severity_label`) for the completion summary. Files
+/// left out of `high_debt_count` are left out here too.
+fn count_severities(files: &[FileScore], warning: f64, critical: f64) -> SeverityCounts {
+    let mut counts = SeverityCounts::default();
+    for file in files.iter().filter(|file| !excluded_from_debt_counts(file)) {
+        match severity_label(file.composite_score, warning, critical) {
+            "critical" => counts.critical += 1,
+            "high" => counts.high += 1,
+            "medium" => counts.medium += 1,
+            _ => counts.low += 1,
+        }
+    }
+    counts
+}
+
+/// A budget is breached when any file matching its pattern scores above it
+fn any_budget_breached(files: &[FileScore], budgets: &[crate::models::budget::DebtBudget]) -> bool {
+    !breached_budgets(files, budgets).is_empty()
+}
+
+/// Budgets with at least one matching file scoring above their limit
+fn breached_budgets<'a>(
+    files: &[FileScore],
+    budgets: &'a [crate::models::budget::DebtBudget],
+) -> Vec<&'a crate::models::budget::DebtBudget> {
+    budgets
+        .iter()
+        .filter(|budget| !budget_breaching_files(files, budget).is_empty())
+        .collect()
+}
+
+/// Files matching the budget's pattern that score above its `max_score`.
+/// Files in their new-file grace period don't breach budgets yet.
+fn budget_breaching_files<'a>(
+    files: &'a [FileScore],
+    budget: &crate::models::budget::DebtBudget,
+) -> Vec<&'a FileScore> {
+    let Ok(pattern) = glob::Pattern::new(&budget.pattern) else {
+        return Vec::new();
+    };
+    files
+        .iter()
+        .filter(|f| f.supervision_status != "new")
+        .filter(|f| pattern.matches(&f.relative_path) && f.composite_score > budget.max_score)
+        .collect()
+}
+
+/// Score `files` on up to `maxThreads` workers. Progress is reported from the
+/// calling thread in completion order; scores and errors keep the input order.
+fn score_files<F>(
+    workspace_path: &str,
+    files: &[String],
+    inputs: &AnalysisInputs,
+    emit_progress: &mut F,
+) -> (Vec<FileScore>, Vec<FileAnalysisError>)
+where
+    F: FnMut(AnalysisProgress),
+{
     let total = files.len();
-    let inputs = load_analysis_inputs(workspace_path)?;
+    let threads = inputs.max_threads.clamp(1, total.max(1));
 
-    let mut scored_files = Vec::with_capacity(total);
+    let file_error = |index: usize, reason: String| FileAnalysisError {
+        path: to_relative_path(workspace_path, &files[index]),
+        reason,
+    };
 
-    for (index, file_path) in files.iter().enumerate() {
-        emit_progress(AnalysisProgress {
-            current: index + 1,
-            total,
-            current_file: file_path.clone(),
-        });
+    if threads == 1 {
+        let mut scored_files = Vec::with_capacity(total);
+        let mut errors = Vec::new();
+        for (index, file_path) in files.iter().enumerate() {
+            emit_progress(AnalysisProgress {
+                current: index + 1,
+                total,
+                current_file: file_path.clone(),
+            });
 
-        if let Ok(score) = score_file(workspace_path, file_path, &inputs) {
-            scored_files.push(score);
+            match score_file(workspace_path, file_path, inputs) {
+                Ok(score) => scored_files.push(score),
+                Err(reason) => errors.push(file_error(index, reason)),
+            }
         }
+        return (scored_files, errors);
     }
 
-    let result = build_analysis_result(scored_files, start.elapsed().as_millis() as u64);
-    persist_result(workspace_path, &result)?;
-    update_cache(cache, workspace_path.to_string(), result.clone());
+    let next = std::sync::atomic::AtomicUsize::new(0);
+    let mut scored: Vec<(usize, FileScore)> = Vec::with_capacity(total);
+    let mut errors: Vec<(usize, String)> = Vec::new();
 
-    Ok(result)
+    std::thread::scope(|scope| {
+        let (tx, rx) = std::sync::mpsc::channel();
+        for _ in 0..threads {
+            let tx = tx.clone();
+            let next = &next;
+            scope.spawn(move || loop {
+                let index = next.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                if index >= total {
+                    break;
+                }
+                let score = score_file(workspace_path, &files[index], inputs);
+                if tx.send((index, score)).is_err() {
+                    break;
+                }
+            });
+        }
+        drop(tx);
+
+        for (done, (index, score)) in rx.iter().enumerate() {
+            emit_progress(AnalysisProgress {
+                current: done + 1,
+                total,
+                current_file: files[index].clone(),
+            });
+            match score {
+                Ok(score) => scored.push((index, score)),
+                Err(reason) => errors.push((index, reason)),
+            }
+        }
+    });
+
+    scored.sort_by_key(|(index, _)| *index);
+    errors.sort_by_key(|(index, _)| *index);
+    (
+        scored.into_iter().map(|(_, score)| score).collect(),
+        errors.into_iter().map(|(index, reason)| file_error(index, reason)).collect(),
+    )
 }
 
 #[tauri::command]
@@ -69,6 +434,7 @@ pub fn reanalyze_file_internal(
     file_path: &str,
     cache: &Arc<Mutex<AnalysisCache>>,
 ) -> Result<FileScore, String> {
+    ensure_full_analysis(cache)?;
     let metadata = std::fs::metadata(file_path)
         .map_err(|e| format!("Could not read file metadata for {file_path}: {e}"))?;
     let current_mtime = metadata
@@ -94,147 +460,2240 @@ pub fn reanalyze_file_internal(
         }
     }
 
-    let inputs = load_analysis_inputs(workspace_path)?;
-    let mut updated = score_file(workspace_path, file_path, &inputs)?;
-    updated.last_modified = current_mtime;
-
-    crate::commands::db::upsert_file_score(&conn, &updated)
-        .map_err(|e| format!("DB upsert error: {e}"))?;
+    // mtime moved (checkout, touch) but the content may not have.
+    let hash = std::fs::read(file_path).ok().map(|bytes| content_hash(&bytes));
+    if let Some(hash) = &hash {
+        let cached_hash = crate::commands::db::load_cached_content_hash(&conn, file_path)
+            .map_err(|e| format!("DB read error: {e}"))?;
+        if cached_hash.as_ref() == Some(hash) {
+            if let Some(mut cached) = crate::commands::db::load_cached_file_score(&conn, file_path)
+                .map_err(|e| format!("DB read error: {e}"))?
+            {
+                cached.last_modified = current_mtime;
+                crate::commands::db::upsert_file_score(&conn, &cached)
+                    .and_then(|_| crate::commands::db::store_content_hash(&conn, file_path, hash))
+                    .map_err(|e| format!("DB upsert error: {e}"))?;
+                patch_cached_result(cache, workspace_path, cached.clone());
+                return Ok(cached);
+            }
+        }
+    }
+
+    let settings = crate::commands::settings::load_effective_analysis_settings(workspace_path)?;
+    let mut updated = match rescore_content_only(workspace_path, file_path, &settings, cache)? {
+        Some(rescored) => rescored,
+        None => {
+            let inputs = load_analysis_inputs(workspace_path, false, None)?;
+            score_file_against_cache(workspace_path, file_path, &inputs, cache)?
+        }
+    };
+    updated.last_modified = current_mtime;
+    flag_rescored_new_files(workspace_path, std::slice::from_mut(&mut updated), settings.new_file_grace_days)?;
+
+    crate::commands::db::upsert_file_score(&conn, &updated)
+        .map_err(|e| format!("DB upsert error: {e}"))?;
+    if let Some(hash) = &hash {
+        crate::commands::db::store_content_hash(&conn, file_path, hash)
+            .map_err(|e| format!("DB upsert error: {e}"))?;
+    }
+
+    patch_cached_result(cache, workspace_path, updated.clone());
+
+    Ok(updated)
+}
+
+/// Full single-file score. With relative normalization it is rescaled
+/// against the ranges the rest of the cache was normalized with; without
+/// them the file stays absolute until the next full analysis.
+fn score_file_against_cache(
+    workspace_path: &str,
+    file_path: &str,
+    inputs: &AnalysisInputs,
+    cache: &Arc<Mutex<AnalysisCache>>,
+) -> Result<FileScore, String> {
+    let mut scored = score_file(workspace_path, file_path, inputs)?;
+    if inputs.relative_blend > 0.0 {
+        if let Some(ranges) = cached_component_ranges(cache, workspace_path) {
+            normalize_file(
+                &mut scored,
+                &ranges,
+                &inputs.composite_formula,
+                &inputs.raw_score_caps,
+                inputs.relative_blend,
+            );
+        }
+    }
+    Ok(scored)
+}
+
+/// Normalization ranges of the cached analysis, when it belongs to `workspace_path`
+fn cached_component_ranges(cache: &Arc<Mutex<AnalysisCache>>, workspace_path: &str) -> Option<ComponentRanges> {
+    let lock = cache.lock().ok()?;
+    if lock.workspace_path.as_deref() != Some(workspace_path) {
+        return None;
+    }
+    lock.component_ranges
+}
+
+/// 64-bit FNV-1a of a file's bytes, hex encoded. Stable across builds,
+/// unlike `DefaultHasher`, since it is persisted.
+fn content_hash(bytes: &[u8]) -> String {
+    let hash = bytes.iter().fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+    });
+    format!("{hash:016x}")
+}
+
+/// Content hash of each readable file, keyed by absolute path, so the
+/// persisted scores can be reused when only a file's mtime moves
+fn content_hashes(files: &[FileScore]) -> std::collections::HashMap<String, String> {
+    files
+        .iter()
+        .filter_map(|file| {
+            let bytes = std::fs::read(&file.path).ok()?;
+            Some((file.path.clone(), content_hash(&bytes)))
+        })
+        .collect()
+}
+
+/// Fast path for a content-only edit: while the analyzed history is unchanged,
+/// reuse the cached git-derived components and recompute the ones that depend
+/// on the file's content (smells, complexity, import coupling and decision
+/// staleness), skipping the churn/blame/co-change scans. Under
+/// relative normalization the cached components are already rescaled and the
+/// recomputed ones are rescaled with the cached ranges. Returns `None` when a
+/// full rescore is needed (no cached score or ranges, history moved, or a
+/// documentation file).
+fn rescore_content_only(
+    workspace_path: &str,
+    file_path: &str,
+    settings: &crate::commands::settings::EffectiveAnalysisSettings,
+    cache: &Arc<Mutex<AnalysisCache>>,
+) -> Result<Option<FileScore>, String> {
+    if crate::commands::git::is_doc_file(std::path::Path::new(file_path)) {
+        return Ok(None);
+    }
+
+    let relative_path = to_relative_path(workspace_path, file_path);
+    let (cached, ranges, commit_count) = {
+        let lock = cache.lock().map_err(|_| "Cache lock error".to_string())?;
+        if lock.workspace_path.as_deref() != Some(workspace_path) || lock.history_head.is_none() {
+            return Ok(None);
+        }
+        if lock.history_head != history_head(workspace_path, settings.analysis_ref.as_deref()) {
+            return Ok(None);
+        }
+        if settings.relative_blend > 0.0 && lock.component_ranges.is_none() {
+            return Ok(None);
+        }
+        let cached = lock.result
+            .as_ref()
+            .and_then(|result| result.files.iter().find(|f| f.relative_path == relative_path))
+            .cloned();
+        let commit_count = lock
+            .commit_counts
+            .as_ref()
+            .map(|counts| counts.get(&relative_path).copied().unwrap_or(0));
+        (cached, lock.component_ranges, commit_count)
+    };
+    let Some(mut file) = cached else {
+        return Ok(None);
+    };
+
+    let source = crate::commands::git::read_workspace_file(workspace_path, file_path)?;
+    let lang = detect_language(file_path);
+    let loc = source.lines().count();
+    let (smell, complexity, functions, smell_counts) = content_component_scores(
+        &relative_path,
+        &source,
+        &lang,
+        loc,
+        settings.file_length_threshold,
+        settings.smell_density_scale,
+        &settings.magic_number_allowlist,
+    );
+    // Frozen complexity needs the file's commit count; without churn it is off.
+    let frozen_raw = commit_count.map_or(0.0, |commit_count| {
+        crate::analysis::staleness::frozen_complexity_penalty(
+            complexity.raw_score,
+            commit_count,
+            settings.frozen_complexity_threshold,
+            settings.frozen_complexity_penalty,
+        )
+    });
+    let workspace_files = crate::commands::git::walkdir(workspace_path);
+    let import_roots = crate::analysis::coupling::workspace_import_roots(workspace_path, &workspace_files);
+    file.components.coupling_index = coupling_component(
+        &relative_path,
+        &source,
+        &lang,
+        &crate::analysis::coupling::ImportDegrees::from_edges(&crate::analysis::coupling::import_edges(
+            workspace_path,
+            &workspace_files,
+            &import_roots,
+        )),
+        &import_roots,
+    );
+    file.components.decision_staleness = staleness_component(
+        &relative_path,
+        workspace_path,
+        smell.raw_score,
+        complexity.raw_score,
+        frozen_raw,
+        settings.history_days,
+        settings.adr_stale_days,
+    );
+    file.components.code_smell_density = smell;
+    file.components.cyclomatic_complexity = complexity;
+    file.functions = functions;
+    file.smell_counts = smell_counts;
+
+    let weights = file_weights(&source, &settings.weights);
+    for (name, component) in file.components.named_mut() {
+        component.weight = weights.get(name).copied().unwrap_or(0.0);
+        component.contribution = component.raw_score * component.weight;
+    }
+    apply_raw_score_caps(&mut file.components, &settings.raw_score_caps);
+    if let (true, Some(ranges)) = (settings.relative_blend > 0.0, ranges) {
+        // Ranges are in `ScoreComponents::named` order.
+        let components = &mut file.components;
+        normalize_component(&mut components.code_smell_density, ranges[1], settings.relative_blend);
+        normalize_component(&mut components.coupling_index, ranges[2], settings.relative_blend);
+        normalize_component(&mut components.cyclomatic_complexity, ranges[6], settings.relative_blend);
+        normalize_component(&mut components.decision_staleness, ranges[7], settings.relative_blend);
+        apply_raw_score_caps(&mut file.components, &settings.raw_score_caps);
+    }
+
+    file.composite_score = composite_score(&file.components, &settings.composite_formula);
+    file.loc = loc;
+    file.language = lang;
+    if let Some(modified) = std::fs::metadata(file_path)
+        .ok()
+        .and_then(|m| m.modified().ok())
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+    {
+        file.last_modified = modified.as_secs() as i64;
+    }
+    Ok(Some(file))
+}
+
+/// Rebuild the in-memory cache from persisted `file_scores` without
+/// re-scoring, so read commands work right after the app reopens a workspace
+#[tauri::command]
+pub async fn load_cache_from_db(
+    workspace_path: String,
+    cache: tauri::State<'_, Arc<Mutex<AnalysisCache>>>,
+) -> Result<AnalysisResult, String> {
+    load_cache_from_db_internal(&workspace_path, cache.inner())
+}
+
+pub fn load_cache_from_db_internal(
+    workspace_path: &str,
+    cache: &Arc<Mutex<AnalysisCache>>,
+) -> Result<AnalysisResult, String> {
+    let conn = crate::commands::db::get_db_connection(workspace_path)
+        .map_err(|e| format!("DB error: {e}"))?;
+    let files = crate::commands::db::load_all_file_scores(&conn)
+        .map_err(|e| format!("DB read error: {e}"))?;
+    if files.is_empty() {
+        return Err("No persisted analysis data. Run analysis first.".to_string());
+    }
+
+    let result = build_analysis_result(files, 0);
+    // The history the scores came from is unknown, so reanalysis takes the full path.
+    update_cache(cache, workspace_path.to_string(), result.clone(), None, None, false);
+    Ok(result)
+}
+
+/// Recompute only `decision_staleness` for every cached file, re-reading
+/// ADRs, and update composites. Use after adding or reviewing ADRs instead
+/// of a full analysis.
+#[tauri::command]
+pub async fn refresh_staleness(
+    workspace_path: String,
+    cache: tauri::State<'_, Arc<Mutex<AnalysisCache>>>,
+) -> Result<AnalysisResult, String> {
+    refresh_staleness_internal(&workspace_path, cache.inner())
+}
+
+pub fn refresh_staleness_internal(
+    workspace_path: &str,
+    cache: &Arc<Mutex<AnalysisCache>>,
+) -> Result<AnalysisResult, String> {
+    ensure_full_analysis(cache)?;
+    let settings = crate::commands::settings::load_effective_analysis_settings(workspace_path)?;
+    if settings.relative_blend > 0.0 {
+        return Err("Staleness cannot be refreshed with relative normalization or blending. Run a full analysis.".to_string());
+    }
+
+    let mut files = {
+        let lock = cache.lock().map_err(|_| "Cache lock error".to_string())?;
+        if lock.workspace_path.as_deref() != Some(workspace_path) {
+            return Err("No analysis data available. Run analysis first.".to_string());
+        }
+        lock.result
+            .as_ref()
+            .ok_or("No analysis data available. Run analysis first.".to_string())?
+            .files
+            .clone()
+    };
+
+    let docs: std::collections::HashSet<String> = files
+        .iter()
+        .filter(|file| crate::commands::git::is_doc_file(std::path::Path::new(&file.path)))
+        .map(|file| file.relative_path.clone())
+        .collect();
+    let doc_last_changed = if docs.is_empty() {
+        Default::default()
+    } else {
+        crate::analysis::churn::last_change_times(workspace_path, &docs, settings.analysis_ref.as_deref())
+            .unwrap_or_default()
+    };
+
+    for file in &mut files {
+        let staleness = &mut file.components.decision_staleness;
+        if docs.contains(&file.relative_path) {
+            staleness.raw_score =
+                crate::analysis::staleness::compute_doc_staleness(
+                doc_last_changed.get(&file.relative_path).copied(),
+                settings.adr_stale_days,
+            );
+            staleness.details = vec!["documentation: last commit is the review date".to_string()];
+        } else {
+            // The frozen-complexity signal comes from git history, which an
+            // ADR edit does not change, so keep it if it applied.
+            staleness.details.retain(|d| d.starts_with("frozen complexity"));
+            let frozen_raw = if staleness.details.is_empty() {
+                0.0
+            } else {
+                settings.frozen_complexity_penalty
+            };
+            staleness.raw_score = crate::analysis::staleness::compute_staleness(
+                &file.relative_path,
+                workspace_path,
+                file.components.code_smell_density.raw_score,
+                settings.adr_stale_days,
+            )
+            .max(frozen_raw);
+        }
+        apply_raw_score_caps(&mut file.components, &settings.raw_score_caps);
+        let staleness = &mut file.components.decision_staleness;
+        staleness.contribution = staleness.raw_score * staleness.weight;
+        file.composite_score = composite_score(&file.components, &settings.composite_formula);
+    }
+
+    let conn = crate::commands::db::get_db_connection(workspace_path)
+        .map_err(|e| format!("DB error: {e}"))?;
+    crate::commands::db::upsert_file_scores(&conn, &files, &content_hashes(&files))
+        .map_err(|e| format!("DB upsert error: {e}"))?;
+
+    let mut lock = cache.lock().map_err(|_| "Cache lock error".to_string())?;
+    let previous = lock.result.take();
+    let mut result = build_analysis_result(files, previous.as_ref().map_or(0, |r| r.duration_ms));
+    if let Some(previous) = previous {
+        result.errors = previous.errors;
+    }
+    lock.heatmap = Some(build_heatmap_tree(workspace_path, &result.files));
+    lock.result = Some(result.clone());
+    Ok(result)
+}
+
+/// Score only files with uncommitted changes in the working tree
+#[tauri::command]
+pub async fn analyze_dirty_files(
+    workspace_path: String,
+    cache: tauri::State<'_, Arc<Mutex<AnalysisCache>>>,
+) -> Result<Vec<FileScore>, String> {
+    analyze_dirty_files_internal(&workspace_path, cache.inner())
+}
+
+pub fn analyze_dirty_files_internal(
+    workspace_path: &str,
+    cache: &Arc<Mutex<AnalysisCache>>,
+) -> Result<Vec<FileScore>, String> {
+    ensure_full_analysis(cache)?;
+    let dirty = crate::commands::git::dirty_files(workspace_path)?;
+    if dirty.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    // Filtering needs only settings; the history scans run once, and only
+    // when a dirty file can't be rescored from the cache.
+    let settings = crate::commands::settings::load_effective_analysis_settings(workspace_path)?;
+    let filters = analysis_inputs(
+        workspace_path,
+        settings.clone(),
+        None,
+        Default::default(),
+        Default::default(),
+        Default::default(),
+    );
+    let mut full_inputs = None;
+    let mut scored = Vec::new();
+    for file in filters
+        .workspace_files
+        .iter()
+        .filter(|file| dirty.contains(&to_relative_path(workspace_path, file)))
+        .filter(|file| filters.include_type_declarations || !crate::commands::git::is_type_declaration(file))
+        .filter(|file| filters.include_generated_code || !crate::commands::git::is_generated_code(file))
+        .filter(|file| language_selected(file, &filters))
+        .filter(|file| path_included(workspace_path, file, &filters))
+    {
+        let rescored = match rescore_content_only(workspace_path, file, &settings, cache)? {
+            Some(rescored) => Some(rescored),
+            None => {
+                if full_inputs.is_none() {
+                    full_inputs = Some(load_analysis_inputs(workspace_path, false, None)?);
+                }
+                full_inputs
+                    .as_ref()
+                    .and_then(|inputs| score_file_against_cache(workspace_path, file, inputs, cache).ok())
+            }
+        };
+        scored.extend(rescored);
+    }
+    flag_rescored_new_files(workspace_path, &mut scored, settings.new_file_grace_days)?;
+
+    let conn = crate::commands::db::get_db_connection(workspace_path)
+        .map_err(|e| format!("DB error: {e}"))?;
+    crate::commands::db::upsert_file_scores(&conn, &scored, &content_hashes(&scored))
+        .map_err(|e| format!("DB upsert error: {e}"))?;
+    for file in &scored {
+        patch_cached_result(cache, workspace_path, file.clone());
+    }
+
+    Ok(scored)
+}
+
+#[tauri::command]
+pub async fn get_heatmap_data(
+    cache: tauri::State<'_, Arc<Mutex<AnalysisCache>>>,
+) -> Result<HeatmapNode, String> {
+    let cache_lock = cache.lock().map_err(|_| "Cache lock error".to_string())?;
+    let mut heatmap = cache_lock
+        .heatmap
+        .clone()
+        .ok_or("No analysis data available. Run analysis first.".to_string())?;
+    round_heatmap_scores(&mut heatmap, score_precision(cache_lock.workspace_path.as_deref()));
+    Ok(heatmap)
+}
+
+#[tauri::command]
+pub async fn get_file_breakdown(
+    path: String,
+    cache: tauri::State<'_, Arc<Mutex<AnalysisCache>>>,
+) -> Result<FileBreakdown, String> {
+    get_file_breakdown_internal(&path, cache.inner())
+}
+
+pub fn get_file_breakdown_internal(
+    path: &str,
+    cache: &Arc<Mutex<AnalysisCache>>,
+) -> Result<FileBreakdown, String> {
+    let cache_lock = cache.lock().map_err(|_| "Cache lock error".to_string())?;
+    let result = cache_lock.result.as_ref().ok_or("No analysis data")?;
+
+    let file = result
+        .files
+        .iter()
+        .find(|f| f.relative_path == path || f.path == path)
+        .ok_or(format!("File not found: {path}"))?;
+
+    let mut breakdown = breakdown_for(file);
+    round_breakdown_scores(&mut breakdown, score_precision(cache_lock.workspace_path.as_deref()));
+    Ok(breakdown)
+}
+
+/// `scorePrecision` for a workspace, or the default when settings are unavailable
+fn score_precision(workspace_path: Option<&str>) -> u32 {
+    workspace_path
+        .and_then(|ws| crate::commands::settings::load_effective_analysis_settings(ws).ok())
+        .map_or(DEFAULT_SCORE_PRECISION, |settings| settings.score_precision)
+}
+
+fn round_breakdown_scores(breakdown: &mut FileBreakdown, precision: u32) {
+    breakdown.composite_score = round_score(breakdown.composite_score, precision);
+    for component in &mut breakdown.components {
+        component.raw_score = round_score(component.raw_score, precision);
+        component.contribution = round_score(component.contribution, precision);
+    }
+}
+
+fn round_heatmap_scores(node: &mut HeatmapNode, precision: u32) {
+    node.score = node.score.map(|score| round_score(score, precision));
+    for child in node.children.iter_mut().flatten() {
+        round_heatmap_scores(child, precision);
+    }
+}
+
+fn breakdown_for(file: &FileScore) -> FileBreakdown {
+    FileBreakdown {
+        path: file.relative_path.clone(),
+        composite_score: file.composite_score,
+        components: file
+            .components
+            .named()
+            .iter()
+            .map(|(name, component)| to_detail(name, component))
+            .collect(),
+    }
+}
+
+/// ADRs linked to a file with their review status; empty when none exist
+#[tauri::command]
+pub async fn get_adrs_for_file(
+    workspace_path: String,
+    relative_path: String,
+) -> Result<Vec<crate::analysis::staleness::AdrInfo>, String> {
+    let settings = crate::commands::settings::load_effective_analysis_settings(&workspace_path)?;
+    Ok(crate::analysis::staleness::describe_adrs_for(
+        &relative_path,
+        &workspace_path,
+        chrono::Utc::now().date_naive(),
+        settings.adr_stale_days,
+    ))
+}
+
+/// ADRs overdue for review (`adrStaleDays`), with the files they document
+#[tauri::command]
+pub async fn get_stale_adrs(workspace_path: String) -> Result<Vec<crate::analysis::staleness::StaleAdr>, String> {
+    get_stale_adrs_internal(&workspace_path)
+}
+
+pub fn get_stale_adrs_internal(workspace_path: &str) -> Result<Vec<crate::analysis::staleness::StaleAdr>, String> {
+    let settings = crate::commands::settings::load_effective_analysis_settings(workspace_path)?;
+    let files: Vec<String> = crate::commands::git::walkdir(workspace_path)
+        .iter()
+        .map(|file| to_relative_path(workspace_path, file))
+        .collect();
+    Ok(crate::analysis::staleness::find_stale_adrs(
+        workspace_path,
+        &files,
+        settings.adr_stale_days,
+    ))
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct CoChangePeer {
+    pub relative_path: String,
+    pub co_change_count: usize,
+    pub coupling_ratio: f64,
+}
+
+/// Everything the file detail panel shows, gathered in one call
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct FileDetail {
+    pub score: FileScore,
+    pub breakdown: FileBreakdown,
+    pub top_functions: Vec<crate::commands::ast::FunctionComplexity>,
+    pub smells: crate::commands::ast::FileSmells,
+    pub top_authors: Vec<(String, usize)>,
+    pub co_change_peers: Vec<CoChangePeer>,
+    pub register_items: Vec<crate::models::register::RegisterItem>,
+    pub adr_path: Option<String>,
+    pub adr_review_age_days: Option<i64>,
+}
+
+#[tauri::command]
+pub async fn get_file_detail(
+    workspace_path: String,
+    relative_path: String,
+    cache: tauri::State<'_, Arc<Mutex<AnalysisCache>>>,
+) -> Result<FileDetail, String> {
+    get_file_detail_internal(&workspace_path, &relative_path, cache.inner())
+}
+
+pub fn get_file_detail_internal(
+    workspace_path: &str,
+    relative_path: &str,
+    cache: &Arc<Mutex<AnalysisCache>>,
+) -> Result<FileDetail, String> {
+    let score = {
+        let cache_lock = cache.lock().map_err(|_| "Cache lock error".to_string())?;
+        let result = cache_lock.result.as_ref().ok_or("No analysis data")?;
+        result
+            .files
+            .iter()
+            .find(|f| f.relative_path == relative_path || f.path == relative_path)
+            .cloned()
+            .ok_or(format!("File not found: {relative_path}"))?
+    };
+    let relative_path = score.relative_path.as_str();
+
+    let settings = crate::commands::settings::load_effective_analysis_settings(workspace_path)?;
+    let source = crate::commands::git::read_workspace_file(workspace_path, &score.path)?;
+    let smells = crate::analysis::smells::detect_smells_in_file(
+        relative_path,
+        &source,
+        &score.language,
+        score.loc,
+        &settings.magic_number_allowlist,
+    );
+    let mut top_functions = crate::analysis::complexity::analyze_complexity(&source, &score.language).functions;
+    top_functions.sort_by(|a, b| b.complexity.cmp(&a.complexity).then_with(|| a.name.cmp(&b.name)));
+    top_functions.truncate(5);
+
+    let analysis_ref = settings.analysis_ref.as_deref();
+
+    let mut top_authors: Vec<(String, usize)> = git2::Repository::open(workspace_path)
+        .ok()
+        .and_then(|repo| {
+            let start = crate::commands::git::resolve_analysis_ref(&repo, analysis_ref).ok()?;
+            crate::analysis::knowledge::blame_authors(&repo, relative_path, start)
+        })
+        .unwrap_or_default()
+        .into_iter()
+        .collect();
+    top_authors.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    top_authors.truncate(5);
+
+    let co_changes =
+        crate::analysis::coupling::analyze_co_changes(
+            workspace_path,
+            settings.co_change_history_days,
+            analysis_ref,
+            settings.max_files_per_commit_for_coupling,
+        )
+        .unwrap_or_default();
+    let key = crate::analysis::coupling::normalize_path_key(relative_path);
+    let change_count = |path: &str| {
+        let key = crate::analysis::coupling::normalize_path_key(path);
+        co_changes
+            .file_change_counts
+            .iter()
+            .find(|(path, _)| crate::analysis::coupling::normalize_path_key(path) == key)
+            .map_or(0, |(_, count)| *count)
+    };
+    let own_changes = change_count(relative_path);
+    let mut co_change_peers: Vec<CoChangePeer> = co_changes
+        .pairs
+        .iter()
+        .filter_map(|(a, b, count)| {
+            let peer = if crate::analysis::coupling::normalize_path_key(a) == key {
+                b
+            } else if crate::analysis::coupling::normalize_path_key(b) == key {
+                a
+            } else {
+                return None;
+            };
+            // Same ratio as `get_change_couplings`: co-changes / min(changes_a, changes_b)
+            let min_changes = own_changes.min(change_count(peer)).max(1);
+            Some(CoChangePeer {
+                relative_path: peer.clone(),
+                co_change_count: *count,
+                coupling_ratio: (*count as f64 / min_changes as f64).min(1.0),
+            })
+        })
+        .collect();
+    co_change_peers.sort_by(|a, b| {
+        b.co_change_count
+            .cmp(&a.co_change_count)
+            .then_with(|| a.relative_path.cmp(&b.relative_path))
+    });
+    co_change_peers.truncate(10);
+
+    let register_items = crate::commands::db::get_db_connection(workspace_path)
+        .and_then(|conn| crate::commands::db::load_register_items(&conn))
+        .map_err(|e| format!("DB read error: {e}"))?
+        .into_iter()
+        .filter(|item| item.file_path.as_deref() == Some(relative_path))
+        .collect();
+
+    let adr = crate::analysis::staleness::find_adr_for(relative_path, workspace_path);
+    let adr_review_age_days = adr
+        .as_deref()
+        .and_then(crate::analysis::staleness::adr_review_age_days);
+
+    Ok(FileDetail {
+        breakdown: breakdown_for(&score),
+        top_functions,
+        smells,
+        top_authors,
+        co_change_peers,
+        register_items,
+        adr_path: adr.map(|p| p.to_string_lossy().to_string()),
+        adr_review_age_days,
+        score,
+    })
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct CouplingPair {
+    pub file_a: String,
+    pub file_b: String,
+    pub coupling_ratio: f64,
+    pub co_change_count: usize,
+    pub has_import_link: bool,
+}
+
+#[tauri::command]
+pub async fn get_change_couplings(
+    workspace_path: String,
+    threshold: Option<f64>,
+    cache: tauri::State<'_, Arc<Mutex<AnalysisCache>>>,
+) -> Result<Vec<CouplingPair>, String> {
+    get_change_couplings_internal(&workspace_path, threshold, cache.inner())
+}
+
+pub fn get_change_couplings_internal(
+    workspace_path: &str,
+    threshold: Option<f64>,
+    cache: &Arc<Mutex<AnalysisCache>>,
+) -> Result<Vec<CouplingPair>, String> {
+    let min_threshold = threshold.unwrap_or(0.05);
+
+    let settings = crate::commands::settings::load_effective_analysis_settings(workspace_path)?;
+    let co_change_result = crate::analysis::coupling::analyze_co_changes(
+        workspace_path,
+        settings.co_change_history_days,
+        settings.analysis_ref.as_deref(),
+        settings.max_files_per_commit_for_coupling,
+    )
+    .unwrap_or_default();
+
+    let cache_lock = cache.lock().map_err(|_| "Cache lock error")?;
+    let all_files: Vec<String> = cache_lock
+        .result
+        .as_ref()
+        .map(|r| r.files.iter().map(|f| f.relative_path.clone()).collect())
+        .unwrap_or_default();
+    drop(cache_lock);
+
+    let import_links = import_links(workspace_path);
+    let mut pairs: Vec<CouplingPair> = co_change_result.pairs
+        .iter()
+        .filter(|(_, _, count)| *count >= 2)
+        .map(|(a, b, count)| CouplingPair {
+            file_a: a.clone(),
+            file_b: b.clone(),
+            coupling_ratio: co_change_ratio(&co_change_result, a, b, *count),
+            co_change_count: *count,
+            has_import_link: import_links.contains(&import_link_key(a, b)),
+        })
+        .filter(|p| {
+            p.coupling_ratio >= min_threshold
+                && (all_files.is_empty()
+                    || all_files.contains(&p.file_a)
+                    || all_files.contains(&p.file_b))
+        })
+        .collect();
+
+    pairs.sort_by_key(|pair| std::cmp::Reverse(pair.co_change_count));
+    pairs.truncate(200);
+    Ok(pairs)
+}
+
+/// Spec formula: co_changes / min(changes_a, changes_b), capped at 1
+fn co_change_ratio(co_changes: &crate::analysis::coupling::CoChangeResult, a: &str, b: &str, count: usize) -> f64 {
+    let changes_a = co_changes.file_change_counts.get(a).copied().unwrap_or(1);
+    let changes_b = co_changes.file_change_counts.get(b).copied().unwrap_or(1);
+    let min_changes = changes_a.min(changes_b).max(1) as f64;
+    (count as f64 / min_changes).min(1.0)
+}
+
+/// Co-change hubs: each analyzed file's number of distinct coupling peers and
+/// total co-change weight, most connected first
+#[tauri::command]
+pub async fn get_coupling_centrality(
+    workspace_path: String,
+    cache: tauri::State<'_, Arc<Mutex<AnalysisCache>>>,
+) -> Result<Vec<crate::analysis::coupling::CouplingCentrality>, String> {
+    get_coupling_centrality_internal(&workspace_path, cache.inner())
+}
+
+pub fn get_coupling_centrality_internal(
+    workspace_path: &str,
+    cache: &Arc<Mutex<AnalysisCache>>,
+) -> Result<Vec<crate::analysis::coupling::CouplingCentrality>, String> {
+    let analyzed: std::collections::HashSet<String> = {
+        let cache_lock = cache.lock().map_err(|_| "Cache lock error".to_string())?;
+        cache_lock
+            .result
+            .as_ref()
+            .ok_or("No analysis data available. Run analysis first.".to_string())?
+            .files
+            .iter()
+            .map(|f| f.relative_path.clone())
+            .collect()
+    };
+
+    let settings = crate::commands::settings::load_effective_analysis_settings(workspace_path)?;
+    let co_changes = crate::analysis::coupling::analyze_co_changes(
+        workspace_path,
+        settings.co_change_history_days,
+        settings.analysis_ref.as_deref(),
+        settings.max_files_per_commit_for_coupling,
+    )?;
+
+    // Same floor as `get_change_couplings`: a single shared commit is incidental.
+    let mut ranked = crate::analysis::coupling::coupling_centrality(&co_changes, 2);
+    ranked.retain(|file| analyzed.contains(&file.relative_path));
+    Ok(ranked)
+}
+
+/// Files likely affected by changing `relative_path`: its direct importers
+/// and strong co-change peers, with a combined risk score
+#[tauri::command]
+pub async fn get_blast_radius(
+    workspace_path: String,
+    relative_path: String,
+    cache: tauri::State<'_, Arc<Mutex<AnalysisCache>>>,
+) -> Result<crate::analysis::coupling::BlastRadius, String> {
+    get_blast_radius_internal(&workspace_path, &relative_path, cache.inner())
+}
+
+pub fn get_blast_radius_internal(
+    workspace_path: &str,
+    relative_path: &str,
+    cache: &Arc<Mutex<AnalysisCache>>,
+) -> Result<crate::analysis::coupling::BlastRadius, String> {
+    {
+        let cache_lock = cache.lock().map_err(|_| "Cache lock error".to_string())?;
+        let result = cache_lock
+            .result
+            .as_ref()
+            .ok_or("No analysis data available. Run analysis first.".to_string())?;
+        if !result.files.iter().any(|f| f.relative_path == relative_path) {
+            return Err(format!("File not found: {relative_path}"));
+        }
+    }
+
+    let target_key = crate::analysis::coupling::normalize_path_key(relative_path);
+    let importers: std::collections::BTreeSet<String> = crate::analysis::coupling::internal_import_edges(workspace_path)
+        .into_iter()
+        .filter(|(importer, targets)| {
+            crate::analysis::coupling::normalize_path_key(importer) != target_key
+                && targets
+                    .iter()
+                    .any(|target| crate::analysis::coupling::normalize_path_key(target) == target_key)
+        })
+        .map(|(importer, _)| importer)
+        .collect();
+
+    let settings = crate::commands::settings::load_effective_analysis_settings(workspace_path)?;
+    let co_changes = crate::analysis::coupling::analyze_co_changes(
+        workspace_path,
+        settings.co_change_history_days,
+        settings.analysis_ref.as_deref(),
+        settings.max_files_per_commit_for_coupling,
+    )?;
+
+    Ok(crate::analysis::coupling::blast_radius(relative_path, &importers, &co_changes))
+}
+
+/// Drop persisted coupling pairs that reference files no longer in the
+/// analyzed file list. Returns the number of pairs removed.
+#[tauri::command]
+pub async fn prune_coupling_pairs(
+    workspace_path: String,
+    cache: tauri::State<'_, Arc<Mutex<AnalysisCache>>>,
+) -> Result<usize, String> {
+    prune_coupling_pairs_internal(&workspace_path, cache.inner())
+}
+
+pub fn prune_coupling_pairs_internal(
+    workspace_path: &str,
+    cache: &Arc<Mutex<AnalysisCache>>,
+) -> Result<usize, String> {
+    let files = {
+        let cache_lock = cache.lock().map_err(|_| "Cache lock error".to_string())?;
+        cache_lock
+            .result
+            .as_ref()
+            .ok_or("No analysis data available. Run analysis first.".to_string())?
+            .files
+            .clone()
+    };
+    prune_stale_coupling_pairs(workspace_path, &files)
+}
+
+fn prune_stale_coupling_pairs(workspace_path: &str, files: &[FileScore]) -> Result<usize, String> {
+    let current: std::collections::HashSet<String> =
+        files.iter().map(|f| f.relative_path.clone()).collect();
+    let conn = crate::commands::db::get_db_connection(workspace_path)
+        .map_err(|e| format!("DB error: {e}"))?;
+    crate::commands::db::prune_coupling_pairs_with_conn(&conn, &current)
+        .map_err(|e| format!("DB prune error: {e}"))
+}
+
+/// Recompute `has_import_link` for every persisted coupling pair against the
+/// current import graph. Returns the number of pairs whose flag was corrected.
+#[tauri::command]
+pub async fn reconcile_coupling(
+    workspace_path: String,
+    cache: tauri::State<'_, Arc<Mutex<AnalysisCache>>>,
+) -> Result<usize, String> {
+    reconcile_coupling_internal(&workspace_path, cache.inner())
+}
+
+pub fn reconcile_coupling_internal(
+    workspace_path: &str,
+    cache: &Arc<Mutex<AnalysisCache>>,
+) -> Result<usize, String> {
+    {
+        let cache_lock = cache.lock().map_err(|_| "Cache lock error".to_string())?;
+        if cache_lock.result.is_none() {
+            return Err("No analysis data available. Run analysis first.".to_string());
+        }
+    }
+
+    let import_links = import_links(workspace_path);
+    let conn = crate::commands::db::get_db_connection(workspace_path)
+        .map_err(|e| format!("DB error: {e}"))?;
+    crate::commands::db::reconcile_coupling_links_with_conn(&conn, &import_links)
+        .map_err(|e| format!("DB reconcile error: {e}"))
+}
+
+/// Every internal import as a `normalize_path_key` pair, lesser key first
+fn import_links(workspace_path: &str) -> std::collections::HashSet<(String, String)> {
+    let mut import_links = std::collections::HashSet::new();
+    for (from, targets) in crate::analysis::coupling::internal_import_edges(workspace_path) {
+        for target in targets.into_iter().filter(|target| *target != from) {
+            import_links.insert(import_link_key(&from, &target));
+        }
+    }
+    import_links
+}
+
+fn import_link_key(a: &str, b: &str) -> (String, String) {
+    let key_a = crate::analysis::coupling::normalize_path_key(a);
+    let key_b = crate::analysis::coupling::normalize_path_key(b);
+    if key_a <= key_b {
+        (key_a, key_b)
+    } else {
+        (key_b, key_a)
+    }
+}
+
+/// Replace the persisted coupling pairs with the ones from this analysis'
+/// co-change window, flagging pairs that also share an import
+fn persist_coupling_pairs(
+    workspace_path: &str,
+    co_changes: &crate::analysis::coupling::CoChangeResult,
+) -> Result<(), String> {
+    let mut pairs: Vec<CouplingPair> = co_changes
+        .pairs
+        .iter()
+        .filter(|(_, _, count)| *count >= 2)
+        .map(|(a, b, count)| CouplingPair {
+            file_a: a.clone(),
+            file_b: b.clone(),
+            coupling_ratio: co_change_ratio(co_changes, a, b, *count),
+            co_change_count: *count,
+            has_import_link: false,
+        })
+        .collect();
+    if !pairs.is_empty() {
+        let links = import_links(workspace_path);
+        for pair in &mut pairs {
+            pair.has_import_link = links.contains(&import_link_key(&pair.file_a, &pair.file_b));
+        }
+    }
+
+    let conn = crate::commands::db::get_db_connection(workspace_path)
+        .map_err(|e| format!("DB error: {e}"))?;
+    crate::commands::db::replace_coupling_pairs_with_conn(&conn, &pairs)
+        .map_err(|e| format!("DB upsert error: {e}"))
+}
+
+/// Take a snapshot of the cached analysis if `snapshotSchedule` says one is
+/// due. Returns whether a snapshot was taken.
+#[tauri::command]
+pub async fn maybe_take_scheduled_snapshot(
+    workspace_path: String,
+    cache: tauri::State<'_, Arc<Mutex<AnalysisCache>>>,
+    app: tauri::AppHandle,
+) -> Result<bool, String> {
+    maybe_take_scheduled_snapshot_internal(&workspace_path, cache.inner(), |alert| {
+        let _ = app.emit("debt_regression_alert", alert);
+    })
+}
+
+/// Scheduled snapshots go through `take_snapshot_internal`, so they embed
+/// the same metadata and raise the same regression alerts as manual ones.
+pub fn maybe_take_scheduled_snapshot_internal<F>(
+    workspace_path: &str,
+    cache: &Arc<Mutex<AnalysisCache>>,
+    on_alert: F,
+) -> Result<bool, String>
+where
+    F: FnOnce(crate::models::snapshot::RegressionAlert),
+{
+    ensure_full_analysis(cache)?;
+    let settings = crate::commands::settings::load_settings_from_disk(workspace_path)?;
+    let schedule = settings
+        .get("snapshotSchedule")
+        .and_then(serde_json::Value::as_str)
+        .unwrap_or("weekly");
+
+    let conn = crate::commands::db::get_db_connection(workspace_path)
+        .map_err(|e| format!("DB error: {e}"))?;
+    let last = crate::commands::db::latest_snapshot_timestamp(&conn)
+        .map_err(|e| format!("DB read error: {e}"))?;
+    let now = chrono::Utc::now().timestamp();
+    if !crate::models::snapshot::snapshot_due(schedule, last, now) {
+        return Ok(false);
+    }
+
+    let (composite_score, file_count, high_debt_count) = {
+        let cache_lock = cache.lock().map_err(|_| "Cache lock error".to_string())?;
+        let result = cache_lock
+            .result
+            .as_ref()
+            .ok_or("No analysis data available. Run analysis first.".to_string())?;
+        (result.workspace_score, result.file_count, result.high_debt_count)
+    };
+    let analysis_ref = crate::commands::settings::load_effective_analysis_settings(workspace_path)?.analysis_ref;
+    let commit_count_week =
+        crate::analysis::churn::count_recent_commits(workspace_path, 7, analysis_ref.as_deref()).unwrap_or(0);
+
+    let snapshot = crate::models::snapshot::DebtSnapshot {
+        id: 0,
+        timestamp: now,
+        composite_score,
+        file_count,
+        high_debt_count,
+        commit_count_week,
+        snapshot_metadata: None,
+    };
+    crate::commands::db::take_snapshot_internal(workspace_path, snapshot, cache, on_alert)?;
+
+    Ok(true)
+}
+
+/// Path and score of every cached file, for snapshot metadata; `None`
+/// unless the cache holds `workspace_path`'s analysis
+pub fn cached_file_summaries(
+    workspace_path: &str,
+    cache: &Arc<Mutex<AnalysisCache>>,
+) -> Option<Vec<crate::models::snapshot::SnapshotFileSummary>> {
+    let cache_lock = cache.lock().ok()?;
+    if cache_lock.workspace_path.as_deref() != Some(workspace_path) {
+        return None;
+    }
+    let files = cache_lock
+        .result
+        .as_ref()?
+        .files
+        .iter()
+        .map(|file| crate::models::snapshot::SnapshotFileSummary {
+            path: file.relative_path.clone(),
+            score: file.composite_score,
+        })
+        .collect();
+    Some(files)
+}
+
+/// Workspace-wide smell totals for the cached analysis, keyed by smell kind
+/// and summed from the counts recorded while scoring; `None` unless the
+/// cache holds `workspace_path`'s analysis
+pub fn cached_smell_counts(
+    workspace_path: &str,
+    cache: &Arc<Mutex<AnalysisCache>>,
+) -> Option<std::collections::BTreeMap<String, usize>> {
+    let cache_lock = cache.lock().ok()?;
+    if cache_lock.workspace_path.as_deref() != Some(workspace_path) {
+        return None;
+    }
+    let mut totals: std::collections::BTreeMap<String, usize> = crate::analysis::smells::SMELL_TYPES
+        .iter()
+        .map(|name| (name.to_string(), 0))
+        .collect();
+    for file in &cache_lock.result.as_ref()?.files {
+        for (name, count) in &file.smell_counts {
+            *totals.entry(name.clone()).or_insert(0) += count;
+        }
+    }
+    Some(totals)
+}
+
+/// Workspace coverage the cached analysis of `workspace_path` merged, if any
+pub fn cached_workspace_coverage(
+    workspace_path: &str,
+    cache: &Arc<Mutex<AnalysisCache>>,
+) -> Option<crate::analysis::coverage::WorkspaceCoverage> {
+    let cache_lock = cache.lock().ok()?;
+    if cache_lock.workspace_path.as_deref() != Some(workspace_path) {
+        return None;
+    }
+    cache_lock.coverage.clone()
+}
+
+/// Line coverage merged across the workspace's coverage reports, reusing the
+/// last analysis' merge when there is one
+#[tauri::command]
+pub async fn get_workspace_coverage(
+    workspace_path: String,
+    cache: tauri::State<'_, Arc<Mutex<AnalysisCache>>>,
+) -> Result<crate::analysis::coverage::WorkspaceCoverage, String> {
+    Ok(cached_workspace_coverage(&workspace_path, cache.inner())
+        .unwrap_or_else(|| crate::analysis::coverage::workspace_coverage(&workspace_path)))
+}
+
+/// Scored files that no snapshot's per-file metadata mentions, i.e. files
+/// whose trend history starts only at the next snapshot
+#[tauri::command]
+pub async fn get_uncovered_files(
+    workspace_path: String,
+    cache: tauri::State<'_, Arc<Mutex<AnalysisCache>>>,
+) -> Result<Vec<String>, String> {
+    get_uncovered_files_internal(&workspace_path, cache.inner())
+}
+
+pub fn get_uncovered_files_internal(
+    workspace_path: &str,
+    cache: &Arc<Mutex<AnalysisCache>>,
+) -> Result<Vec<String>, String> {
+    let conn = crate::commands::db::get_db_connection(workspace_path)
+        .map_err(|e| format!("DB error: {e}"))?;
+    let snapshots = crate::commands::db::load_snapshots(&conn)
+        .map_err(|e| format!("DB read error: {e}"))?;
+    let covered: std::collections::HashSet<String> = snapshots
+        .iter()
+        .flat_map(crate::models::snapshot::snapshot_files)
+        .map(|entry| crate::analysis::coupling::normalize_path_key(&entry.path))
+        .collect();
+
+    let cache_lock = cache.lock().map_err(|_| "Cache lock error".to_string())?;
+    let result = cache_lock
+        .result
+        .as_ref()
+        .ok_or("No analysis data available. Run analysis first.".to_string())?;
+    let mut uncovered: Vec<String> = result
+        .files
+        .iter()
+        .filter(|f| !covered.contains(&crate::analysis::coupling::normalize_path_key(&f.relative_path)))
+        .map(|f| f.relative_path.clone())
+        .collect();
+    uncovered.sort();
+    Ok(uncovered)
+}
+
+/// Readiness probes the dashboard needs before rendering
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct AnalysisState {
+    pub has_cache: bool,
+    pub cached_file_count: usize,
+    pub db_score_count: usize,
+    pub last_snapshot_at: Option<i64>,
+    pub last_analysis_at: Option<i64>,
+    pub git_ok: bool,
+}
+
+#[tauri::command]
+pub async fn get_analysis_state(
+    workspace_path: String,
+    cache: tauri::State<'_, Arc<Mutex<AnalysisCache>>>,
+) -> Result<AnalysisState, String> {
+    get_analysis_state_internal(&workspace_path, cache.inner())
+}
+
+pub fn get_analysis_state_internal(
+    workspace_path: &str,
+    cache: &Arc<Mutex<AnalysisCache>>,
+) -> Result<AnalysisState, String> {
+    let cached_file_count = {
+        let cache_lock = cache.lock().map_err(|_| "Cache lock error".to_string())?;
+        cache_lock
+            .result
+            .as_ref()
+            .filter(|_| cache_lock.workspace_path.as_deref() == Some(workspace_path))
+            .map(|result| result.files.len())
+    };
+
+    let conn = crate::commands::db::get_db_connection(workspace_path)
+        .map_err(|e| format!("DB error: {e}"))?;
+    let (db_score_count, last_analysis_at) = crate::commands::db::file_score_summary(&conn)
+        .map_err(|e| format!("DB read error: {e}"))?;
+    let last_snapshot_at = crate::commands::db::latest_snapshot_timestamp(&conn)
+        .map_err(|e| format!("DB read error: {e}"))?;
+
+    Ok(AnalysisState {
+        has_cache: cached_file_count.is_some(),
+        cached_file_count: cached_file_count.unwrap_or(0),
+        db_score_count,
+        last_snapshot_at,
+        last_analysis_at,
+        git_ok: git2::Repository::open(workspace_path).is_ok(),
+    })
+}
+
+/// Default `get_risk_triage` cut-offs on the churn and coverage-gap raw scores
+const DEFAULT_TRIAGE_CHURN: f64 = MEDIUM_SEVERITY_THRESHOLD;
+const DEFAULT_TRIAGE_COVERAGE_GAP: f64 = HIGH_DEBT_THRESHOLD;
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct RiskTriageEntry {
+    pub relative_path: String,
+    pub composite_score: f64,
+    pub churn_score: f64,
+    pub coverage_gap_score: f64,
+}
+
+/// Files that are churning, poorly tested and have no linked ADR, highest
+/// composite first
+#[tauri::command]
+pub async fn get_risk_triage(
+    workspace_path: String,
+    churn_threshold: Option<f64>,
+    coverage_gap_threshold: Option<f64>,
+    cache: tauri::State<'_, Arc<Mutex<AnalysisCache>>>,
+) -> Result<Vec<RiskTriageEntry>, String> {
+    let cache_lock = cache.lock().map_err(|_| "Cache lock error".to_string())?;
+    let result = cache_lock
+        .result
+        .as_ref()
+        .ok_or("No analysis data available. Run analysis first.".to_string())?;
+    Ok(risk_triage(
+        &result.files,
+        churn_threshold.unwrap_or(DEFAULT_TRIAGE_CHURN),
+        coverage_gap_threshold.unwrap_or(DEFAULT_TRIAGE_COVERAGE_GAP),
+        |relative_path| crate::analysis::staleness::find_adr_for(relative_path, &workspace_path).is_some(),
+    ))
+}
+
+fn risk_triage<F>(
+    files: &[FileScore],
+    churn_threshold: f64,
+    coverage_gap_threshold: f64,
+    has_adr: F,
+) -> Vec<RiskTriageEntry>
+where
+    F: Fn(&str) -> bool,
+{
+    let mut entries: Vec<RiskTriageEntry> = files
+        .iter()
+        .filter(|f| f.components.churn_rate.raw_score >= churn_threshold)
+        .filter(|f| f.components.test_coverage_gap.raw_score >= coverage_gap_threshold)
+        .filter(|f| !has_adr(&f.relative_path))
+        .map(|f| RiskTriageEntry {
+            relative_path: f.relative_path.clone(),
+            composite_score: f.composite_score,
+            churn_score: f.components.churn_rate.raw_score,
+            coverage_gap_score: f.components.test_coverage_gap.raw_score,
+        })
+        .collect();
+    entries.sort_by(|a, b| {
+        b.composite_score
+            .partial_cmp(&a.composite_score)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| a.relative_path.cmp(&b.relative_path))
+    });
+    entries
+}
+
+/// Plain-language summary of why a file scores what it does, naming its top
+/// contributing components
+#[tauri::command]
+pub async fn explain_file_score(
+    relative_path: String,
+    cache: tauri::State<'_, Arc<Mutex<AnalysisCache>>>,
+) -> Result<String, String> {
+    explain_file_score_internal(&relative_path, cache.inner())
+}
+
+pub fn explain_file_score_internal(
+    relative_path: &str,
+    cache: &Arc<Mutex<AnalysisCache>>,
+) -> Result<String, String> {
+    let (file, workspace_path) = {
+        let cache_lock = cache.lock().map_err(|_| "Cache lock error".to_string())?;
+        let result = cache_lock
+            .result
+            .as_ref()
+            .ok_or("No analysis data available. Run analysis first.".to_string())?;
+        let file = result
+            .files
+            .iter()
+            .find(|f| f.relative_path == relative_path)
+            .cloned()
+            .ok_or(format!("File not found: {relative_path}"))?;
+        (file, cache_lock.workspace_path.clone())
+    };
+
+    // Same thresholds the analysis summary counted severities with
+    let (warning, critical) = match workspace_path.as_deref() {
+        Some(ws) => {
+            let settings = crate::commands::settings::load_effective_analysis_settings(ws)?;
+            (settings.warning_threshold, settings.critical_threshold)
+        }
+        None => (HIGH_DEBT_THRESHOLD, 80.0),
+    };
+
+    Ok(explain_score(&file, warning, critical))
+}
+
+/// Suggest natural split points (consecutive groups of functions) for an
+/// analyzed file longer than `fileLengthThreshold`; none while it is 0 (off)
+#[tauri::command]
+pub async fn suggest_splits(
+    relative_path: String,
+    cache: tauri::State<'_, Arc<Mutex<AnalysisCache>>>,
+) -> Result<Vec<crate::analysis::complexity::SplitSuggestion>, String> {
+    suggest_splits_internal(&relative_path, cache.inner())
+}
+
+pub fn suggest_splits_internal(
+    relative_path: &str,
+    cache: &Arc<Mutex<AnalysisCache>>,
+) -> Result<Vec<crate::analysis::complexity::SplitSuggestion>, String> {
+    let (file, workspace_path) = {
+        let cache_lock = cache.lock().map_err(|_| "Cache lock error".to_string())?;
+        let result = cache_lock
+            .result
+            .as_ref()
+            .ok_or("No analysis data available. Run analysis first.".to_string())?;
+        let file = result
+            .files
+            .iter()
+            .find(|f| f.relative_path == relative_path)
+            .cloned()
+            .ok_or(format!("File not found: {relative_path}"))?;
+        let workspace_path = cache_lock
+            .workspace_path
+            .clone()
+            .ok_or("No analysis data available. Run analysis first.".to_string())?;
+        (file, workspace_path)
+    };
+
+    let settings = crate::commands::settings::load_effective_analysis_settings(&workspace_path)?;
+    let source = crate::commands::git::read_workspace_file(&workspace_path, &file.path)?;
+    let functions = crate::analysis::complexity::analyze_complexity(&source, &file.language).functions;
+    Ok(crate::analysis::complexity::suggest_splits(
+        &functions,
+        source.lines().count(),
+        settings.file_length_threshold,
+    ))
+}
+
+/// Documentation coverage of public declarations for each analyzed file with
+/// a supported language, least documented first
+#[tauri::command]
+pub async fn get_doc_coverage(
+    cache: tauri::State<'_, Arc<Mutex<AnalysisCache>>>,
+) -> Result<Vec<crate::analysis::docs::DocCoverage>, String> {
+    get_doc_coverage_internal(cache.inner())
+}
+
+pub fn get_doc_coverage_internal(
+    cache: &Arc<Mutex<AnalysisCache>>,
+) -> Result<Vec<crate::analysis::docs::DocCoverage>, String> {
+    let (files, workspace_path) = {
+        let cache_lock = cache.lock().map_err(|_| "Cache lock error".to_string())?;
+        let result = cache_lock
+            .result
+            .as_ref()
+            .ok_or("No analysis data available. Run analysis first.".to_string())?;
+        let workspace_path = cache_lock
+            .workspace_path
+            .clone()
+            .ok_or("No analysis data available. Run analysis first.".to_string())?;
+        (result.files.clone(), workspace_path)
+    };
+
+    let mut coverage: Vec<_> = files
+        .iter()
+        .filter_map(|file| {
+            let source = crate::commands::git::read_workspace_file(&workspace_path, &file.path).ok()?;
+            crate::analysis::docs::compute_doc_coverage(&file.relative_path, &source, &file.language)
+        })
+        .collect();
+    coverage.sort_by(|a, b| {
+        a.doc_coverage
+            .partial_cmp(&b.doc_coverage)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| a.path.cmp(&b.path))
+    });
+    Ok(coverage)
+}
+
+/// Every analyzed function bucketed by cyclomatic complexity (1–5, 6–10,
+/// 11–20, 21+), with the single most complex function
+#[tauri::command]
+pub async fn get_function_complexity_distribution(
+    cache: tauri::State<'_, Arc<Mutex<AnalysisCache>>>,
+) -> Result<crate::analysis::complexity::ComplexityDistribution, String> {
+    get_function_complexity_distribution_internal(cache.inner())
+}
+
+pub fn get_function_complexity_distribution_internal(
+    cache: &Arc<Mutex<AnalysisCache>>,
+) -> Result<crate::analysis::complexity::ComplexityDistribution, String> {
+    let cache_lock = cache.lock().map_err(|_| "Cache lock error".to_string())?;
+    let result = cache_lock
+        .result
+        .as_ref()
+        .ok_or("No analysis data available. Run analysis first.".to_string())?;
+    Ok(crate::analysis::complexity::complexity_distribution(
+        result
+            .files
+            .iter()
+            .map(|file| (file.relative_path.as_str(), file.functions.as_slice())),
+    ))
+}
+
+/// Up to three components with the largest contributions drive the sentence;
+/// ties keep the canonical component order so the text is stable.
+fn explain_score(file: &FileScore, warning: f64, critical: f64) -> String {
+    let mut components: Vec<(&str, &ComponentScore)> = file
+        .components
+        .named()
+        .into_iter()
+        .filter(|(_, c)| c.contribution > 0.0)
+        .collect();
+    components.sort_by(|a, b| {
+        b.1.contribution
+            .partial_cmp(&a.1.contribution)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+
+    let drivers: Vec<String> = components
+        .iter()
+        .take(3)
+        .map(|(name, component)| describe_component(name, component))
+        .collect();
+
+    let headline = format!(
+        "This file scores {:.0} ({}).",
+        file.composite_score,
+        severity_label(file.composite_score, warning, critical)
+    );
+    match drivers.as_slice() {
+        [] => format!("{headline} No component contributes to its debt."),
+        [only] => format!("{headline} The main driver is {only}."),
+        [init @ .., last] => format!("{headline} The main drivers are {} and {last}.", init.join(", ")),
+    }
+}
+
+fn describe_component(name: &str, component: &ComponentScore) -> String {
+    let level = if component.raw_score >= HIGH_DEBT_THRESHOLD {
+        "high"
+    } else if component.raw_score >= MEDIUM_SEVERITY_THRESHOLD {
+        "moderate"
+    } else {
+        "some"
+    };
+    let phrase = match name {
+        "churn_rate" => format!("{level} churn"),
+        "code_smell_density" => format!("{level} code smell density"),
+        "coupling_index" => format!("{level} import coupling"),
+        "change_coupling" => format!("{level} change coupling with other files"),
+        "test_coverage_gap" => match level {
+            "high" => "low test coverage".to_string(),
+            "moderate" => "partial test coverage".to_string(),
+            _ => "small test coverage gaps".to_string(),
+        },
+        "knowledge_concentration" => format!("{level} knowledge concentration"),
+        "cyclomatic_complexity" => format!("{level} cyclomatic complexity"),
+        "decision_staleness" => format!("{level} decision record staleness"),
+        other => format!("{level} {}", other.replace('_', " ")),
+    };
+    if component.details.is_empty() {
+        phrase
+    } else {
+        format!("{phrase} ({})", component.details.join("; "))
+    }
+}
+
+/// Churn × complexity for one file, independent of the composite
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct Hotspot {
+    pub relative_path: String,
+    pub churn_score: f64,
+    pub complexity_score: f64,
+    /// churn × complexity rescaled to 0–100
+    pub hotspot_score: f64,
+    pub composite_score: f64,
+}
+
+/// Files ranked by the classic hotspot metric, churn × complexity
+#[tauri::command]
+pub async fn get_hotspots(cache: tauri::State<'_, Arc<Mutex<AnalysisCache>>>) -> Result<Vec<Hotspot>, String> {
+    let cache_lock = cache.lock().map_err(|_| "Cache lock error".to_string())?;
+    let result = cache_lock
+        .result
+        .as_ref()
+        .ok_or("No analysis data available. Run analysis first.".to_string())?;
+    Ok(rank_hotspots(&result.files))
+}
+
+fn rank_hotspots(files: &[FileScore]) -> Vec<Hotspot> {
+    let mut hotspots: Vec<Hotspot> = files
+        .iter()
+        .map(|f| {
+            let churn = f.components.churn_rate.raw_score.clamp(0.0, 100.0);
+            let complexity = f.components.cyclomatic_complexity.raw_score.clamp(0.0, 100.0);
+            Hotspot {
+                relative_path: f.relative_path.clone(),
+                churn_score: churn,
+                complexity_score: complexity,
+                hotspot_score: churn * complexity / 100.0,
+                composite_score: f.composite_score,
+            }
+        })
+        .collect();
+    hotspots.sort_by(|a, b| {
+        b.hotspot_score
+            .partial_cmp(&a.hotspot_score)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| a.relative_path.cmp(&b.relative_path))
+    });
+    hotspots
+}
+
+/// Component raw scores as a files × components matrix, for CSV/numpy export
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct ScoreMatrix {
+    pub files: Vec<String>,
+    pub components: Vec<String>,
+    /// `raw_scores[i][j]` is file `i`'s raw score for component `j`
+    pub raw_scores: Vec<Vec<f64>>,
+}
+
+#[tauri::command]
+pub async fn get_score_matrix(
+    cache: tauri::State<'_, Arc<Mutex<AnalysisCache>>>,
+) -> Result<ScoreMatrix, String> {
+    get_score_matrix_internal(cache.inner())
+}
+
+pub fn get_score_matrix_internal(cache: &Arc<Mutex<AnalysisCache>>) -> Result<ScoreMatrix, String> {
+    let cache_lock = cache.lock().map_err(|_| "Cache lock error".to_string())?;
+    let result = cache_lock
+        .result
+        .as_ref()
+        .ok_or("No analysis data available. Run analysis first.".to_string())?;
+    let mut matrix = score_matrix(&result.files);
+    let precision = score_precision(cache_lock.workspace_path.as_deref());
+    for score in matrix.raw_scores.iter_mut().flatten() {
+        *score = round_score(*score, precision);
+    }
+    Ok(matrix)
+}
+
+fn score_matrix(files: &[FileScore]) -> ScoreMatrix {
+    ScoreMatrix {
+        files: files.iter().map(|f| f.relative_path.clone()).collect(),
+        components: ScoreComponents::default()
+            .named()
+            .into_iter()
+            .map(|(name, _)| name.to_string())
+            .collect(),
+        raw_scores: files
+            .iter()
+            .map(|f| f.components.named().into_iter().map(|(_, c)| c.raw_score).collect())
+            .collect(),
+    }
+}
+
+/// Pairwise Pearson correlation of component raw scores across files
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct ComponentCorrelations {
+    pub components: Vec<String>,
+    /// `correlations[i][j]` correlates components `i` and `j`; 0 when either
+    /// has no variance across files
+    pub correlations: Vec<Vec<f64>>,
+}
+
+#[tauri::command]
+pub async fn get_component_correlations(
+    cache: tauri::State<'_, Arc<Mutex<AnalysisCache>>>,
+) -> Result<ComponentCorrelations, String> {
+    let cache_lock = cache.lock().map_err(|_| "Cache lock error".to_string())?;
+    let result = cache_lock
+        .result
+        .as_ref()
+        .ok_or("No analysis data available. Run analysis first.".to_string())?;
+    Ok(component_correlations(&result.files))
+}
+
+fn component_correlations(files: &[FileScore]) -> ComponentCorrelations {
+    let matrix = score_matrix(files);
+    let column = |j: usize| -> Vec<f64> { matrix.raw_scores.iter().map(|row| row[j]).collect() };
+    let columns: Vec<Vec<f64>> = (0..matrix.components.len()).map(column).collect();
+    let correlations = columns
+        .iter()
+        .map(|a| columns.iter().map(|b| pearson(a, b)).collect())
+        .collect();
+    ComponentCorrelations { components: matrix.components, correlations }
+}
+
+/// Pearson correlation of two equal-length samples; 0 without variance
+fn pearson(a: &[f64], b: &[f64]) -> f64 {
+    let n = a.len() as f64;
+    if a.is_empty() {
+        return 0.0;
+    }
+    let (mean_a, mean_b) = (a.iter().sum::<f64>() / n, b.iter().sum::<f64>() / n);
+    let (mut covariance, mut variance_a, mut variance_b) = (0.0, 0.0, 0.0);
+    for (x, y) in a.iter().zip(b) {
+        let (dx, dy) = (x - mean_a, y - mean_b);
+        covariance += dx * dy;
+        variance_a += dx * dx;
+        variance_b += dy * dy;
+    }
+    if variance_a <= f64::EPSILON || variance_b <= f64::EPSILON {
+        return 0.0;
+    }
+    (covariance / (variance_a * variance_b).sqrt()).clamp(-1.0, 1.0)
+}
+
+/// The `n` largest files by LOC (fewer if the workspace has fewer), with
+/// their scores
+#[tauri::command]
+pub async fn get_largest_files(
+    cache: tauri::State<'_, Arc<Mutex<AnalysisCache>>>,
+    n: usize,
+) -> Result<Vec<FileScore>, String> {
+    let cache_lock = cache.lock().map_err(|_| "Cache lock error".to_string())?;
+    let result = cache_lock
+        .result
+        .as_ref()
+        .ok_or("No analysis data available. Run analysis first.".to_string())?;
+    Ok(largest_files(&result.files, n))
+}
+
+fn largest_files(files: &[FileScore], n: usize) -> Vec<FileScore> {
+    let mut largest = files.to_vec();
+    largest.sort_by(|a, b| b.loc.cmp(&a.loc).then_with(|| a.relative_path.cmp(&b.relative_path)));
+    largest.truncate(n);
+    largest
+}
+
+/// Churn × coverage gap for one file, a change-failure risk proxy
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct ChangeRisk {
+    pub relative_path: String,
+    pub churn_score: f64,
+    pub coverage_gap_score: f64,
+    /// churn × coverage gap rescaled to 0–100
+    pub risk_score: f64,
+    pub composite_score: f64,
+}
+
+/// Files ranked by churn × coverage gap: frequently changed, poorly tested
+/// code is the likeliest source of incidents
+#[tauri::command]
+pub async fn get_change_risk(cache: tauri::State<'_, Arc<Mutex<AnalysisCache>>>) -> Result<Vec<ChangeRisk>, String> {
+    let cache_lock = cache.lock().map_err(|_| "Cache lock error".to_string())?;
+    let result = cache_lock
+        .result
+        .as_ref()
+        .ok_or("No analysis data available. Run analysis first.".to_string())?;
+    Ok(rank_change_risk(&result.files))
+}
+
+fn rank_change_risk(files: &[FileScore]) -> Vec<ChangeRisk> {
+    let mut risks: Vec<ChangeRisk> = files
+        .iter()
+        .map(|f| {
+            let churn = f.components.churn_rate.raw_score.clamp(0.0, 100.0);
+            let coverage_gap = f.components.test_coverage_gap.raw_score.clamp(0.0, 100.0);
+            ChangeRisk {
+                relative_path: f.relative_path.clone(),
+                churn_score: churn,
+                coverage_gap_score: coverage_gap,
+                risk_score: churn * coverage_gap / 100.0,
+                composite_score: f.composite_score,
+            }
+        })
+        .collect();
+    risks.sort_by(|a, b| {
+        b.risk_score
+            .partial_cmp(&a.risk_score)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| a.relative_path.cmp(&b.relative_path))
+    });
+    risks
+}
+
+/// Composite debt summed per dominant blame owner, for team retros. The
+/// attribution is heuristic: it says who mostly owns the code now, not who
+/// created the debt.
+#[tauri::command]
+pub async fn get_author_debt(
+    workspace_path: String,
+    cache: tauri::State<'_, Arc<Mutex<AnalysisCache>>>,
+) -> Result<Vec<crate::analysis::knowledge::AuthorDebt>, String> {
+    let analysis_ref = crate::commands::settings::load_effective_analysis_settings(&workspace_path)
+        .ok()
+        .and_then(|settings| settings.analysis_ref);
+    let blame = crate::analysis::knowledge::analyze_knowledge(&workspace_path, analysis_ref.as_deref())?;
+
+    let cache_lock = cache.lock().map_err(|_| "Cache lock error".to_string())?;
+    let result = cache_lock
+        .result
+        .as_ref()
+        .ok_or("No analysis data available. Run analysis first.".to_string())?;
+    Ok(crate::analysis::knowledge::compute_author_debt(
+        &blame,
+        result
+            .files
+            .iter()
+            .map(|f| (f.relative_path.as_str(), f.composite_score)),
+    ))
+}
+
+/// Serialize the cached per-file scores as an opaque baseline string for a
+/// later `diff_against_baseline`. Nothing is written to the database.
+#[tauri::command]
+pub async fn capture_baseline(cache: tauri::State<'_, Arc<Mutex<AnalysisCache>>>) -> Result<String, String> {
+    capture_baseline_internal(cache.inner())
+}
+
+pub fn capture_baseline_internal(cache: &Arc<Mutex<AnalysisCache>>) -> Result<String, String> {
+    let cache_lock = cache.lock().map_err(|_| "Cache lock error".to_string())?;
+    let result = cache_lock
+        .result
+        .as_ref()
+        .ok_or("No analysis data available. Run analysis first.".to_string())?;
+    let baseline = crate::models::baseline::ScoreBaseline::from_result(
+        cache_lock.workspace_path.as_deref().unwrap_or_default(),
+        result,
+        chrono::Utc::now().timestamp(),
+        score_precision(cache_lock.workspace_path.as_deref()),
+    );
+    serde_json::to_string(&baseline).map_err(|e| format!("Serialize error: {e}"))
+}
+
+/// Per-file score deltas between a captured baseline and the cached analysis
+#[tauri::command]
+pub async fn diff_against_baseline(
+    baseline: String,
+    cache: tauri::State<'_, Arc<Mutex<AnalysisCache>>>,
+) -> Result<crate::models::baseline::BaselineDiff, String> {
+    diff_against_baseline_internal(&baseline, cache.inner())
+}
+
+pub fn diff_against_baseline_internal(
+    baseline: &str,
+    cache: &Arc<Mutex<AnalysisCache>>,
+) -> Result<crate::models::baseline::BaselineDiff, String> {
+    let baseline: crate::models::baseline::ScoreBaseline =
+        serde_json::from_str(baseline).map_err(|e| format!("INVALID_BASELINE: {e}"))?;
+    let cache_lock = cache.lock().map_err(|_| "Cache lock error".to_string())?;
+    if cache_lock.workspace_path.as_deref() != Some(baseline.workspace_path.as_str()) {
+        return Err("INVALID_BASELINE: baseline was captured for a different workspace".to_string());
+    }
+    let result = cache_lock
+        .result
+        .as_ref()
+        .ok_or("No analysis data available. Run analysis first.".to_string())?;
+    let precision = score_precision(cache_lock.workspace_path.as_deref());
+    Ok(crate::models::baseline::diff_scores(&baseline, result, precision))
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct ScoreBucket {
+    pub min: f64,
+    pub max: f64,
+    pub count: usize,
+}
+
+/// Histogram of composite scores; `bucket_size` must evenly divide 100.
+#[tauri::command]
+pub async fn get_score_distribution(
+    bucket_size: u32,
+    cache: tauri::State<'_, Arc<Mutex<AnalysisCache>>>,
+) -> Result<Vec<ScoreBucket>, String> {
+    let cache_lock = cache.lock().map_err(|_| "Cache lock error".to_string())?;
+    let result = cache_lock
+        .result
+        .as_ref()
+        .ok_or("No analysis data available. Run analysis first.".to_string())?;
+    compute_score_distribution(&result.files, bucket_size)
+}
+
+fn compute_score_distribution(files: &[FileScore], bucket_size: u32) -> Result<Vec<ScoreBucket>, String> {
+    if bucket_size == 0 || bucket_size > 100 || 100 % bucket_size != 0 {
+        return Err(format!(
+            "INVALID_BUCKET_SIZE: {bucket_size} must be between 1 and 100 and divide 100 evenly"
+        ));
+    }
+
+    let bucket_count = (100 / bucket_size) as usize;
+    let mut buckets: Vec<ScoreBucket> = (0..bucket_count)
+        .map(|i| ScoreBucket {
+            min: (i as u32 * bucket_size) as f64,
+            max: ((i as u32 + 1) * bucket_size) as f64,
+            count: 0,
+        })
+        .collect();
+
+    for file in files {
+        // Upper bounds are exclusive except for the last bucket, which holds 100.
+        let index = (file.composite_score.clamp(0.0, 100.0) / bucket_size as f64) as usize;
+        buckets[index.min(bucket_count - 1)].count += 1;
+    }
+
+    Ok(buckets)
+}
+
+/// Aggregate debt for one detected language
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct LanguageSummary {
+    pub language: String,
+    pub file_count: usize,
+    pub total_loc: usize,
+    pub average_composite_score: f64,
+    pub high_debt_count: usize,
+}
+
+/// Per-language file counts, LOC and debt for the cached analysis, largest
+/// languages first
+#[tauri::command]
+pub async fn get_language_summary(
+    cache: tauri::State<'_, Arc<Mutex<AnalysisCache>>>,
+) -> Result<Vec<LanguageSummary>, String> {
+    let cache_lock = cache.lock().map_err(|_| "Cache lock error".to_string())?;
+    let result = cache_lock
+        .result
+        .as_ref()
+        .ok_or("No analysis data available. Run analysis first.".to_string())?;
+    Ok(summarize_languages(&result.files))
+}
+
+fn summarize_languages(files: &[FileScore]) -> Vec<LanguageSummary> {
+    let mut by_language: std::collections::BTreeMap<&str, LanguageSummary> = std::collections::BTreeMap::new();
+    for file in files {
+        let language = match file.language.trim() {
+            "" => "unknown",
+            language => language,
+        };
+        let summary = by_language.entry(language).or_insert_with(|| LanguageSummary {
+            language: language.to_string(),
+            file_count: 0,
+            total_loc: 0,
+            average_composite_score: 0.0,
+            high_debt_count: 0,
+        });
+        summary.file_count += 1;
+        summary.total_loc += file.loc;
+        // Summed here, divided by the file count below.
+        summary.average_composite_score += file.composite_score;
+        if counts_as_high_debt(file) {
+            summary.high_debt_count += 1;
+        }
+    }
+
+    let mut summaries: Vec<LanguageSummary> = by_language
+        .into_values()
+        .map(|mut summary| {
+            summary.average_composite_score /= summary.file_count as f64;
+            summary
+        })
+        .collect();
+    summaries.sort_by(|a, b| b.file_count.cmp(&a.file_count).then_with(|| a.language.cmp(&b.language)));
+    summaries
+}
 
-    patch_cached_result(cache, workspace_path, updated.clone());
+/// A file pushing a budget over its limit
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct BreachingFile {
+    pub relative_path: String,
+    pub composite_score: f64,
+}
 
-    Ok(updated)
+/// A breached budget with the files that breach it, worst first
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct BudgetBreach {
+    pub budget: crate::models::budget::DebtBudget,
+    pub files: Vec<BreachingFile>,
 }
 
+/// Every breached budget with the cached files that put it over its limit
 #[tauri::command]
-pub async fn get_heatmap_data(
+pub async fn get_breaching_files(
+    workspace_path: String,
     cache: tauri::State<'_, Arc<Mutex<AnalysisCache>>>,
-) -> Result<HeatmapNode, String> {
+) -> Result<Vec<BudgetBreach>, String> {
+    let conn = crate::commands::db::get_db_connection(&workspace_path)
+        .map_err(|e| format!("DB error: {e}"))?;
+    let budgets = crate::commands::db::load_budgets(&conn).map_err(|e| format!("DB read error: {e}"))?;
+
     let cache_lock = cache.lock().map_err(|_| "Cache lock error".to_string())?;
-    cache_lock
-        .heatmap
-        .clone()
-        .ok_or("No analysis data available. Run analysis first.".to_string())
+    let result = cache_lock
+        .result
+        .as_ref()
+        .ok_or("No analysis data available. Run analysis first.".to_string())?;
+    Ok(budget_breaches(&result.files, &budgets))
+}
+
+fn budget_breaches(files: &[FileScore], budgets: &[crate::models::budget::DebtBudget]) -> Vec<BudgetBreach> {
+    budgets
+        .iter()
+        .filter_map(|budget| {
+            let mut breaching: Vec<BreachingFile> = budget_breaching_files(files, budget)
+                .into_iter()
+                .map(|f| BreachingFile {
+                    relative_path: f.relative_path.clone(),
+                    composite_score: f.composite_score,
+                })
+                .collect();
+            if breaching.is_empty() {
+                return None;
+            }
+            breaching.sort_by(|a, b| {
+                b.composite_score
+                    .partial_cmp(&a.composite_score)
+                    .unwrap_or(std::cmp::Ordering::Equal)
+                    .then_with(|| a.relative_path.cmp(&b.relative_path))
+            });
+            Some(BudgetBreach {
+                budget: budget.clone(),
+                files: breaching,
+            })
+        })
+        .collect()
+}
+
+/// Outcome of a CI quality gate; a wrapper fails the build when `passed` is false
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct GateResult {
+    pub passed: bool,
+    pub reasons: Vec<String>,
 }
 
+/// Evaluate the cached analysis against CI limits. Unset limits are not
+/// checked; with `include_budgets`, any breached debt budget also fails.
 #[tauri::command]
-pub async fn get_file_breakdown(
-    path: String,
+pub async fn check_gate(
+    workspace_path: String,
     cache: tauri::State<'_, Arc<Mutex<AnalysisCache>>>,
-) -> Result<FileBreakdown, String> {
+    max_workspace_score: Option<f64>,
+    max_high_debt_count: Option<usize>,
+    include_budgets: Option<bool>,
+) -> Result<GateResult, String> {
+    check_gate_internal(
+        &workspace_path,
+        cache.inner(),
+        max_workspace_score,
+        max_high_debt_count,
+        include_budgets.unwrap_or(false),
+    )
+}
+
+pub fn check_gate_internal(
+    workspace_path: &str,
+    cache: &Arc<Mutex<AnalysisCache>>,
+    max_workspace_score: Option<f64>,
+    max_high_debt_count: Option<usize>,
+    include_budgets: bool,
+) -> Result<GateResult, String> {
+    let budgets = if include_budgets {
+        let conn = crate::commands::db::get_db_connection(workspace_path)
+            .map_err(|e| format!("DB error: {e}"))?;
+        crate::commands::db::load_budgets(&conn).map_err(|e| format!("DB read error: {e}"))?
+    } else {
+        Vec::new()
+    };
+
     let cache_lock = cache.lock().map_err(|_| "Cache lock error".to_string())?;
-    let result = cache_lock.result.as_ref().ok_or("No analysis data")?;
+    if cache_lock.workspace_path.as_deref() != Some(workspace_path) {
+        return Err("No analysis data available for this workspace. Run analysis first.".to_string());
+    }
+    let result = cache_lock
+        .result
+        .as_ref()
+        .ok_or("No analysis data available. Run analysis first.".to_string())?;
+    Ok(evaluate_gate(result, max_workspace_score, max_high_debt_count, &budgets))
+}
 
-    let file = result
-        .files
-        .iter()
-        .find(|f| f.relative_path == path || f.path == path)
-        .ok_or(format!("File not found: {path}"))?;
+fn evaluate_gate(
+    result: &AnalysisResult,
+    max_workspace_score: Option<f64>,
+    max_high_debt_count: Option<usize>,
+    budgets: &[crate::models::budget::DebtBudget],
+) -> GateResult {
+    let mut reasons = Vec::new();
+    if let Some(max_score) = max_workspace_score {
+        if result.workspace_score > max_score {
+            reasons.push(format!(
+                "workspace score {:.1} exceeds limit {:.1}",
+                result.workspace_score, max_score
+            ));
+        }
+    }
+    if let Some(max_count) = max_high_debt_count {
+        if result.high_debt_count > max_count {
+            reasons.push(format!(
+                "{} high-debt files exceed limit {}",
+                result.high_debt_count, max_count
+            ));
+        }
+    }
+    for budget in breached_budgets(&result.files, budgets) {
+        reasons.push(format!(
+            "budget '{}' ({}) breached: a file scores above {:.1}",
+            budget.label, budget.pattern, budget.max_score
+        ));
+    }
 
-    Ok(FileBreakdown {
-        path: file.relative_path.clone(),
-        composite_score: file.composite_score,
-        components: vec![
-            to_detail("churn_rate", &file.components.churn_rate),
-            to_detail("code_smell_density", &file.components.code_smell_density),
-            to_detail("coupling_index", &file.components.coupling_index),
-            to_detail("change_coupling", &file.components.change_coupling),
-            to_detail("test_coverage_gap", &file.components.test_coverage_gap),
-            to_detail("knowledge_concentration", &file.components.knowledge_concentration),
-            to_detail("cyclomatic_complexity", &file.components.cyclomatic_complexity),
-            to_detail("decision_staleness", &file.components.decision_staleness),
-        ],
-    })
+    GateResult {
+        passed: reasons.is_empty(),
+        reasons,
+    }
 }
 
 #[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
-pub struct CouplingPair {
-    pub file_a: String,
-    pub file_b: String,
-    pub coupling_ratio: f64,
-    pub co_change_count: usize,
-    pub has_import_link: bool,
+pub struct HotspotEstimate {
+    pub relative_path: String,
+    pub composite_score: f64,
+    pub loc: usize,
+    pub estimated_hours: f64,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct RemediationEstimate {
+    pub total_hours: f64,
+    pub register_hours: f64,
+    pub register_item_count: usize,
+    pub hotspot_hours: f64,
+    pub hotspots: Vec<HotspotEstimate>,
 }
 
 #[tauri::command]
-pub async fn get_change_couplings(
+pub async fn estimate_remediation(
     workspace_path: String,
-    threshold: Option<f64>,
     cache: tauri::State<'_, Arc<Mutex<AnalysisCache>>>,
-) -> Result<Vec<CouplingPair>, String> {
-    let min_threshold = threshold.unwrap_or(0.05);
+) -> Result<RemediationEstimate, String> {
+    estimate_remediation_internal(&workspace_path, cache.inner())
+}
 
-    let co_change_result = crate::analysis::coupling::analyze_co_changes(&workspace_path, 90)
-        .unwrap_or_default();
+pub fn estimate_remediation_internal(
+    workspace_path: &str,
+    cache: &Arc<Mutex<AnalysisCache>>,
+) -> Result<RemediationEstimate, String> {
+    let conn = crate::commands::db::get_db_connection(workspace_path)
+        .map_err(|e| format!("DB error: {e}"))?;
+    let items = crate::commands::db::load_register_items(&conn)
+        .map_err(|e| format!("DB read error: {e}"))?;
 
-    let cache_lock = cache.lock().map_err(|_| "Cache lock error")?;
-    let all_files: Vec<String> = cache_lock
-        .result
-        .as_ref()
-        .map(|r| r.files.iter().map(|f| f.relative_path.clone()).collect())
-        .unwrap_or_default();
-    drop(cache_lock);
+    let cache_lock = cache.lock().map_err(|_| "Cache lock error".to_string())?;
+    let result = cache_lock.result.as_ref().ok_or("No analysis data")?;
 
-    let mut pairs: Vec<CouplingPair> = co_change_result.pairs
+    Ok(compute_remediation_estimate(&result.files, &items))
+}
+
+/// Rough remediation cost: estimated hours of unresolved register items plus a
+/// heuristic for high-debt files nobody has registered yet (1h per 100 LOC for
+/// every 10 points above the high-debt threshold, minimum 1h).
+fn compute_remediation_estimate(
+    files: &[FileScore],
+    items: &[crate::models::register::RegisterItem],
+) -> RemediationEstimate {
+    let open_items: Vec<_> = items
         .iter()
-        .filter(|(_, _, count)| *count >= 2)
-        .map(|(a, b, count)| {
-            // Use spec formula: co_changes / min(changes_a, changes_b)
-            let changes_a = co_change_result.file_change_counts.get(a).copied().unwrap_or(1);
-            let changes_b = co_change_result.file_change_counts.get(b).copied().unwrap_or(1);
-            let min_changes = changes_a.min(changes_b).max(1) as f64;
-            let ratio = (*count as f64 / min_changes).min(1.0);
-            let b_basename = std::path::Path::new(b)
-                .file_stem()
-                .map(|s| s.to_string_lossy().to_string())
-                .unwrap_or_default();
-            let source_path = format!("{workspace_path}/{a}");
-            let has_import_link = std::fs::read_to_string(&source_path)
-                .map(|src| src.contains(&b_basename))
-                .unwrap_or(false);
-            CouplingPair {
-                file_a: a.clone(),
-                file_b: b.clone(),
-                coupling_ratio: ratio,
-                co_change_count: *count,
-                has_import_link,
-            }
+        .filter(|item| item.status == "open" || item.status == "in_progress")
+        .collect();
+    let register_hours: f64 = open_items.iter().filter_map(|item| item.estimated_hours).sum();
+
+    let mut hotspots: Vec<HotspotEstimate> = files
+        .iter()
+        .filter(|f| f.composite_score > HIGH_DEBT_THRESHOLD)
+        .filter(|f| {
+            !open_items.iter().any(|item| {
+                item.file_path.as_deref() == Some(f.relative_path.as_str())
+                    || item.file_path.as_deref() == Some(f.path.as_str())
+            })
         })
-        .filter(|p| {
-            p.coupling_ratio >= min_threshold
-                && (all_files.is_empty()
-                    || all_files.contains(&p.file_a)
-                    || all_files.contains(&p.file_b))
+        .map(|f| {
+            let points_over = (f.composite_score - HIGH_DEBT_THRESHOLD) / 10.0;
+            HotspotEstimate {
+                relative_path: f.relative_path.clone(),
+                composite_score: f.composite_score,
+                loc: f.loc,
+                estimated_hours: (f.loc as f64 / 100.0 * points_over).max(1.0),
+            }
         })
         .collect();
+    hotspots.sort_by(|a, b| {
+        b.estimated_hours
+            .partial_cmp(&a.estimated_hours)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
 
-    pairs.sort_by(|a, b| b.co_change_count.cmp(&a.co_change_count));
-    pairs.truncate(200);
-    Ok(pairs)
+    let hotspot_hours: f64 = hotspots.iter().map(|h| h.estimated_hours).sum();
+
+    RemediationEstimate {
+        total_hours: register_hours + hotspot_hours,
+        register_hours,
+        register_item_count: open_items.len(),
+        hotspot_hours,
+        hotspots,
+    }
 }
 
-fn load_analysis_inputs(workspace_path: &str) -> Result<AnalysisInputs, String> {
-    let settings = crate::commands::settings::load_effective_analysis_settings(workspace_path)?;
+/// With `quick_scan`, the git history is left unread: churn, blame and
+/// co-change data stay empty, docs (scored only on git history) are skipped,
+/// and the frozen-complexity signal is off since every file would look untouched.
+fn load_analysis_inputs(
+    workspace_path: &str,
+    quick_scan: bool,
+    weights_override: Option<&serde_json::Value>,
+) -> Result<AnalysisInputs, String> {
+    let mut settings =
+        crate::commands::settings::load_effective_analysis_settings_with_weights(workspace_path, weights_override)?;
+    if quick_scan {
+        settings.track_docs = false;
+        settings.frozen_complexity_threshold = 0.0;
+        let mut inputs = analysis_inputs(
+            workspace_path,
+            settings,
+            None,
+            Default::default(),
+            Default::default(),
+            Default::default(),
+        );
+        inputs.coverage = load_coverage(workspace_path, &inputs);
+        inputs.import_degrees = load_import_degrees(workspace_path, &inputs);
+        return Ok(inputs);
+    }
 
-    let churn = crate::analysis::churn::analyze_churn(workspace_path, settings.history_days)
-        .unwrap_or_default();
-    let blame = crate::analysis::knowledge::analyze_knowledge(workspace_path).unwrap_or_default();
-    let co_change_result =
-        crate::analysis::coupling::analyze_co_changes(workspace_path, settings.history_days)
-            .unwrap_or_default();
+    // Fail early on a misconfigured ref rather than silently scoring with no history.
+    let analysis_ref = settings.analysis_ref.as_deref();
+    if analysis_ref.is_some() {
+        let repo = git2::Repository::open(workspace_path).map_err(|e| format!("Git error: {e}"))?;
+        crate::commands::git::resolve_analysis_ref(&repo, analysis_ref)?;
+    }
+    let history_head = history_head(workspace_path, analysis_ref);
+
+    // A failed git input scores as zero; record it so the result says so.
+    let mut degraded = Vec::new();
+    let mut mark_degraded = |input: &str, error: String| {
+        degraded.push(format!("{input} unavailable: {error}"));
+    };
+    let mut churn_unavailable = history_head.is_none();
+    let churn = crate::analysis::churn::analyze_churn(workspace_path, settings.history_days, analysis_ref)
+        .unwrap_or_else(|e| {
+            churn_unavailable = true;
+            mark_degraded("churn", e);
+            Default::default()
+        });
+    let blame = crate::analysis::knowledge::analyze_knowledge(workspace_path, analysis_ref).unwrap_or_else(|e| {
+        mark_degraded("knowledge", e);
+        Default::default()
+    });
+    let co_changes = crate::analysis::coupling::analyze_co_changes(
+        workspace_path,
+        settings.co_change_history_days,
+        analysis_ref,
+        settings.max_files_per_commit_for_coupling,
+    )
+    .unwrap_or_else(|e| {
+        mark_degraded("change coupling", e);
+        Default::default()
+    });
+
+    let analysis_ref = settings.analysis_ref.clone();
+    let mut inputs = analysis_inputs(workspace_path, settings, history_head, churn, blame, co_changes);
+    inputs.coverage = load_coverage(workspace_path, &inputs);
+    inputs.import_degrees = load_import_degrees(workspace_path, &inputs);
+    inputs.churn_available = !churn_unavailable;
+    if churn_unavailable {
+        // Without churn every file looks untouched, so frozen complexity
+        // would flag every complex file.
+        inputs.frozen_complexity_threshold = 0.0;
+    }
+    if !inputs.doc_files.is_empty() {
+        let docs = inputs.doc_files.iter().map(|file| to_relative_path(workspace_path, file)).collect();
+        inputs.doc_last_changed =
+            crate::analysis::churn::last_change_times(workspace_path, &docs, analysis_ref.as_deref())
+                .unwrap_or_else(|e| {
+                    degraded.push(format!("documentation history unavailable: {e}"));
+                    Default::default()
+                });
+    }
+    inputs.degraded = degraded;
+    Ok(inputs)
+}
+
+fn load_coverage(workspace_path: &str, inputs: &AnalysisInputs) -> crate::analysis::coverage::CoverageIndex {
+    crate::analysis::coverage::CoverageIndex::load(
+        workspace_path,
+        &inputs.skip_directories,
+        inputs.jacoco_report_path.as_deref(),
+    )
+}
 
-    Ok(AnalysisInputs {
+/// Import graph over the analysis' walked files, read once per run
+fn load_import_degrees(workspace_path: &str, inputs: &AnalysisInputs) -> crate::analysis::coupling::ImportDegrees {
+    crate::analysis::coupling::ImportDegrees::from_edges(&crate::analysis::coupling::import_edges(
+        workspace_path,
+        &inputs.workspace_files,
+        &inputs.import_roots,
+    ))
+}
+
+fn analysis_inputs(
+    workspace_path: &str,
+    settings: crate::commands::settings::EffectiveAnalysisSettings,
+    history_head: Option<String>,
+    churn: crate::analysis::churn::ChurnData,
+    blame: crate::analysis::knowledge::BlameData,
+    co_changes: crate::analysis::coupling::CoChangeResult,
+) -> AnalysisInputs {
+    let skip_directories = crate::commands::settings::load_skip_directories(workspace_path);
+    let workspace_files = crate::commands::git::walkdir_with(workspace_path, &skip_directories);
+    AnalysisInputs {
         history_days: settings.history_days,
         weights: settings.weights,
+        raw_score_caps: settings.raw_score_caps,
+        file_length_threshold: settings.file_length_threshold,
+        smell_density_scale: settings.smell_density_scale,
+        magic_number_allowlist: settings.magic_number_allowlist,
+        composite_formula: settings.composite_formula,
+        relative_blend: settings.relative_blend,
+        tracked_files_only: settings.tracked_files_only,
+        include_type_declarations: settings.include_type_declarations,
+        include_generated_code: settings.include_generated_code,
+        include_languages: settings.include_languages,
+        exclude_languages: settings.exclude_languages,
+        frozen_complexity_threshold: settings.frozen_complexity_threshold,
+        frozen_complexity_penalty: settings.frozen_complexity_penalty,
+        accepted_patterns: settings
+            .accepted_patterns
+            .iter()
+            .filter_map(|pattern| glob::Pattern::new(pattern).ok())
+            .collect(),
+        include_patterns: settings
+            .include_patterns
+            .iter()
+            .filter_map(|pattern| glob::Pattern::new(pattern).ok())
+            .collect(),
+        exclude_patterns: settings
+            .exclude_patterns
+            .iter()
+            .filter_map(|pattern| glob::Pattern::new(pattern).ok())
+            .collect(),
+        new_file_grace_days: settings.new_file_grace_days,
+        adr_stale_days: settings.adr_stale_days,
+        max_threads: settings.max_threads,
+        history_head,
+        import_roots: crate::analysis::coupling::workspace_import_roots(workspace_path, &workspace_files),
+        import_degrees: Default::default(),
+        doc_files: if settings.track_docs {
+            crate::commands::git::doc_files(workspace_path, &skip_directories)
+        } else {
+            Vec::new()
+        },
+        doc_last_changed: Default::default(),
+        workspace_files,
+        skip_directories,
+        jacoco_report_path: crate::commands::settings::load_jacoco_report_path(workspace_path),
+        coverage: Default::default(),
         churn,
+        churn_available: false,
         blame,
-        co_changes: co_change_result,
-    })
+        co_changes,
+        bare_repo: crate::commands::git::open_bare_repo(workspace_path).map(Mutex::new),
+        degraded: Vec::new(),
+    }
 }
 
 fn score_file(workspace_path: &str, file_path: &str, inputs: &AnalysisInputs) -> Result<FileScore, String> {
-    let source = std::fs::read_to_string(file_path)
-        .map_err(|e| format!("Failed to read {file_path}: {e}"))?;
+    let source =
+        crate::commands::git::read_workspace_file_with(workspace_path, file_path, inputs.bare_repo.as_ref())?;
 
     let relative_path = to_relative_path(workspace_path, file_path);
     let lang = detect_language(file_path);
@@ -246,83 +2705,108 @@ fn score_file(workspace_path: &str, file_path: &str, inputs: &AnalysisInputs) ->
         .map(|d| d.as_secs() as i64)
         .unwrap_or(0);
 
+    if crate::commands::git::is_doc_file(std::path::Path::new(file_path)) {
+        return Ok(score_doc_file(file_path, relative_path, loc, last_modified, inputs));
+    }
+
     let churn_raw = crate::analysis::churn::compute_file_churn(
         &inputs.churn,
         &relative_path,
         inputs.history_days,
     );
-    let smells = crate::analysis::smells::detect_smells(&source, &lang, loc);
-    let smell_raw = compute_smell_score(&smells, loc);
-    let coupling_raw = crate::analysis::coupling::compute_coupling_index(&relative_path, workspace_path);
+    let (smell, complexity, functions, smell_counts) = content_component_scores(
+        &relative_path,
+        &source,
+        &lang,
+        loc,
+        inputs.file_length_threshold,
+        inputs.smell_density_scale,
+        &inputs.magic_number_allowlist,
+    );
+    let smell_raw = smell.raw_score;
+    let complexity_raw = complexity.raw_score;
+    let coupling = coupling_component(&relative_path, &source, &lang, &inputs.import_degrees, &inputs.import_roots);
+    let coupling_raw = coupling.raw_score;
     let change_coupling_raw =
         crate::analysis::coupling::compute_change_coupling(&relative_path, &inputs.co_changes);
-    let coverage_raw = crate::analysis::coverage::compute_coverage_gap(&relative_path, workspace_path);
+    let coverage_raw =
+        crate::analysis::coverage::compute_coverage_gap(&relative_path, workspace_path, &inputs.coverage);
     let knowledge_raw =
         crate::analysis::knowledge::compute_knowledge_concentration(&inputs.blame, &relative_path);
-    let complexity_data = crate::analysis::complexity::analyze_complexity(&source, &lang);
-    let complexity_raw = (complexity_data.average / 20.0 * 100.0).min(100.0);
-    let staleness_raw = crate::analysis::staleness::compute_staleness(&relative_path, workspace_path, smell_raw);
+    let commit_count = inputs.churn.get(&relative_path).copied().unwrap_or(0);
+    let frozen_raw = crate::analysis::staleness::frozen_complexity_penalty(
+        complexity_raw,
+        commit_count,
+        inputs.frozen_complexity_threshold,
+        inputs.frozen_complexity_penalty,
+    );
+    let staleness = staleness_component(
+        &relative_path,
+        workspace_path,
+        smell_raw,
+        complexity_raw,
+        frozen_raw,
+        inputs.history_days,
+        inputs.adr_stale_days,
+    );
+    let staleness_raw = staleness.raw_score;
 
-    let w = &inputs.weights;
-    let components = ScoreComponents {
+    let w = &file_weights(&source, &inputs.weights);
+    let mut components = ScoreComponents {
         churn_rate: ComponentScore {
             raw_score: churn_raw,
             weight: *w.get("churn_rate").unwrap_or(&0.22),
             contribution: churn_raw * w.get("churn_rate").unwrap_or(&0.22),
-            details: vec![],
+            details: vec![format!("{} commits in {} days", commit_count, inputs.history_days)],
+            confidence: None,
         },
         code_smell_density: ComponentScore {
-            raw_score: smell_raw,
             weight: *w.get("code_smell_density").unwrap_or(&0.20),
             contribution: smell_raw * w.get("code_smell_density").unwrap_or(&0.20),
-            details: vec![format!("{} smells in {} LOC", smells.total, loc)],
+            ..smell
         },
         coupling_index: ComponentScore {
-            raw_score: coupling_raw,
             weight: *w.get("coupling_index").unwrap_or(&0.18),
             contribution: coupling_raw * w.get("coupling_index").unwrap_or(&0.18),
-            details: vec![],
+            ..coupling
         },
         change_coupling: ComponentScore {
             raw_score: change_coupling_raw,
             weight: *w.get("change_coupling").unwrap_or(&0.12),
             contribution: change_coupling_raw * w.get("change_coupling").unwrap_or(&0.12),
             details: vec![],
+            confidence: None,
         },
         test_coverage_gap: ComponentScore {
             raw_score: coverage_raw,
             weight: *w.get("test_coverage_gap").unwrap_or(&0.12),
             contribution: coverage_raw * w.get("test_coverage_gap").unwrap_or(&0.12),
             details: vec![],
+            confidence: None,
         },
         knowledge_concentration: ComponentScore {
             raw_score: knowledge_raw,
             weight: *w.get("knowledge_concentration").unwrap_or(&0.08),
             contribution: knowledge_raw * w.get("knowledge_concentration").unwrap_or(&0.08),
             details: vec![],
+            confidence: None,
         },
         cyclomatic_complexity: ComponentScore {
-            raw_score: complexity_raw,
             weight: *w.get("cyclomatic_complexity").unwrap_or(&0.05),
             contribution: complexity_raw * w.get("cyclomatic_complexity").unwrap_or(&0.05),
-            details: vec![format!("avg complexity: {:.1}", complexity_data.average)],
+            ..complexity
         },
         decision_staleness: ComponentScore {
-            raw_score: staleness_raw,
             weight: *w.get("decision_staleness").unwrap_or(&0.03),
             contribution: staleness_raw * w.get("decision_staleness").unwrap_or(&0.03),
-            details: vec![],
+            ..staleness
         },
     };
 
-    let composite_score = components.churn_rate.contribution
-        + components.code_smell_density.contribution
-        + components.coupling_index.contribution
-        + components.change_coupling.contribution
-        + components.test_coverage_gap.contribution
-        + components.knowledge_concentration.contribution
-        + components.cyclomatic_complexity.contribution
-        + components.decision_staleness.contribution;
+    apply_raw_score_caps(&mut components, &inputs.raw_score_caps);
+
+    let composite_score = composite_score(&components, &inputs.composite_formula);
+    let supervision_status = supervision_status_for(&relative_path, &inputs.accepted_patterns);
 
     Ok(FileScore {
         path: file_path.to_string(),
@@ -332,21 +2816,416 @@ fn score_file(workspace_path: &str, file_path: &str, inputs: &AnalysisInputs) ->
         loc,
         language: lang,
         last_modified,
-        supervision_status: "none".to_string(),
+        supervision_status,
+        functions,
+        smell_counts,
     })
 }
 
+/// Import coupling component, unweighted. It depends on this file's imports
+/// and on the rest of the workspace's import graph.
+fn coupling_component(
+    relative_path: &str,
+    source: &str,
+    lang: &str,
+    import_degrees: &crate::analysis::coupling::ImportDegrees,
+    import_roots: &std::collections::HashSet<String>,
+) -> ComponentScore {
+    let (internal_imports, external_imports) = crate::analysis::coupling::split_imports(
+        &crate::analysis::coupling::extract_imports(source, lang),
+        lang,
+        import_roots,
+    );
+    ComponentScore {
+        raw_score: crate::analysis::coupling::compute_coupling_index(relative_path, import_degrees),
+        weight: 0.0,
+        contribution: 0.0,
+        details: vec![format!(
+            "{} internal imports, {} external dependencies",
+            internal_imports.len(),
+            external_imports.len()
+        )],
+        confidence: None,
+    }
+}
+
+/// Decision staleness component, unweighted: the linked ADR's review age, or
+/// the no-ADR penalty for smelly files, raised to `frozen_raw` when the file
+/// counts as frozen complexity
+fn staleness_component(
+    relative_path: &str,
+    workspace_path: &str,
+    smell_raw: f64,
+    complexity_raw: f64,
+    frozen_raw: f64,
+    history_days: u32,
+    adr_stale_days: i64,
+) -> ComponentScore {
+    let mut details = vec![];
+    if frozen_raw > 0.0 {
+        details.push(format!(
+            "frozen complexity: {complexity_raw:.0} complexity with no commits in {history_days} days"
+        ));
+    }
+    ComponentScore {
+        raw_score: crate::analysis::staleness::compute_staleness(relative_path, workspace_path, smell_raw, adr_stale_days)
+            .max(frozen_raw),
+        weight: 0.0,
+        contribution: 0.0,
+        details,
+        confidence: None,
+    }
+}
+
+/// Smell density and cyclomatic complexity components, unweighted, plus the
+/// per-function complexity list: everything that depends only on the file's
+/// content
+fn content_component_scores(
+    relative_path: &str,
+    source: &str,
+    lang: &str,
+    loc: usize,
+    file_length_threshold: usize,
+    smell_density_scale: f64,
+    magic_number_allowlist: &std::collections::HashMap<String, Vec<f64>>,
+) -> (
+    ComponentScore,
+    ComponentScore,
+    Vec<crate::commands::ast::FunctionComplexity>,
+    std::collections::BTreeMap<String, usize>,
+) {
+    let smells =
+        crate::analysis::smells::detect_smells_in_file(relative_path, source, lang, loc, magic_number_allowlist);
+    let mut smell_counts = crate::analysis::smells::smell_counts(&smells);
+    smell_counts.retain(|_, count| *count > 0);
+    let smell = ComponentScore {
+        raw_score: compute_smell_score(&smells, loc, smell_density_scale),
+        weight: 0.0,
+        contribution: 0.0,
+        details: vec![format!("{} smells in {} LOC", smells.total, loc)],
+        confidence: Some(smells.confidence),
+    };
+
+    let complexity_data = crate::analysis::complexity::analyze_complexity(source, lang);
+    let complexity_raw = crate::analysis::complexity::size_adjusted_complexity(
+        complexity_data.average,
+        loc,
+        file_length_threshold,
+    );
+    let length_penalty = crate::analysis::complexity::file_length_penalty(loc, file_length_threshold);
+    let mut complexity_details = vec![format!("avg complexity: {:.1}", complexity_data.average)];
+    if length_penalty > 0.0 {
+        complexity_details.push(format!(
+            "file length: {} LOC over {} threshold (+{:.1})",
+            loc, file_length_threshold, length_penalty
+        ));
+    }
+
+    let complexity = ComponentScore {
+        raw_score: complexity_raw,
+        weight: 0.0,
+        contribution: 0.0,
+        details: complexity_details,
+        confidence: Some(complexity_data.confidence),
+    };
+
+    (smell, complexity, complexity_data.functions, smell_counts)
+}
+
+/// Documentation files (`trackDocs`) are scored on churn and staleness only;
+/// code-oriented components stay at zero.
+fn score_doc_file(
+    file_path: &str,
+    relative_path: String,
+    loc: usize,
+    last_modified: i64,
+    inputs: &AnalysisInputs,
+) -> FileScore {
+    let churn_raw = crate::analysis::churn::compute_file_churn(
+        &inputs.churn,
+        &relative_path,
+        inputs.history_days,
+    );
+    let staleness_raw = crate::analysis::staleness::compute_doc_staleness(
+        inputs.doc_last_changed.get(&relative_path).copied(),
+        inputs.adr_stale_days,
+    );
+
+    let mut components = ScoreComponents::default();
+    for (name, component) in components.named_mut() {
+        component.weight = inputs.weights.get(name).copied().unwrap_or(0.0);
+    }
+    components.churn_rate.raw_score = churn_raw;
+    components.churn_rate.contribution = churn_raw * components.churn_rate.weight;
+    components.churn_rate.details = vec![format!(
+        "{} commits in {} days",
+        inputs.churn.get(&relative_path).copied().unwrap_or(0),
+        inputs.history_days
+    )];
+    components.decision_staleness.raw_score = staleness_raw;
+    components.decision_staleness.contribution = staleness_raw * components.decision_staleness.weight;
+    components.decision_staleness.details = vec!["documentation: last commit is the review date".to_string()];
+    apply_raw_score_caps(&mut components, &inputs.raw_score_caps);
+
+    let language = match std::path::Path::new(file_path).extension().and_then(|e| e.to_str()) {
+        Some("md") => "markdown",
+        Some("rst") => "restructuredtext",
+        _ => "text",
+    };
+
+    FileScore {
+        path: file_path.to_string(),
+        composite_score: composite_score(&components, &inputs.composite_formula),
+        supervision_status: supervision_status_for(&relative_path, &inputs.accepted_patterns),
+        functions: Vec::new(),
+        smell_counts: Default::default(),
+        relative_path,
+        components,
+        loc,
+        language: language.to_string(),
+        last_modified,
+    }
+}
+
+/// Apply in-file weight directives such as `// debtlens: weight churn_rate=0.4`
+/// found in the first few lines. Precedence is file > language > global; there
+/// are no language-level weights yet, so directives sit directly on top of the
+/// settings weights. Overridden weights are rescaled so the total matches the
+/// settings total, keeping `normalizeWeights: false` workspaces on their scale.
+fn file_weights(
+    source: &str,
+    weights: &std::collections::HashMap<String, f64>,
+) -> std::collections::HashMap<String, f64> {
+    let mut overridden = weights.clone();
+    let mut changed = false;
+    for line in source.lines().take(WEIGHT_DIRECTIVE_LINES) {
+        let Some((_, directive)) = line.split_once("debtlens:") else {
+            continue;
+        };
+        let Some(assignments) = directive.trim_start().strip_prefix("weight ") else {
+            continue;
+        };
+        for assignment in assignments.split(|c: char| c.is_whitespace() || c == ',') {
+            let Some((key, value)) = assignment.split_once('=') else {
+                continue;
+            };
+            let Ok(value) = value.trim_end_matches("*/").parse::<f64>() else {
+                continue;
+            };
+            if let Some(weight) = overridden.get_mut(key) {
+                *weight = value.clamp(0.0, 1.0);
+                changed = true;
+            }
+        }
+    }
+    if !changed {
+        return overridden;
+    }
+
+    let target: f64 = weights.values().sum();
+    let sum: f64 = overridden.values().sum();
+    if sum <= f64::EPSILON {
+        return weights.clone();
+    }
+    for weight in overridden.values_mut() {
+        *weight *= target / sum;
+    }
+    overridden
+}
+
+/// Clamp each raw score to its `rawScoreCap` so one runaway metric can't
+/// swamp the composite, recomputing the contribution of capped components
+fn apply_raw_score_caps(components: &mut ScoreComponents, caps: &std::collections::HashMap<String, f64>) {
+    for (name, component) in components.named_mut() {
+        let cap = caps.get(name).copied().unwrap_or(100.0);
+        if component.raw_score > cap {
+            component
+                .details
+                .push(format!("capped at {cap:.0} (raw {:.1})", component.raw_score));
+            component.raw_score = cap;
+            component.contribution = cap * component.weight;
+        }
+    }
+}
+
+/// `acceptable` for files under an `acceptedPatterns` glob, otherwise `none`
+fn supervision_status_for(relative_path: &str, accepted_patterns: &[glob::Pattern]) -> String {
+    if accepted_patterns.iter().any(|pattern| pattern.matches(relative_path)) {
+        "acceptable".to_string()
+    } else {
+        "none".to_string()
+    }
+}
+
+/// High-debt files that haven't been accepted as-is or are still in their
+/// `newFileGraceDays` grace period
+fn counts_as_high_debt(file: &FileScore) -> bool {
+    file.composite_score > HIGH_DEBT_THRESHOLD && !excluded_from_debt_counts(file)
+}
+
+/// Accepted debt and files still in their new-file grace period
+fn excluded_from_debt_counts(file: &FileScore) -> bool {
+    matches!(file.supervision_status.as_str(), "acceptable" | "new")
+}
+
+/// Mark unsupervised files first scored less than `grace_days` ago as `new`.
+/// Files missing from `first_seen` are being scored for the first time,
+/// unless nothing has been persisted yet: the first analysis is the baseline.
+fn flag_new_files(
+    files: &mut [FileScore],
+    first_seen: &std::collections::HashMap<String, i64>,
+    now: i64,
+    grace_days: u32,
+) {
+    if grace_days == 0 || first_seen.is_empty() {
+        return;
+    }
+    let cutoff = now - i64::from(grace_days) * 86_400;
+    for file in files.iter_mut().filter(|file| file.supervision_status == "none") {
+        let seen_at = first_seen.get(&file.relative_path).copied().unwrap_or(now);
+        if seen_at > cutoff {
+            file.supervision_status = "new".to_string();
+        }
+    }
+}
+
+/// `flag_new_files` for files rescored outside a full analysis. A `new` flag
+/// carried over from the cache is re-derived, since the grace period may
+/// have ended since.
+fn flag_rescored_new_files(workspace_path: &str, files: &mut [FileScore], grace_days: u32) -> Result<(), String> {
+    for file in files.iter_mut().filter(|file| file.supervision_status == "new") {
+        file.supervision_status = "none".to_string();
+    }
+    let first_seen = load_first_seen(workspace_path)?;
+    flag_new_files(files, &first_seen, chrono::Utc::now().timestamp(), grace_days);
+    Ok(())
+}
+
+/// Min-max rescale each component to 0–100 across `files` and blend it into
+/// the absolute raw score (`blend` 1 replaces it, 0.5 averages the two), then
+/// recompute contributions and composites. Components with no spread across
+/// the workspace rescale to 0. The returned ranges let single-file
+/// re-analysis rescale against the same workspace.
+fn normalize_components_relative(
+    files: &mut [FileScore],
+    formula: &CompositeFormula,
+    caps: &std::collections::HashMap<String, f64>,
+    blend: f64,
+) -> ComponentRanges {
+    let mut ranges = [(f64::INFINITY, f64::NEG_INFINITY); 8];
+    for file in files.iter() {
+        for (range, (_, component)) in ranges.iter_mut().zip(file.components.named()) {
+            range.0 = range.0.min(component.raw_score);
+            range.1 = range.1.max(component.raw_score);
+        }
+    }
+
+    for file in files.iter_mut() {
+        normalize_file(file, &ranges, formula, caps, blend);
+    }
+    ranges
+}
+
+/// Rescale one file's absolute components against workspace `ranges`.
+/// Rescaling can stretch a capped component back to 100, so `rawScoreCap`
+/// is applied again before the composite.
+fn normalize_file(
+    file: &mut FileScore,
+    ranges: &ComponentRanges,
+    formula: &CompositeFormula,
+    caps: &std::collections::HashMap<String, f64>,
+    blend: f64,
+) {
+    for (range, (_, component)) in ranges.iter().zip(file.components.named_mut()) {
+        normalize_component(component, *range, blend);
+    }
+    apply_raw_score_caps(&mut file.components, caps);
+    file.composite_score = composite_score(&file.components, formula);
+}
+
+fn normalize_component(component: &mut ComponentScore, (min, max): (f64, f64), blend: f64) {
+    let absolute = component.raw_score;
+    let spread = max - min;
+    let relative = if spread > f64::EPSILON {
+        ((absolute - min) / spread * 100.0).clamp(0.0, 100.0)
+    } else {
+        0.0
+    };
+    component.raw_score = absolute + (relative - absolute) * blend;
+    component.contribution = component.raw_score * component.weight;
+    component.details.push(if blend < 1.0 {
+        format!("{:.0}% relative to workspace (absolute {absolute:.1})", blend * 100.0)
+    } else {
+        format!("relative to workspace (absolute {absolute:.1})")
+    });
+}
+
+/// Combine component scores into the 0–100 composite.
+fn composite_score(components: &ScoreComponents, formula: &CompositeFormula) -> f64 {
+    let named = components.named();
+    let weighted_sum: f64 = named.iter().map(|(_, c)| c.contribution).sum();
+
+    match formula {
+        CompositeFormula::WeightedSum => weighted_sum,
+        CompositeFormula::WeightedGeomean => {
+            // Geometric mean of "health" (101 − raw) so a single near-100
+            // component pulls the composite up instead of being averaged away.
+            let total_weight: f64 = named.iter().map(|(_, c)| c.weight).sum();
+            if total_weight <= f64::EPSILON {
+                return 0.0;
+            }
+            let log_health: f64 = named
+                .iter()
+                .map(|(_, c)| c.weight / total_weight * (101.0 - c.raw_score.clamp(0.0, 100.0)).ln())
+                .sum();
+            (101.0 - log_health.exp()).clamp(0.0, 100.0)
+        }
+        CompositeFormula::MaxPlus => {
+            let worst = named
+                .iter()
+                .map(|(_, c)| c.raw_score.clamp(0.0, 100.0))
+                .fold(0.0, f64::max);
+            (worst + (100.0 - worst) * weighted_sum.clamp(0.0, 100.0) / 100.0).clamp(0.0, 100.0)
+        }
+        CompositeFormula::Custom(formula) => formula.evaluate(components),
+    }
+}
+
+/// Compare fresh scores with those persisted by the previous analysis
+fn score_changes_since_last_run(
+    workspace_path: &str,
+    files: &[FileScore],
+) -> Result<Vec<crate::models::baseline::FileScoreDelta>, String> {
+    let conn = crate::commands::db::get_db_connection(workspace_path)
+        .map_err(|e| format!("DB error: {e}"))?;
+    let previous = crate::commands::db::load_composite_scores(&conn)
+        .map_err(|e| format!("DB read error: {e}"))?;
+    Ok(crate::models::baseline::classify_against_previous(&previous, files))
+}
+
+fn load_first_seen(workspace_path: &str) -> Result<std::collections::HashMap<String, i64>, String> {
+    let conn = crate::commands::db::get_db_connection(workspace_path)
+        .map_err(|e| format!("DB error: {e}"))?;
+    crate::commands::db::load_first_seen(&conn).map_err(|e| format!("DB read error: {e}"))
+}
+
+fn backdate_first_seen(workspace_path: &str) -> Result<(), String> {
+    let conn = crate::commands::db::get_db_connection(workspace_path)
+        .map_err(|e| format!("DB error: {e}"))?;
+    crate::commands::db::backdate_first_seen(&conn).map_err(|e| format!("DB upsert error: {e}"))
+}
+
 fn persist_result(workspace_path: &str, result: &AnalysisResult) -> Result<(), String> {
     let conn = crate::commands::db::get_db_connection(workspace_path)
         .map_err(|e| format!("DB error: {e}"))?;
-    crate::commands::db::upsert_file_scores(&conn, &result.files)
+    crate::commands::db::upsert_file_scores(&conn, &result.files, &content_hashes(&result.files))
         .map_err(|e| format!("DB upsert error: {e}"))
 }
 
 fn build_analysis_result(files: Vec<FileScore>, duration_ms: u64) -> AnalysisResult {
     let file_count = files.len();
     let total_score: f64 = files.iter().map(|f| f.composite_score).sum();
-    let high_debt_count = files.iter().filter(|f| f.composite_score > 65.0).count();
+    let high_debt_count = files.iter().filter(|f| counts_as_high_debt(f)).count();
 
     AnalysisResult {
         workspace_score: if file_count == 0 {
@@ -358,6 +3237,10 @@ fn build_analysis_result(files: Vec<FileScore>, duration_ms: u64) -> AnalysisRes
         high_debt_count,
         files,
         duration_ms,
+        errors: Vec::new(),
+        score_changes: Vec::new(),
+        partial: false,
+        degraded: Vec::new(),
     }
 }
 
@@ -365,12 +3248,21 @@ fn patch_cached_result(cache: &Arc<Mutex<AnalysisCache>>, workspace_path: &str,
     if let Ok(mut lock) = cache.lock() {
         if lock.workspace_path.as_deref() != Some(workspace_path) {
             lock.workspace_path = Some(workspace_path.to_string());
+            lock.history_head = None;
+            lock.component_ranges = None;
+            lock.commit_counts = None;
+            lock.weights_overridden = false;
+            lock.coverage = None;
             lock.result = Some(AnalysisResult {
                 workspace_score: file.composite_score,
                 file_count: 1,
-                high_debt_count: usize::from(file.composite_score > 65.0),
+                high_debt_count: usize::from(counts_as_high_debt(&file)),
                 files: vec![file.clone()],
                 duration_ms: 0,
+                errors: Vec::new(),
+                score_changes: Vec::new(),
+                partial: false,
+                degraded: Vec::new(),
             });
             lock.heatmap = lock
                 .result
@@ -385,6 +3277,10 @@ fn patch_cached_result(cache: &Arc<Mutex<AnalysisCache>>, workspace_path: &str,
             high_debt_count: 0,
             files: Vec::new(),
             duration_ms: 0,
+            errors: Vec::new(),
+            score_changes: Vec::new(),
+            partial: false,
+            degraded: Vec::new(),
         });
 
         if let Some(existing) = result
@@ -404,27 +3300,68 @@ fn patch_cached_result(cache: &Arc<Mutex<AnalysisCache>>, workspace_path: &str,
         } else {
             total / result.files.len() as f64
         };
-        result.high_debt_count = result.files.iter().filter(|f| f.composite_score > 65.0).count();
+        result.high_debt_count = result.files.iter().filter(|f| counts_as_high_debt(f)).count();
 
         lock.heatmap = Some(build_heatmap_tree(workspace_path, &result.files));
     }
 }
 
-fn update_cache(cache: &Arc<Mutex<AnalysisCache>>, workspace_path: String, result: AnalysisResult) {
+/// Error when the cached analysis is a quick scan, whose churn, knowledge and
+/// change coupling are zeros, or was scored with a weights override. Nothing
+/// may be persisted or derived from either.
+pub fn ensure_full_analysis(cache: &Arc<Mutex<AnalysisCache>>) -> Result<(), String> {
+    let lock = cache.lock().map_err(|_| "Cache lock error".to_string())?;
+    match &lock.result {
+        Some(result) if result.partial => {
+            Err("PARTIAL_ANALYSIS: the cached analysis is a quick scan. Run a full analysis first.".to_string())
+        }
+        Some(_) if lock.weights_overridden => Err(
+            "WEIGHTS_OVERRIDDEN: the cached analysis used a weights override. Run a full analysis first.".to_string(),
+        ),
+        _ => Ok(()),
+    }
+}
+
+/// Cache `result`; `inputs` are the analysis inputs it was scored from, or
+/// `None` for scores loaded from the database
+fn update_cache(
+    cache: &Arc<Mutex<AnalysisCache>>,
+    workspace_path: String,
+    result: AnalysisResult,
+    inputs: Option<&AnalysisInputs>,
+    component_ranges: Option<ComponentRanges>,
+    weights_overridden: bool,
+) {
     if let Ok(mut lock) = cache.lock() {
         lock.workspace_path = Some(workspace_path.clone());
         lock.heatmap = Some(build_heatmap_tree(&workspace_path, &result.files));
         lock.result = Some(result);
+        lock.history_head = inputs.and_then(|inputs| inputs.history_head.clone());
+        lock.component_ranges = component_ranges;
+        lock.commit_counts = inputs
+            .filter(|inputs| inputs.churn_available)
+            .map(|inputs| inputs.churn.clone());
+        lock.weights_overridden = weights_overridden;
+        lock.coverage = inputs.map(|inputs| inputs.coverage.summary());
     }
 }
 
+/// Commit id history analysis starts from (`analysisRef` or HEAD), if any
+fn history_head(workspace_path: &str, analysis_ref: Option<&str>) -> Option<String> {
+    let repo = git2::Repository::open(workspace_path).ok()?;
+    crate::commands::git::resolve_analysis_ref(&repo, analysis_ref)
+        .ok()
+        .flatten()
+        .map(|oid| oid.to_string())
+}
+
+/// Workspace-relative path with forward slashes, regardless of platform separators.
 fn to_relative_path(workspace_path: &str, file_path: &str) -> String {
     file_path
         .strip_prefix(workspace_path)
         .unwrap_or(file_path)
-        .trim_start_matches('/')
-        .trim_start_matches('\\')
-        .to_string()
+        .trim_start_matches(['/', '\\'])
+        .replace('\\', "/")
 }
 
 fn to_detail(name: &str, component: &ComponentScore) -> ComponentDetail {
@@ -434,20 +3371,24 @@ fn to_detail(name: &str, component: &ComponentScore) -> ComponentDetail {
         weight: component.weight,
         contribution: component.contribution,
         details: component.details.clone(),
+        confidence: component.confidence.clone(),
     }
 }
 
-fn compute_smell_score(smells: &crate::commands::ast::FileSmells, loc: usize) -> f64 {
+/// Smell density component (0–100): `smells / loc * scale`, capped at 100.
+/// With the default `smellDensityScale` of 5000, one smell per 50 lines
+/// scores 100.
+fn compute_smell_score(smells: &crate::commands::ast::FileSmells, loc: usize, scale: f64) -> f64 {
     if loc == 0 {
         return 0.0;
     }
-    (smells.total as f64 / loc as f64 * 5000.0).min(100.0)
+    (smells.total as f64 / loc as f64 * scale).min(100.0)
 }
 
 fn detect_language(path: &str) -> String {
     match std::path::Path::new(path).extension().and_then(|e| e.to_str()) {
         Some("ts") | Some("tsx") => "typescript".to_string(),
-        Some("js") | Some("jsx") => "javascript".to_string(),
+        Some("js") | Some("jsx") | Some("mjs") | Some("cjs") => "javascript".to_string(),
         Some("py") => "python".to_string(),
         Some("go") => "go".to_string(),
         Some("rs") => "rust".to_string(),
@@ -456,7 +3397,100 @@ fn detect_language(path: &str) -> String {
     }
 }
 
+/// Heatmap colored by a single component's raw score (or "composite"),
+/// with directories scored by the LOC-weighted mean of their files.
+#[tauri::command]
+pub async fn get_heatmap_by(
+    dimension: String,
+    cache: tauri::State<'_, Arc<Mutex<AnalysisCache>>>,
+) -> Result<HeatmapNode, String> {
+    get_heatmap_by_internal(cache.inner(), &dimension)
+}
+
+pub fn get_heatmap_by_internal(
+    cache: &Arc<Mutex<AnalysisCache>>,
+    dimension: &str,
+) -> Result<HeatmapNode, String> {
+    let cache_lock = cache.lock().map_err(|_| "Cache lock error".to_string())?;
+    let result = cache_lock
+        .result
+        .as_ref()
+        .ok_or("No analysis data available. Run analysis first.".to_string())?;
+    let workspace_path = cache_lock.workspace_path.clone().unwrap_or_default();
+    let mut heatmap = heatmap_for_dimension(&workspace_path, &result.files, dimension)?;
+    round_heatmap_scores(&mut heatmap, score_precision(cache_lock.workspace_path.as_deref()));
+    Ok(heatmap)
+}
+
+fn heatmap_for_dimension(
+    workspace_path: &str,
+    files: &[FileScore],
+    dimension: &str,
+) -> Result<HeatmapNode, String> {
+    let valid = dimension == "composite"
+        || ScoreComponents::default().named().iter().any(|(name, _)| *name == dimension);
+    if !valid {
+        return Err(format!("Unknown heatmap dimension: {dimension}"));
+    }
+
+    let rescored: Vec<FileScore> = files
+        .iter()
+        .map(|file| {
+            let mut file = file.clone();
+            if let Some((_, component)) = file
+                .components
+                .named()
+                .into_iter()
+                .find(|(name, _)| *name == dimension)
+            {
+                file.composite_score = component.raw_score;
+            }
+            file
+        })
+        .collect();
+
+    let mut root = build_heatmap_tree(workspace_path, &rescored);
+    aggregate_directory_scores(&mut root);
+    Ok(root)
+}
+
+/// Fill directory `score`/`loc` from their children; returns (score, loc).
+fn aggregate_directory_scores(node: &mut HeatmapNode) -> (f64, usize) {
+    let Some(children) = node.children.as_mut() else {
+        return (node.score.unwrap_or(0.0), node.loc.unwrap_or(0));
+    };
+
+    let mut weighted = 0.0;
+    let mut total_loc = 0;
+    let mut plain_sum = 0.0;
+    for child in children.iter_mut() {
+        let (score, loc) = aggregate_directory_scores(child);
+        weighted += score * loc as f64;
+        total_loc += loc;
+        plain_sum += score;
+    }
+
+    if !children.is_empty() {
+        let score = if total_loc > 0 {
+            weighted / total_loc as f64
+        } else {
+            plain_sum / children.len() as f64
+        };
+        node.score = Some(score);
+        node.loc = Some(total_loc);
+    }
+    (node.score.unwrap_or(0.0), total_loc)
+}
+
 fn build_heatmap_tree(workspace_path: &str, files: &[FileScore]) -> HeatmapNode {
+    let max_depth = crate::commands::settings::load_effective_analysis_settings(workspace_path)
+        .map_or(DEFAULT_MAX_HEATMAP_DEPTH, |settings| settings.max_heatmap_depth);
+    build_heatmap_tree_with_depth(workspace_path, files, max_depth)
+}
+
+/// Paths more than `max_depth` levels deep keep their first `max_depth - 1`
+/// directories; the rest of the path becomes a single leaf's name.
+fn build_heatmap_tree_with_depth(workspace_path: &str, files: &[FileScore], max_depth: usize) -> HeatmapNode {
     let root_name = std::path::Path::new(workspace_path)
         .file_name()
         .map(|n| n.to_string_lossy().to_string())
@@ -470,63 +3504,73 @@ fn build_heatmap_tree(workspace_path: &str, files: &[FileScore]) -> HeatmapNode
         children: Some(Vec::new()),
     };
 
+    let max_depth = max_depth.max(1);
     for file in files {
         let parts: Vec<&str> = file.relative_path.split('/').collect();
-        insert_into_tree(&mut root, &parts, file, String::new());
+        let leaf_start = (parts.len() - 1).min(max_depth - 1);
+        insert_into_tree(&mut root, &parts[..leaf_start], parts[leaf_start..].join("/"), file);
     }
 
     root
 }
 
-fn insert_into_tree(node: &mut HeatmapNode, parts: &[&str], file: &FileScore, prefix: String) {
-    if parts.is_empty() {
-        return;
-    }
+/// Walk (creating as needed) the directory nodes for `dirs`, then add the
+/// file leaf. Iterative so deeply nested paths cannot overflow the stack.
+fn insert_into_tree(root: &mut HeatmapNode, dirs: &[&str], leaf_name: String, file: &FileScore) {
+    let mut node = root;
+    let mut prefix = String::new();
+    for dir_name in dirs {
+        if !prefix.is_empty() {
+            prefix.push('/');
+        }
+        prefix.push_str(dir_name);
 
-    if parts.len() == 1 {
         let children = node.children.get_or_insert_with(Vec::new);
-        children.push(HeatmapNode {
-            name: parts[0].to_string(),
-            path: file.relative_path.clone(),
-            score: Some(file.composite_score),
-            loc: Some(file.loc),
-            children: None,
-        });
-        return;
-    }
-
-    let dir_name = parts[0];
-    let next_prefix = if prefix.is_empty() {
-        dir_name.to_string()
-    } else {
-        format!("{prefix}/{dir_name}")
-    };
-
-    let children = node.children.get_or_insert_with(Vec::new);
-
-    if let Some(existing) = children
-        .iter_mut()
-        .find(|child| child.name == dir_name && child.children.is_some())
-    {
-        insert_into_tree(existing, &parts[1..], file, next_prefix);
-        return;
+        let index = match children
+            .iter()
+            .position(|child| child.name == *dir_name && child.children.is_some())
+        {
+            Some(index) => index,
+            None => {
+                children.push(HeatmapNode {
+                    name: dir_name.to_string(),
+                    path: prefix.clone(),
+                    score: None,
+                    loc: None,
+                    children: Some(Vec::new()),
+                });
+                children.len() - 1
+            }
+        };
+        node = &mut children[index];
     }
 
-    let mut new_dir = HeatmapNode {
-        name: dir_name.to_string(),
-        path: next_prefix.clone(),
-        score: None,
-        loc: None,
-        children: Some(Vec::new()),
-    };
-    insert_into_tree(&mut new_dir, &parts[1..], file, next_prefix);
-    children.push(new_dir);
+    node.children.get_or_insert_with(Vec::new).push(HeatmapNode {
+        name: leaf_name,
+        path: file.relative_path.clone(),
+        score: Some(file.composite_score),
+        loc: Some(file.loc),
+        children: None,
+    });
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn weights_override_is_parsed_from_the_raw_env_value() {
+        assert_eq!(parse_weights_override(None), Ok(None));
+        assert_eq!(parse_weights_override(Some("  ")), Ok(None));
+        assert_eq!(
+            parse_weights_override(Some(r#"{"churn_rate": 30}"#)),
+            Ok(Some(serde_json::json!({ "churn_rate": 30 })))
+        );
+
+        let error = parse_weights_override(Some("{not json")).expect_err("invalid override");
+        assert!(error.starts_with("INVALID_WEIGHTS_OVERRIDE"));
+    }
+
     #[test]
     fn builds_valid_relative_path() {
         let relative = to_relative_path("/tmp/repo", "/tmp/repo/src/main.rs");
@@ -545,54 +3589,64 @@ mod tests {
                     weight: 0.0,
                     contribution: 0.0,
                     details: vec![],
+                    confidence: None,
                 },
                 code_smell_density: ComponentScore {
                     raw_score: 0.0,
                     weight: 0.0,
                     contribution: 0.0,
                     details: vec![],
+                    confidence: None,
                 },
                 coupling_index: ComponentScore {
                     raw_score: 0.0,
                     weight: 0.0,
                     contribution: 0.0,
                     details: vec![],
+                    confidence: None,
                 },
                 change_coupling: ComponentScore {
                     raw_score: 0.0,
                     weight: 0.0,
                     contribution: 0.0,
                     details: vec![],
+                    confidence: None,
                 },
                 test_coverage_gap: ComponentScore {
                     raw_score: 0.0,
                     weight: 0.0,
                     contribution: 0.0,
                     details: vec![],
+                    confidence: None,
                 },
                 knowledge_concentration: ComponentScore {
                     raw_score: 0.0,
                     weight: 0.0,
                     contribution: 0.0,
                     details: vec![],
+                    confidence: None,
                 },
                 cyclomatic_complexity: ComponentScore {
                     raw_score: 0.0,
                     weight: 0.0,
                     contribution: 0.0,
                     details: vec![],
+                    confidence: None,
                 },
                 decision_staleness: ComponentScore {
                     raw_score: 0.0,
                     weight: 0.0,
                     contribution: 0.0,
                     details: vec![],
+                    confidence: None,
                 },
             },
             loc: 1,
             language: "rust".to_string(),
             last_modified: 0,
             supervision_status: "none".to_string(),
+            functions: Vec::new(),
+            smell_counts: Default::default(),
         };
 
         let result = build_analysis_result(vec![file], 10);
@@ -612,11 +3666,715 @@ mod tests {
             magic_number: 0,
             empty_catch: 0,
             todo_fixme: 0,
+            ignored_error: 0,
             total: 500,
             loc: 10,
+            confidence: crate::commands::ast::HEURISTIC_CONFIDENCE.to_string(),
+        };
+
+        assert_eq!(compute_smell_score(&smells, 0, 5000.0), 0.0);
+        assert_eq!(compute_smell_score(&smells, 10, 5000.0), 100.0);
+    }
+
+    #[test]
+    fn breakdown_carries_analyzer_confidence() {
+        let (smell, complexity, _, _) = content_component_scores(
+            "src/a.rs",
+            "fn a() {\n    if true {}\n}\n",
+            "rust",
+            3,
+            500,
+            5000.0,
+            &crate::analysis::smells::default_magic_number_allowlist(),
+        );
+        let mut file = test_file("src/a.rs", 10.0);
+        file.components.code_smell_density = smell;
+        file.components.cyclomatic_complexity = complexity;
+
+        let breakdown = breakdown_for(&file);
+        let confidence = |name: &str| {
+            breakdown
+                .components
+                .iter()
+                .find(|c| c.name == name)
+                .and_then(|c| c.confidence.clone())
+        };
+        assert_eq!(confidence("code_smell_density").as_deref(), Some("heuristic"));
+        assert_eq!(confidence("cyclomatic_complexity").as_deref(), Some("heuristic"));
+        assert_eq!(confidence("churn_rate"), None);
+    }
+
+    #[test]
+    fn smell_score_scales_with_density_scale() {
+        let smells = crate::commands::ast::FileSmells {
+            god_function: 0,
+            deep_nesting: 0,
+            long_param_list: 0,
+            duplicate_block: 0,
+            dead_import: 0,
+            magic_number: 0,
+            empty_catch: 0,
+            todo_fixme: 1,
+            ignored_error: 0,
+            total: 1,
+            loc: 200,
+            confidence: crate::commands::ast::HEURISTIC_CONFIDENCE.to_string(),
+        };
+
+        let default_scale = compute_smell_score(&smells, 200, 5000.0);
+        let halved = compute_smell_score(&smells, 200, 2500.0);
+        assert!((default_scale - 25.0).abs() < 1e-9);
+        assert!((halved - default_scale / 2.0).abs() < 1e-9);
+    }
+
+    fn test_file(relative_path: &str, composite_score: f64) -> FileScore {
+        FileScore {
+            path: format!("/tmp/repo/{relative_path}"),
+            relative_path: relative_path.to_string(),
+            composite_score,
+            components: ScoreComponents::default(),
+            loc: 10,
+            language: "rust".to_string(),
+            last_modified: 0,
+            supervision_status: "none".to_string(),
+            functions: Vec::new(),
+            smell_counts: Default::default(),
+        }
+    }
+
+    #[test]
+    fn severity_counts_leave_out_accepted_and_new_files() {
+        let mut accepted = test_file("src/accepted.rs", 90.0);
+        accepted.supervision_status = "acceptable".to_string();
+        let mut new = test_file("src/new.rs", 90.0);
+        new.supervision_status = "new".to_string();
+        let files = [accepted, new, test_file("src/hot.rs", 90.0)];
+
+        let counts = count_severities(&files, 65.0, 80.0);
+
+        assert_eq!(counts.critical, 1);
+        assert_eq!(counts.high + counts.medium + counts.low, 0);
+    }
+
+    #[test]
+    fn severity_labels_match_severity_counts_at_the_thresholds() {
+        assert_eq!(severity_label(65.0, 65.0, 80.0), "medium");
+        assert_eq!(severity_label(65.5, 65.0, 80.0), "high");
+        assert_eq!(severity_label(80.0, 65.0, 80.0), "high");
+        assert_eq!(severity_label(80.5, 65.0, 80.0), "critical");
+
+        let files = [test_file("src/warning.rs", 65.0), test_file("src/critical.rs", 80.0)];
+        let counts = count_severities(&files, 65.0, 80.0);
+        assert_eq!((counts.critical, counts.high, counts.medium), (0, 1, 1));
+    }
+
+    #[test]
+    fn severity_counts_use_the_high_debt_comparison() {
+        let files = [test_file("src/at.rs", 65.0), test_file("src/above.rs", 65.5)];
+
+        let counts = count_severities(&files, 65.0, 80.0);
+
+        assert_eq!(counts.high, 1);
+        assert_eq!(counts.medium, 1);
+        assert_eq!(counts.high, files.iter().filter(|f| counts_as_high_debt(f)).count());
+    }
+
+    #[test]
+    fn windows_paths_are_normalized_and_nest_in_heatmap() {
+        let relative = to_relative_path("C:\\repo", "C:\\repo\\src\\commands\\db.rs");
+        assert_eq!(relative, "src/commands/db.rs");
+
+        let heatmap = build_heatmap_tree("C:\\repo", &[test_file(&relative, 40.0)]);
+        let src = &heatmap.children.as_ref().expect("root children")[0];
+        assert_eq!(src.name, "src");
+        let commands = &src.children.as_ref().expect("src children")[0];
+        assert_eq!(commands.name, "commands");
+        assert_eq!(commands.path, "src/commands");
+        let leaf = &commands.children.as_ref().expect("commands children")[0];
+        assert_eq!(leaf.name, "db.rs");
+        assert_eq!(leaf.score, Some(40.0));
+    }
+
+    #[test]
+    fn deep_paths_collapse_into_a_leaf_bucket() {
+        let relative = (0..50).map(|level| format!("d{level}")).collect::<Vec<_>>().join("/") + "/gen.rs";
+        let heatmap = build_heatmap_tree_with_depth("/tmp/repo", &[test_file(&relative, 40.0)], 4);
+
+        let mut node = &heatmap;
+        for level in 0..3 {
+            node = &node.children.as_ref().expect("directory children")[0];
+            assert_eq!(node.name, format!("d{level}"));
+        }
+        let leaf = &node.children.as_ref().expect("bucket parent children")[0];
+        assert!(leaf.children.is_none());
+        assert!(leaf.name.starts_with("d3/d4/"));
+        assert!(leaf.name.ends_with("d49/gen.rs"));
+        assert_eq!(leaf.path, relative);
+    }
+
+    #[test]
+    fn accepted_patterns_are_scored_but_not_counted_as_high_debt() {
+        let patterns = vec![glob::Pattern::new("legacy/**").expect("pattern")];
+        let files: Vec<FileScore> = ["legacy/old/parser.rs", "src/parser.rs"]
+            .iter()
+            .map(|path| FileScore {
+                supervision_status: supervision_status_for(path, &patterns),
+                ..test_file(path, 90.0)
+            })
+            .collect();
+
+        let result = build_analysis_result(files, 0);
+
+        assert_eq!(result.files[0].supervision_status, "acceptable");
+        assert_eq!(result.files[1].supervision_status, "none");
+        assert_eq!(result.file_count, 2);
+        assert_eq!(result.workspace_score, 90.0);
+        assert_eq!(result.high_debt_count, 1);
+    }
+
+    #[test]
+    fn files_first_seen_within_grace_period_are_not_high_debt() {
+        let now = 1_700_000_000;
+        let first_seen = std::collections::HashMap::from([
+            ("src/today.rs".to_string(), now),
+            ("src/old.rs".to_string(), now - 30 * 86_400),
+        ]);
+        let mut files = vec![
+            test_file("src/today.rs", 90.0),
+            test_file("src/old.rs", 90.0),
+            test_file("src/added.rs", 90.0),
+        ];
+        flag_new_files(&mut files, &first_seen, now, 7);
+
+        let result = build_analysis_result(files, 0);
+        let statuses: Vec<&str> = result.files.iter().map(|f| f.supervision_status.as_str()).collect();
+        assert_eq!(statuses, vec!["new", "none", "new"]);
+        assert_eq!(result.file_count, 3);
+        assert_eq!(result.high_debt_count, 1);
+
+        // Nothing persisted yet: the first analysis is the baseline.
+        let mut baseline = vec![test_file("src/today.rs", 90.0)];
+        flag_new_files(&mut baseline, &std::collections::HashMap::new(), now, 7);
+        assert_eq!(baseline[0].supervision_status, "none");
+    }
+
+    #[test]
+    fn risk_triage_requires_churn_coverage_gap_and_missing_adr() {
+        let file = |path: &str, churn: f64, coverage_gap: f64| {
+            let mut file = test_file(path, churn + coverage_gap);
+            file.components.churn_rate.raw_score = churn;
+            file.components.test_coverage_gap.raw_score = coverage_gap;
+            file
+        };
+        let files = vec![
+            file("src/risky.rs", 60.0, 80.0),
+            file("src/calm.rs", 10.0, 80.0),
+            file("src/tested.rs", 60.0, 30.0),
+            file("src/documented.rs", 60.0, 80.0),
+        ];
+
+        let triage = risk_triage(&files, 35.0, 65.0, |path| path == "src/documented.rs");
+
+        let paths: Vec<&str> = triage.iter().map(|e| e.relative_path.as_str()).collect();
+        assert_eq!(paths, vec!["src/risky.rs"]);
+        assert_eq!(triage[0].churn_score, 60.0);
+        assert_eq!(triage[0].coverage_gap_score, 80.0);
+    }
+
+    #[test]
+    fn raw_score_caps_bound_component_contributions() {
+        let mut components = ScoreComponents {
+            churn_rate: ComponentScore {
+                raw_score: 100.0,
+                weight: 0.22,
+                contribution: 22.0,
+                details: vec![],
+                confidence: None,
+            },
+            code_smell_density: ComponentScore {
+                raw_score: 30.0,
+                weight: 0.20,
+                contribution: 6.0,
+                details: vec![],
+                confidence: None,
+            },
+            ..Default::default()
+        };
+        let mut caps = default_raw_score_caps();
+        caps.insert("churn_rate".to_string(), 40.0);
+        caps.insert("code_smell_density".to_string(), 50.0);
+
+        apply_raw_score_caps(&mut components, &caps);
+
+        assert_eq!(components.churn_rate.raw_score, 40.0);
+        assert!((components.churn_rate.contribution - 40.0 * 0.22).abs() < 1e-9);
+        assert_eq!(components.churn_rate.details, vec!["capped at 40 (raw 100.0)".to_string()]);
+        assert_eq!(components.code_smell_density.raw_score, 30.0);
+        assert_eq!(components.code_smell_density.contribution, 6.0);
+    }
+
+    #[test]
+    fn explanation_names_top_contributing_components() {
+        let mut file = test_file("src/app.rs", 78.0);
+        file.components.churn_rate = ComponentScore {
+            raw_score: 90.0,
+            weight: 0.22,
+            contribution: 19.8,
+            details: vec!["42 commits in 90 days".to_string()],
+            confidence: None,
+        };
+        file.components.test_coverage_gap = ComponentScore {
+            raw_score: 80.0,
+            weight: 0.12,
+            contribution: 9.6,
+            details: vec![],
+            confidence: None,
+        };
+        file.components.decision_staleness = ComponentScore {
+            raw_score: 10.0,
+            weight: 0.03,
+            contribution: 0.3,
+            details: vec![],
+            confidence: None,
+        };
+
+        let explanation = explain_score(&file, 65.0, 80.0);
+
+        assert_eq!(
+            explanation,
+            "This file scores 78 (high). The main drivers are high churn (42 commits in 90 days), \
+             low test coverage and some decision record staleness."
+        );
+        assert_eq!(explanation, explain_score(&file, 65.0, 80.0));
+        assert!(explain_score(&test_file("src/empty.rs", 0.0), 65.0, 80.0)
+            .ends_with("No component contributes to its debt."));
+    }
+
+    #[test]
+    fn hotspots_rank_churn_and_complexity_together() {
+        let file = |path: &str, churn: f64, complexity: f64| {
+            let mut file = test_file(path, 50.0);
+            file.components.churn_rate.raw_score = churn;
+            file.components.cyclomatic_complexity.raw_score = complexity;
+            file
+        };
+        let files = vec![
+            file("src/churny.rs", 95.0, 10.0),
+            file("src/both.rs", 70.0, 70.0),
+            file("src/complex.rs", 5.0, 100.0),
+        ];
+
+        let ranked = rank_hotspots(&files);
+
+        let order: Vec<&str> = ranked.iter().map(|h| h.relative_path.as_str()).collect();
+        assert_eq!(order, vec!["src/both.rs", "src/churny.rs", "src/complex.rs"]);
+        assert!((ranked[0].hotspot_score - 49.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn score_matrix_has_one_row_per_file_and_column_per_component() {
+        let mut churny = test_file("src/churny.rs", 40.0);
+        churny.components.churn_rate.raw_score = 90.0;
+        let files = vec![churny, test_file("src/quiet.rs", 5.0)];
+
+        let matrix = score_matrix(&files);
+
+        assert_eq!(matrix.files, vec!["src/churny.rs".to_string(), "src/quiet.rs".to_string()]);
+        assert_eq!(matrix.components.len(), 8);
+        assert_eq!(matrix.raw_scores.len(), files.len());
+        assert!(matrix.raw_scores.iter().all(|row| row.len() == 8));
+        let churn = matrix.components.iter().position(|name| name == "churn_rate").expect("churn column");
+        assert_eq!(matrix.raw_scores[0][churn], 90.0);
+    }
+
+    #[test]
+    fn perfectly_correlated_components_correlate_to_one() {
+        let files: Vec<FileScore> = [10.0, 40.0, 70.0]
+            .iter()
+            .enumerate()
+            .map(|(i, &churn)| {
+                let mut file = test_file(&format!("src/{i}.rs"), 50.0);
+                file.components.churn_rate.raw_score = churn;
+                file.components.cyclomatic_complexity.raw_score = churn * 0.5 + 5.0;
+                file.components.test_coverage_gap.raw_score = 100.0 - churn;
+                file
+            })
+            .collect();
+
+        let result = component_correlations(&files);
+        let index = |name: &str| result.components.iter().position(|c| c == name).expect(name);
+        let (churn, complexity, gap, staleness) = (
+            index("churn_rate"),
+            index("cyclomatic_complexity"),
+            index("test_coverage_gap"),
+            index("decision_staleness"),
+        );
+
+        assert_eq!(result.correlations.len(), 8);
+        assert!((result.correlations[churn][complexity] - 1.0).abs() < 1e-9);
+        assert!((result.correlations[complexity][churn] - 1.0).abs() < 1e-9);
+        assert!((result.correlations[churn][gap] + 1.0).abs() < 1e-9);
+        // Every file has the same (zero) staleness.
+        assert_eq!(result.correlations[churn][staleness], 0.0);
+        assert_eq!(result.correlations[staleness][staleness], 0.0);
+    }
+
+    #[test]
+    fn largest_files_are_ordered_by_descending_loc() {
+        let file = |path: &str, loc: usize| FileScore {
+            loc,
+            ..test_file(path, 10.0)
+        };
+        let files = vec![file("src/small.rs", 20), file("src/huge.rs", 900), file("src/mid.rs", 300)];
+
+        let largest = largest_files(&files, 2);
+
+        let order: Vec<(&str, usize)> = largest.iter().map(|f| (f.relative_path.as_str(), f.loc)).collect();
+        assert_eq!(order, vec![("src/huge.rs", 900), ("src/mid.rs", 300)]);
+        assert_eq!(largest_files(&files, 10).len(), 3);
+    }
+
+    #[test]
+    fn change_risk_ranks_untested_churn_above_tested_churn() {
+        let file = |path: &str, churn: f64, coverage_gap: f64| {
+            let mut file = test_file(path, 50.0);
+            file.components.churn_rate.raw_score = churn;
+            file.components.test_coverage_gap.raw_score = coverage_gap;
+            file
+        };
+        let files = vec![
+            file("src/tested.rs", 90.0, 10.0),
+            file("src/untested.rs", 90.0, 100.0),
+            file("src/quiet.rs", 5.0, 100.0),
+        ];
+
+        let ranked = rank_change_risk(&files);
+
+        let order: Vec<&str> = ranked.iter().map(|r| r.relative_path.as_str()).collect();
+        assert_eq!(order, vec!["src/untested.rs", "src/tested.rs", "src/quiet.rs"]);
+        assert!((ranked[0].risk_score - 90.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn remediation_estimate_includes_register_and_unregistered_hotspots() {
+        let register_item = |file_path: Option<&str>, hours: f64, status: &str| {
+            crate::models::register::RegisterItem {
+                id: format!("{file_path:?}-{status}"),
+                created_at: 0,
+                updated_at: 0,
+                title: "Debt".to_string(),
+                description: String::new(),
+                file_path: file_path.map(str::to_string),
+                severity: "high".to_string(),
+                item_type: "code".to_string(),
+                owner: None,
+                target_sprint: None,
+                estimated_hours: Some(hours),
+                actual_hours: None,
+                status: status.to_string(),
+                tags: vec![],
+                linked_commit: None,
+                notes: None,
+            }
+        };
+
+        let mut unregistered = test_file("src/hot.rs", 85.0);
+        unregistered.loc = 500;
+        let files = vec![unregistered, test_file("src/tracked.rs", 90.0), test_file("src/ok.rs", 20.0)];
+        let items = vec![
+            register_item(Some("src/tracked.rs"), 6.0, "open"),
+            register_item(None, 4.0, "in_progress"),
+            register_item(None, 50.0, "resolved"),
+        ];
+
+        let estimate = compute_remediation_estimate(&files, &items);
+        assert_eq!(estimate.register_hours, 10.0);
+        assert_eq!(estimate.register_item_count, 2);
+        assert_eq!(estimate.hotspots.len(), 1);
+        assert_eq!(estimate.hotspots[0].relative_path, "src/hot.rs");
+        assert!((estimate.hotspot_hours - 10.0).abs() < 1e-9);
+        assert!((estimate.total_hours - 20.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn composite_formulas_weigh_a_single_extreme_component_differently() {
+        let weights = default_weights();
+        let component = |key: &str, raw_score: f64| ComponentScore {
+            raw_score,
+            weight: weights[key],
+            contribution: raw_score * weights[key],
+            details: vec![],
+            confidence: None,
         };
+        let components = ScoreComponents {
+            churn_rate: component("churn_rate", 100.0),
+            code_smell_density: component("code_smell_density", 0.0),
+            coupling_index: component("coupling_index", 0.0),
+            change_coupling: component("change_coupling", 0.0),
+            test_coverage_gap: component("test_coverage_gap", 0.0),
+            knowledge_concentration: component("knowledge_concentration", 0.0),
+            cyclomatic_complexity: component("cyclomatic_complexity", 0.0),
+            decision_staleness: component("decision_staleness", 0.0),
+        };
+
+        let sum = composite_score(&components, &CompositeFormula::WeightedSum);
+        let geomean = composite_score(&components, &CompositeFormula::WeightedGeomean);
+        let max_plus = composite_score(&components, &CompositeFormula::MaxPlus);
+
+        assert!((sum - 22.0).abs() < 1e-9, "weighted sum was {sum}");
+        assert!(geomean > sum && geomean < max_plus, "geomean was {geomean}");
+        assert!((max_plus - 100.0).abs() < 1e-9, "max_plus was {max_plus}");
+    }
+
+    #[test]
+    fn custom_formula_composite_equals_expression_value() {
+        let component = |raw_score: f64| ComponentScore {
+            raw_score,
+            weight: 0.0,
+            contribution: 0.0,
+            details: vec![],
+            confidence: None,
+        };
+        let components = ScoreComponents {
+            churn_rate: component(30.0),
+            code_smell_density: component(10.0),
+            coupling_index: component(0.0),
+            change_coupling: component(0.0),
+            test_coverage_gap: component(25.0),
+            knowledge_concentration: component(0.0),
+            cyclomatic_complexity: component(0.0),
+            decision_staleness: component(0.0),
+        };
+
+        let formula = CompositeFormula::from_setting("custom", "churn_rate + coverage_gap");
+        assert!(matches!(formula, CompositeFormula::Custom(_)));
+        assert!((composite_score(&components, &formula) - 55.0).abs() < 1e-9);
+        assert_eq!(
+            CompositeFormula::from_setting("custom", "churn_rate + bogus"),
+            CompositeFormula::WeightedSum
+        );
+    }
+
+    #[test]
+    fn churn_heatmap_uses_component_raw_scores() {
+        let mut lib = test_file("src/lib.rs", 50.0);
+        lib.components.churn_rate.raw_score = 40.0;
+        let mut main = test_file("src/main.rs", 70.0);
+        main.components.churn_rate.raw_score = 90.0;
+        let files = vec![lib, main];
+
+        let root = heatmap_for_dimension("/tmp/repo", &files, "churn_rate").expect("churn heatmap");
+        let src = &root.children.as_ref().expect("root children")[0];
+        let leaves = src.children.as_ref().expect("src children");
+        for leaf in leaves {
+            let file = files.iter().find(|f| f.relative_path == leaf.path).expect("leaf file");
+            assert_eq!(leaf.score, Some(file.components.churn_rate.raw_score));
+        }
+        assert_eq!(src.score, Some(65.0));
+
+        assert!(heatmap_for_dimension("/tmp/repo", &files, "vibes").is_err());
+    }
+
+    #[test]
+    fn language_summary_aggregates_each_language() {
+        let python = |path: &str, score: f64, loc: usize| FileScore {
+            language: "python".to_string(),
+            loc,
+            ..test_file(path, score)
+        };
+        let files = vec![
+            test_file("src/a.rs", 80.0),
+            test_file("src/b.rs", 40.0),
+            test_file("src/c.rs", 30.0),
+            python("tools/x.py", 70.0, 25),
+            python("tools/y.py", 20.0, 15),
+            FileScore { language: String::new(), ..test_file("Makefile", 10.0) },
+        ];
+
+        let summaries = summarize_languages(&files);
+        let languages: Vec<&str> = summaries.iter().map(|s| s.language.as_str()).collect();
+        assert_eq!(languages, vec!["rust", "python", "unknown"]);
+
+        let rust = &summaries[0];
+        assert_eq!((rust.file_count, rust.total_loc, rust.high_debt_count), (3, 30, 1));
+        assert!((rust.average_composite_score - 50.0).abs() < 1e-9);
+
+        let python = &summaries[1];
+        assert_eq!((python.file_count, python.total_loc, python.high_debt_count), (2, 40, 1));
+        assert!((python.average_composite_score - 45.0).abs() < 1e-9);
+
+        let unknown = &summaries[2];
+        assert_eq!((unknown.file_count, unknown.total_loc, unknown.high_debt_count), (1, 10, 0));
+        assert!((unknown.average_composite_score - 10.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn gate_fails_with_reason_when_workspace_score_exceeds_limit() {
+        let result = build_analysis_result(vec![test_file("src/a.rs", 70.0), test_file("src/b.rs", 50.0)], 10);
+
+        let failed = evaluate_gate(&result, Some(55.0), Some(1), &[]);
+        assert!(!failed.passed);
+        assert_eq!(failed.reasons, vec!["workspace score 60.0 exceeds limit 55.0".to_string()]);
+
+        let budget = crate::models::budget::DebtBudget {
+            id: "b1".to_string(),
+            pattern: "src/**".to_string(),
+            label: "Core".to_string(),
+            max_score: 65.0,
+            created_at: 0,
+            notify_on_breach: false,
+            warning_score: None,
+        };
+        let over_budget = evaluate_gate(&result, Some(60.0), None, std::slice::from_ref(&budget));
+        assert!(!over_budget.passed);
+        assert_eq!(over_budget.reasons.len(), 1);
+        assert!(over_budget.reasons[0].contains("budget 'Core'"));
+
+        assert!(evaluate_gate(&result, Some(60.0), Some(1), &[]).passed);
+    }
+
+    #[test]
+    fn budget_breaches_list_only_files_over_the_limit() {
+        let files = vec![
+            test_file("src/a.rs", 75.0),
+            test_file("src/b.rs", 40.0),
+            test_file("src/c.rs", 90.0),
+            test_file("tests/d.rs", 95.0),
+        ];
+        let budget = |id: &str, max_score: f64| crate::models::budget::DebtBudget {
+            id: id.to_string(),
+            pattern: "src/**".to_string(),
+            label: id.to_string(),
+            max_score,
+            created_at: 0,
+            notify_on_breach: false,
+            warning_score: None,
+        };
+
+        let breaches = budget_breaches(&files, &[budget("core", 60.0), budget("loose", 99.0)]);
+        assert_eq!(breaches.len(), 1);
+        assert_eq!(breaches[0].budget.id, "core");
+        let paths: Vec<&str> = breaches[0].files.iter().map(|f| f.relative_path.as_str()).collect();
+        assert_eq!(paths, vec!["src/c.rs", "src/a.rs"]);
+
+        // Files in their new-file grace period don't breach yet
+        let new_file = FileScore {
+            supervision_status: "new".to_string(),
+            ..test_file("src/e.rs", 95.0)
+        };
+        assert!(!any_budget_breached(std::slice::from_ref(&new_file), &[budget("core", 60.0)]));
+        let breaches = budget_breaches(&[files[2].clone(), new_file], &[budget("core", 60.0)]);
+        let paths: Vec<&str> = breaches[0].files.iter().map(|f| f.relative_path.as_str()).collect();
+        assert_eq!(paths, vec!["src/c.rs"]);
+    }
+
+    #[test]
+    fn score_distribution_counts_files_per_bucket() {
+        let files = vec![
+            test_file("a.rs", 0.0),
+            test_file("b.rs", 9.9),
+            test_file("c.rs", 10.0),
+            test_file("d.rs", 55.0),
+            test_file("e.rs", 100.0),
+        ];
+
+        let buckets = compute_score_distribution(&files, 10).expect("distribution");
+        let counts: Vec<usize> = buckets.iter().map(|b| b.count).collect();
+        assert_eq!(counts, vec![2, 1, 0, 0, 0, 1, 0, 0, 0, 1]);
+        assert_eq!((buckets[5].min, buckets[5].max), (50.0, 60.0));
+
+        let empty = compute_score_distribution(&[], 25).expect("empty distribution");
+        assert_eq!(empty.len(), 4);
+        assert!(empty.iter().all(|b| b.count == 0));
+
+        assert!(compute_score_distribution(&files, 0).is_err());
+        assert!(compute_score_distribution(&files, 30).is_err());
+    }
+
+    #[test]
+    fn relative_normalization_lifts_worst_file_to_100() {
+        let mut files: Vec<FileScore> = [5.0, 10.0, 20.0]
+            .into_iter()
+            .enumerate()
+            .map(|(i, churn)| {
+                let mut file = test_file(&format!("src/f{i}.rs"), 0.0);
+                file.components.churn_rate = ComponentScore {
+                    raw_score: churn,
+                    weight: 0.22,
+                    contribution: churn * 0.22,
+                    details: vec![],
+                    confidence: None,
+                };
+                file
+            })
+            .collect();
+
+        normalize_components_relative(&mut files, &CompositeFormula::WeightedSum, &Default::default(), 1.0);
+
+        let worst = &files[2].components.churn_rate;
+        assert!((worst.raw_score - 100.0).abs() < 1e-9);
+        assert!((files[2].composite_score - 22.0).abs() < 1e-9);
+        assert_eq!(files[0].components.churn_rate.raw_score, 0.0);
+        assert_eq!(files[1].components.code_smell_density.raw_score, 0.0);
+    }
+
+    #[test]
+    fn half_blend_lands_between_absolute_and_relative() {
+        let mut files: Vec<FileScore> = [20.0, 60.0]
+            .into_iter()
+            .enumerate()
+            .map(|(i, churn)| {
+                let mut file = test_file(&format!("src/f{i}.rs"), 0.0);
+                file.components.churn_rate.raw_score = churn;
+                file.components.churn_rate.weight = 0.22;
+                file
+            })
+            .collect();
+
+        normalize_components_relative(&mut files, &CompositeFormula::WeightedSum, &Default::default(), 0.5);
+
+        // Absolute 20 vs relative 0, and absolute 60 vs relative 100.
+        assert!((files[0].components.churn_rate.raw_score - 10.0).abs() < 1e-9);
+        assert!((files[1].components.churn_rate.raw_score - 80.0).abs() < 1e-9);
+        assert!((files[1].composite_score - 80.0 * 0.22).abs() < 1e-9);
+    }
+
+    #[test]
+    fn raw_score_caps_still_apply_after_normalization() {
+        let mut files: Vec<FileScore> = [10.0, 40.0]
+            .into_iter()
+            .enumerate()
+            .map(|(i, churn)| {
+                let mut file = test_file(&format!("src/f{i}.rs"), 0.0);
+                file.components.churn_rate.raw_score = churn;
+                file.components.churn_rate.weight = 0.22;
+                file
+            })
+            .collect();
+        let caps = [("churn_rate".to_string(), 40.0)].into_iter().collect();
+
+        normalize_components_relative(&mut files, &CompositeFormula::WeightedSum, &caps, 1.0);
+
+        let churn = &files[1].components.churn_rate;
+        assert_eq!(churn.raw_score, 40.0);
+        assert!((churn.contribution - 40.0 * 0.22).abs() < 1e-9);
+        assert!((files[1].composite_score - 40.0 * 0.22).abs() < 1e-9);
+    }
+
+    #[test]
+    fn single_file_is_rescaled_against_cached_ranges() {
+        let mut ranges: ComponentRanges = [(0.0, 0.0); 8];
+        ranges[0] = (10.0, 50.0);
+        let mut file = test_file("src/a.rs", 0.0);
+        file.components.churn_rate.raw_score = 30.0;
+        file.components.churn_rate.weight = 0.22;
+
+        normalize_file(&mut file, &ranges, &CompositeFormula::WeightedSum, &Default::default(), 1.0);
+        assert!((file.components.churn_rate.raw_score - 50.0).abs() < 1e-9);
 
-        assert_eq!(compute_smell_score(&smells, 0), 0.0);
-        assert_eq!(compute_smell_score(&smells, 10), 100.0);
+        // A file beyond the cached maximum saturates instead of exceeding 100.
+        file.components.churn_rate.raw_score = 90.0;
+        normalize_file(&mut file, &ranges, &CompositeFormula::WeightedSum, &Default::default(), 1.0);
+        assert!((file.components.churn_rate.raw_score - 100.0).abs() < 1e-9);
     }
 }
@@ -1,15 +1,122 @@
-use crate::models::file_score::default_weights;
+use crate::analysis::staleness::DEFAULT_ADR_STALE_DAYS;
+use crate::models::file_score::{
+    default_raw_score_caps, default_weights, ComponentNormalization, CompositeFormula, DEFAULT_MAX_HEATMAP_DEPTH,
+    DEFAULT_SCORE_PRECISION,
+};
+use crate::models::workspace::WorkspaceIssue;
 use serde_json::{json, Map, Value};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
 
-const SETTINGS_SCHEMA_VERSION: i64 = 2;
+pub const SETTINGS_SCHEMA_VERSION: i64 = 2;
+
+/// Generated, vendored and build-output directories never walked for source
+pub const DEFAULT_SKIP_DIRECTORIES: [&str; 11] = [
+    "node_modules",
+    "target",
+    "__pycache__",
+    "vendor",
+    "dist",
+    "build",
+    ".next",
+    "coverage",
+    "out",
+    "bin",
+    "obj",
+];
+
+/// Defaults that are build output or dependencies wherever they appear, e.g.
+/// in every package of a monorepo. Other entries, such as `bin` (Rust keeps
+/// sources in `src/bin`), only match at the workspace root.
+const NESTED_SKIP_DIRECTORIES: [&str; 8] = [
+    "node_modules",
+    "target",
+    "__pycache__",
+    "vendor",
+    "dist",
+    "build",
+    ".next",
+    "coverage",
+];
+
+/// `skipDirectories` resolved once for a walk (see `load_skip_directories`)
+#[derive(Debug, Clone, Default)]
+pub struct SkipDirectories {
+    /// Matched by directory name at any depth
+    anywhere: HashSet<String>,
+    /// Matched against the workspace-relative directory path
+    rooted: HashSet<String>,
+}
+
+impl SkipDirectories {
+    /// Whether the directory at `relative_path` (forward slashes) is skipped
+    pub fn matches(&self, relative_path: &str) -> bool {
+        let name = relative_path.rsplit('/').next().unwrap_or(relative_path);
+        self.anywhere.contains(name) || self.rooted.contains(relative_path)
+    }
+}
 
 #[derive(Debug, Clone)]
 pub struct EffectiveAnalysisSettings {
     pub history_days: u32,
+    /// Window for co-change coupling; falls back to `history_days`
+    pub co_change_history_days: u32,
+    /// Commits touching more files than this generate no co-change pairs
+    pub max_files_per_commit_for_coupling: usize,
     pub weights: HashMap<String, f64>,
+    /// LOC above which the cyclomatic component gets a size penalty (0 = off)
+    pub file_length_threshold: usize,
+    /// Multiplier turning smells per line into the 0–100 smell density score
+    pub smell_density_scale: f64,
+    pub composite_formula: CompositeFormula,
+    pub component_normalization: ComponentNormalization,
+    /// Share of each raw score taken from its workspace-relative value
+    /// (0 = absolute, 1 = relative); always 1 under relative normalization
+    pub relative_blend: f64,
+    /// Score only files in the git index, skipping untracked files
+    pub tracked_files_only: bool,
+    /// Ref history analysis walks from; `None` means HEAD
+    pub analysis_ref: Option<String>,
+    /// Worker threads used to score files; 1 scores sequentially
+    pub max_threads: usize,
+    /// Score `.d.ts` declaration files along with code
+    pub include_type_declarations: bool,
+    /// Score protobuf stubs and other generated code (`GENERATED_CODE_SUFFIXES`)
+    pub include_generated_code: bool,
+    /// Globs whose files are marked `acceptable` and left out of high-debt counts
+    pub accepted_patterns: Vec<String>,
+    /// When non-empty, only files matching one of these globs are scored
+    pub include_patterns: Vec<String>,
+    /// Files matching one of these globs are not scored, even when included
+    pub exclude_patterns: Vec<String>,
+    /// Only score these languages (`rust`, `typescript`, ...); empty = all
+    pub include_languages: Vec<String>,
+    /// Never score these languages
+    pub exclude_languages: Vec<String>,
+    /// Ceiling applied to each component's raw score before weighting
+    pub raw_score_caps: HashMap<String, f64>,
+    /// Also score `.md`/`.rst`/`.txt` files on churn and staleness
+    pub track_docs: bool,
+    /// Complexity score at which an untouched file counts as frozen (0 = off)
+    pub frozen_complexity_threshold: f64,
+    /// Staleness raw score given to frozen complex files
+    pub frozen_complexity_penalty: f64,
+    /// Decimal places scores are rounded to in responses (not in storage)
+    pub score_precision: u32,
+    /// Heatmap nesting limit; deeper paths collapse into one leaf
+    pub max_heatmap_depth: usize,
+    /// Review age in days after which an ADR is reported as stale
+    pub adr_stale_days: i64,
+    /// Days after first being scored during which a file is flagged `new`
+    /// and left out of high-debt counts; 0 disables the grace period
+    pub new_file_grace_days: u32,
+    /// Composite score above which a file is high severity
+    pub warning_threshold: f64,
+    /// Composite score above which a file is critical
+    pub critical_threshold: f64,
+    /// Numbers per language that are never magic, on top of the global allowlist
+    pub magic_number_allowlist: HashMap<String, Vec<f64>>,
 }
 
 #[tauri::command]
@@ -22,13 +129,187 @@ pub async fn save_settings(workspace_path: String, settings: Value) -> Result<Va
     save_settings_to_disk(&workspace_path, settings)
 }
 
+/// Settings that differ from the defaults, keyed by dotted path
+/// (e.g. `weights.churn_rate`) and mapped to their current value.
+#[tauri::command]
+pub async fn get_settings_overrides(workspace_path: String) -> Result<Value, String> {
+    let current = load_settings_from_disk(&workspace_path)?;
+    Ok(Value::Object(settings_overrides(&current, &migrate_settings(default_settings()))))
+}
+
 pub fn load_effective_analysis_settings(workspace_path: &str) -> Result<EffectiveAnalysisSettings, String> {
-    let settings = load_settings_from_disk(workspace_path)?;
+    load_effective_analysis_settings_with_weights(workspace_path, None)
+}
+
+/// Effective settings with `weights_override` standing in for the saved
+/// weights. The override is applied in memory only and normalized like the
+/// saved weights; nothing is written back to disk.
+pub fn load_effective_analysis_settings_with_weights(
+    workspace_path: &str,
+    weights_override: Option<&Value>,
+) -> Result<EffectiveAnalysisSettings, String> {
+    let mut settings = load_settings_from_disk(workspace_path)?;
+    if let Some(weights) = weights_override {
+        if !weights.is_object() {
+            return Err("INVALID_WEIGHTS_OVERRIDE: expected an object of component weights".to_string());
+        }
+        // Same treatment as saved weights: percentages become fractions,
+        // then they are clamped and normalized.
+        settings["weights"] = weights.clone();
+        migrate_weights_from_percentages(&mut settings);
+        sanitize_settings(&mut settings);
+    }
     let history_days = settings
         .get("gitHistoryDays")
         .and_then(Value::as_u64)
         .unwrap_or(90)
         .clamp(7, 365) as u32;
+    let co_change_history_days = settings
+        .get("coChangeHistoryDays")
+        .and_then(Value::as_u64)
+        .map(|days| days.clamp(7, 730) as u32)
+        .unwrap_or(history_days);
+    let max_files_per_commit_for_coupling = settings
+        .get("maxFilesPerCommitForCoupling")
+        .and_then(Value::as_u64)
+        .map(|max| max.clamp(2, 10_000) as usize)
+        .unwrap_or(crate::analysis::coupling::DEFAULT_MAX_FILES_PER_COMMIT);
+    let file_length_threshold = settings
+        .get("fileLengthThreshold")
+        .and_then(Value::as_u64)
+        .unwrap_or(0)
+        .min(10_000) as usize;
+
+    let smell_density_scale = settings
+        .get("smellDensityScale")
+        .and_then(Value::as_f64)
+        .unwrap_or(5000.0)
+        .clamp(1.0, 100_000.0);
+    let score_precision = settings
+        .get("scorePrecision")
+        .and_then(Value::as_u64)
+        .unwrap_or(DEFAULT_SCORE_PRECISION as u64)
+        .min(6) as u32;
+    let max_heatmap_depth = settings
+        .get("maxHeatmapDepth")
+        .and_then(Value::as_u64)
+        .map(|depth| depth.clamp(1, 256) as usize)
+        .unwrap_or(DEFAULT_MAX_HEATMAP_DEPTH);
+    let adr_stale_days = settings
+        .get("adrStaleDays")
+        .and_then(Value::as_u64)
+        .map(|days| days.clamp(30, 3650) as i64)
+        .unwrap_or(DEFAULT_ADR_STALE_DAYS);
+    let new_file_grace_days = settings
+        .get("newFileGraceDays")
+        .and_then(Value::as_u64)
+        .map(|days| days.min(365) as u32)
+        .unwrap_or(0);
+    let warning_threshold = settings
+        .get("warningThreshold")
+        .and_then(Value::as_f64)
+        .unwrap_or(65.0)
+        .clamp(30.0, 90.0);
+    let critical_threshold = settings
+        .get("criticalThreshold")
+        .and_then(Value::as_f64)
+        .unwrap_or(80.0)
+        .clamp(50.0, 100.0);
+
+    let composite_formula = CompositeFormula::from_setting(
+        settings
+            .get("compositeFormula")
+            .and_then(Value::as_str)
+            .unwrap_or("weighted_sum"),
+        settings.get("customFormula").and_then(Value::as_str).unwrap_or(""),
+    );
+
+    let component_normalization = ComponentNormalization::from_setting(
+        settings
+            .get("componentNormalization")
+            .and_then(Value::as_str)
+            .unwrap_or("absolute"),
+    );
+    let relative_blend = match component_normalization {
+        ComponentNormalization::Relative => 1.0,
+        ComponentNormalization::Absolute => settings
+            .get("relativeBlend")
+            .and_then(Value::as_f64)
+            .unwrap_or(0.0)
+            .clamp(0.0, 1.0),
+    };
+    let tracked_files_only = settings
+        .get("trackedFilesOnly")
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
+
+    let analysis_ref = settings
+        .get("analysisRef")
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .filter(|r| !r.is_empty())
+        .map(str::to_string);
+    let track_docs = settings
+        .get("trackDocs")
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
+    let frozen_complexity_threshold = settings
+        .get("frozenComplexityThreshold")
+        .and_then(Value::as_f64)
+        .unwrap_or(0.0)
+        .clamp(0.0, 100.0);
+    let frozen_complexity_penalty = settings
+        .get("frozenComplexityPenalty")
+        .and_then(Value::as_f64)
+        .unwrap_or(75.0)
+        .clamp(0.0, 100.0);
+    let include_type_declarations = settings
+        .get("includeTypeDeclarations")
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
+    let include_generated_code = settings
+        .get("includeGeneratedCode")
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
+
+    let accepted_patterns = string_list(&settings, "acceptedPatterns");
+    let include_patterns = string_list(&settings, "includePatterns");
+    let exclude_patterns = string_list(&settings, "excludePatterns");
+    let magic_number_allowlist = settings
+        .get("magicNumberAllowlist")
+        .and_then(Value::as_object)
+        .map(|languages| {
+            languages
+                .iter()
+                .map(|(language, numbers)| {
+                    let numbers = numbers
+                        .as_array()
+                        .map(|numbers| numbers.iter().filter_map(Value::as_f64).collect())
+                        .unwrap_or_default();
+                    (language.to_lowercase(), numbers)
+                })
+                .collect()
+        })
+        .unwrap_or_else(crate::analysis::smells::default_magic_number_allowlist);
+    let lowercase = |values: Vec<String>| -> Vec<String> { values.iter().map(|v| v.to_lowercase()).collect() };
+    let include_languages = lowercase(string_list(&settings, "includeLanguages"));
+    let exclude_languages = lowercase(string_list(&settings, "excludeLanguages"));
+
+    // null means one thread per available core.
+    let max_threads = settings
+        .get("maxThreads")
+        .and_then(Value::as_u64)
+        .map(|threads| threads.clamp(1, 256) as usize)
+        .unwrap_or_else(|| std::thread::available_parallelism().map_or(1, |n| n.get()));
+
+    let mut raw_score_caps = default_raw_score_caps();
+    if let Some(obj) = settings.get("rawScoreCap").and_then(Value::as_object) {
+        for (key, value) in obj {
+            if let Some(cap) = value.as_f64() {
+                raw_score_caps.insert(key.clone(), cap.clamp(0.0, 100.0));
+            }
+        }
+    }
 
     let mut weights = default_weights();
     if let Some(obj) = settings.get("weights").and_then(Value::as_object) {
@@ -39,19 +320,57 @@ pub fn load_effective_analysis_settings(workspace_path: &str) -> Result<Effectiv
         }
     }
 
-    // Defensive normalization to avoid malformed saved state.
+    // Defensive normalization to avoid malformed saved state. With
+    // `normalizeWeights: false` the saved weights are used as-is, so a sum
+    // below 1.0 caps composites below 100 and thresholds should be scaled down.
+    let normalize_weights = settings
+        .get("normalizeWeights")
+        .and_then(Value::as_bool)
+        .unwrap_or(true);
     let sum: f64 = weights.values().copied().sum();
-    if sum > f64::EPSILON {
+    if sum <= f64::EPSILON {
+        weights = default_weights();
+    } else if normalize_weights {
         for value in weights.values_mut() {
             *value = (*value / sum).clamp(0.0, 1.0);
         }
     } else {
-        weights = default_weights();
+        for value in weights.values_mut() {
+            *value = value.clamp(0.0, 1.0);
+        }
     }
 
     Ok(EffectiveAnalysisSettings {
         history_days,
+        co_change_history_days,
+        max_files_per_commit_for_coupling,
         weights,
+        file_length_threshold,
+        smell_density_scale,
+        composite_formula,
+        component_normalization,
+        relative_blend,
+        tracked_files_only,
+        analysis_ref,
+        max_threads,
+        include_type_declarations,
+        include_generated_code,
+        accepted_patterns,
+        include_patterns,
+        exclude_patterns,
+        include_languages,
+        exclude_languages,
+        raw_score_caps,
+        track_docs,
+        frozen_complexity_threshold,
+        frozen_complexity_penalty,
+        score_precision,
+        max_heatmap_depth,
+        adr_stale_days,
+        new_file_grace_days,
+        warning_threshold,
+        critical_threshold,
+        magic_number_allowlist,
     })
 }
 
@@ -75,6 +394,140 @@ pub fn load_settings_from_disk(workspace_path: &str) -> Result<Value, String> {
     Ok(migrated)
 }
 
+/// Directories skipped when walking the workspace: the defaults plus any
+/// `skipDirectories` entries. Reads settings.json without creating or
+/// migrating it, since the file walk runs in many read-only paths; callers
+/// walking more than once should load this once and reuse it.
+pub fn load_skip_directories(workspace_path: &str) -> SkipDirectories {
+    let configured = fs::read_to_string(settings_path(workspace_path))
+        .ok()
+        .and_then(|raw| serde_json::from_str::<Value>(&raw).ok());
+    let configured = configured
+        .as_ref()
+        .and_then(|settings| settings.get("skipDirectories"))
+        .and_then(Value::as_array)
+        .map(|dirs| dirs.iter().filter_map(Value::as_str).collect::<Vec<_>>())
+        .unwrap_or_default();
+
+    let mut skip = SkipDirectories::default();
+    for entry in DEFAULT_SKIP_DIRECTORIES.iter().copied().chain(configured) {
+        let entry = entry.trim().replace('\\', "/");
+        let entry = entry.trim_matches('/');
+        if entry.is_empty() {
+            continue;
+        }
+        if NESTED_SKIP_DIRECTORIES.contains(&entry) {
+            skip.anywhere.insert(entry.to_string());
+        } else {
+            skip.rooted.insert(entry.to_string());
+        }
+    }
+    skip
+}
+
+/// `jacocoReportPath` resolved against the workspace, if one is configured.
+/// Read straight from disk like `skipDirectories`.
+pub fn load_jacoco_report_path(workspace_path: &str) -> Option<PathBuf> {
+    let raw = fs::read_to_string(settings_path(workspace_path)).ok()?;
+    let settings = serde_json::from_str::<Value>(&raw).ok()?;
+    let configured = settings.get("jacocoReportPath")?.as_str()?.trim();
+    if configured.is_empty() {
+        return None;
+    }
+    Some(Path::new(workspace_path).join(configured))
+}
+
+/// `schema_version` of settings.json as stored on disk, read without
+/// migrating; `None` when the file is missing or not a JSON object
+pub fn stored_settings_version(workspace_path: &str) -> Option<i64> {
+    let raw = fs::read_to_string(settings_path(workspace_path)).ok()?;
+    match serde_json::from_str::<Value>(&raw).ok()? {
+        Value::Object(map) => Some(map.get("schema_version").and_then(Value::as_i64).unwrap_or(0)),
+        _ => None,
+    }
+}
+
+/// Check settings.json without rewriting it. Unparseable or outdated files
+/// are repairable by re-running the load/migrate path.
+pub fn check_settings_file(workspace_path: &str) -> Vec<WorkspaceIssue> {
+    let issue = |message: String, repairable: bool| WorkspaceIssue {
+        area: "settings".to_string(),
+        message,
+        repairable,
+    };
+
+    let path = settings_path(workspace_path);
+    if !path.exists() {
+        return vec![issue("settings.json is missing".to_string(), true)];
+    }
+
+    let raw = match fs::read_to_string(&path) {
+        Ok(raw) => raw,
+        Err(e) => return vec![issue(format!("Failed to read settings.json: {e}"), false)],
+    };
+    let parsed = match serde_json::from_str::<Value>(&raw) {
+        Ok(Value::Object(map)) => Value::Object(map),
+        Ok(_) => return vec![issue("settings.json is not a JSON object".to_string(), true)],
+        Err(e) => return vec![issue(format!("settings.json is not valid JSON: {e}"), true)],
+    };
+
+    let version = parsed
+        .get("schema_version")
+        .and_then(Value::as_i64)
+        .unwrap_or(0);
+    if version > SETTINGS_SCHEMA_VERSION {
+        return vec![issue(
+            format!("schema version {version} is newer than supported version {SETTINGS_SCHEMA_VERSION}"),
+            false,
+        )];
+    }
+
+    if !values_equivalent(&migrate_settings(parsed.clone()), &parsed) {
+        return vec![issue("settings.json needs migration or has invalid values".to_string(), true)];
+    }
+
+    Vec::new()
+}
+
+/// Structural equality that tolerates float noise from re-normalizing weights.
+fn values_equivalent(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::Number(x), Value::Number(y)) => match (x.as_f64(), y.as_f64()) {
+            (Some(x), Some(y)) => (x - y).abs() <= 1e-9,
+            _ => x == y,
+        },
+        (Value::Object(x), Value::Object(y)) => {
+            x.len() == y.len()
+                && x.iter().all(|(key, value)| y.get(key).is_some_and(|other| values_equivalent(value, other)))
+        }
+        (Value::Array(x), Value::Array(y)) => {
+            x.len() == y.len() && x.iter().zip(y).all(|(a, b)| values_equivalent(a, b))
+        }
+        _ => a == b,
+    }
+}
+
+fn settings_overrides(current: &Value, defaults: &Value) -> Map<String, Value> {
+    fn collect(prefix: &str, current: &Value, defaults: Option<&Value>, out: &mut Map<String, Value>) {
+        match (current, defaults) {
+            (Value::Object(fields), Some(Value::Object(default_fields))) => {
+                for (key, value) in fields {
+                    let path = if prefix.is_empty() { key.clone() } else { format!("{prefix}.{key}") };
+                    collect(&path, value, default_fields.get(key), out);
+                }
+            }
+            (value, Some(default)) if values_equivalent(value, default) => {}
+            (value, _) => {
+                out.insert(prefix.to_string(), value.clone());
+            }
+        }
+    }
+
+    let mut out = Map::new();
+    collect("", current, Some(defaults), &mut out);
+    out
+}
+
 pub fn save_settings_to_disk(workspace_path: &str, settings: Value) -> Result<Value, String> {
     let path = settings_path(workspace_path);
     ensure_debtengine_dir(workspace_path)?;
@@ -83,10 +536,53 @@ pub fn save_settings_to_disk(workspace_path: &str, settings: Value) -> Result<Va
     merge_settings(&mut merged, &settings);
 
     let migrated = migrate_settings(merged);
+    validate_custom_formula(&migrated)?;
+    validate_glob_patterns(&migrated)?;
+    validate_magic_number_allowlist(&migrated)?;
     write_settings_file(&path, &migrated)?;
     Ok(migrated)
 }
 
+/// Reject a `customFormula` that does not parse (e.g. unknown identifiers)
+/// while `compositeFormula` is `"custom"`
+fn validate_custom_formula(settings: &Value) -> Result<(), String> {
+    if settings.get("compositeFormula").and_then(Value::as_str) != Some("custom") {
+        return Ok(());
+    }
+    let formula = settings.get("customFormula").and_then(Value::as_str).unwrap_or("");
+    crate::models::formula::Formula::parse(formula).map(|_| ())
+}
+
+/// Reject glob settings that do not compile; analysis would otherwise drop
+/// them and, for `includePatterns`, silently widen the scored set
+fn validate_glob_patterns(settings: &Value) -> Result<(), String> {
+    for key in ["acceptedPatterns", "includePatterns", "excludePatterns"] {
+        for pattern in string_list(settings, key) {
+            glob::Pattern::new(&pattern).map_err(|e| format!("INVALID_GLOB: {key} entry \"{pattern}\": {e}"))?;
+        }
+    }
+    Ok(())
+}
+
+/// Reject `magicNumberAllowlist` languages whose value is not a list of
+/// numbers; analysis would otherwise drop the entries and flag them again
+fn validate_magic_number_allowlist(settings: &Value) -> Result<(), String> {
+    let Some(languages) = settings.get("magicNumberAllowlist").and_then(Value::as_object) else {
+        return Ok(());
+    };
+    for (language, numbers) in languages {
+        let valid = numbers
+            .as_array()
+            .is_some_and(|numbers| numbers.iter().all(Value::is_number));
+        if !valid {
+            return Err(format!(
+                "INVALID_MAGIC_NUMBER_ALLOWLIST: magicNumberAllowlist entry \"{language}\": expected a list of numbers"
+            ));
+        }
+    }
+    Ok(())
+}
+
 fn settings_path(workspace_path: &str) -> PathBuf {
     Path::new(workspace_path)
         .join(".debtengine")
@@ -145,6 +641,8 @@ fn default_settings() -> Value {
         "gitHistoryDays": 90,
         "churnNormalizationPercentile": 90,
         "weights": default_weights(),
+        "rawScoreCap": default_raw_score_caps(),
+        "magicNumberAllowlist": crate::analysis::smells::default_magic_number_allowlist(),
         "warningThreshold": 65,
         "criticalThreshold": 80,
         "busFactor": 70,
@@ -153,7 +651,36 @@ fn default_settings() -> Value {
         "animationsEnabled": true,
         "snapshotSchedule": "weekly",
         "snapshotRetention": 52,
-        "notificationsEnabled": true
+        "regressionAlertDelta": 5.0,
+        "notificationsEnabled": true,
+        "fileLengthThreshold": 0,
+        "smellDensityScale": 5000,
+        "scorePrecision": 1,
+        "maxHeatmapDepth": 32,
+        "adrStaleDays": 180,
+        "newFileGraceDays": 0,
+        "normalizeWeights": true,
+        "compositeFormula": "weighted_sum",
+        "customFormula": "",
+        "trackedFilesOnly": false,
+        "analysisRef": "",
+        "jacocoReportPath": "",
+        "coChangeHistoryDays": null,
+        "maxFilesPerCommitForCoupling": 50,
+        "componentNormalization": "absolute",
+        "relativeBlend": 0.0,
+        "maxThreads": null,
+        "includeTypeDeclarations": false,
+        "includeGeneratedCode": false,
+        "trackDocs": false,
+        "frozenComplexityThreshold": 0,
+        "frozenComplexityPenalty": 75,
+        "acceptedPatterns": [],
+        "includePatterns": [],
+        "excludePatterns": [],
+        "includeLanguages": [],
+        "excludeLanguages": [],
+        "skipDirectories": DEFAULT_SKIP_DIRECTORIES
     })
 }
 
@@ -228,17 +755,88 @@ fn sanitize_settings(settings: &mut Value) {
     clamp_u64(obj, "criticalThreshold", 50, 100, 80);
     clamp_u64(obj, "busFactor", 50, 95, 70);
     clamp_u64(obj, "snapshotRetention", 10, 260, 52);
+    clamp_u64(obj, "fileLengthThreshold", 0, 10_000, 0);
+    clamp_f64(obj, "smellDensityScale", 1.0, 100_000.0, 5000.0);
+    clamp_u64(obj, "scorePrecision", 0, 6, 1);
+    clamp_u64(obj, "maxHeatmapDepth", 1, 256, 32);
+    clamp_u64(obj, "adrStaleDays", 30, 3650, 180);
+    clamp_u64(obj, "newFileGraceDays", 0, 365, 0);
+    clamp_f64(obj, "frozenComplexityThreshold", 0.0, 100.0, 0.0);
+    clamp_f64(obj, "frozenComplexityPenalty", 0.0, 100.0, 75.0);
+    clamp_u64(obj, "maxFilesPerCommitForCoupling", 2, 10_000, 50);
+    // null means "same as gitHistoryDays".
+    if obj.get("coChangeHistoryDays").is_some_and(|v| !v.is_null()) {
+        clamp_u64(obj, "coChangeHistoryDays", 7, 730, 90);
+    }
+    // null means one thread per available core.
+    if obj.get("maxThreads").is_some_and(|v| !v.is_null()) {
+        clamp_u64(obj, "maxThreads", 1, 256, 1);
+    }
 
     // Validate enums.
     sanitize_enum(obj, "colorScheme", &["default", "heatwave", "monochrome"], "default");
     sanitize_enum(obj, "nodeLabel", &["always", "hover", "never"], "always");
     sanitize_enum(obj, "snapshotSchedule", &["weekly", "biweekly", "manual"], "weekly");
+    sanitize_enum(
+        obj,
+        "compositeFormula",
+        &["weighted_sum", "weighted_geomean", "max_plus", "custom"],
+        "weighted_sum",
+    );
+    sanitize_enum(obj, "componentNormalization", &["absolute", "relative"], "absolute");
+    clamp_f64(obj, "relativeBlend", 0.0, 1.0, 0.0);
+    clamp_f64(obj, "regressionAlertDelta", 0.0, 100.0, 5.0);
 
     // Bools with defaults.
     ensure_bool(obj, "animationsEnabled", true);
     ensure_bool(obj, "notificationsEnabled", true);
+    ensure_bool(obj, "normalizeWeights", true);
+    ensure_bool(obj, "trackedFilesOnly", false);
+    ensure_bool(obj, "includeTypeDeclarations", false);
+    ensure_bool(obj, "includeGeneratedCode", false);
+    ensure_bool(obj, "trackDocs", false);
+    ensure_string_array(obj, "acceptedPatterns", json!([]));
+    ensure_string_array(obj, "includePatterns", json!([]));
+    ensure_string_array(obj, "excludePatterns", json!([]));
+    ensure_string_array(obj, "includeLanguages", json!([]));
+    ensure_string_array(obj, "excludeLanguages", json!([]));
+    ensure_string_array(obj, "skipDirectories", json!(DEFAULT_SKIP_DIRECTORIES));
+    if !obj.get("analysisRef").is_some_and(Value::is_string) {
+        obj.insert("analysisRef".to_string(), json!(""));
+    }
+    if !obj.get("jacocoReportPath").is_some_and(Value::is_string) {
+        obj.insert("jacocoReportPath".to_string(), json!(""));
+    }
+    if !obj.get("customFormula").is_some_and(Value::is_string) {
+        obj.insert("customFormula".to_string(), json!(""));
+    }
+
+    if !obj.get("magicNumberAllowlist").is_some_and(Value::is_object) {
+        obj.insert(
+            "magicNumberAllowlist".to_string(),
+            json!(crate::analysis::smells::default_magic_number_allowlist()),
+        );
+    }
+
+    // Component caps stay within the 0–100 raw score range.
+    let caps = obj
+        .entry("rawScoreCap".to_string())
+        .or_insert_with(|| json!({}));
+    if !caps.is_object() {
+        *caps = json!({});
+    }
+    if let Some(cap_obj) = caps.as_object_mut() {
+        for (key, default_value) in default_raw_score_caps() {
+            let current = cap_obj.get(&key).and_then(Value::as_f64).unwrap_or(default_value);
+            cap_obj.insert(key, json!(current.clamp(0.0, 100.0)));
+        }
+    }
 
-    // Normalize weights.
+    // Normalize weights unless the user opted into raw weights.
+    let normalize_weights = obj
+        .get("normalizeWeights")
+        .and_then(Value::as_bool)
+        .unwrap_or(true);
     let default_weight_map = default_weights();
     let weights = obj
         .entry("weights".to_string())
@@ -251,7 +849,7 @@ fn sanitize_settings(settings: &mut Value) {
         }
 
         let sum: f64 = weight_obj.values().filter_map(Value::as_f64).sum();
-        if sum > f64::EPSILON {
+        if normalize_weights && sum > f64::EPSILON {
             for value in weight_obj.values_mut() {
                 if let Some(v) = value.as_f64() {
                     *value = json!((v / sum).clamp(0.0, 1.0));
@@ -268,6 +866,11 @@ fn clamp_u64(map: &mut Map<String, Value>, key: &str, min: u64, max: u64, defaul
     map.insert(key.to_string(), json!(raw.clamp(min, max)));
 }
 
+fn clamp_f64(map: &mut Map<String, Value>, key: &str, min: f64, max: f64, default: f64) {
+    let raw = map.get(key).and_then(Value::as_f64).unwrap_or(default);
+    map.insert(key.to_string(), json!(raw.clamp(min, max)));
+}
+
 fn sanitize_enum(map: &mut Map<String, Value>, key: &str, allowed: &[&str], default: &str) {
     let valid = map
         .get(key)
@@ -277,6 +880,32 @@ fn sanitize_enum(map: &mut Map<String, Value>, key: &str, allowed: &[&str], defa
     map.insert(key.to_string(), json!(valid));
 }
 
+/// Trimmed, non-empty strings of a settings array; empty when absent
+fn string_list(settings: &Value, key: &str) -> Vec<String> {
+    settings
+        .get(key)
+        .and_then(Value::as_array)
+        .map(|values| {
+            values
+                .iter()
+                .filter_map(Value::as_str)
+                .map(str::trim)
+                .filter(|v| !v.is_empty())
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn ensure_string_array(map: &mut Map<String, Value>, key: &str, default: Value) {
+    match map.get_mut(key) {
+        Some(Value::Array(items)) => items.retain(Value::is_string),
+        _ => {
+            map.insert(key.to_string(), default);
+        }
+    }
+}
+
 fn ensure_bool(map: &mut Map<String, Value>, key: &str, default: bool) {
     let value = map.get(key).and_then(Value::as_bool).unwrap_or(default);
     map.insert(key.to_string(), json!(value));
@@ -331,4 +960,116 @@ mod tests {
         assert_eq!(migrated["criticalThreshold"], json!(80));
         assert!(migrated.get("weights").is_some());
     }
+
+    #[test]
+    fn fractional_frozen_complexity_settings_survive_sanitizing() {
+        let mut existing = default_settings();
+        merge_settings(
+            &mut existing,
+            &json!({ "frozenComplexityThreshold": 42.5, "frozenComplexityPenalty": 60.5 }),
+        );
+        let migrated = migrate_settings(existing);
+
+        assert_eq!(migrated["frozenComplexityThreshold"], json!(42.5));
+        assert_eq!(migrated["frozenComplexityPenalty"], json!(60.5));
+        assert_eq!(migrate_settings(default_settings())["frozenComplexityThreshold"], json!(0.0));
+    }
+
+    #[test]
+    fn fractional_smell_density_scale_survives_sanitizing() {
+        let mut existing = default_settings();
+        merge_settings(&mut existing, &json!({ "smellDensityScale": 2500.5 }));
+
+        assert_eq!(migrate_settings(existing)["smellDensityScale"], json!(2500.5));
+    }
+
+    #[test]
+    fn non_normalizing_mode_preserves_raw_weights() {
+        let tmp = tempfile::tempdir().expect("temp dir");
+        let workspace_path = tmp.path().to_string_lossy().to_string();
+
+        let mut weights = serde_json::Map::new();
+        for key in default_weights().keys() {
+            weights.insert(key.clone(), json!(0.05));
+        }
+        weights.insert("churn_rate".to_string(), json!(0.3));
+
+        save_settings_to_disk(
+            &workspace_path,
+            json!({ "normalizeWeights": false, "weights": weights }),
+        )
+        .expect("save settings");
+
+        let effective = load_effective_analysis_settings(&workspace_path).expect("effective settings");
+        assert!((effective.weights["churn_rate"] - 0.3).abs() < 1e-9);
+        assert!((effective.weights["decision_staleness"] - 0.05).abs() < 1e-9);
+
+        let sum: f64 = effective.weights.values().sum();
+        assert!((sum - 0.65).abs() < 1e-9, "Expected raw sum 0.65, got {sum}");
+    }
+
+    #[test]
+    fn percentage_weights_override_is_migrated_like_saved_weights() {
+        let tmp = tempfile::tempdir().expect("temp dir");
+        let workspace_path = tmp.path().to_string_lossy().to_string();
+
+        let effective =
+            load_effective_analysis_settings_with_weights(&workspace_path, Some(&json!({ "churn_rate": 30 })))
+                .expect("effective settings");
+        // 30% joins the other default fractions (0.78) before normalizing.
+        assert!((effective.weights["churn_rate"] - 0.3 / 1.08).abs() < 1e-9);
+        let sum: f64 = effective.weights.values().sum();
+        assert!((sum - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn severity_thresholds_are_read_from_saved_settings() {
+        let tmp = tempfile::tempdir().expect("temp dir");
+        let workspace_path = tmp.path().to_string_lossy().to_string();
+
+        let defaults = load_effective_analysis_settings(&workspace_path).expect("effective settings");
+        assert_eq!((defaults.warning_threshold, defaults.critical_threshold), (65.0, 80.0));
+
+        save_settings_to_disk(&workspace_path, json!({ "warningThreshold": 50, "criticalThreshold": 70 }))
+            .expect("save settings");
+        let saved = load_effective_analysis_settings(&workspace_path).expect("effective settings");
+        assert_eq!((saved.warning_threshold, saved.critical_threshold), (50.0, 70.0));
+    }
+
+    #[test]
+    fn magic_number_allowlist_is_configurable_and_validated() {
+        let tmp = tempfile::tempdir().expect("temp dir");
+        let workspace_path = tmp.path().to_string_lossy().to_string();
+
+        let defaults = load_effective_analysis_settings(&workspace_path).expect("effective settings");
+        assert_eq!(defaults.magic_number_allowlist["python"], vec![10.0, 60.0]);
+
+        save_settings_to_disk(&workspace_path, json!({ "magicNumberAllowlist": { "python": [24, 3600] } }))
+            .expect("save settings");
+        let saved = load_effective_analysis_settings(&workspace_path).expect("effective settings");
+        assert_eq!(saved.magic_number_allowlist["python"], vec![24.0, 3600.0]);
+        assert_eq!(saved.magic_number_allowlist["go"], defaults.magic_number_allowlist["go"]);
+
+        let err = save_settings_to_disk(&workspace_path, json!({ "magicNumberAllowlist": { "python": ["24"] } }))
+            .expect_err("non-numeric allowlist");
+        assert!(err.starts_with("INVALID_MAGIC_NUMBER_ALLOWLIST:"), "{err}");
+    }
+
+    #[test]
+    fn overrides_list_only_changed_keys() {
+        let defaults = migrate_settings(default_settings());
+        let mut current = defaults.clone();
+        merge_settings(
+            &mut current,
+            &json!({ "gitHistoryDays": 30, "normalizeWeights": false, "weights": { "churn_rate": 0.5 } }),
+        );
+        let current = migrate_settings(current);
+
+        let overrides = settings_overrides(&current, &defaults);
+        let mut keys: Vec<&str> = overrides.keys().map(String::as_str).collect();
+        keys.sort();
+        assert_eq!(keys, vec!["gitHistoryDays", "normalizeWeights", "weights.churn_rate"]);
+        assert_eq!(overrides.get("gitHistoryDays"), Some(&json!(30)));
+        assert!(settings_overrides(&defaults, &defaults).is_empty());
+    }
 }
@@ -1,13 +1,15 @@
+#![recursion_limit = "256"]
+
 pub mod commands;
 pub mod models;
 pub mod analysis;
 
 use commands::{
-    git::{open_workspace, run_git_analysis},
-    scoring::{run_full_analysis, get_heatmap_data, get_file_breakdown, get_change_couplings, reanalyze_file},
-    ast::run_ast_analysis,
-    db::{register_crud, budget_crud, take_snapshot, get_debt_snapshots, watchlist_crud},
-    settings::{get_settings, save_settings},
+    git::{open_workspace, run_git_analysis, get_line_blame, get_knowledge_risk, validate_workspace, get_version_info, analyze_churn_range, get_file_churn_detail, get_ownership_coupling, get_churn_acceleration, get_relocated_files},
+    scoring::{run_full_analysis, get_heatmap_data, get_heatmap_by, get_file_breakdown, get_file_detail, get_adrs_for_file, get_stale_adrs, get_change_couplings, get_coupling_centrality, get_blast_radius, reanalyze_file, estimate_remediation, get_score_distribution, prune_coupling_pairs, reconcile_coupling, maybe_take_scheduled_snapshot, get_uncovered_files, get_workspace_coverage, get_analysis_state, analyze_dirty_files, capture_baseline, diff_against_baseline, get_hotspots, get_change_risk, get_largest_files, get_score_matrix, get_component_correlations, get_author_debt, explain_file_score, get_risk_triage, get_language_summary, check_gate, get_breaching_files, load_cache_from_db, refresh_staleness, suggest_splits, get_doc_coverage, get_function_complexity_distribution},
+    ast::{run_ast_analysis, classify_files, get_dependency_debt, get_import_cycles},
+    db::{register_crud, budget_crud, take_snapshot, get_debt_snapshots, get_smoothed_trend, get_debt_velocity, get_debt_halflife, watchlist_crud, get_register_priority_score, import_register_items, get_recent_register_activity, get_smell_trend, get_snapshot_near, get_coverage_trend, find_duplicate_register_items},
+    settings::{get_settings, get_settings_overrides, save_settings},
     watcher::start_file_watcher,
 };
 use models::file_score::AnalysisCache;
@@ -22,18 +24,74 @@ pub fn run() {
         .invoke_handler(tauri::generate_handler![
             open_workspace,
             run_git_analysis,
+            get_line_blame,
+            get_knowledge_risk,
+            validate_workspace,
+            get_version_info,
+            analyze_churn_range,
+            get_file_churn_detail,
+            get_ownership_coupling,
+            get_churn_acceleration,
+            get_relocated_files,
             run_full_analysis,
             reanalyze_file,
             run_ast_analysis,
+            classify_files,
+            get_dependency_debt,
+            get_import_cycles,
             get_heatmap_data,
+            get_heatmap_by,
             get_file_breakdown,
+            get_file_detail,
+            get_adrs_for_file,
+            get_stale_adrs,
             get_change_couplings,
+            get_coupling_centrality,
+            get_blast_radius,
+            estimate_remediation,
+            get_score_distribution,
+            prune_coupling_pairs,
+            reconcile_coupling,
+            maybe_take_scheduled_snapshot,
+            get_uncovered_files,
+            get_workspace_coverage,
+            get_analysis_state,
+            analyze_dirty_files,
+            capture_baseline,
+            diff_against_baseline,
+            get_hotspots,
+            get_change_risk,
+            get_largest_files,
+            get_score_matrix,
+            get_component_correlations,
+            get_author_debt,
+            explain_file_score,
+            get_risk_triage,
+            get_language_summary,
+            check_gate,
+            get_breaching_files,
+            load_cache_from_db,
+            refresh_staleness,
+            suggest_splits,
+            get_doc_coverage,
+            get_function_complexity_distribution,
             take_snapshot,
             get_debt_snapshots,
+            get_smoothed_trend,
+            get_debt_velocity,
+            get_debt_halflife,
             register_crud,
             budget_crud,
             watchlist_crud,
+            get_register_priority_score,
+            get_recent_register_activity,
+            get_smell_trend,
+            get_snapshot_near,
+            get_coverage_trend,
+            find_duplicate_register_items,
+            import_register_items,
             get_settings,
+            get_settings_overrides,
             save_settings,
             start_file_watcher,
         ])
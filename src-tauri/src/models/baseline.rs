@@ -0,0 +1,155 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::models::file_score::{round_score, AnalysisResult};
+
+/// Score changes smaller than this are treated as unchanged
+const DELTA_EPSILON: f64 = 0.01;
+
+/// Per-file composite scores captured from an in-memory analysis run,
+/// rounded to `scorePrecision`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScoreBaseline {
+    pub workspace_path: String,
+    pub captured_at: i64,
+    pub workspace_score: f64,
+    pub scores: BTreeMap<String, f64>,
+}
+
+impl ScoreBaseline {
+    pub fn from_result(workspace_path: &str, result: &AnalysisResult, captured_at: i64, precision: u32) -> Self {
+        ScoreBaseline {
+            workspace_path: workspace_path.to_string(),
+            captured_at,
+            workspace_score: round_score(result.workspace_score, precision),
+            scores: result
+                .files
+                .iter()
+                .map(|f| (f.relative_path.clone(), round_score(f.composite_score, precision)))
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileScoreDelta {
+    pub relative_path: String,
+    pub baseline_score: Option<f64>,
+    pub current_score: Option<f64>,
+    pub delta: f64,
+    pub status: String, // "regressed" | "improved" | "unchanged" | "added" | "removed"
+}
+
+/// Classify each current file against its previously persisted score as
+/// `regressed`, `improved`, `unchanged`, or `new`, in the order of `current`.
+/// Files that disappeared are not reported.
+pub fn classify_against_previous(
+    previous: &BTreeMap<String, f64>,
+    current: &[crate::models::file_score::FileScore],
+) -> Vec<FileScoreDelta> {
+    current
+        .iter()
+        .map(|file| {
+            let before = previous.get(&file.relative_path).copied();
+            let delta = before.map_or(file.composite_score, |before| file.composite_score - before);
+            let status = match before {
+                None => "new",
+                Some(_) if delta > DELTA_EPSILON => "regressed",
+                Some(_) if delta < -DELTA_EPSILON => "improved",
+                Some(_) => "unchanged",
+            };
+            FileScoreDelta {
+                relative_path: file.relative_path.clone(),
+                baseline_score: before,
+                current_score: Some(file.composite_score),
+                delta,
+                status: status.to_string(),
+            }
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BaselineDiffSummary {
+    pub baseline_workspace_score: f64,
+    pub current_workspace_score: f64,
+    pub workspace_delta: f64,
+    pub regressed: usize,
+    pub improved: usize,
+    pub unchanged: usize,
+    pub added: usize,
+    pub removed: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BaselineDiff {
+    pub files: Vec<FileScoreDelta>,
+    pub summary: BaselineDiffSummary,
+}
+
+/// Compare current scores, rounded to `precision` like the baseline's,
+/// against a baseline. Files are ordered by delta, largest regression first.
+pub fn diff_scores(baseline: &ScoreBaseline, current: &AnalysisResult, precision: u32) -> BaselineDiff {
+    let current_scores: BTreeMap<&str, f64> = current
+        .files
+        .iter()
+        .map(|f| (f.relative_path.as_str(), round_score(f.composite_score, precision)))
+        .collect();
+
+    let mut files: Vec<FileScoreDelta> = baseline
+        .scores
+        .iter()
+        .map(|(path, &before)| {
+            let after = current_scores.get(path.as_str()).copied();
+            let delta = round_score(after.map_or(-before, |after| after - before), precision);
+            let status = match after {
+                None => "removed",
+                Some(_) if delta > DELTA_EPSILON => "regressed",
+                Some(_) if delta < -DELTA_EPSILON => "improved",
+                Some(_) => "unchanged",
+            };
+            FileScoreDelta {
+                relative_path: path.clone(),
+                baseline_score: Some(before),
+                current_score: after,
+                delta,
+                status: status.to_string(),
+            }
+        })
+        .collect();
+
+    files.extend(
+        current_scores
+            .iter()
+            .filter(|(path, _)| !baseline.scores.contains_key(**path))
+            .map(|(path, &after)| FileScoreDelta {
+                relative_path: path.to_string(),
+                baseline_score: None,
+                current_score: Some(after),
+                delta: after,
+                status: "added".to_string(),
+            }),
+    );
+
+    files.sort_by(|a, b| {
+        b.delta
+            .partial_cmp(&a.delta)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| a.relative_path.cmp(&b.relative_path))
+    });
+
+    let count = |status: &str| files.iter().filter(|f| f.status == status).count();
+    let current_workspace_score = round_score(current.workspace_score, precision);
+    let summary = BaselineDiffSummary {
+        baseline_workspace_score: baseline.workspace_score,
+        current_workspace_score,
+        workspace_delta: round_score(current_workspace_score - baseline.workspace_score, precision),
+        regressed: count("regressed"),
+        improved: count("improved"),
+        unchanged: count("unchanged"),
+        added: count("added"),
+        removed: count("removed"),
+    };
+
+    BaselineDiff { files, summary }
+}
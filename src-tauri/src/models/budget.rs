@@ -8,4 +8,8 @@ pub struct DebtBudget {
     pub max_score: f64,
     pub created_at: i64,
     pub notify_on_breach: bool,
+    /// Local warning threshold for files in this budget's scope; the global
+    /// `warningThreshold` applies when unset
+    #[serde(default)]
+    pub warning_score: Option<f64>,
 }
@@ -1,15 +1,19 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ComponentScore {
     pub raw_score: f64,
     pub weight: f64,
     pub contribution: f64,
     pub details: Vec<String>,
+    /// Analyzer confidence ("heuristic" or "high") for estimated components;
+    /// `None` for components measured directly from git or the filesystem
+    #[serde(default)]
+    pub confidence: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ScoreComponents {
     pub churn_rate: ComponentScore,
     pub code_smell_density: ComponentScore,
@@ -21,6 +25,83 @@ pub struct ScoreComponents {
     pub decision_staleness: ComponentScore,
 }
 
+impl ScoreComponents {
+    /// All eight components paired with their settings/weight key
+    pub fn named(&self) -> [(&'static str, &ComponentScore); 8] {
+        [
+            ("churn_rate", &self.churn_rate),
+            ("code_smell_density", &self.code_smell_density),
+            ("coupling_index", &self.coupling_index),
+            ("change_coupling", &self.change_coupling),
+            ("test_coverage_gap", &self.test_coverage_gap),
+            ("knowledge_concentration", &self.knowledge_concentration),
+            ("cyclomatic_complexity", &self.cyclomatic_complexity),
+            ("decision_staleness", &self.decision_staleness),
+        ]
+    }
+
+    pub fn named_mut(&mut self) -> [(&'static str, &mut ComponentScore); 8] {
+        [
+            ("churn_rate", &mut self.churn_rate),
+            ("code_smell_density", &mut self.code_smell_density),
+            ("coupling_index", &mut self.coupling_index),
+            ("change_coupling", &mut self.change_coupling),
+            ("test_coverage_gap", &mut self.test_coverage_gap),
+            ("knowledge_concentration", &mut self.knowledge_concentration),
+            ("cyclomatic_complexity", &mut self.cyclomatic_complexity),
+            ("decision_staleness", &mut self.decision_staleness),
+        ]
+    }
+}
+
+/// How component scores combine into the composite (`compositeFormula` setting)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum CompositeFormula {
+    /// Σ raw × weight
+    #[default]
+    WeightedSum,
+    /// 101 − weighted geometric mean of (101 − raw)
+    WeightedGeomean,
+    /// Worst component, topped up by the weighted sum
+    MaxPlus,
+    /// User expression over raw scores (`customFormula`)
+    Custom(std::sync::Arc<crate::models::formula::Formula>),
+}
+
+impl CompositeFormula {
+    /// `custom_formula` is only read for `"custom"`; an unparsable one falls
+    /// back to the weighted sum (saving settings rejects it up front).
+    pub fn from_setting(value: &str, custom_formula: &str) -> Self {
+        match value {
+            "weighted_geomean" => CompositeFormula::WeightedGeomean,
+            "max_plus" => CompositeFormula::MaxPlus,
+            "custom" => crate::models::formula::Formula::parse(custom_formula)
+                .map(|formula| CompositeFormula::Custom(std::sync::Arc::new(formula)))
+                .unwrap_or_default(),
+            _ => CompositeFormula::WeightedSum,
+        }
+    }
+}
+
+/// Whether component scores are absolute or rescaled against the workspace
+/// (`componentNormalization` setting)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ComponentNormalization {
+    #[default]
+    Absolute,
+    /// Min-max rescaled per component across all analyzed files
+    Relative,
+}
+
+impl ComponentNormalization {
+    pub fn from_setting(value: &str) -> Self {
+        match value {
+            "relative" => ComponentNormalization::Relative,
+            _ => ComponentNormalization::Absolute,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FileScore {
     pub path: String,
@@ -30,7 +111,15 @@ pub struct FileScore {
     pub loc: usize,
     pub language: String,
     pub last_modified: i64,
-    pub supervision_status: String, // "none" | "acceptable" | "regressed"
+    pub supervision_status: String, // "none" | "acceptable" | "regressed" | "new"
+    /// Per-function cyclomatic complexity; persisted in `score_data_json` and
+    /// served by `get_function_complexity_distribution`, not with every score
+    #[serde(default, skip_serializing)]
+    pub functions: Vec<crate::commands::ast::FunctionComplexity>,
+    /// Non-zero smell counts keyed by `SMELL_TYPES` name, recorded while
+    /// scoring for snapshot totals; persisted in `score_data_json`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub smell_counts: BTreeMap<String, usize>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -56,6 +145,7 @@ pub struct ComponentDetail {
     pub weight: f64,
     pub contribution: f64,
     pub details: Vec<String>,
+    pub confidence: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -65,6 +155,45 @@ pub struct AnalysisResult {
     pub high_debt_count: usize,
     pub files: Vec<FileScore>,
     pub duration_ms: u64,
+    /// Files that could not be read or scored, and why
+    #[serde(default)]
+    pub errors: Vec<FileAnalysisError>,
+    /// How each file's score moved since the previously persisted analysis
+    #[serde(default)]
+    pub score_changes: Vec<crate::models::baseline::FileScoreDelta>,
+    /// Quick scan: git-derived components were skipped and are zero
+    #[serde(default)]
+    pub partial: bool,
+    /// Git inputs that failed to load (e.g. "churn unavailable: ..."); their
+    /// components are zero rather than measured
+    #[serde(default)]
+    pub degraded: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileAnalysisError {
+    pub path: String,
+    pub reason: String,
+}
+
+/// Files per severity band, using the `criticalThreshold`/`warningThreshold`
+/// settings and the heatmap's fixed medium cut-off
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SeverityCounts {
+    pub critical: usize,
+    pub high: usize,
+    pub medium: usize,
+    pub low: usize,
+}
+
+/// Payload of the `analysis_complete` event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnalysisSummary {
+    pub workspace_score: f64,
+    pub file_count: usize,
+    pub severity_counts: SeverityCounts,
+    pub duration_ms: u64,
+    pub budget_breached: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -88,10 +217,44 @@ pub fn default_weights() -> HashMap<String, f64> {
     w
 }
 
+/// Default per-component raw score ceilings (`rawScoreCap`): uncapped
+pub fn default_raw_score_caps() -> HashMap<String, f64> {
+    default_weights().into_keys().map(|key| (key, 100.0)).collect()
+}
+
+/// Default for `scorePrecision`
+pub const DEFAULT_SCORE_PRECISION: u32 = 1;
+
+/// Default for `maxHeatmapDepth`
+pub const DEFAULT_MAX_HEATMAP_DEPTH: usize = 32;
+
+/// Round a score to `precision` decimal places for display
+pub fn round_score(score: f64, precision: u32) -> f64 {
+    let factor = 10f64.powi(precision as i32);
+    (score * factor).round() / factor
+}
+
+/// Per-component `(min, max)` absolute raw scores across a workspace, in
+/// `ScoreComponents::named` order
+pub type ComponentRanges = [(f64, f64); 8];
+
 /// In-memory cache for analysis results
 #[derive(Debug, Default)]
 pub struct AnalysisCache {
     pub workspace_path: Option<String>,
     pub result: Option<AnalysisResult>,
     pub heatmap: Option<HeatmapNode>,
+    /// Commit the cached git-derived scores were computed at
+    pub history_head: Option<String>,
+    /// Ranges the cached scores were normalized against (`relativeBlend`);
+    /// `None` for absolute scores or scores loaded from the database
+    pub component_ranges: Option<ComponentRanges>,
+    /// Commits per file in the history window of the cached scores; `None`
+    /// when churn was unavailable
+    pub commit_counts: Option<HashMap<String, usize>>,
+    /// The cached scores used a one-off weights override and were not persisted
+    pub weights_overridden: bool,
+    /// Workspace coverage from the reports the cached analysis merged; `None`
+    /// for scores loaded from the database
+    pub coverage: Option<crate::analysis::coverage::WorkspaceCoverage>,
 }
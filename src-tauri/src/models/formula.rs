@@ -0,0 +1,244 @@
+use crate::models::file_score::ScoreComponents;
+
+/// Longest `customFormula` accepted; keeps parsing and evaluation bounded
+pub const MAX_FORMULA_LENGTH: usize = 500;
+
+/// Short names accepted in `customFormula` besides the eight component keys
+const COMPONENT_ALIASES: &[(&str, &str)] = &[
+    ("churn", "churn_rate"),
+    ("smells", "code_smell_density"),
+    ("smell_density", "code_smell_density"),
+    ("coupling", "coupling_index"),
+    ("co_change", "change_coupling"),
+    ("coverage_gap", "test_coverage_gap"),
+    ("knowledge", "knowledge_concentration"),
+    ("complexity", "cyclomatic_complexity"),
+    ("staleness", "decision_staleness"),
+];
+
+const COMPONENT_KEYS: [&str; 8] = [
+    "churn_rate",
+    "code_smell_density",
+    "coupling_index",
+    "change_coupling",
+    "test_coverage_gap",
+    "knowledge_concentration",
+    "cyclomatic_complexity",
+    "decision_staleness",
+];
+
+/// A parsed `customFormula`: arithmetic (`+ - * / ^`, parentheses, numbers)
+/// over component raw scores. Identifiers are resolved when parsing, so an
+/// accepted formula can always be evaluated.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Formula {
+    expr: Expr,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    Number(f64),
+    Component(&'static str),
+    Neg(Box<Expr>),
+    Binary(char, Box<Expr>, Box<Expr>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Ident(String),
+    Op(char),
+}
+
+impl Formula {
+    pub fn parse(source: &str) -> Result<Self, String> {
+        if source.len() > MAX_FORMULA_LENGTH {
+            return Err(format!("INVALID_FORMULA: longer than {MAX_FORMULA_LENGTH} characters"));
+        }
+        let tokens = tokenize(source)?;
+        let mut parser = Parser { tokens: &tokens, pos: 0 };
+        let expr = parser.expr()?;
+        if let Some(token) = tokens.get(parser.pos) {
+            return Err(format!("INVALID_FORMULA: unexpected {token:?}"));
+        }
+        Ok(Formula { expr })
+    }
+
+    /// Composite for `components`, clamped to 0–100. Non-finite results
+    /// (division by zero) score 0.
+    pub fn evaluate(&self, components: &ScoreComponents) -> f64 {
+        let named = components.named();
+        let raw = |key: &str| {
+            named
+                .iter()
+                .find(|(name, _)| *name == key)
+                .map_or(0.0, |(_, component)| component.raw_score)
+        };
+        let value = eval(&self.expr, &raw);
+        if value.is_finite() {
+            value.clamp(0.0, 100.0)
+        } else {
+            0.0
+        }
+    }
+}
+
+fn eval(expr: &Expr, raw: &dyn Fn(&str) -> f64) -> f64 {
+    match expr {
+        Expr::Number(value) => *value,
+        Expr::Component(key) => raw(key),
+        Expr::Neg(inner) => -eval(inner, raw),
+        Expr::Binary(op, lhs, rhs) => {
+            let (lhs, rhs) = (eval(lhs, raw), eval(rhs, raw));
+            match op {
+                '+' => lhs + rhs,
+                '-' => lhs - rhs,
+                '*' => lhs * rhs,
+                '/' => lhs / rhs,
+                _ => lhs.powf(rhs),
+            }
+        }
+    }
+}
+
+fn resolve_component(name: &str) -> Option<&'static str> {
+    COMPONENT_KEYS.iter().copied().find(|key| *key == name).or_else(|| {
+        COMPONENT_ALIASES
+            .iter()
+            .find(|(alias, _)| *alias == name)
+            .map(|(_, key)| *key)
+    })
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if "+-*/^()".contains(c) {
+            tokens.push(Token::Op(c));
+            i += 1;
+        } else if c.is_ascii_digit() || c == '.' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                i += 1;
+            }
+            let literal: String = chars[start..i].iter().collect();
+            let value = literal
+                .parse()
+                .map_err(|_| format!("INVALID_FORMULA: bad number '{literal}'"))?;
+            tokens.push(Token::Number(value));
+        } else if c.is_ascii_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            tokens.push(Token::Ident(chars[start..i].iter().collect()));
+        } else {
+            return Err(format!("INVALID_FORMULA: unexpected character '{c}'"));
+        }
+    }
+    Ok(tokens)
+}
+
+/// Recursive descent; `^` binds tighter than unary minus and is right-associative
+struct Parser<'a> {
+    tokens: &'a [Token],
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn peek_op(&self) -> Option<char> {
+        match self.tokens.get(self.pos) {
+            Some(Token::Op(op)) => Some(*op),
+            _ => None,
+        }
+    }
+
+    fn expr(&mut self) -> Result<Expr, String> {
+        let mut lhs = self.term()?;
+        while let Some(op @ ('+' | '-')) = self.peek_op() {
+            self.pos += 1;
+            lhs = Expr::Binary(op, Box::new(lhs), Box::new(self.term()?));
+        }
+        Ok(lhs)
+    }
+
+    fn term(&mut self) -> Result<Expr, String> {
+        let mut lhs = self.unary()?;
+        while let Some(op @ ('*' | '/')) = self.peek_op() {
+            self.pos += 1;
+            lhs = Expr::Binary(op, Box::new(lhs), Box::new(self.unary()?));
+        }
+        Ok(lhs)
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        if self.peek_op() == Some('-') {
+            self.pos += 1;
+            return Ok(Expr::Neg(Box::new(self.unary()?)));
+        }
+        let base = self.primary()?;
+        if self.peek_op() == Some('^') {
+            self.pos += 1;
+            return Ok(Expr::Binary('^', Box::new(base), Box::new(self.unary()?)));
+        }
+        Ok(base)
+    }
+
+    fn primary(&mut self) -> Result<Expr, String> {
+        let token = self
+            .tokens
+            .get(self.pos)
+            .ok_or("INVALID_FORMULA: unexpected end of formula".to_string())?;
+        self.pos += 1;
+        match token {
+            Token::Number(value) => Ok(Expr::Number(*value)),
+            Token::Ident(name) => resolve_component(name)
+                .map(Expr::Component)
+                .ok_or(format!("INVALID_FORMULA: unknown identifier '{name}'")),
+            Token::Op('(') => {
+                let inner = self.expr()?;
+                if self.peek_op() != Some(')') {
+                    return Err("INVALID_FORMULA: missing ')'".to_string());
+                }
+                self.pos += 1;
+                Ok(inner)
+            }
+            Token::Op(op) => Err(format!("INVALID_FORMULA: unexpected '{op}'")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_precedence_and_rejects_unknown_identifiers() {
+        let number = |source: &str| eval(&Formula::parse(source).expect("valid formula").expr, &|_| 0.0);
+        assert_eq!(number("1 + 2 * 3"), 7.0);
+        assert_eq!(number("(1 + 2) * 3"), 9.0);
+        assert_eq!(number("2 ^ 3 ^ 2"), 512.0);
+        assert_eq!(number("-2 ^ 2"), -4.0);
+
+        let churn_squared = Formula::parse("churn^2 * coverage_gap").expect("aliases resolve");
+        assert_eq!(
+            churn_squared.expr,
+            Expr::Binary(
+                '*',
+                Box::new(Expr::Binary('^', Box::new(Expr::Component("churn_rate")), Box::new(Expr::Number(2.0)))),
+                Box::new(Expr::Component("test_coverage_gap")),
+            )
+        );
+
+        let err = Formula::parse("churn_rate + bogus").unwrap_err();
+        assert!(err.contains("unknown identifier 'bogus'"), "{err}");
+        assert!(Formula::parse("churn_rate +").is_err());
+        assert!(Formula::parse("(churn_rate").is_err());
+        assert!(Formula::parse("churn_rate; drop").is_err());
+    }
+}
@@ -4,3 +4,5 @@ pub mod snapshot;
 pub mod register;
 pub mod budget;
 pub mod coupling;
+pub mod baseline;
+pub mod formula;
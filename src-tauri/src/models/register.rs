@@ -19,3 +19,172 @@ pub struct RegisterItem {
     pub linked_commit: Option<String>,
     pub notes: Option<String>,
 }
+
+pub const SEVERITIES: [&str; 4] = ["low", "medium", "high", "critical"];
+pub const ITEM_TYPES: [&str; 7] = [
+    "design",
+    "code",
+    "test",
+    "dependency",
+    "documentation",
+    "security",
+    "performance",
+];
+pub const STATUSES: [&str; 5] = ["open", "in_progress", "resolved", "deferred", "accepted"];
+
+/// Check the fields the `debt_register` CHECK constraints enforce, with
+/// messages fit for showing to a user.
+pub fn validate_register_item(item: &RegisterItem) -> Result<(), String> {
+    if item.title.trim().is_empty() {
+        return Err("title must not be empty".to_string());
+    }
+    if !SEVERITIES.contains(&item.severity.as_str()) {
+        return Err(format!(
+            "severity '{}' must be one of: {}",
+            item.severity,
+            SEVERITIES.join(", ")
+        ));
+    }
+    if !ITEM_TYPES.contains(&item.item_type.as_str()) {
+        return Err(format!(
+            "item_type '{}' must be one of: {}",
+            item.item_type,
+            ITEM_TYPES.join(", ")
+        ));
+    }
+    if !STATUSES.contains(&item.status.as_str()) {
+        return Err(format!(
+            "status '{}' must be one of: {}",
+            item.status,
+            STATUSES.join(", ")
+        ));
+    }
+    Ok(())
+}
+
+/// Outcome of importing one row of a bulk register import
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegisterImportRow {
+    pub index: usize,
+    pub id: Option<String>,
+    pub success: bool,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegisterImportReport {
+    pub imported: usize,
+    pub failed: usize,
+    pub rows: Vec<RegisterImportRow>,
+}
+
+/// A register item in the activity feed, with what last happened to it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegisterActivity {
+    #[serde(flatten)]
+    pub item: RegisterItem,
+    /// "created" | "updated"
+    pub activity: String,
+}
+
+impl RegisterActivity {
+    pub fn from_item(item: RegisterItem) -> Self {
+        let activity = if item.updated_at > item.created_at { "updated" } else { "created" };
+        RegisterActivity {
+            activity: activity.to_string(),
+            item,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegisterPriority {
+    pub item: RegisterItem,
+    pub severity_weight: f64,
+    pub file_score: Option<f64>,
+    pub priority_score: f64,
+}
+
+/// Numeric weight for a register severity (low=1, medium=3, high=8, critical=20)
+pub fn severity_weight(severity: &str) -> f64 {
+    match severity {
+        "low" => 1.0,
+        "medium" => 3.0,
+        "high" => 8.0,
+        "critical" => 20.0,
+        _ => 1.0,
+    }
+}
+
+/// Combine severity with the linked file's composite score (0–100).
+/// A file at score 100 doubles the item's priority; unlinked items use severity alone.
+pub fn priority_score(severity: &str, file_score: Option<f64>) -> f64 {
+    let multiplier = 1.0 + file_score.unwrap_or(0.0).clamp(0.0, 100.0) / 100.0;
+    severity_weight(severity) * multiplier
+}
+
+/// Register items that look like the same piece of debt
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DuplicateCluster {
+    pub items: Vec<RegisterItem>,
+}
+
+/// Filler words ignored when comparing titles
+const TITLE_STOPWORDS: [&str; 9] = ["a", "an", "the", "of", "to", "in", "on", "for", "and"];
+
+/// Lowercased alphanumeric words of a title, minus `TITLE_STOPWORDS`
+pub fn title_tokens(title: &str) -> std::collections::BTreeSet<String> {
+    title
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty() && !TITLE_STOPWORDS.contains(word))
+        .map(str::to_string)
+        .collect()
+}
+
+/// Jaccard overlap (0–1) of two titles' tokens
+pub fn title_similarity(a: &str, b: &str) -> f64 {
+    let (a, b) = (title_tokens(a), title_tokens(b));
+    let union = a.union(&b).count();
+    if union == 0 {
+        return 0.0;
+    }
+    a.intersection(&b).count() as f64 / union as f64
+}
+
+/// Group items whose titles overlap by at least `similarity` or that point at
+/// the same file. Groups are transitive; only groups of two or more are
+/// returned, largest first.
+pub fn find_duplicate_clusters(items: Vec<RegisterItem>, similarity: f64) -> Vec<DuplicateCluster> {
+    fn root(parent: &mut [usize], mut i: usize) -> usize {
+        while parent[i] != i {
+            parent[i] = parent[parent[i]];
+            i = parent[i];
+        }
+        i
+    }
+
+    let mut parent: Vec<usize> = (0..items.len()).collect();
+    for i in 0..items.len() {
+        for j in i + 1..items.len() {
+            let same_file = items[i].file_path.is_some() && items[i].file_path == items[j].file_path;
+            if same_file || title_similarity(&items[i].title, &items[j].title) >= similarity {
+                let (a, b) = (root(&mut parent, i), root(&mut parent, j));
+                parent[b] = a;
+            }
+        }
+    }
+
+    let mut groups: std::collections::BTreeMap<usize, Vec<RegisterItem>> = std::collections::BTreeMap::new();
+    for (i, item) in items.into_iter().enumerate() {
+        let group = root(&mut parent, i);
+        groups.entry(group).or_default().push(item);
+    }
+    let mut clusters: Vec<DuplicateCluster> = groups
+        .into_values()
+        .filter(|items| items.len() > 1)
+        .map(|items| DuplicateCluster { items })
+        .collect();
+    clusters.sort_by_key(|cluster| std::cmp::Reverse(cluster.items.len()));
+    clusters
+}
@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DebtSnapshot {
@@ -10,3 +11,539 @@ pub struct DebtSnapshot {
     pub commit_count_week: usize,
     pub snapshot_metadata: Option<String>, // JSON string
 }
+
+/// One entry of a snapshot's embedded per-file metadata
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotFileSummary {
+    pub path: String,
+    #[serde(default)]
+    pub score: f64,
+}
+
+/// Object form of snapshot metadata: the per-file list plus workspace-wide
+/// smell totals taken at snapshot time
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SnapshotMetadata {
+    #[serde(default)]
+    pub files: Vec<SnapshotFileSummary>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub smells: Option<BTreeMap<String, usize>>,
+    /// Workspace line coverage percentage from coverage reports
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub coverage: Option<f64>,
+}
+
+/// Metadata as stored: older snapshots hold just the file list
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StoredMetadata {
+    Files(Vec<SnapshotFileSummary>),
+    Full(SnapshotMetadata),
+}
+
+fn parse_metadata(json: &str) -> Option<SnapshotMetadata> {
+    match serde_json::from_str(json).ok()? {
+        StoredMetadata::Files(files) => Some(SnapshotMetadata {
+            files,
+            smells: None,
+            coverage: None,
+        }),
+        StoredMetadata::Full(metadata) => Some(metadata),
+    }
+}
+
+/// Per-file entries embedded in a snapshot; empty when it has none or the
+/// metadata is not a file list
+pub fn snapshot_files(snapshot: &DebtSnapshot) -> Vec<SnapshotFileSummary> {
+    snapshot
+        .snapshot_metadata
+        .as_deref()
+        .and_then(parse_metadata)
+        .map(|metadata| metadata.files)
+        .unwrap_or_default()
+}
+
+/// Smell totals embedded in a snapshot, if it recorded any
+pub fn snapshot_smells(snapshot: &DebtSnapshot) -> Option<BTreeMap<String, usize>> {
+    snapshot
+        .snapshot_metadata
+        .as_deref()
+        .and_then(parse_metadata)
+        .and_then(|metadata| metadata.smells)
+}
+
+/// Workspace coverage percentage embedded in a snapshot, if it recorded one
+pub fn snapshot_coverage(snapshot: &DebtSnapshot) -> Option<f64> {
+    snapshot
+        .snapshot_metadata
+        .as_deref()
+        .and_then(parse_metadata)
+        .and_then(|metadata| metadata.coverage)
+}
+
+/// Apply `update` to snapshot metadata, keeping its file list. Metadata in
+/// an unrecognized shape is returned untouched.
+fn update_metadata(metadata_json: Option<String>, update: impl FnOnce(&mut SnapshotMetadata)) -> Option<String> {
+    let mut metadata = match metadata_json.as_deref() {
+        None => SnapshotMetadata::default(),
+        Some(json) => match parse_metadata(json) {
+            Some(metadata) => metadata,
+            None => return metadata_json,
+        },
+    };
+    update(&mut metadata);
+    serde_json::to_string(&metadata).ok().or(metadata_json)
+}
+
+/// Replace the per-file list in snapshot metadata
+pub fn embed_files(metadata_json: Option<String>, files: Vec<SnapshotFileSummary>) -> Option<String> {
+    update_metadata(metadata_json, |metadata| metadata.files = files)
+}
+
+/// Add smell totals to snapshot metadata
+pub fn embed_smell_counts(metadata_json: Option<String>, smells: BTreeMap<String, usize>) -> Option<String> {
+    update_metadata(metadata_json, |metadata| metadata.smells = Some(smells))
+}
+
+/// Add the workspace coverage percentage to snapshot metadata
+pub fn embed_coverage(metadata_json: Option<String>, coverage_percent: f64) -> Option<String> {
+    update_metadata(metadata_json, |metadata| metadata.coverage = Some(coverage_percent))
+}
+
+/// One snapshot's total for a single smell kind
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SmellTrendPoint {
+    pub snapshot_id: i64,
+    pub timestamp: i64,
+    pub count: usize,
+}
+
+/// `smell_type` totals across `snapshots`, skipping snapshots taken before
+/// smell totals were recorded
+pub fn smell_trend(snapshots: &[DebtSnapshot], smell_type: &str) -> Vec<SmellTrendPoint> {
+    snapshots
+        .iter()
+        .filter_map(|snapshot| {
+            let smells = snapshot_smells(snapshot)?;
+            Some(SmellTrendPoint {
+                snapshot_id: snapshot.id,
+                timestamp: snapshot.timestamp,
+                count: smells.get(smell_type).copied().unwrap_or(0),
+            })
+        })
+        .collect()
+}
+
+/// One snapshot's workspace coverage percentage
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CoverageTrendPoint {
+    pub snapshot_id: i64,
+    pub timestamp: i64,
+    pub coverage_percent: f64,
+}
+
+/// Coverage across `snapshots`, skipping snapshots taken without coverage
+/// reports or before coverage was recorded
+pub fn coverage_trend(snapshots: &[DebtSnapshot]) -> Vec<CoverageTrendPoint> {
+    snapshots
+        .iter()
+        .filter_map(|snapshot| {
+            Some(CoverageTrendPoint {
+                snapshot_id: snapshot.id,
+                timestamp: snapshot.timestamp,
+                coverage_percent: snapshot_coverage(snapshot)?,
+            })
+        })
+        .collect()
+}
+
+/// Default `regressionAlertDelta`: score points a new snapshot may rise over
+/// the previous one before a regression alert fires.
+pub const DEFAULT_REGRESSION_ALERT_DELTA: f64 = 5.0;
+
+/// Regressed files listed in a regression alert
+pub const REGRESSION_ALERT_TOP_FILES: usize = 5;
+
+/// A file whose score rose between two snapshots
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegressedFile {
+    pub path: String,
+    pub previous_score: f64,
+    pub current_score: f64,
+    pub delta: f64,
+}
+
+/// Payload of the `debt_regression_alert` event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegressionAlert {
+    pub previous_score: f64,
+    pub current_score: f64,
+    pub delta: f64,
+    pub top_files: Vec<RegressedFile>,
+}
+
+/// Alert when `current` scores more than `min_delta` above `previous`. The
+/// top files are those in `current`'s metadata whose score rose the most; a
+/// file missing from `previous` counts as having scored 0.
+pub fn regression_alert(
+    previous: &DebtSnapshot,
+    current: &DebtSnapshot,
+    min_delta: f64,
+) -> Option<RegressionAlert> {
+    let delta = current.composite_score - previous.composite_score;
+    if delta <= min_delta {
+        return None;
+    }
+
+    let before: std::collections::HashMap<String, f64> = snapshot_files(previous)
+        .into_iter()
+        .map(|file| (file.path, file.score))
+        .collect();
+    let mut top_files: Vec<RegressedFile> = snapshot_files(current)
+        .into_iter()
+        .filter_map(|file| {
+            let previous_score = before.get(&file.path).copied().unwrap_or(0.0);
+            (file.score > previous_score).then_some(RegressedFile {
+                delta: file.score - previous_score,
+                previous_score,
+                current_score: file.score,
+                path: file.path,
+            })
+        })
+        .collect();
+    top_files.sort_by(|a, b| {
+        b.delta
+            .partial_cmp(&a.delta)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| a.path.cmp(&b.path))
+    });
+    top_files.truncate(REGRESSION_ALERT_TOP_FILES);
+
+    Some(RegressionAlert {
+        previous_score: previous.composite_score,
+        current_score: current.composite_score,
+        delta,
+        top_files,
+    })
+}
+
+/// Days between automatic snapshots for a `snapshotSchedule` value;
+/// `None` for manual.
+pub fn snapshot_interval_days(schedule: &str) -> Option<i64> {
+    match schedule {
+        "weekly" => Some(7),
+        "biweekly" => Some(14),
+        _ => None,
+    }
+}
+
+/// Whether a scheduled snapshot is due given the last snapshot time
+pub fn snapshot_due(schedule: &str, last_timestamp: Option<i64>, now: i64) -> bool {
+    match (snapshot_interval_days(schedule), last_timestamp) {
+        (None, _) => false,
+        (Some(_), None) => true,
+        (Some(days), Some(last)) => now - last >= days * 86400,
+    }
+}
+
+/// Snapshot whose timestamp is closest to `timestamp`; on a tie the earlier
+/// one wins, so a point midway between two snapshots reports the state before
+pub fn nearest_snapshot(snapshots: &[DebtSnapshot], timestamp: i64) -> Option<&DebtSnapshot> {
+    snapshots
+        .iter()
+        .min_by_key(|snapshot| ((snapshot.timestamp - timestamp).abs(), snapshot.timestamp))
+}
+
+/// A snapshot with its trailing moving-average score
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SmoothedSnapshot {
+    #[serde(flatten)]
+    pub snapshot: DebtSnapshot,
+    pub smoothed_score: f64,
+}
+
+/// Attach a trailing moving average over `window` points. Early points
+/// (and windows longer than the series) average whatever history exists.
+pub fn smooth_snapshots(snapshots: Vec<DebtSnapshot>, window: usize) -> Vec<SmoothedSnapshot> {
+    let window = window.max(1);
+    let scores: Vec<f64> = snapshots.iter().map(|s| s.composite_score).collect();
+    snapshots
+        .into_iter()
+        .enumerate()
+        .map(|(i, snapshot)| {
+            let start = (i + 1).saturating_sub(window);
+            let points = &scores[start..=i];
+            SmoothedSnapshot {
+                smoothed_score: points.iter().sum::<f64>() / points.len() as f64,
+                snapshot,
+            }
+        })
+        .collect()
+}
+
+/// Least-squares trend of the composite score over a snapshot window
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DebtVelocity {
+    pub snapshot_count: usize,
+    /// False with fewer than two snapshots spanning some time; the other
+    /// trend fields are then zero/empty
+    pub sufficient_data: bool,
+    /// Fitted change in composite score per week (positive = accruing debt)
+    pub points_per_week: f64,
+    pub current_score: Option<f64>,
+    pub target_score: f64,
+    /// Weeks until the trend reaches `target_score`, if it is heading there
+    pub weeks_to_target: Option<f64>,
+    pub projected_crossing_at: Option<i64>,
+}
+
+const SECONDS_PER_WEEK: f64 = 7.0 * 86400.0;
+
+/// Fit a line through the snapshot scores and project when it reaches
+/// `target_score` from the latest snapshot, if the trend is moving toward it.
+pub fn debt_velocity(snapshots: &[DebtSnapshot], target_score: f64) -> DebtVelocity {
+    let latest = snapshots.iter().max_by_key(|s| s.timestamp);
+    let mut velocity = DebtVelocity {
+        snapshot_count: snapshots.len(),
+        sufficient_data: false,
+        points_per_week: 0.0,
+        current_score: latest.map(|s| s.composite_score),
+        target_score,
+        weeks_to_target: None,
+        projected_crossing_at: None,
+    };
+    let Some(latest) = latest else {
+        return velocity;
+    };
+
+    let n = snapshots.len() as f64;
+    let weeks: Vec<f64> = snapshots
+        .iter()
+        .map(|s| (s.timestamp - latest.timestamp) as f64 / SECONDS_PER_WEEK)
+        .collect();
+    let mean_x = weeks.iter().sum::<f64>() / n;
+    let mean_y = snapshots.iter().map(|s| s.composite_score).sum::<f64>() / n;
+    let variance: f64 = weeks.iter().map(|x| (x - mean_x).powi(2)).sum();
+    if snapshots.len() < 2 || variance <= f64::EPSILON {
+        return velocity;
+    }
+    let covariance: f64 = weeks
+        .iter()
+        .zip(snapshots)
+        .map(|(x, s)| (x - mean_x) * (s.composite_score - mean_y))
+        .sum();
+    let slope = covariance / variance;
+    velocity.sufficient_data = true;
+    velocity.points_per_week = slope;
+
+    let gap = target_score - latest.composite_score;
+    let weeks_to_target = if gap == 0.0 {
+        Some(0.0)
+    } else if slope.abs() > f64::EPSILON && gap.signum() == slope.signum() {
+        Some(gap / slope)
+    } else {
+        None
+    };
+    velocity.weeks_to_target = weeks_to_target;
+    velocity.projected_crossing_at =
+        weeks_to_target.map(|weeks| latest.timestamp + (weeks * SECONDS_PER_WEEK).round() as i64);
+    velocity
+}
+
+/// How long the composite score takes to halve, fitted over the snapshot trend
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DebtHalfLife {
+    pub snapshot_count: usize,
+    /// False with fewer than two snapshots spanning some time
+    pub sufficient_data: bool,
+    /// "exponential", "linear" or "none", whichever fits the scores closer
+    pub fit: String,
+    /// Fitted change per week from the latest score (negative = paying down)
+    pub points_per_week: f64,
+    pub current_score: Option<f64>,
+    /// Weeks for the latest score to halve, when the trend is declining
+    pub half_life_weeks: Option<f64>,
+    /// The trend is rising, so the score never halves
+    pub diverging: bool,
+    pub warning: Option<String>,
+}
+
+/// Least-squares `(intercept, slope)` of `ys` over `xs`, or `None` when `xs`
+/// has no spread
+fn linear_fit(xs: &[f64], ys: &[f64]) -> Option<(f64, f64)> {
+    let n = xs.len() as f64;
+    let mean_x = xs.iter().sum::<f64>() / n;
+    let mean_y = ys.iter().sum::<f64>() / n;
+    let variance: f64 = xs.iter().map(|x| (x - mean_x).powi(2)).sum();
+    if xs.len() < 2 || variance <= f64::EPSILON {
+        return None;
+    }
+    let covariance: f64 = xs.iter().zip(ys).map(|(x, y)| (x - mean_x) * (y - mean_y)).sum();
+    let slope = covariance / variance;
+    Some((mean_y - slope * mean_x, slope))
+}
+
+/// Fit both a line and an exponential (a line through ln(score)) and keep the
+/// one with the smaller squared error against the actual scores. Scores at or
+/// below zero rule out the exponential. A declining fit yields the weeks for
+/// the latest score to halve; a rising one is flagged as diverging. Noisy,
+/// non-monotonic series are still fitted; only the overall trend matters.
+pub fn debt_halflife(snapshots: &[DebtSnapshot]) -> DebtHalfLife {
+    let latest = snapshots.iter().max_by_key(|s| s.timestamp);
+    let mut halflife = DebtHalfLife {
+        snapshot_count: snapshots.len(),
+        sufficient_data: false,
+        fit: "none".to_string(),
+        points_per_week: 0.0,
+        current_score: latest.map(|s| s.composite_score),
+        half_life_weeks: None,
+        diverging: false,
+        warning: None,
+    };
+    let Some(latest) = latest else {
+        return halflife;
+    };
+
+    let weeks: Vec<f64> = snapshots
+        .iter()
+        .map(|s| (s.timestamp - latest.timestamp) as f64 / SECONDS_PER_WEEK)
+        .collect();
+    let scores: Vec<f64> = snapshots.iter().map(|s| s.composite_score).collect();
+    let Some((intercept, slope)) = linear_fit(&weeks, &scores) else {
+        return halflife;
+    };
+    halflife.sufficient_data = true;
+
+    let squared_error = |predict: &dyn Fn(f64) -> f64| {
+        weeks.iter().zip(&scores).map(|(x, y)| (predict(*x) - y).powi(2)).sum::<f64>()
+    };
+    let linear_error = squared_error(&|x| intercept + slope * x);
+    let exponential = if scores.iter().all(|score| *score > 0.0) {
+        let logs: Vec<f64> = scores.iter().map(|score| score.ln()).collect();
+        linear_fit(&weeks, &logs)
+            .map(|(log_intercept, rate)| (log_intercept, rate, squared_error(&|x| (log_intercept + rate * x).exp())))
+    } else {
+        None
+    };
+
+    let current = latest.composite_score;
+    match exponential {
+        Some((log_intercept, rate, error)) if error <= linear_error => {
+            halflife.fit = "exponential".to_string();
+            // Derivative of the fitted curve at the latest snapshot (x = 0)
+            halflife.points_per_week = rate * log_intercept.exp();
+            if rate < -f64::EPSILON {
+                halflife.half_life_weeks = Some(std::f64::consts::LN_2 / -rate);
+            }
+        }
+        _ => {
+            halflife.fit = "linear".to_string();
+            halflife.points_per_week = slope;
+            if slope < -f64::EPSILON && current > 0.0 {
+                halflife.half_life_weeks = Some(current / 2.0 / -slope);
+            }
+        }
+    }
+
+    if halflife.points_per_week > f64::EPSILON {
+        halflife.diverging = true;
+        halflife.warning = Some(format!(
+            "Debt is rising by {:.2} points per week; the score is diverging and will not halve",
+            halflife.points_per_week
+        ));
+    } else if halflife.half_life_weeks.is_none() {
+        halflife.warning = Some("Debt is flat; no half-life can be estimated".to_string());
+    }
+    halflife
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(timestamp: i64, composite_score: f64) -> DebtSnapshot {
+        DebtSnapshot {
+            id: timestamp,
+            timestamp,
+            composite_score,
+            file_count: 1,
+            high_debt_count: 0,
+            commit_count_week: 0,
+            snapshot_metadata: None,
+        }
+    }
+
+    #[test]
+    fn rising_series_has_positive_velocity_and_projects_crossing() {
+        let week = 7 * 86400;
+        let series: Vec<DebtSnapshot> = (0..5).map(|i| snapshot(i * week, 40.0 + 2.0 * i as f64)).collect();
+
+        let velocity = debt_velocity(&series, 65.0);
+
+        assert!(velocity.sufficient_data);
+        assert!((velocity.points_per_week - 2.0).abs() < 1e-9);
+        assert_eq!(velocity.current_score, Some(48.0));
+        let weeks = velocity.weeks_to_target.expect("crossing projected");
+        assert!((weeks - 8.5).abs() < 1e-9);
+        assert_eq!(velocity.projected_crossing_at, Some(4 * week + (8.5 * week as f64) as i64));
+
+        let receding = debt_velocity(&series, 30.0);
+        assert_eq!(receding.weeks_to_target, None);
+
+        let single = debt_velocity(&series[..1], 65.0);
+        assert!(!single.sufficient_data);
+        assert_eq!(single.points_per_week, 0.0);
+    }
+
+    #[test]
+    fn trailing_moving_average_matches_hand_computed_values() {
+        let series = vec![snapshot(1, 10.0), snapshot(2, 20.0), snapshot(3, 60.0), snapshot(4, 30.0)];
+
+        let smoothed: Vec<f64> = smooth_snapshots(series.clone(), 3)
+            .iter()
+            .map(|s| s.smoothed_score)
+            .collect();
+        assert_eq!(smoothed, vec![10.0, 15.0, 30.0, 110.0 / 3.0]);
+
+        let wide: Vec<f64> = smooth_snapshots(series, 10).iter().map(|s| s.smoothed_score).collect();
+        assert_eq!(wide, vec![10.0, 15.0, 30.0, 30.0]);
+    }
+
+    #[test]
+    fn embedding_smells_keeps_the_file_list() {
+        let mut smells = BTreeMap::new();
+        smells.insert("todo_fixme".to_string(), 4);
+        let mut with_smells = snapshot(1, 40.0);
+        with_smells.snapshot_metadata =
+            embed_smell_counts(Some(r#"[{"path":"src/a.rs","score":40.0}]"#.to_string()), smells);
+
+        assert_eq!(snapshot_files(&with_smells)[0].path, "src/a.rs");
+        assert_eq!(smell_trend(&[snapshot(0, 30.0), with_smells], "todo_fixme")[0].count, 4);
+    }
+
+    #[test]
+    fn exponentially_decaying_series_has_matching_half_life() {
+        let week = 7 * 86400;
+        // Halves every 4 weeks, with a little noise
+        let series: Vec<DebtSnapshot> = (0..9)
+            .map(|i| {
+                let noise = if i % 2 == 0 { 0.3 } else { -0.3 };
+                snapshot(i * week, 80.0 * 0.5f64.powf(i as f64 / 4.0) + noise)
+            })
+            .collect();
+
+        let halflife = debt_halflife(&series);
+
+        assert!(halflife.sufficient_data);
+        assert_eq!(halflife.fit, "exponential");
+        assert!(!halflife.diverging);
+        let weeks = halflife.half_life_weeks.expect("half-life estimated");
+        assert!((weeks - 4.0).abs() < 0.5, "half-life {weeks}");
+
+        let rising: Vec<DebtSnapshot> = (0..4).map(|i| snapshot(i * week, 40.0 + 3.0 * i as f64)).collect();
+        let diverging = debt_halflife(&rising);
+        assert!(diverging.diverging);
+        assert_eq!(diverging.half_life_weeks, None);
+        assert!(diverging.warning.is_some());
+    }
+}
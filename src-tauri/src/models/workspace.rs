@@ -8,3 +8,35 @@ pub struct WorkspaceMeta {
     pub file_count: usize,
     pub last_analysis_at: Option<i64>,
 }
+
+/// A single problem found in a workspace's `.debtengine` directory
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkspaceIssue {
+    /// "database" or "settings"
+    pub area: String,
+    pub message: String,
+    pub repairable: bool,
+}
+
+/// Stored vs. supported schema versions of a workspace's `.debtengine` files
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VersionInfo {
+    /// `state.db` `user_version`; `None` when the database is missing
+    pub db_version: Option<i64>,
+    pub expected_db_version: i64,
+    /// settings.json `schema_version`; `None` when the file is missing
+    pub settings_version: Option<i64>,
+    pub expected_settings_version: i64,
+    /// Either file is older than this build and will be migrated on open
+    pub needs_migration: bool,
+    /// Either file was written by a newer build
+    pub is_future: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkspaceValidation {
+    pub valid: bool,
+    pub issues: Vec<WorkspaceIssue>,
+    pub can_auto_repair: bool,
+    pub repaired: bool,
+}
@@ -5,13 +5,35 @@ use std::path::Path;
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
 use tempfile::TempDir;
-use debtlens_lib::commands::db::{budget_crud, register_crud, watchlist_crud};
-use debtlens_lib::commands::git::open_workspace;
-use debtlens_lib::commands::scoring::{reanalyze_file_internal, run_full_analysis_internal};
-use debtlens_lib::commands::settings::{get_settings, save_settings};
+use debtlens_lib::analysis::churn::{analyze_churn, analyze_churn_range, count_recent_commits};
+use debtlens_lib::analysis::coupling::analyze_co_changes;
+use debtlens_lib::analysis::knowledge::analyze_knowledge;
+use debtlens_lib::commands::ast::run_ast_analysis;
+use debtlens_lib::commands::db::{
+    budget_crud, find_duplicate_register_items, get_coverage_trend, get_db_connection, get_debt_snapshots,
+    get_recent_register_activity, get_smell_trend, get_snapshot_near, insert_snapshot, register_crud,
+    take_snapshot_internal, watchlist_crud,
+};
+use debtlens_lib::commands::git::{
+    get_churn_acceleration, get_file_churn_detail, get_line_blame, get_relocated_files_internal, get_version_info,
+    open_workspace, validate_workspace,
+};
+use debtlens_lib::commands::scoring::{
+    analyze_dirty_files_internal, capture_baseline_internal, diff_against_baseline_internal,
+    get_blast_radius_internal, get_change_couplings_internal, get_function_complexity_distribution_internal, get_stale_adrs_internal,
+    get_analysis_state_internal, get_coupling_centrality_internal, get_file_breakdown_internal,
+    get_file_detail_internal, get_score_matrix_internal,
+    get_uncovered_files_internal, load_cache_from_db_internal, maybe_take_scheduled_snapshot_internal,
+    reanalyze_file_internal, reconcile_coupling_internal, refresh_staleness_internal, run_full_analysis_internal,
+    run_full_analysis_with_events, run_full_analysis_with_weights_internal, run_quick_scan_internal,
+};
+use debtlens_lib::commands::settings::{
+    get_settings, load_effective_analysis_settings, save_settings,
+};
 use debtlens_lib::models::budget::DebtBudget;
 use debtlens_lib::models::file_score::AnalysisCache;
 use debtlens_lib::models::register::RegisterItem;
+use debtlens_lib::models::snapshot::{embed_coverage, snapshot_files, DebtSnapshot};
 
 fn create_workspace_with_git_repo() -> (TempDir, String, String) {
     let temp_dir = tempfile::tempdir().expect("create temp dir");
@@ -41,6 +63,29 @@ fn create_workspace_with_git_repo() -> (TempDir, String, String) {
     )
 }
 
+/// Open register item created now, optionally tied to a workspace file
+fn register_item(id: &str, file_path: Option<&str>) -> RegisterItem {
+    let now = chrono::Utc::now().timestamp();
+    RegisterItem {
+        id: id.to_string(),
+        created_at: now,
+        updated_at: now,
+        title: format!("Item {id}"),
+        description: String::new(),
+        file_path: file_path.map(str::to_string),
+        severity: "medium".to_string(),
+        item_type: "code".to_string(),
+        owner: None,
+        target_sprint: None,
+        estimated_hours: None,
+        actual_hours: None,
+        status: "open".to_string(),
+        tags: vec![],
+        linked_commit: None,
+        notes: None,
+    }
+}
+
 #[tokio::test]
 async fn open_workspace_returns_expected_metadata_contract() {
     let (_tmp, workspace_path, _file_path) = create_workspace_with_git_repo();
@@ -116,6 +161,7 @@ async fn register_and_budget_commands_support_full_crud_contract() {
         "create".to_string(),
         Some(register_item.clone()),
         None,
+        None,
     )
     .await
     .expect("create register item");
@@ -126,13 +172,14 @@ async fn register_and_budget_commands_support_full_crud_contract() {
         "read".to_string(),
         None,
         Some(register_item.id.clone()),
+        None,
     )
     .await
     .expect("read register item");
     assert_eq!(read_register["id"], json!(register_item.id.clone()));
     assert_eq!(read_register["title"], json!(register_item.title.clone()));
 
-    let list_register = register_crud(workspace_path.clone(), "list".to_string(), None, None)
+    let list_register = register_crud(workspace_path.clone(), "list".to_string(), None, None, None)
         .await
         .expect("list register items");
     let register_items = list_register.as_array().expect("register list array");
@@ -151,6 +198,7 @@ async fn register_and_budget_commands_support_full_crud_contract() {
             ..register_item.clone()
         }),
         None,
+        None,
     )
     .await
     .expect("update register item");
@@ -163,6 +211,7 @@ async fn register_and_budget_commands_support_full_crud_contract() {
         max_score: 70.0,
         created_at: now,
         notify_on_breach: true,
+        warning_score: None,
     };
 
     let create_budget = budget_crud(
@@ -210,6 +259,41 @@ async fn register_and_budget_commands_support_full_crud_contract() {
     );
 }
 
+#[tokio::test]
+async fn budget_round_trips_custom_warning_score() {
+    let (_tmp, workspace_path, _file_path) = create_workspace_with_git_repo();
+    let budget = DebtBudget {
+        id: "budget-legacy".to_string(),
+        pattern: "legacy/**".to_string(),
+        label: "Legacy".to_string(),
+        max_score: 85.0,
+        created_at: 0,
+        notify_on_breach: false,
+        warning_score: Some(75.0),
+    };
+
+    budget_crud(workspace_path.clone(), "create".to_string(), Some(budget.clone()), None)
+        .await
+        .expect("create budget");
+    let read = budget_crud(workspace_path.clone(), "read".to_string(), None, Some(budget.id.clone()))
+        .await
+        .expect("read budget");
+    assert_eq!(read["warning_score"], json!(75.0));
+
+    budget_crud(
+        workspace_path.clone(),
+        "update".to_string(),
+        Some(DebtBudget { warning_score: None, ..budget.clone() }),
+        None,
+    )
+    .await
+    .expect("update budget");
+    let listed = budget_crud(workspace_path, "list".to_string(), None, None)
+        .await
+        .expect("list budgets");
+    assert_eq!(listed[0]["warning_score"], serde_json::Value::Null);
+}
+
 #[tokio::test]
 async fn watchlist_commands_pin_list_and_unpin_files() {
     let (_tmp, workspace_path, file_path) = create_workspace_with_git_repo();
@@ -278,3 +362,1872 @@ async fn reanalyze_file_updates_cache_and_persisted_mtime() {
     let cached_result = cache_lock.result.as_ref().expect("cached result");
     assert!(cached_result.files.iter().any(|file| file.path == file_path));
 }
+
+#[tokio::test]
+async fn content_only_reanalysis_reuses_cached_git_components() {
+    let (_tmp, workspace_path, file_path) = create_workspace_with_git_repo();
+    open_workspace(workspace_path.clone())
+        .await
+        .expect("open workspace");
+
+    let cache = Arc::new(Mutex::new(AnalysisCache::default()));
+    run_full_analysis_internal(&workspace_path, &cache, |_| {}).expect("run full analysis");
+
+    // Sentinel: a churn score no git scan would produce for this file.
+    {
+        let mut cache_lock = cache.lock().expect("cache lock");
+        let result = cache_lock.result.as_mut().expect("cached result");
+        let file = result
+            .files
+            .iter_mut()
+            .find(|file| file.path == file_path)
+            .expect("cached file");
+        file.components.churn_rate.raw_score = 42.0;
+    }
+
+    std::thread::sleep(Duration::from_secs(1));
+    fs::write(
+        &file_path,
+        "fn main() {\n    // TODO: tidy\n    // FIXME: later\n    println!(\"hello\");\n}\n",
+    )
+    .expect("rewrite source file");
+
+    let updated = reanalyze_file_internal(&workspace_path, &file_path, &cache)
+        .expect("reanalyze changed file");
+    assert_eq!(updated.components.churn_rate.raw_score, 42.0, "churn should not be rescanned");
+    assert_eq!(updated.loc, 5);
+    assert!(updated.components.code_smell_density.raw_score > 0.0);
+}
+
+#[tokio::test]
+async fn content_only_reanalysis_recomputes_import_coupling() {
+    let (tmp, workspace_path, file_path) = create_workspace_with_git_repo();
+    fs::write(tmp.path().join("src/util.rs"), "pub fn helper() {}\n").expect("write util file");
+    let cache = Arc::new(Mutex::new(AnalysisCache::default()));
+    let analyzed = run_full_analysis_internal(&workspace_path, &cache, |_| {}).expect("run full analysis");
+    let before = analyzed.files.iter().find(|f| f.path == file_path).expect("main scored");
+    assert_eq!(before.components.coupling_index.raw_score, 0.0);
+
+    std::thread::sleep(Duration::from_secs(1));
+    fs::write(&file_path, "use crate::util::helper;\n\nfn main() {\n    helper();\n}\n").expect("add import");
+    let updated = reanalyze_file_internal(&workspace_path, &file_path, &cache).expect("reanalyze");
+
+    assert!(updated.components.coupling_index.raw_score > 0.0);
+    assert_eq!(updated.components.coupling_index.details, vec!["1 internal imports, 0 external dependencies"]);
+}
+
+#[tokio::test]
+async fn cache_is_rebuilt_from_persisted_scores() {
+    let (_tmp, workspace_path, file_path) = create_workspace_with_git_repo();
+    let analyzed_cache = Arc::new(Mutex::new(AnalysisCache::default()));
+    let analyzed = run_full_analysis_internal(&workspace_path, &analyzed_cache, |_| {}).expect("run full analysis");
+
+    let cache = Arc::new(Mutex::new(AnalysisCache::default()));
+    let reloaded = load_cache_from_db_internal(&workspace_path, &cache).expect("reload cache");
+    assert_eq!(reloaded.file_count, analyzed.file_count);
+    assert!((reloaded.workspace_score - analyzed.workspace_score).abs() < 1e-9);
+
+    let cache_lock = cache.lock().expect("cache lock");
+    assert_eq!(cache_lock.workspace_path.as_deref(), Some(workspace_path.as_str()));
+    assert!(cache_lock.heatmap.is_some(), "heatmap should be rebuilt");
+    let cached = cache_lock.result.as_ref().expect("cached result");
+    let file = cached.files.iter().find(|f| f.path == file_path).expect("reloaded file");
+    assert!(file.components.code_smell_density.weight > 0.0, "components should be deserialized");
+}
+
+#[tokio::test]
+async fn line_blame_returns_committing_author_and_clamps_range() {
+    let (_tmp, workspace_path, _file_path) = create_workspace_with_git_repo();
+
+    let blame = get_line_blame(workspace_path.clone(), "src/main.rs".to_string(), 1, 500)
+        .await
+        .expect("line blame");
+    assert_eq!(blame.len(), 3, "range should be clamped to the file length");
+    assert_eq!(blame[0].line, 1);
+    assert_eq!(blame[0].author, "Test User");
+    assert_eq!(blame[0].commit_short.len(), 7);
+
+    let missing = get_line_blame(workspace_path, "src/missing.rs".to_string(), 1, 1).await;
+    assert!(missing.is_err());
+}
+
+#[tokio::test]
+async fn file_churn_detail_lists_commits_touching_the_file() {
+    let (_tmp, workspace_path, _file_path) = create_workspace_with_git_repo();
+
+    let detail = get_file_churn_detail(workspace_path.clone(), "src/main.rs".to_string(), 30)
+        .await
+        .expect("file churn detail");
+    assert_eq!(detail.commits.len(), 1);
+    assert_eq!(detail.commits[0].summary, "init");
+    assert_eq!(detail.commits[0].author, "Test User");
+    assert_eq!(detail.commits[0].commit_short.len(), 7);
+    assert!(detail.churn_score > 0.0);
+
+    let untouched = get_file_churn_detail(workspace_path, "src/missing.rs".to_string(), 30)
+        .await
+        .expect("churn detail for untouched path");
+    assert!(untouched.commits.is_empty());
+    assert_eq!(untouched.churn_score, 0.0);
+}
+
+#[tokio::test]
+async fn bare_repository_is_scored_from_tree_blobs() {
+    let temp_dir = tempfile::tempdir().expect("create temp dir");
+    let workspace_path = temp_dir.path().to_string_lossy().to_string();
+
+    let repo = Repository::init_bare(temp_dir.path()).expect("init bare repo");
+    let blob_id = repo
+        .blob(b"fn main() {\n    if true {\n        println!(\"bare\");\n    }\n}\n")
+        .expect("write blob");
+    let mut builder = repo.treebuilder(None).expect("tree builder");
+    builder.insert("main.rs", blob_id, 0o100644).expect("insert blob");
+    let tree_id = builder.write().expect("write tree");
+    let tree = repo.find_tree(tree_id).expect("find tree");
+    let signature = Signature::now("Test User", "test@example.com").expect("signature");
+    repo.commit(Some("HEAD"), &signature, &signature, "init", &tree, &[])
+        .expect("commit");
+
+    let meta = open_workspace(workspace_path.clone())
+        .await
+        .expect("open bare workspace");
+    assert_eq!(meta.file_count, 1);
+
+    let cache = Arc::new(Mutex::new(AnalysisCache::default()));
+    let result = run_full_analysis_internal(&workspace_path, &cache, |_| {})
+        .expect("run full analysis");
+
+    assert_eq!(result.file_count, 1);
+    let file = &result.files[0];
+    assert_eq!(file.relative_path, "main.rs");
+    assert_eq!(file.loc, 5);
+    assert_eq!(file.language, "rust");
+}
+
+#[tokio::test]
+async fn validate_workspace_reports_and_repairs_corrupt_settings() {
+    let (_tmp, workspace_path, _file_path) = create_workspace_with_git_repo();
+    open_workspace(workspace_path.clone())
+        .await
+        .expect("open workspace");
+
+    let clean = validate_workspace(workspace_path.clone(), None)
+        .await
+        .expect("validate clean workspace");
+    assert!(clean.valid, "unexpected issues: {:?}", clean.issues);
+
+    let settings_path = Path::new(&workspace_path).join(".debtengine/settings.json");
+    fs::write(&settings_path, "{ not json").expect("corrupt settings");
+
+    let report = validate_workspace(workspace_path.clone(), None)
+        .await
+        .expect("validate corrupt workspace");
+    assert!(!report.valid);
+    assert!(report.can_auto_repair);
+    assert!(!report.repaired);
+    assert!(report.issues.iter().any(|issue| issue.area == "settings"));
+
+    let repaired = validate_workspace(workspace_path.clone(), Some(true))
+        .await
+        .expect("repair workspace");
+    assert!(repaired.repaired);
+
+    let settings = get_settings(workspace_path.clone()).await.expect("get settings");
+    assert_eq!(settings["gitHistoryDays"], json!(90));
+    assert!(validate_workspace(workspace_path, None).await.expect("revalidate").valid);
+}
+
+#[tokio::test]
+async fn tracked_files_only_excludes_untracked_sources() {
+    let (tmp, workspace_path, _file_path) = create_workspace_with_git_repo();
+    fs::write(tmp.path().join("src/scratch.rs"), "fn scratch() {}\n").expect("write untracked file");
+    open_workspace(workspace_path.clone())
+        .await
+        .expect("open workspace");
+
+    let cache = Arc::new(Mutex::new(AnalysisCache::default()));
+    let all = run_full_analysis_internal(&workspace_path, &cache, |_| {})
+        .expect("run full analysis");
+    assert!(all.files.iter().any(|file| file.relative_path == "src/scratch.rs"));
+
+    save_settings(workspace_path.clone(), json!({ "trackedFilesOnly": true }))
+        .await
+        .expect("enable trackedFilesOnly");
+    let tracked = run_full_analysis_internal(&workspace_path, &cache, |_| {})
+        .expect("run tracked-only analysis");
+    assert!(tracked.files.iter().all(|file| file.relative_path != "src/scratch.rs"));
+    assert!(tracked.files.iter().any(|file| file.relative_path == "src/main.rs"));
+}
+
+#[tokio::test]
+async fn churn_follows_explicit_analysis_ref_when_branches_diverge() {
+    let (tmp, workspace_path, _file_path) = create_workspace_with_git_repo();
+    let repo = Repository::open(&workspace_path).expect("open repo");
+    let base_commit = repo.head().expect("head").peel_to_commit().expect("head commit");
+    repo.branch("base", &base_commit, false).expect("create base branch");
+
+    fs::write(tmp.path().join("src/feature.rs"), "pub fn feature() {}\n").expect("write feature file");
+    let mut index = repo.index().expect("open git index");
+    index.add_path(Path::new("src/feature.rs")).expect("add feature file");
+    index.write().expect("write git index");
+    let tree = repo.find_tree(index.write_tree().expect("write tree")).expect("find tree");
+    let signature = Signature::now("Test User", "test@example.com").expect("signature");
+    repo.commit(Some("HEAD"), &signature, &signature, "feature", &tree, &[&base_commit])
+        .expect("commit feature");
+
+    let head_churn = analyze_churn(&workspace_path, 30, None).expect("churn from HEAD");
+    let base_churn = analyze_churn(&workspace_path, 30, Some("base")).expect("churn from base");
+
+    assert_eq!(head_churn.get("src/feature.rs"), Some(&1));
+    assert!(!base_churn.contains_key("src/feature.rs"));
+    assert_eq!(base_churn.get("src/main.rs"), Some(&1));
+    assert!(analyze_churn(&workspace_path, 30, Some("no-such-branch")).is_err());
+
+    assert_eq!(count_recent_commits(&workspace_path, 7, None), Ok(2));
+    assert_eq!(count_recent_commits(&workspace_path, 7, Some("base")), Ok(1));
+}
+
+#[tokio::test]
+async fn file_detail_bundles_score_and_co_change_peers() {
+    let (tmp, workspace_path, file_path) = create_workspace_with_git_repo();
+    let repo = Repository::open(&workspace_path).expect("open repo");
+    let parent = repo.head().expect("head").peel_to_commit().expect("head commit");
+
+    fs::write(&file_path, "fn main() {\n    lib();\n}\n").expect("rewrite main");
+    fs::write(tmp.path().join("src/lib.rs"), "pub fn lib() {}\n").expect("write lib");
+    let mut index = repo.index().expect("open git index");
+    index.add_path(Path::new("src/main.rs")).expect("add main");
+    index.add_path(Path::new("src/lib.rs")).expect("add lib");
+    index.write().expect("write git index");
+    let tree = repo.find_tree(index.write_tree().expect("write tree")).expect("find tree");
+    let signature = Signature::now("Test User", "test@example.com").expect("signature");
+    repo.commit(Some("HEAD"), &signature, &signature, "add lib", &tree, &[&parent])
+        .expect("commit lib");
+
+    open_workspace(workspace_path.clone())
+        .await
+        .expect("open workspace");
+    let cache = Arc::new(Mutex::new(AnalysisCache::default()));
+    run_full_analysis_internal(&workspace_path, &cache, |_| {}).expect("run full analysis");
+
+    let detail = get_file_detail_internal(&workspace_path, "src/main.rs", &cache)
+        .expect("file detail");
+
+    assert_eq!(detail.score.relative_path, "src/main.rs");
+    assert_eq!(detail.breakdown.components.len(), 8);
+    assert!(detail
+        .co_change_peers
+        .iter()
+        .any(|peer| peer.relative_path == "src/lib.rs" && peer.co_change_count == 1));
+    assert_eq!(detail.top_authors.first().map(|(author, _)| author.as_str()), Some("Test User"));
+}
+
+#[tokio::test]
+async fn co_change_window_is_independent_of_churn_window() {
+    let (tmp, workspace_path, file_path) = create_workspace_with_git_repo();
+    let repo = Repository::open(&workspace_path).expect("open repo");
+    let parent = repo.head().expect("head").peel_to_commit().expect("head commit");
+
+    // A co-change 100 days ago: inside a 180-day window, outside a 30-day one.
+    let old_time = git2::Time::new(chrono::Utc::now().timestamp() - 100 * 86400, 0);
+    let old_signature = Signature::new("Test User", "test@example.com", &old_time).expect("signature");
+    fs::write(&file_path, "fn main() {\n    lib();\n}\n").expect("rewrite main");
+    fs::write(tmp.path().join("src/lib.rs"), "pub fn lib() {}\n").expect("write lib");
+    let mut index = repo.index().expect("open git index");
+    index.add_path(Path::new("src/main.rs")).expect("add main");
+    index.add_path(Path::new("src/lib.rs")).expect("add lib");
+    index.write().expect("write git index");
+    let tree = repo.find_tree(index.write_tree().expect("write tree")).expect("find tree");
+    let old_commit = repo
+        .commit(Some("HEAD"), &old_signature, &old_signature, "old pair", &tree, &[&parent])
+        .expect("commit old pair");
+    let old_commit = repo.find_commit(old_commit).expect("find old commit");
+
+    fs::write(tmp.path().join("src/lib.rs"), "pub fn lib() {}\npub fn more() {}\n").expect("edit lib");
+    index.add_path(Path::new("src/lib.rs")).expect("add lib");
+    index.write().expect("write git index");
+    let tree = repo.find_tree(index.write_tree().expect("write tree")).expect("find tree");
+    let signature = Signature::now("Test User", "test@example.com").expect("signature");
+    repo.commit(Some("HEAD"), &signature, &signature, "recent", &tree, &[&old_commit])
+        .expect("commit recent change");
+
+    save_settings(
+        workspace_path.clone(),
+        json!({ "gitHistoryDays": 30, "coChangeHistoryDays": 180 }),
+    )
+    .await
+    .expect("save windows");
+    let settings = load_effective_analysis_settings(&workspace_path).expect("effective settings");
+    assert_eq!(settings.history_days, 30);
+    assert_eq!(settings.co_change_history_days, 180);
+
+    let has_pair = |days: u32| {
+        analyze_co_changes(&workspace_path, days, None, settings.max_files_per_commit_for_coupling)
+            .expect("co-change analysis")
+            .pairs
+            .iter()
+            .any(|(a, b, _)| a == "src/lib.rs" && b == "src/main.rs")
+    };
+    assert!(has_pair(settings.co_change_history_days));
+    assert!(!has_pair(settings.history_days));
+}
+
+#[tokio::test]
+async fn scheduled_snapshot_is_taken_only_when_due() {
+    for (age_days, expect_snapshot) in [(8, true), (2, false)] {
+        let (_tmp, workspace_path, _file_path) = create_workspace_with_git_repo();
+        open_workspace(workspace_path.clone())
+            .await
+            .expect("open workspace");
+        let cache = Arc::new(Mutex::new(AnalysisCache::default()));
+        run_full_analysis_internal(&workspace_path, &cache, |_| {}).expect("run full analysis");
+
+        let conn = get_db_connection(&workspace_path).expect("db connection");
+        insert_snapshot(
+            &conn,
+            DebtSnapshot {
+                id: 0,
+                timestamp: chrono::Utc::now().timestamp() - age_days * 86400,
+                composite_score: 40.0,
+                file_count: 1,
+                high_debt_count: 0,
+                commit_count_week: 0,
+                snapshot_metadata: None,
+            },
+        )
+        .expect("insert old snapshot");
+
+        let taken = maybe_take_scheduled_snapshot_internal(&workspace_path, &cache, |_| {})
+            .expect("scheduled snapshot");
+        let snapshots = get_debt_snapshots(workspace_path.clone())
+            .await
+            .expect("list snapshots");
+
+        assert_eq!(taken, expect_snapshot, "last snapshot {age_days} days old");
+        assert_eq!(snapshots.len(), if expect_snapshot { 2 } else { 1 });
+    }
+}
+
+#[tokio::test]
+async fn scheduled_snapshot_embeds_files_and_raises_regression_alerts() {
+    let (_tmp, workspace_path, _file_path) = create_workspace_with_git_repo();
+    open_workspace(workspace_path.clone())
+        .await
+        .expect("open workspace");
+    save_settings(workspace_path.clone(), json!({ "regressionAlertDelta": 0.0 }))
+        .await
+        .expect("save alert delta");
+    let cache = Arc::new(Mutex::new(AnalysisCache::default()));
+    run_full_analysis_internal(&workspace_path, &cache, |_| {}).expect("run full analysis");
+
+    let conn = get_db_connection(&workspace_path).expect("db connection");
+    insert_snapshot(
+        &conn,
+        DebtSnapshot {
+            id: 0,
+            timestamp: chrono::Utc::now().timestamp() - 8 * 86400,
+            composite_score: 0.0,
+            file_count: 1,
+            high_debt_count: 0,
+            commit_count_week: 0,
+            snapshot_metadata: None,
+        },
+    )
+    .expect("insert old snapshot");
+
+    let mut alerts = Vec::new();
+    assert!(maybe_take_scheduled_snapshot_internal(&workspace_path, &cache, |alert| alerts.push(alert))
+        .expect("scheduled snapshot"));
+    assert_eq!(alerts.len(), 1);
+    assert_eq!(alerts[0].top_files[0].path, "src/main.rs");
+
+    let snapshots = get_debt_snapshots(workspace_path.clone()).await.expect("list snapshots");
+    let files = snapshot_files(snapshots.last().expect("scheduled snapshot"));
+    assert_eq!(files.iter().map(|f| f.path.as_str()).collect::<Vec<_>>(), vec!["src/main.rs"]);
+}
+
+#[tokio::test]
+async fn repository_without_commits_analyzes_with_zero_git_components() {
+    let temp_dir = tempfile::tempdir().expect("create temp dir");
+    let workspace_path = temp_dir.path().to_string_lossy().to_string();
+    fs::create_dir_all(temp_dir.path().join("src")).expect("create src dir");
+    fs::write(temp_dir.path().join("src/main.rs"), "fn main() {}\n").expect("write source file");
+    Repository::init(temp_dir.path()).expect("init git repo");
+
+    assert!(analyze_churn(&workspace_path, 90, None).expect("churn").is_empty());
+    assert!(analyze_co_changes(&workspace_path, 90, None, 50).expect("co-changes").pairs.is_empty());
+    assert!(analyze_knowledge(&workspace_path, None).expect("knowledge").is_empty());
+
+    open_workspace(workspace_path.clone())
+        .await
+        .expect("open workspace");
+    let cache = Arc::new(Mutex::new(AnalysisCache::default()));
+    let result = run_full_analysis_internal(&workspace_path, &cache, |_| {})
+        .expect("run full analysis");
+
+    let file = result
+        .files
+        .iter()
+        .find(|file| file.relative_path == "src/main.rs")
+        .expect("scored main.rs");
+    assert_eq!(file.components.churn_rate.raw_score, 0.0);
+    assert_eq!(file.components.change_coupling.raw_score, 0.0);
+    assert_eq!(file.components.knowledge_concentration.raw_score, 0.0);
+}
+
+#[tokio::test]
+async fn analysis_state_reflects_analyzed_and_unanalyzed_workspaces() {
+    let (_temp_dir, workspace_path, _) = create_workspace_with_git_repo();
+    let cache = Arc::new(Mutex::new(AnalysisCache::default()));
+
+    let before = get_analysis_state_internal(&workspace_path, &cache).expect("state before");
+    assert!(before.git_ok);
+    assert!(!before.has_cache);
+    assert_eq!(before.cached_file_count, 0);
+    assert_eq!(before.db_score_count, 0);
+    assert_eq!(before.last_analysis_at, None);
+    assert_eq!(before.last_snapshot_at, None);
+
+    run_full_analysis_internal(&workspace_path, &cache, |_| {}).expect("analysis");
+
+    let after = get_analysis_state_internal(&workspace_path, &cache).expect("state after");
+    assert!(after.git_ok);
+    assert!(after.has_cache);
+    assert_eq!(after.cached_file_count, 1);
+    assert_eq!(after.db_score_count, 1);
+    assert!(after.last_analysis_at.is_some());
+}
+
+#[tokio::test]
+async fn analyze_dirty_files_scores_only_uncommitted_changes() {
+    let (temp_dir, workspace_path, main_file) = create_workspace_with_git_repo();
+    let repo = Repository::open(temp_dir.path()).expect("open repo");
+    fs::write(temp_dir.path().join("src/lib.rs"), "pub fn lib() {}\n").expect("write lib file");
+    let mut index = repo.index().expect("open git index");
+    index.add_path(Path::new("src/lib.rs")).expect("add lib file");
+    index.write().expect("write git index");
+    let tree = repo.find_tree(index.write_tree().expect("write tree")).expect("find tree");
+    let head = repo.head().expect("head").peel_to_commit().expect("head commit");
+    let signature = Signature::now("Test User", "test@example.com").expect("signature");
+    repo.commit(Some("HEAD"), &signature, &signature, "add lib", &tree, &[&head])
+        .expect("commit");
+
+    fs::write(&main_file, "fn main() {\n    println!(\"changed\");\n}\n").expect("modify main");
+    let cache = Arc::new(Mutex::new(AnalysisCache::default()));
+
+    let scored = analyze_dirty_files_internal(&workspace_path, &cache).expect("dirty analysis");
+
+    let paths: Vec<&str> = scored.iter().map(|file| file.relative_path.as_str()).collect();
+    assert_eq!(paths, vec!["src/main.rs"]);
+}
+
+#[tokio::test]
+async fn dirty_files_keep_relative_scores_and_cached_history() {
+    let (temp_dir, workspace_path, main_file) = create_workspace_with_git_repo();
+    fs::write(
+        temp_dir.path().join("src/big.rs"),
+        "pub fn big(x: u32) -> u32 {\n    if x > 1 { if x > 2 { if x > 3 { x } else { 3 } } else { 2 } } else { 1 }\n}\n",
+    )
+    .expect("write big file");
+    save_settings(workspace_path.clone(), json!({ "relativeBlend": 1.0 }))
+        .await
+        .expect("save relative blend");
+    let cache = Arc::new(Mutex::new(AnalysisCache::default()));
+    let analyzed = run_full_analysis_internal(&workspace_path, &cache, |_| {}).expect("run full analysis");
+    let before = analyzed
+        .files
+        .iter()
+        .find(|file| file.relative_path == "src/main.rs")
+        .expect("main scored")
+        .clone();
+
+    fs::write(&main_file, "fn main() {\n    println!(\"changed\");\n}\n").expect("modify main");
+    let scored = analyze_dirty_files_internal(&workspace_path, &cache).expect("dirty analysis");
+
+    let main = scored.iter().find(|file| file.relative_path == "src/main.rs").expect("main rescored");
+    assert_eq!(main.components.churn_rate.raw_score, before.components.churn_rate.raw_score);
+    assert!(main
+        .components
+        .cyclomatic_complexity
+        .details
+        .iter()
+        .any(|detail| detail.starts_with("relative to workspace")));
+}
+
+#[tokio::test]
+async fn single_thread_analysis_matches_parallel_analysis() {
+    let (temp_dir, workspace_path, _) = create_workspace_with_git_repo();
+    for name in ["a", "b", "c", "d"] {
+        fs::write(
+            temp_dir.path().join(format!("src/{name}.rs")),
+            format!("pub fn {name}(x: u32) -> u32 {{\n    if x > 42 {{ x * 7 }} else {{ x }}\n}}\n"),
+        )
+        .expect("write source file");
+    }
+    let cache = Arc::new(Mutex::new(AnalysisCache::default()));
+
+    save_settings(workspace_path.clone(), json!({ "maxThreads": 4 }))
+        .await
+        .expect("save parallel settings");
+    let parallel = run_full_analysis_internal(&workspace_path, &cache, |_| {}).expect("parallel analysis");
+
+    save_settings(workspace_path.clone(), json!({ "maxThreads": 1 }))
+        .await
+        .expect("save sequential settings");
+    assert_eq!(
+        load_effective_analysis_settings(&workspace_path).expect("settings").max_threads,
+        1
+    );
+    let mut progress = Vec::new();
+    let sequential = run_full_analysis_internal(&workspace_path, &cache, |p| progress.push(p.current))
+        .expect("sequential analysis");
+
+    assert_eq!(sequential.file_count, 5);
+    assert_eq!(progress, vec![1, 2, 3, 4, 5]);
+    let scores = |result: &debtlens_lib::models::file_score::AnalysisResult| {
+        result
+            .files
+            .iter()
+            .map(|f| (f.relative_path.clone(), f.composite_score))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(scores(&sequential), scores(&parallel));
+}
+
+#[tokio::test]
+async fn baseline_diff_reports_regressed_file() {
+    let (temp_dir, workspace_path, main_file) = create_workspace_with_git_repo();
+    fs::write(temp_dir.path().join("src/util.rs"), "pub fn util() {}\n").expect("write util file");
+    let cache = Arc::new(Mutex::new(AnalysisCache::default()));
+    run_full_analysis_internal(&workspace_path, &cache, |_| {}).expect("baseline analysis");
+
+    let baseline = capture_baseline_internal(&cache).expect("capture baseline");
+
+    fs::write(
+        &main_file,
+        "fn main() {\n    // TODO: clean up\n    // FIXME: magic\n    run(7, 13, 42, 99, 123, 4096);\n}\n\
+         fn run(a: u32, b: u32, c: u32, d: u32, e: u32, f: u32) {}\n",
+    )
+    .expect("worsen main");
+    run_full_analysis_internal(&workspace_path, &cache, |_| {}).expect("rescored analysis");
+
+    let diff = diff_against_baseline_internal(&baseline, &cache).expect("diff");
+
+    let main = diff
+        .files
+        .iter()
+        .find(|f| f.relative_path == "src/main.rs")
+        .expect("main delta");
+    assert_eq!(main.status, "regressed");
+    assert!(main.delta > 0.0);
+    assert_eq!(diff.files[0].relative_path, "src/main.rs");
+    assert_eq!(diff.summary.regressed, 1);
+    assert_eq!(diff.summary.unchanged, 1);
+    assert!(diff.summary.workspace_delta > 0.0);
+}
+
+#[tokio::test]
+async fn full_analysis_classifies_score_changes_since_previous_run() {
+    let (temp_dir, workspace_path, main_file) = create_workspace_with_git_repo();
+    fs::write(temp_dir.path().join("src/util.rs"), "pub fn util() {}\n").expect("write util file");
+    let cache = Arc::new(Mutex::new(AnalysisCache::default()));
+
+    let first = run_full_analysis_internal(&workspace_path, &cache, |_| {}).expect("first analysis");
+    assert!(first.score_changes.iter().all(|change| change.status == "new"));
+
+    fs::write(
+        &main_file,
+        "fn main() {\n    // TODO: clean up\n    // FIXME: magic\n    run(7, 13, 42, 99, 123, 4096);\n}\n\
+         fn run(a: u32, b: u32, c: u32, d: u32, e: u32, f: u32) {}\n",
+    )
+    .expect("worsen main");
+    let second = run_full_analysis_internal(&workspace_path, &cache, |_| {}).expect("second analysis");
+
+    let status = |path: &str| {
+        second
+            .score_changes
+            .iter()
+            .find(|change| change.relative_path == path)
+            .map(|change| change.status.clone())
+    };
+    assert_eq!(status("src/main.rs").as_deref(), Some("regressed"));
+    assert_eq!(status("src/util.rs").as_deref(), Some("unchanged"));
+}
+
+#[tokio::test]
+async fn module_javascript_is_scored_and_type_declarations_skipped_by_default() {
+    let (temp_dir, workspace_path, _) = create_workspace_with_git_repo();
+    fs::write(temp_dir.path().join("src/util.mjs"), "export function util() {}\n").expect("write mjs");
+    fs::write(temp_dir.path().join("src/legacy.cjs"), "module.exports = {};\n").expect("write cjs");
+    fs::write(temp_dir.path().join("src/types.d.ts"), "export declare const x: number;\n").expect("write d.ts");
+    let cache = Arc::new(Mutex::new(AnalysisCache::default()));
+
+    let result = run_full_analysis_internal(&workspace_path, &cache, |_| {}).expect("analysis");
+    let language_of = |result: &debtlens_lib::models::file_score::AnalysisResult, path: &str| {
+        result
+            .files
+            .iter()
+            .find(|f| f.relative_path == path)
+            .map(|f| f.language.clone())
+    };
+    assert_eq!(language_of(&result, "src/util.mjs").as_deref(), Some("javascript"));
+    assert_eq!(language_of(&result, "src/legacy.cjs").as_deref(), Some("javascript"));
+    assert_eq!(language_of(&result, "src/types.d.ts"), None);
+
+    save_settings(workspace_path.clone(), json!({ "includeTypeDeclarations": true }))
+        .await
+        .expect("enable declarations");
+    let with_declarations = run_full_analysis_internal(&workspace_path, &cache, |_| {}).expect("analysis");
+    assert_eq!(
+        language_of(&with_declarations, "src/types.d.ts").as_deref(),
+        Some("typescript")
+    );
+}
+
+#[tokio::test]
+async fn unreadable_files_are_reported_in_analysis_errors() {
+    let (temp_dir, workspace_path, _) = create_workspace_with_git_repo();
+    fs::write(temp_dir.path().join("src/binary.rs"), [0xff, 0xfe, 0x00, 0x80]).expect("write invalid utf-8");
+    let cache = Arc::new(Mutex::new(AnalysisCache::default()));
+
+    let result = run_full_analysis_internal(&workspace_path, &cache, |_| {}).expect("analysis");
+
+    assert_eq!(result.file_count, 1);
+    assert_eq!(result.errors.len(), 1);
+    assert_eq!(result.errors[0].path, "src/binary.rs");
+    assert!(result.errors[0].reason.contains("Failed to read"), "{}", result.errors[0].reason);
+}
+
+#[tokio::test]
+async fn configured_skip_directories_are_not_analyzed() {
+    let (temp_dir, workspace_path, _) = create_workspace_with_git_repo();
+    fs::create_dir_all(temp_dir.path().join("generated")).expect("create generated dir");
+    fs::write(temp_dir.path().join("generated/schema.rs"), "pub struct Schema;\n").expect("write generated file");
+    let cache = Arc::new(Mutex::new(AnalysisCache::default()));
+
+    let before = run_full_analysis_internal(&workspace_path, &cache, |_| {}).expect("analysis");
+    assert!(before.files.iter().any(|f| f.relative_path == "generated/schema.rs"));
+
+    let saved = save_settings(workspace_path.clone(), json!({ "skipDirectories": ["generated"] }))
+        .await
+        .expect("save skip directories");
+    assert_eq!(saved["skipDirectories"], json!(["generated"]));
+    let after = run_full_analysis_internal(&workspace_path, &cache, |_| {}).expect("analysis");
+
+    assert!(after.files.iter().all(|f| f.relative_path != "generated/schema.rs"));
+    assert!(after.files.iter().any(|f| f.relative_path == "src/main.rs"));
+}
+
+#[tokio::test]
+async fn skip_directories_match_at_root_or_as_build_output() {
+    let (temp_dir, workspace_path, _) = create_workspace_with_git_repo();
+    for file in ["src/bin/tool.rs", "bin/setup.rs", "packages/web/dist/bundle.js", "packages/web/index.js"] {
+        let path = temp_dir.path().join(file);
+        fs::create_dir_all(path.parent().expect("parent")).expect("create dir");
+        fs::write(path, "export const x = 1;\n").expect("write file");
+    }
+    let cache = Arc::new(Mutex::new(AnalysisCache::default()));
+
+    let result = run_full_analysis_internal(&workspace_path, &cache, |_| {}).expect("analysis");
+
+    let paths: Vec<&str> = result.files.iter().map(|f| f.relative_path.as_str()).collect();
+    assert!(paths.contains(&"src/bin/tool.rs"));
+    assert!(paths.contains(&"packages/web/index.js"));
+    assert!(!paths.contains(&"bin/setup.rs"));
+    assert!(!paths.contains(&"packages/web/dist/bundle.js"));
+}
+
+#[tokio::test]
+async fn analysis_complete_summary_fires_once_with_counts() {
+    let (temp_dir, workspace_path, _) = create_workspace_with_git_repo();
+    fs::write(temp_dir.path().join("src/util.rs"), "pub fn util() {}\n").expect("write util file");
+    budget_crud(
+        workspace_path.clone(),
+        "create".to_string(),
+        Some(DebtBudget {
+            id: "budget-src".to_string(),
+            pattern: "src/**".to_string(),
+            label: "Source".to_string(),
+            max_score: 0.0,
+            created_at: 0,
+            notify_on_breach: true,
+            warning_score: None,
+        }),
+        None,
+    )
+    .await
+    .expect("create budget");
+    let cache = Arc::new(Mutex::new(AnalysisCache::default()));
+
+    let mut summaries = Vec::new();
+    let result = run_full_analysis_with_events(&workspace_path, &cache, |_| {}, |summary| summaries.push(summary))
+        .expect("analysis");
+
+    assert_eq!(summaries.len(), 1);
+    let summary = &summaries[0];
+    assert_eq!(summary.file_count, 2);
+    assert_eq!(summary.file_count, result.file_count);
+    assert_eq!(summary.workspace_score, result.workspace_score);
+    let counts = &summary.severity_counts;
+    assert_eq!(counts.critical + counts.high + counts.medium + counts.low, 2);
+    assert!(summary.budget_breached);
+}
+
+#[tokio::test]
+async fn churn_range_counts_only_commits_between_tags() {
+    let (tmp, workspace_path, _file_path) = create_workspace_with_git_repo();
+    let repo = Repository::open(&workspace_path).expect("open repo");
+    let signature = Signature::now("Test User", "test@example.com").expect("signature");
+    let tag_head = |name: &str| {
+        let head = repo.head().expect("head").peel_to_commit().expect("head commit");
+        repo.tag_lightweight(name, head.as_object(), false).expect("tag");
+    };
+    let commit_file = |relative: &str, content: &str, message: &str| {
+        fs::write(tmp.path().join(relative), content).expect("write file");
+        let mut index = repo.index().expect("open git index");
+        index.add_path(Path::new(relative)).expect("add file");
+        index.write().expect("write git index");
+        let tree = repo.find_tree(index.write_tree().expect("write tree")).expect("find tree");
+        let parent = repo.head().expect("head").peel_to_commit().expect("head commit");
+        repo.commit(Some("HEAD"), &signature, &signature, message, &tree, &[&parent])
+            .expect("commit");
+    };
+
+    tag_head("v1.0");
+    commit_file("src/lib.rs", "pub fn lib() {}\n", "add lib");
+    commit_file("src/lib.rs", "pub fn lib() { todo!() }\n", "change lib");
+    commit_file("src/main.rs", "fn main() {}\n", "change main");
+    tag_head("v1.1");
+    commit_file("src/lib.rs", "pub fn lib() {}\n", "after release");
+
+    let churn = analyze_churn_range(&workspace_path, "v1.0", "v1.1").expect("range churn");
+
+    assert_eq!(churn.get("src/lib.rs"), Some(&2));
+    assert_eq!(churn.get("src/main.rs"), Some(&1));
+    assert_eq!(churn.len(), 2);
+    assert!(analyze_churn_range(&workspace_path, "v0.9", "v1.1").is_err());
+}
+
+#[tokio::test]
+async fn tracked_docs_are_scored_on_staleness() {
+    let (tmp, workspace_path, _file_path) = create_workspace_with_git_repo();
+    let repo = Repository::open(&workspace_path).expect("open repo");
+    let parent = repo.head().expect("head").peel_to_commit().expect("head commit");
+
+    let old_time = git2::Time::new(chrono::Utc::now().timestamp() - 400 * 86400, 0);
+    let old_signature = Signature::new("Test User", "test@example.com", &old_time).expect("signature");
+    fs::write(tmp.path().join("docs.md"), "# Architecture\n\nSee src/main.rs.\n").expect("write doc");
+    let mut index = repo.index().expect("open git index");
+    index.add_path(Path::new("docs.md")).expect("add doc");
+    index.write().expect("write git index");
+    let tree = repo.find_tree(index.write_tree().expect("write tree")).expect("find tree");
+    repo.commit(Some("HEAD"), &old_signature, &old_signature, "docs", &tree, &[&parent])
+        .expect("commit doc");
+    let cache = Arc::new(Mutex::new(AnalysisCache::default()));
+
+    let without_docs = run_full_analysis_internal(&workspace_path, &cache, |_| {}).expect("analysis");
+    assert!(without_docs.files.iter().all(|f| f.relative_path != "docs.md"));
+
+    save_settings(workspace_path.clone(), json!({ "trackDocs": true }))
+        .await
+        .expect("enable trackDocs");
+    let with_docs = run_full_analysis_internal(&workspace_path, &cache, |_| {}).expect("analysis");
+
+    let doc = with_docs
+        .files
+        .iter()
+        .find(|f| f.relative_path == "docs.md")
+        .expect("doc scored");
+    assert_eq!(doc.language, "markdown");
+    assert_eq!(doc.components.decision_staleness.raw_score, 100.0);
+    assert_eq!(doc.components.code_smell_density.raw_score, 0.0);
+    assert_eq!(doc.components.cyclomatic_complexity.raw_score, 0.0);
+    assert!(doc.composite_score > 0.0);
+
+    // Doc history follows analysisRef like churn does.
+    let doc_commit = repo.head().expect("head").peel_to_commit().expect("doc commit");
+    repo.branch("reviewed", &doc_commit, false).expect("branch");
+    fs::write(tmp.path().join("docs.md"), "# Architecture\n\nSee src/main.rs and src/lib.rs.\n").expect("edit doc");
+    let mut index = repo.index().expect("open git index");
+    index.add_path(Path::new("docs.md")).expect("add doc");
+    index.write().expect("write git index");
+    let tree = repo.find_tree(index.write_tree().expect("write tree")).expect("find tree");
+    let signature = Signature::now("Test User", "test@example.com").expect("signature");
+    repo.commit(Some("HEAD"), &signature, &signature, "refresh docs", &tree, &[&doc_commit])
+        .expect("commit doc edit");
+    let doc_staleness = |result: &debtlens_lib::models::file_score::AnalysisResult| {
+        result
+            .files
+            .iter()
+            .find(|f| f.relative_path == "docs.md")
+            .expect("doc scored")
+            .components
+            .decision_staleness
+            .raw_score
+    };
+
+    let fresh = run_full_analysis_internal(&workspace_path, &cache, |_| {}).expect("analysis");
+    assert_eq!(doc_staleness(&fresh), 0.0);
+    save_settings(workspace_path.clone(), json!({ "analysisRef": "reviewed" }))
+        .await
+        .expect("save analysis ref");
+    let at_ref = run_full_analysis_internal(&workspace_path, &cache, |_| {}).expect("analysis");
+    assert_eq!(doc_staleness(&at_ref), 100.0);
+}
+
+#[tokio::test]
+async fn in_file_weight_directive_only_affects_that_file() {
+    let (tmp, workspace_path, file_path) = create_workspace_with_git_repo();
+    let other_path = tmp.path().join("src/other.rs");
+    fs::write(&other_path, "fn other() {\n    println!(\"other\");\n}\n").expect("write other file");
+    let cache = Arc::new(Mutex::new(AnalysisCache::default()));
+
+    let before = run_full_analysis_internal(&workspace_path, &cache, |_| {}).expect("analysis");
+    let other_before = before
+        .files
+        .iter()
+        .find(|f| f.relative_path == "src/other.rs")
+        .expect("other file scored")
+        .composite_score;
+
+    fs::write(
+        &file_path,
+        "// debtlens: weight churn_rate=0.4\nfn main() {\n    println!(\"hello\");\n}\n",
+    )
+    .expect("write directive");
+    let after = run_full_analysis_internal(&workspace_path, &cache, |_| {}).expect("analysis");
+
+    let main = after
+        .files
+        .iter()
+        .find(|f| f.relative_path == "src/main.rs")
+        .expect("main scored");
+    let expected = 0.4 / (0.4 + 0.78);
+    assert!((main.components.churn_rate.weight - expected).abs() < 1e-9);
+    let weight_sum: f64 = main.components.named().iter().map(|(_, c)| c.weight).sum();
+    assert!((weight_sum - 1.0).abs() < 1e-9);
+
+    let other = after
+        .files
+        .iter()
+        .find(|f| f.relative_path == "src/other.rs")
+        .expect("other file scored");
+    assert!((other.components.churn_rate.weight - 0.22).abs() < 1e-9);
+    assert_eq!(other.composite_score, other_before);
+}
+
+#[tokio::test]
+async fn files_added_after_the_last_snapshot_are_uncovered() {
+    let (tmp, workspace_path, _file_path) = create_workspace_with_git_repo();
+    open_workspace(workspace_path.clone())
+        .await
+        .expect("open workspace");
+    fs::write(tmp.path().join("src/lib.rs"), "pub fn lib() {}\n").expect("write second file");
+    let cache = Arc::new(Mutex::new(AnalysisCache::default()));
+    run_full_analysis_internal(&workspace_path, &cache, |_| {}).expect("run full analysis");
+
+    // The caller's top-N list is replaced by every cached file.
+    let snapshot = DebtSnapshot {
+        id: 0,
+        timestamp: chrono::Utc::now().timestamp(),
+        composite_score: 40.0,
+        file_count: 2,
+        high_debt_count: 0,
+        commit_count_week: 0,
+        snapshot_metadata: Some(json!([{ "path": "src/main.rs", "score": 40.0 }]).to_string()),
+    };
+    take_snapshot_internal(&workspace_path, snapshot, &cache, |_| {}).expect("take snapshot");
+    assert!(get_uncovered_files_internal(&workspace_path, &cache)
+        .expect("uncovered files")
+        .is_empty());
+
+    fs::write(tmp.path().join("src/added.rs"), "pub fn added() {}\n").expect("write new file");
+    run_full_analysis_internal(&workspace_path, &cache, |_| {}).expect("rerun full analysis");
+
+    let uncovered = get_uncovered_files_internal(&workspace_path, &cache).expect("uncovered files");
+    assert_eq!(uncovered, vec!["src/added.rs".to_string()]);
+}
+
+#[tokio::test]
+async fn breakdown_scores_are_rounded_to_score_precision() {
+    let (_tmp, workspace_path, _file_path) = create_workspace_with_git_repo();
+    open_workspace(workspace_path.clone())
+        .await
+        .expect("open workspace");
+    let cache = Arc::new(Mutex::new(AnalysisCache::default()));
+    run_full_analysis_internal(&workspace_path, &cache, |_| {}).expect("run full analysis");
+    {
+        let mut lock = cache.lock().expect("cache lock");
+        let result = lock.result.as_mut().expect("cached result");
+        let file = result.files.iter_mut().find(|f| f.relative_path == "src/main.rs").expect("main.rs");
+        file.composite_score = 42.573333;
+        file.components.churn_rate.raw_score = 12.3456;
+    }
+
+    let breakdown = get_file_breakdown_internal("src/main.rs", &cache).expect("default breakdown");
+    assert_eq!(breakdown.composite_score, 42.6);
+
+    save_settings(workspace_path.clone(), json!({ "scorePrecision": 2 }))
+        .await
+        .expect("save precision");
+    let breakdown = get_file_breakdown_internal("src/main.rs", &cache).expect("precise breakdown");
+    assert_eq!(breakdown.composite_score, 42.57);
+
+    let matrix = get_score_matrix_internal(&cache).expect("score matrix");
+    let row = matrix.files.iter().position(|f| f == "src/main.rs").expect("main.rs row");
+    assert_eq!(matrix.raw_scores[row][0], 12.35);
+    let baseline: serde_json::Value =
+        serde_json::from_str(&capture_baseline_internal(&cache).expect("capture baseline")).expect("baseline json");
+    assert_eq!(baseline["scores"]["src/main.rs"], json!(42.57));
+    let diff = diff_against_baseline_internal(&baseline.to_string(), &cache).expect("diff");
+    assert_eq!(diff.summary.unchanged, diff.files.len());
+
+    let lock = cache.lock().expect("cache lock");
+    let result = lock.result.as_ref().expect("cached result");
+    let file = result.files.iter().find(|f| f.relative_path == "src/main.rs").expect("main.rs");
+    assert_eq!(file.composite_score, 42.573333, "stored score keeps full precision");
+}
+
+#[tokio::test]
+async fn version_info_flags_a_database_from_a_newer_build() {
+    let (_tmp, workspace_path, _file_path) = create_workspace_with_git_repo();
+    open_workspace(workspace_path.clone())
+        .await
+        .expect("open workspace");
+
+    let current = get_version_info(workspace_path.clone()).await.expect("version info");
+    assert_eq!(current.db_version, Some(current.expected_db_version));
+    assert_eq!(current.settings_version, Some(current.expected_settings_version));
+    assert!(!current.needs_migration);
+    assert!(!current.is_future);
+
+    let future_version = current.expected_db_version + 5;
+    get_db_connection(&workspace_path)
+        .expect("db connection")
+        .pragma_update(None, "user_version", future_version)
+        .expect("set future user_version");
+
+    let info = get_version_info(workspace_path.clone()).await.expect("version info");
+    assert_eq!(info.db_version, Some(future_version));
+    assert!(info.is_future);
+    assert!(!info.needs_migration);
+}
+
+#[tokio::test]
+async fn refreshing_staleness_picks_up_a_newly_added_adr() {
+    let (tmp, workspace_path, _file_path) = create_workspace_with_git_repo();
+    open_workspace(workspace_path.clone())
+        .await
+        .expect("open workspace");
+    // An ADR without a review date counts as moderately stale.
+    fs::write(tmp.path().join("src/main.adr.md"), "# Entry point\n").expect("write undated ADR");
+    let cache = Arc::new(Mutex::new(AnalysisCache::default()));
+    let before = run_full_analysis_internal(&workspace_path, &cache, |_| {}).expect("run full analysis");
+    let staleness = |result: &debtlens_lib::models::file_score::AnalysisResult| {
+        result
+            .files
+            .iter()
+            .find(|f| f.relative_path == "src/main.rs")
+            .expect("main.rs scored")
+            .components
+            .decision_staleness
+            .raw_score
+    };
+    assert_eq!(staleness(&before), 50.0);
+
+    let adrs_dir = tmp.path().join(".debtengine/adrs");
+    fs::create_dir_all(&adrs_dir).expect("create adrs dir");
+    fs::write(
+        adrs_dir.join("main.adr.md"),
+        format!("# Entry point\nlast_reviewed_at: {}\n", chrono::Utc::now().format("%Y-%m-%d")),
+    )
+    .expect("write reviewed ADR");
+
+    let after = refresh_staleness_internal(&workspace_path, &cache).expect("refresh staleness");
+    assert_eq!(staleness(&after), 0.0);
+    let main_before = before.files.iter().find(|f| f.relative_path == "src/main.rs").expect("main.rs");
+    let main_after = after.files.iter().find(|f| f.relative_path == "src/main.rs").expect("main.rs");
+    assert!(main_after.composite_score < main_before.composite_score);
+}
+
+#[tokio::test]
+async fn quick_scan_scores_content_without_reading_git_history() {
+    let (tmp, workspace_path, _file_path) = create_workspace_with_git_repo();
+    open_workspace(workspace_path.clone())
+        .await
+        .expect("open workspace");
+    fs::write(
+        tmp.path().join("src/main.rs"),
+        "fn main() {\n    // TODO: handle args\n    if true && false {\n        println!(\"hello\");\n    }\n}\n",
+    )
+    .expect("rewrite source file");
+    // A full analysis resolves this ref up front and fails; a quick scan never looks.
+    save_settings(workspace_path.clone(), json!({ "analysisRef": "no-such-branch" }))
+        .await
+        .expect("save bad ref");
+    let cache = Arc::new(Mutex::new(AnalysisCache::default()));
+    assert!(run_full_analysis_internal(&workspace_path, &cache, |_| {}).is_err());
+
+    let result = run_quick_scan_internal(&workspace_path, &cache, |_| {}).expect("quick scan");
+
+    assert!(result.partial);
+    let main = result.files.iter().find(|f| f.relative_path == "src/main.rs").expect("main.rs scored");
+    assert!(main.components.code_smell_density.raw_score > 0.0);
+    assert!(main.components.cyclomatic_complexity.confidence.is_some());
+    assert_eq!(main.components.churn_rate.raw_score, 0.0);
+    assert_eq!(main.components.knowledge_concentration.raw_score, 0.0);
+    assert_eq!(main.components.change_coupling.raw_score, 0.0);
+
+    // Nothing may be persisted or derived from the zeroed history components.
+    let partial = |outcome: Result<(), String>| outcome.expect_err("rejected").starts_with("PARTIAL_ANALYSIS");
+    let main_path = tmp.path().join("src/main.rs").to_string_lossy().to_string();
+    assert!(partial(reanalyze_file_internal(&workspace_path, &main_path, &cache).map(|_| ())));
+    assert!(partial(analyze_dirty_files_internal(&workspace_path, &cache).map(|_| ())));
+    assert!(partial(refresh_staleness_internal(&workspace_path, &cache).map(|_| ())));
+    assert!(partial(maybe_take_scheduled_snapshot_internal(&workspace_path, &cache, |_| {}).map(|_| ())));
+    let snapshot = DebtSnapshot {
+        id: 0,
+        timestamp: chrono::Utc::now().timestamp(),
+        composite_score: result.workspace_score,
+        file_count: result.file_count,
+        high_debt_count: result.high_debt_count,
+        commit_count_week: 0,
+        snapshot_metadata: None,
+    };
+    assert!(partial(take_snapshot_internal(&workspace_path, snapshot, &cache, |_| {}).map(|_| ())));
+    assert!(get_debt_snapshots(workspace_path.clone()).await.expect("list snapshots").is_empty());
+}
+
+#[tokio::test]
+async fn updated_register_item_tops_recent_activity() {
+    let (_tmp, workspace_path, _file_path) = create_workspace_with_git_repo();
+    open_workspace(workspace_path.clone())
+        .await
+        .expect("open workspace");
+    let now = chrono::Utc::now().timestamp();
+    let item = |id: &str, created_at: i64| RegisterItem {
+        created_at,
+        updated_at: created_at,
+        ..register_item(id, None)
+    };
+    for (id, created_at) in [("first", now), ("second", now + 5)] {
+        register_crud(workspace_path.clone(), "create".to_string(), Some(item(id, created_at)), None, None)
+            .await
+            .expect("create register item");
+    }
+    register_crud(
+        workspace_path.clone(),
+        "update".to_string(),
+        Some(RegisterItem {
+            status: "in_progress".to_string(),
+            updated_at: now + 10,
+            ..item("first", now)
+        }),
+        None,
+        None,
+    )
+    .await
+    .expect("update register item");
+
+    let activity = get_recent_register_activity(workspace_path.clone(), 10)
+        .await
+        .expect("recent activity");
+
+    assert_eq!(activity.len(), 2);
+    assert_eq!(activity[0].item.id, "first");
+    assert_eq!(activity[0].activity, "updated");
+    assert_eq!(activity[1].item.id, "second");
+    assert_eq!(activity[1].activity, "created");
+    assert_eq!(
+        get_recent_register_activity(workspace_path, 1).await.expect("limited").len(),
+        1
+    );
+}
+
+#[tokio::test]
+async fn touched_but_unchanged_file_reuses_score_via_content_hash() {
+    let (_tmp, workspace_path, file_path) = create_workspace_with_git_repo();
+    open_workspace(workspace_path.clone())
+        .await
+        .expect("open workspace");
+    let cache = Arc::new(Mutex::new(AnalysisCache::default()));
+    reanalyze_file_internal(&workspace_path, &file_path, &cache).expect("first reanalysis");
+
+    // Sentinel: a rescore would overwrite this.
+    let conn = get_db_connection(&workspace_path).expect("db connection");
+    conn.execute("UPDATE file_scores SET composite_score = 99.0 WHERE path = ?1", [&file_path])
+        .expect("plant sentinel score");
+
+    let touch = |offset: u64| {
+        fs::File::options()
+            .write(true)
+            .open(&file_path)
+            .expect("open source file")
+            .set_modified(std::time::SystemTime::now() + Duration::from_secs(offset))
+            .expect("touch source file");
+    };
+    touch(100);
+    let touched = reanalyze_file_internal(&workspace_path, &file_path, &cache).expect("touched reanalysis");
+    assert_eq!(touched.composite_score, 99.0);
+
+    fs::write(&file_path, "fn main() {\n    println!(\"changed\");\n}\n").expect("rewrite source file");
+    touch(200);
+    let changed = reanalyze_file_internal(&workspace_path, &file_path, &cache).expect("changed reanalysis");
+    assert_ne!(changed.composite_score, 99.0);
+}
+
+#[tokio::test]
+async fn full_analysis_records_content_hashes() {
+    let (_tmp, workspace_path, file_path) = create_workspace_with_git_repo();
+    open_workspace(workspace_path.clone())
+        .await
+        .expect("open workspace");
+    let cache = Arc::new(Mutex::new(AnalysisCache::default()));
+    run_full_analysis_internal(&workspace_path, &cache, |_| {}).expect("run full analysis");
+
+    let conn = get_db_connection(&workspace_path).expect("db connection");
+    conn.execute("UPDATE file_scores SET composite_score = 99.0 WHERE path = ?1", [&file_path])
+        .expect("plant sentinel score");
+    fs::File::options()
+        .write(true)
+        .open(&file_path)
+        .expect("open source file")
+        .set_modified(std::time::SystemTime::now() + Duration::from_secs(100))
+        .expect("touch source file");
+
+    let touched = reanalyze_file_internal(&workspace_path, &file_path, &cache).expect("touched reanalysis");
+    assert_eq!(touched.composite_score, 99.0);
+}
+
+#[tokio::test]
+async fn full_analysis_persists_co_change_pairs() {
+    let (tmp, workspace_path, _file_path) = create_workspace_with_git_repo();
+    let repo = Repository::open(&workspace_path).expect("open repo");
+    let signature = Signature::now("Test User", "test@example.com").expect("signature");
+    for revision in 1..=2 {
+        fs::write(tmp.path().join("src/api.ts"), format!("export const get = () => {revision};\n"))
+            .expect("write api");
+        fs::write(
+            tmp.path().join("src/client.ts"),
+            format!("import {{ get }} from './api';\nexport const run = () => get() + {revision};\n"),
+        )
+        .expect("write client");
+        let mut index = repo.index().expect("open git index");
+        index.add_path(Path::new("src/api.ts")).expect("add api");
+        index.add_path(Path::new("src/client.ts")).expect("add client");
+        index.write().expect("write git index");
+        let tree = repo.find_tree(index.write_tree().expect("write tree")).expect("find tree");
+        let parent = repo.head().expect("head").peel_to_commit().expect("head commit");
+        repo.commit(Some("HEAD"), &signature, &signature, "edit pair", &tree, &[&parent])
+            .expect("commit pair");
+    }
+    open_workspace(workspace_path.clone())
+        .await
+        .expect("open workspace");
+    let cache = Arc::new(Mutex::new(AnalysisCache::default()));
+    run_full_analysis_internal(&workspace_path, &cache, |_| {}).expect("run full analysis");
+
+    let conn = get_db_connection(&workspace_path).expect("db connection");
+    let (count, linked): (i64, i64) = conn
+        .query_row(
+            "SELECT co_change_count, has_import_link FROM coupling_pairs WHERE file_a = 'src/api.ts' AND file_b = 'src/client.ts'",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .expect("persisted pair");
+    assert_eq!((count, linked), (2, 1));
+    assert_eq!(reconcile_coupling_internal(&workspace_path, &cache).expect("reconcile"), 0);
+
+    // The live command agrees with the persisted row
+    let live = get_change_couplings_internal(&workspace_path, None, &cache).expect("change couplings");
+    let pair = live
+        .iter()
+        .find(|pair| pair.file_a == "src/api.ts" && pair.file_b == "src/client.ts")
+        .expect("live pair");
+    assert!(pair.has_import_link);
+}
+
+#[tokio::test]
+async fn reconcile_coupling_flags_pair_once_an_import_is_added() {
+    let (tmp, workspace_path, _file_path) = create_workspace_with_git_repo();
+    let api = tmp.path().join("src/api.ts");
+    let client = tmp.path().join("src/client.ts");
+    fs::write(&api, "export const get = () => 1;\n").expect("write api");
+    fs::write(&client, "export const run = () => 2;\n").expect("write client");
+    open_workspace(workspace_path.clone())
+        .await
+        .expect("open workspace");
+    let cache = Arc::new(Mutex::new(AnalysisCache::default()));
+    run_full_analysis_internal(&workspace_path, &cache, |_| {}).expect("run full analysis");
+
+    let conn = get_db_connection(&workspace_path).expect("db connection");
+    conn.execute_batch(
+        "INSERT OR REPLACE INTO coupling_pairs (file_a, file_b, co_change_count, has_import_link)
+         VALUES ('src/api.ts', 'src/client.ts', 4, 0);",
+    )
+    .expect("insert pair");
+    assert_eq!(reconcile_coupling_internal(&workspace_path, &cache).expect("reconcile"), 0);
+
+    fs::write(&client, "import { get } from './api';\nexport const run = () => get();\n")
+        .expect("add import");
+    assert_eq!(reconcile_coupling_internal(&workspace_path, &cache).expect("reconcile"), 1);
+    let linked: i64 = conn
+        .query_row(
+            "SELECT has_import_link FROM coupling_pairs WHERE file_a = 'src/api.ts' AND file_b = 'src/client.ts'",
+            [],
+            |row| row.get(0),
+        )
+        .expect("read pair");
+    assert_eq!(linked, 1);
+}
+
+#[tokio::test]
+async fn regression_alert_fires_only_on_a_large_score_jump() {
+    let (_tmp, workspace_path, _file_path) = create_workspace_with_git_repo();
+    open_workspace(workspace_path.clone())
+        .await
+        .expect("open workspace");
+    let snapshot = |offset: i64, score: f64, files: serde_json::Value| DebtSnapshot {
+        id: 0,
+        timestamp: 1_700_000_000 + offset,
+        composite_score: score,
+        file_count: 2,
+        high_debt_count: 0,
+        commit_count_week: 0,
+        snapshot_metadata: Some(files.to_string()),
+    };
+    let conn = get_db_connection(&workspace_path).expect("db connection");
+    insert_snapshot(
+        &conn,
+        snapshot(0, 40.0, json!([{ "path": "src/a.rs", "score": 40.0 }, { "path": "src/b.rs", "score": 40.0 }])),
+    )
+    .expect("insert baseline snapshot");
+
+    // Without a cached analysis the supplied file list is kept.
+    let cache = Arc::new(Mutex::new(AnalysisCache::default()));
+    let mut alerts = Vec::new();
+    take_snapshot_internal(
+        &workspace_path,
+        snapshot(60, 42.0, json!([{ "path": "src/a.rs", "score": 44.0 }, { "path": "src/b.rs", "score": 40.0 }])),
+        &cache,
+        |alert| alerts.push(alert),
+    )
+    .expect("small jump snapshot");
+    assert!(alerts.is_empty());
+
+    take_snapshot_internal(
+        &workspace_path,
+        snapshot(
+            120,
+            60.0,
+            json!([
+                { "path": "src/a.rs", "score": 45.0 },
+                { "path": "src/b.rs", "score": 75.0 },
+                { "path": "src/c.rs", "score": 80.0 }
+            ]),
+        ),
+        &cache,
+        |alert| alerts.push(alert),
+    )
+    .expect("large jump snapshot");
+    assert_eq!(alerts.len(), 1);
+    assert!((alerts[0].delta - 18.0).abs() < 1e-9);
+    // A file new to the top list regresses from 0.
+    assert_eq!(alerts[0].top_files[0].path, "src/c.rs");
+    assert_eq!(alerts[0].top_files[0].previous_score, 0.0);
+    assert_eq!(alerts[0].top_files[1].path, "src/b.rs");
+    assert_eq!(alerts[0].top_files.len(), 3);
+}
+
+#[tokio::test]
+async fn smell_trend_follows_todo_counts_across_snapshots() {
+    let (_tmp, workspace_path, file_path) = create_workspace_with_git_repo();
+    fs::write(&file_path, "fn main() {\n    // TODO: greet properly\n    println!(\"hello\");\n}\n")
+        .expect("write source with todo");
+    open_workspace(workspace_path.clone())
+        .await
+        .expect("open workspace");
+    run_full_analysis_internal(&workspace_path, &Arc::new(Mutex::new(AnalysisCache::default())), |_| {})
+        .expect("run full analysis");
+    // Totals come from the counts recorded while scoring, not from re-reading
+    // files, and survive a reload from the database.
+    fs::write(&file_path, "fn main() {\n    println!(\"hello\");\n}\n").expect("drop the todo");
+    let cache = Arc::new(Mutex::new(AnalysisCache::default()));
+    load_cache_from_db_internal(&workspace_path, &cache).expect("reload cache");
+    assert!(maybe_take_scheduled_snapshot_internal(&workspace_path, &cache, |_| {}).expect("scheduled snapshot"));
+
+    let conn = get_db_connection(&workspace_path).expect("db connection");
+    insert_snapshot(
+        &conn,
+        DebtSnapshot {
+            id: 0,
+            timestamp: chrono::Utc::now().timestamp() + 3600,
+            composite_score: 45.0,
+            file_count: 1,
+            high_debt_count: 0,
+            commit_count_week: 0,
+            snapshot_metadata: Some(json!({ "files": [], "smells": { "todo_fixme": 3 } }).to_string()),
+        },
+    )
+    .expect("insert later snapshot");
+
+    let trend = get_smell_trend(workspace_path.clone(), "todo_fixme".to_string())
+        .await
+        .expect("smell trend");
+    let counts: Vec<usize> = trend.iter().map(|point| point.count).collect();
+    assert_eq!(counts, vec![1, 3]);
+    assert!(get_smell_trend(workspace_path, "bogus".to_string()).await.is_err());
+}
+
+#[tokio::test]
+async fn generated_protobuf_code_is_skipped_by_default() {
+    let (temp_dir, workspace_path, _) = create_workspace_with_git_repo();
+    fs::write(temp_dir.path().join("src/foo.pb.go"), "package foo\n\nfunc Reset() {}\n").expect("write pb.go");
+    let cache = Arc::new(Mutex::new(AnalysisCache::default()));
+    let scored = |result: &debtlens_lib::models::file_score::AnalysisResult| {
+        result.files.iter().any(|f| f.relative_path == "src/foo.pb.go")
+    };
+
+    let result = run_full_analysis_internal(&workspace_path, &cache, |_| {}).expect("analysis");
+    assert!(!scored(&result));
+
+    save_settings(workspace_path.clone(), json!({ "includeGeneratedCode": true }))
+        .await
+        .expect("enable generated code");
+    let with_generated = run_full_analysis_internal(&workspace_path, &cache, |_| {}).expect("analysis");
+    assert!(scored(&with_generated));
+}
+
+#[tokio::test]
+async fn snapshot_near_returns_the_closest_snapshot() {
+    let (_tmp, workspace_path, _file_path) = create_workspace_with_git_repo();
+    open_workspace(workspace_path.clone())
+        .await
+        .expect("open workspace");
+    assert!(get_snapshot_near(workspace_path.clone(), 1_700_000_000)
+        .await
+        .expect("empty lookup")
+        .is_none());
+
+    let conn = get_db_connection(&workspace_path).expect("db connection");
+    for (timestamp, score) in [(1_700_000_000, 30.0), (1_700_086_400, 35.0), (1_700_604_800, 50.0)] {
+        insert_snapshot(
+            &conn,
+            DebtSnapshot {
+                id: 0,
+                timestamp,
+                composite_score: score,
+                file_count: 1,
+                high_debt_count: 0,
+                commit_count_week: 0,
+                snapshot_metadata: None,
+            },
+        )
+        .expect("insert snapshot");
+    }
+
+    let near = get_snapshot_near(workspace_path.clone(), 1_700_200_000)
+        .await
+        .expect("lookup")
+        .expect("snapshot found");
+    assert_eq!(near.composite_score, 35.0);
+    let after_all = get_snapshot_near(workspace_path, 1_800_000_000)
+        .await
+        .expect("lookup")
+        .expect("snapshot found");
+    assert_eq!(after_all.composite_score, 50.0);
+}
+
+#[tokio::test]
+async fn similar_register_titles_are_clustered_as_duplicates() {
+    let (_tmp, workspace_path, _file_path) = create_workspace_with_git_repo();
+    open_workspace(workspace_path.clone())
+        .await
+        .expect("open workspace");
+    let item = |id: &str, title: &str| RegisterItem {
+        title: title.to_string(),
+        ..register_item(id, None)
+    };
+    for (id, title) in [("a", "Refactor parser"), ("b", "refactor the parser"), ("c", "Upgrade tokio")] {
+        register_crud(workspace_path.clone(), "create".to_string(), Some(item(id, title)), None, None)
+            .await
+            .expect("create register item");
+    }
+
+    let clusters = find_duplicate_register_items(workspace_path.clone(), 0.8)
+        .await
+        .expect("find duplicates");
+
+    assert_eq!(clusters.len(), 1);
+    let mut ids: Vec<&str> = clusters[0].items.iter().map(|item| item.id.as_str()).collect();
+    ids.sort();
+    assert_eq!(ids, vec!["a", "b"]);
+    assert!(find_duplicate_register_items(workspace_path, 1.5).await.is_err());
+}
+
+#[tokio::test]
+async fn recently_changed_file_shows_positive_churn_acceleration() {
+    let (tmp, workspace_path, _file_path) = create_workspace_with_git_repo();
+    let repo = Repository::open(&workspace_path).expect("open repo");
+    let parent = repo.head().expect("head").peel_to_commit().expect("head commit");
+    let mut index = repo.index().expect("open git index");
+
+    // Older half of the default 90-day window: 60 days ago.
+    let old_time = git2::Time::new(chrono::Utc::now().timestamp() - 60 * 86400, 0);
+    let old_signature = Signature::new("Test User", "test@example.com", &old_time).expect("signature");
+    fs::write(tmp.path().join("src/old.rs"), "pub fn old() {}\n").expect("write old");
+    index.add_path(Path::new("src/old.rs")).expect("add old");
+    index.write().expect("write git index");
+    let tree = repo.find_tree(index.write_tree().expect("write tree")).expect("find tree");
+    let old_commit = repo
+        .commit(Some("HEAD"), &old_signature, &old_signature, "old change", &tree, &[&parent])
+        .expect("commit old change");
+    let old_commit = repo.find_commit(old_commit).expect("find old commit");
+
+    fs::write(tmp.path().join("src/new.rs"), "pub fn new() {}\n").expect("write new");
+    index.add_path(Path::new("src/new.rs")).expect("add new");
+    index.write().expect("write git index");
+    let tree = repo.find_tree(index.write_tree().expect("write tree")).expect("find tree");
+    let signature = Signature::now("Test User", "test@example.com").expect("signature");
+    repo.commit(Some("HEAD"), &signature, &signature, "recent change", &tree, &[&old_commit])
+        .expect("commit recent change");
+
+    let accelerating = get_churn_acceleration(workspace_path)
+        .await
+        .expect("churn acceleration");
+
+    let new_file = accelerating
+        .iter()
+        .find(|file| file.relative_path == "src/new.rs")
+        .expect("recent file accelerating");
+    assert_eq!(new_file.recent_commits, 1);
+    assert_eq!(new_file.older_commits, 0);
+    assert!(new_file.acceleration > 0);
+    assert!(accelerating.iter().all(|file| file.relative_path != "src/old.rs"));
+}
+
+#[tokio::test]
+async fn failed_git_inputs_are_listed_as_degraded() {
+    let temp_dir = tempfile::tempdir().expect("create temp dir");
+    let workspace_path = temp_dir.path().to_string_lossy().to_string();
+    fs::create_dir_all(temp_dir.path().join("src")).expect("create src dir");
+    fs::write(temp_dir.path().join("src/main.rs"), "fn main() {}\n").expect("write source file");
+    let cache = Arc::new(Mutex::new(AnalysisCache::default()));
+
+    // No repository at all: every git-derived input fails to load.
+    let result = run_full_analysis_internal(&workspace_path, &cache, |_| {}).expect("analysis");
+
+    assert_eq!(result.file_count, 1);
+    assert!(result.degraded.iter().any(|reason| reason.starts_with("churn unavailable:")));
+
+    let (_tmp, git_workspace, _file_path) = create_workspace_with_git_repo();
+    let healthy = run_full_analysis_internal(&git_workspace, &cache, |_| {}).expect("analysis");
+    assert!(healthy.degraded.is_empty());
+}
+
+#[tokio::test]
+async fn hub_file_co_changing_with_many_others_ranks_highest_in_centrality() {
+    let (tmp, workspace_path, _file_path) = create_workspace_with_git_repo();
+    let repo = Repository::open(&workspace_path).expect("open repo");
+    let signature = Signature::now("Test User", "test@example.com").expect("signature");
+    let mut index = repo.index().expect("open git index");
+    let groups: [&[&str]; 4] = [
+        &["src/hub.rs", "src/a.rs", "src/b.rs"],
+        &["src/hub.rs", "src/a.rs", "src/b.rs"],
+        &["src/hub.rs", "src/c.rs"],
+        &["src/hub.rs", "src/c.rs"],
+    ];
+    for (round, files) in groups.iter().enumerate() {
+        for file in files.iter() {
+            fs::write(tmp.path().join(file), format!("pub fn v{round}() {{}}\n")).expect("write file");
+            index.add_path(Path::new(file)).expect("add file");
+        }
+        index.write().expect("write git index");
+        let tree = repo.find_tree(index.write_tree().expect("write tree")).expect("find tree");
+        let parent = repo.head().expect("head").peel_to_commit().expect("head commit");
+        repo.commit(Some("HEAD"), &signature, &signature, "co-change", &tree, &[&parent])
+            .expect("commit");
+    }
+    let cache = Arc::new(Mutex::new(AnalysisCache::default()));
+    run_full_analysis_internal(&workspace_path, &cache, |_| {}).expect("run full analysis");
+
+    let ranked = get_coupling_centrality_internal(&workspace_path, &cache).expect("centrality");
+
+    assert_eq!(ranked[0].relative_path, "src/hub.rs");
+    assert_eq!(ranked[0].degree, 3);
+    assert_eq!(ranked[0].total_weight, 6);
+    let degree_of = |path: &str| ranked.iter().find(|f| f.relative_path == path).map(|f| f.degree);
+    assert_eq!(degree_of("src/a.rs"), Some(2));
+    assert_eq!(degree_of("src/c.rs"), Some(1));
+}
+
+#[tokio::test]
+async fn include_languages_limits_scoring_to_listed_languages() {
+    let (temp_dir, workspace_path, _) = create_workspace_with_git_repo();
+    fs::write(temp_dir.path().join("src/app.ts"), "export const app = 1;\n").expect("write ts");
+    fs::write(temp_dir.path().join("src/tool.py"), "def tool():\n    pass\n").expect("write py");
+    save_settings(workspace_path.clone(), json!({ "includeLanguages": ["rust"] }))
+        .await
+        .expect("save languages");
+    let cache = Arc::new(Mutex::new(AnalysisCache::default()));
+
+    let result = run_full_analysis_internal(&workspace_path, &cache, |_| {}).expect("analysis");
+
+    let paths: Vec<&str> = result.files.iter().map(|f| f.relative_path.as_str()).collect();
+    assert_eq!(paths, vec!["src/main.rs"]);
+    assert!(result.files.iter().all(|f| f.language == "rust"));
+}
+
+#[tokio::test]
+async fn blast_radius_includes_importers_and_co_change_peers() {
+    let (tmp, workspace_path, _file_path) = create_workspace_with_git_repo();
+    let repo = Repository::open(&workspace_path).expect("open repo");
+    let signature = Signature::now("Test User", "test@example.com").expect("signature");
+    let mut index = repo.index().expect("open git index");
+    for round in 0..2 {
+        for file in ["src/target.ts", "src/peer.ts"] {
+            fs::write(tmp.path().join(file), format!("export const v{round} = {round};\n")).expect("write file");
+            index.add_path(Path::new(file)).expect("add file");
+        }
+        index.write().expect("write git index");
+        let tree = repo.find_tree(index.write_tree().expect("write tree")).expect("find tree");
+        let parent = repo.head().expect("head").peel_to_commit().expect("head commit");
+        repo.commit(Some("HEAD"), &signature, &signature, "co-change", &tree, &[&parent])
+            .expect("commit");
+    }
+    fs::write(
+        tmp.path().join("src/importer.ts"),
+        "import { v1 } from './target';\nexport const use = v1;\n",
+    )
+    .expect("write importer");
+    let cache = Arc::new(Mutex::new(AnalysisCache::default()));
+    run_full_analysis_internal(&workspace_path, &cache, |_| {}).expect("run full analysis");
+
+    let radius = get_blast_radius_internal(&workspace_path, "src/target.ts", &cache).expect("blast radius");
+
+    let entry = |path: &str| radius.files.iter().find(|f| f.relative_path == path).cloned();
+    let importer = entry("src/importer.ts").expect("importer in blast radius");
+    assert!(importer.is_importer);
+    let peer = entry("src/peer.ts").expect("co-change peer in blast radius");
+    assert_eq!(peer.co_change_count, 2);
+    assert!(!peer.is_importer);
+    assert!(entry("src/main.rs").is_none());
+    assert!(radius.risk_score > 1.0);
+}
+
+#[tokio::test]
+async fn weights_override_applies_to_one_run_without_persisting() {
+    let (_tmp, workspace_path, _file_path) = create_workspace_with_git_repo();
+    let cache = Arc::new(Mutex::new(AnalysisCache::default()));
+    let override_weights = json!({
+        "churn_rate": 0.0,
+        "code_smell_density": 0.0,
+        "coupling_index": 0.0,
+        "change_coupling": 0.0,
+        "test_coverage_gap": 0.0,
+        "knowledge_concentration": 0.0,
+        "cyclomatic_complexity": 2.0,
+        "decision_staleness": 0.0,
+    });
+
+    let result = run_full_analysis_with_weights_internal(&workspace_path, &cache, &override_weights, |_| {})
+        .expect("run analysis with override");
+
+    let file = result
+        .files
+        .iter()
+        .find(|f| f.relative_path == "src/main.rs")
+        .expect("main.rs scored");
+    assert!((file.components.cyclomatic_complexity.weight - 1.0).abs() < 1e-9);
+    assert_eq!(file.components.churn_rate.weight, 0.0);
+
+    let saved = get_settings(workspace_path.clone()).await.expect("load settings");
+    assert!((saved["weights"]["churn_rate"].as_f64().unwrap() - 0.22).abs() < 1e-9);
+    let effective = load_effective_analysis_settings(&workspace_path).expect("effective settings");
+    assert!((effective.weights["cyclomatic_complexity"] - 0.05).abs() < 1e-9);
+
+    // One-off scores stay out of the history and nothing is derived from them.
+    let conn = get_db_connection(&workspace_path).expect("db connection");
+    let persisted: i64 = conn
+        .query_row("SELECT COUNT(*) FROM file_scores", [], |row| row.get(0))
+        .expect("count scores");
+    assert_eq!(persisted, 0);
+    let rejected = analyze_dirty_files_internal(&workspace_path, &cache).expect_err("override cache");
+    assert!(rejected.starts_with("WEIGHTS_OVERRIDDEN"));
+
+    run_full_analysis_internal(&workspace_path, &cache, |_| {}).expect("run full analysis");
+    assert!(analyze_dirty_files_internal(&workspace_path, &cache).is_ok());
+}
+
+#[tokio::test]
+async fn stale_adrs_reports_only_overdue_reviews() {
+    let (tmp, workspace_path, _file_path) = create_workspace_with_git_repo();
+    fs::write(tmp.path().join("src/parser.rs"), "pub fn parse() {}\n").expect("write parser");
+    let adrs_dir = tmp.path().join(".debtengine/adrs");
+    fs::create_dir_all(&adrs_dir).expect("create adrs dir");
+    let today = chrono::Utc::now().date_naive();
+    let reviewed = |days: i64| (today - chrono::Duration::days(days)).format("%Y-%m-%d").to_string();
+    fs::write(
+        adrs_dir.join("main.adr.md"),
+        format!("# Entry point\nlast_reviewed_at: {}\n", reviewed(5)),
+    )
+    .expect("write fresh adr");
+    fs::write(
+        adrs_dir.join("parser.adr.md"),
+        format!("# Parser\nlast_reviewed_at: {}\n", reviewed(200)),
+    )
+    .expect("write stale adr");
+
+    let stale = get_stale_adrs_internal(&workspace_path).expect("stale adrs");
+
+    assert_eq!(stale.len(), 1);
+    assert_eq!(stale[0].path, ".debtengine/adrs/parser.adr.md");
+    assert_eq!(stale[0].days_since_review, 200);
+    assert_eq!(stale[0].days_overdue, 20);
+    assert_eq!(stale[0].documented_files, vec!["src/parser.rs".to_string()]);
+}
+
+#[tokio::test]
+async fn save_settings_rejects_custom_formula_with_unknown_identifier() {
+    let (_tmp, workspace_path, _file_path) = create_workspace_with_git_repo();
+
+    let err = save_settings(
+        workspace_path.clone(),
+        json!({ "compositeFormula": "custom", "customFormula": "churn_rate * velocity" }),
+    )
+    .await
+    .unwrap_err();
+    assert!(err.starts_with("INVALID_FORMULA"), "{err}");
+    let saved = get_settings(workspace_path.clone()).await.expect("load settings");
+    assert_eq!(saved["compositeFormula"], json!("weighted_sum"));
+
+    let saved = save_settings(
+        workspace_path,
+        json!({ "compositeFormula": "custom", "customFormula": "churn_rate + coverage_gap" }),
+    )
+    .await
+    .expect("valid formula saves");
+    assert_eq!(saved["customFormula"], json!("churn_rate + coverage_gap"));
+}
+
+#[tokio::test]
+async fn renamed_file_is_reported_as_relocated() {
+    let (tmp, workspace_path, file_path) = create_workspace_with_git_repo();
+    open_workspace(workspace_path.clone())
+        .await
+        .expect("open workspace");
+    register_crud(
+        workspace_path.clone(),
+        "create".to_string(),
+        Some(register_item("reg-main", Some("src/main.rs"))),
+        None,
+        None,
+    )
+    .await
+    .expect("create register item");
+
+    fs::create_dir_all(tmp.path().join("app")).expect("create app dir");
+    fs::rename(&file_path, tmp.path().join("app/main.rs")).expect("move file");
+    let repo = Repository::open(&workspace_path).expect("open repo");
+    let mut index = repo.index().expect("open git index");
+    index.remove_path(Path::new("src/main.rs")).expect("remove old path");
+    index.add_path(Path::new("app/main.rs")).expect("add new path");
+    index.write().expect("write git index");
+    let tree = repo.find_tree(index.write_tree().expect("write tree")).expect("find tree");
+    let signature = Signature::now("Test User", "test@example.com").expect("signature");
+    let parent = repo.head().expect("head").peel_to_commit().expect("head commit");
+    repo.commit(Some("HEAD"), &signature, &signature, "move main", &tree, &[&parent])
+        .expect("commit");
+
+    let relocated = get_relocated_files_internal(&workspace_path).expect("relocated files");
+
+    assert_eq!(relocated.len(), 1);
+    assert_eq!(relocated[0].relocation.old_path, "src/main.rs");
+    assert_eq!(relocated[0].relocation.new_path, "app/main.rs");
+    assert_eq!(relocated[0].stale_register_items, vec!["reg-main".to_string()]);
+    assert!(!relocated[0].stale_adr);
+
+    let adrs_dir = tmp.path().join(".debtengine/adrs");
+    fs::create_dir_all(&adrs_dir).expect("create adrs dir");
+    let adr = adrs_dir.join("entry.md");
+    fs::write(&adr, "See `lib/src/main.rs` and src/main.rs.bak\n").expect("write adr");
+    let relocated = get_relocated_files_internal(&workspace_path).expect("relocated files");
+    assert!(!relocated[0].stale_adr, "longer paths containing the old path are not mentions");
+
+    fs::write(&adr, "The entry point lives in src/main.rs.\n").expect("write adr");
+    let relocated = get_relocated_files_internal(&workspace_path).expect("relocated files");
+    assert!(relocated[0].stale_adr);
+}
+
+#[tokio::test]
+async fn coverage_trend_follows_snapshot_coverage() {
+    let (tmp, workspace_path, _file_path) = create_workspace_with_git_repo();
+    fs::write(tmp.path().join("lcov.info"), "SF:src/main.rs\nLF:4\nLH:2\nend_of_record\n").expect("write lcov");
+    open_workspace(workspace_path.clone())
+        .await
+        .expect("open workspace");
+    let cache = Arc::new(Mutex::new(AnalysisCache::default()));
+    run_full_analysis_internal(&workspace_path, &cache, |_| {}).expect("run full analysis");
+    // The snapshot reuses the analysis' merged coverage rather than re-reading reports.
+    fs::remove_file(tmp.path().join("lcov.info")).expect("remove lcov");
+    assert!(maybe_take_scheduled_snapshot_internal(&workspace_path, &cache, |_| {}).expect("scheduled snapshot"));
+
+    let conn = get_db_connection(&workspace_path).expect("db connection");
+    let now = chrono::Utc::now().timestamp();
+    for (offset, metadata) in [(3600, None), (7200, embed_coverage(None, 85.0))] {
+        insert_snapshot(
+            &conn,
+            DebtSnapshot {
+                id: 0,
+                timestamp: now + offset,
+                composite_score: 40.0,
+                file_count: 1,
+                high_debt_count: 0,
+                commit_count_week: 1,
+                snapshot_metadata: metadata,
+            },
+        )
+        .expect("insert snapshot");
+    }
+
+    let trend = get_coverage_trend(workspace_path).await.expect("coverage trend");
+
+    let percents: Vec<f64> = trend.iter().map(|point| point.coverage_percent).collect();
+    assert_eq!(percents, vec![50.0, 85.0]);
+}
+
+#[tokio::test]
+async fn include_patterns_limit_scoring_to_matching_files() {
+    let (tmp, workspace_path, _file_path) = create_workspace_with_git_repo();
+    fs::create_dir_all(tmp.path().join("src/api")).expect("create api dir");
+    fs::write(tmp.path().join("src/api/routes.rs"), "pub fn routes() {}\n").expect("write routes");
+    fs::write(tmp.path().join("src/api/handlers.rs"), "pub fn handle() {}\n").expect("write handlers");
+    fs::write(tmp.path().join("src/db.rs"), "pub fn connect() {}\n").expect("write db");
+    save_settings(workspace_path.clone(), json!({ "includePatterns": ["src/api/**"] }))
+        .await
+        .expect("save settings");
+    let cache = Arc::new(Mutex::new(AnalysisCache::default()));
+
+    let result = run_full_analysis_internal(&workspace_path, &cache, |_| {}).expect("run full analysis");
+
+    let mut scored: Vec<&str> = result.files.iter().map(|f| f.relative_path.as_str()).collect();
+    scored.sort();
+    assert_eq!(scored, vec!["src/api/handlers.rs", "src/api/routes.rs"]);
+
+    save_settings(workspace_path.clone(), json!({ "excludePatterns": ["src/api/handlers.rs"] }))
+        .await
+        .expect("save settings");
+    let result = run_full_analysis_internal(&workspace_path, &cache, |_| {}).expect("run full analysis");
+    let scored: Vec<&str> = result.files.iter().map(|f| f.relative_path.as_str()).collect();
+    assert_eq!(scored, vec!["src/api/routes.rs"]);
+
+    let err = save_settings(workspace_path.clone(), json!({ "includePatterns": ["src/[api/**"] }))
+        .await
+        .expect_err("invalid glob rejected");
+    assert!(err.starts_with("INVALID_GLOB: includePatterns"), "{err}");
+}
+
+#[tokio::test]
+async fn function_complexity_distribution_reports_most_complex_function() {
+    let (tmp, workspace_path, _file_path) = create_workspace_with_git_repo();
+    let branches: String = (0..24).map(|i| format!("    if x == {i} {{\n        return {i};\n    }}\n")).collect();
+    fs::write(
+        tmp.path().join("src/rules.rs"),
+        format!("fn simple() -> i32 {{\n    1\n}}\n\nfn tangled(x: i32) -> i32 {{\n{branches}    0\n}}\n"),
+    )
+    .expect("write rules");
+    let cache = Arc::new(Mutex::new(AnalysisCache::default()));
+    let result = run_full_analysis_internal(&workspace_path, &cache, |_| {}).expect("run full analysis");
+    // Function lists stay out of the analysis payload sent to the frontend
+    let payload = serde_json::to_value(&result).expect("serialize result");
+    assert!(payload["files"][0].get("functions").is_none());
+
+    // Function lists survive a reload from the persisted scores
+    let reloaded = Arc::new(Mutex::new(AnalysisCache::default()));
+    load_cache_from_db_internal(&workspace_path, &reloaded).expect("reload cache");
+    let distribution = get_function_complexity_distribution_internal(&reloaded).expect("distribution");
+
+    let max = distribution.max_function.expect("max function");
+    assert_eq!(max.relative_path, "src/rules.rs");
+    assert_eq!(max.name, "tangled");
+    assert!(max.complexity > 20, "complexity {}", max.complexity);
+    let top = distribution.buckets.last().expect("top bucket");
+    assert_eq!(top.label, "21+");
+    assert_eq!(top.count, 1);
+    assert_eq!(
+        distribution.buckets.iter().map(|bucket| bucket.count).sum::<usize>(),
+        distribution.total_functions
+    );
+}
+
+#[tokio::test]
+async fn new_file_flags_persist_and_apply_to_incremental_rescoring() {
+    let (tmp, workspace_path, main_file) = create_workspace_with_git_repo();
+    save_settings(workspace_path.clone(), json!({ "newFileGraceDays": 14 }))
+        .await
+        .expect("save settings");
+    let cache = Arc::new(Mutex::new(AnalysisCache::default()));
+    run_full_analysis_internal(&workspace_path, &cache, |_| {}).expect("baseline analysis");
+
+    fs::write(tmp.path().join("src/fresh.rs"), "pub fn fresh() {}\n").expect("write fresh");
+    run_full_analysis_internal(&workspace_path, &cache, |_| {}).expect("second analysis");
+
+    // first_seen_at survives in the database, so the flags do too
+    let reloaded = Arc::new(Mutex::new(AnalysisCache::default()));
+    let result = load_cache_from_db_internal(&workspace_path, &reloaded).expect("reload cache");
+    let status = |path: &str| {
+        result
+            .files
+            .iter()
+            .find(|file| file.relative_path == path)
+            .map(|file| file.supervision_status.clone())
+            .expect("scored file")
+    };
+    assert_eq!(status("src/main.rs"), "none");
+    assert_eq!(status("src/fresh.rs"), "new");
+
+    let later = tmp.path().join("src/later.rs");
+    fs::write(&later, "pub fn later() {}\n").expect("write later");
+    let rescored = reanalyze_file_internal(&workspace_path, &later.to_string_lossy(), &reloaded)
+        .expect("reanalyze new file");
+    assert_eq!(rescored.supervision_status, "new");
+    let rescored = reanalyze_file_internal(&workspace_path, &main_file, &reloaded).expect("reanalyze main");
+    assert_eq!(rescored.supervision_status, "none");
+
+    fs::write(tmp.path().join("src/dirty.rs"), "pub fn dirty() {}\n").expect("write dirty");
+    fs::write(&main_file, "fn main() {\n    println!(\"changed\");\n}\n").expect("modify main");
+    let scored = analyze_dirty_files_internal(&workspace_path, &reloaded).expect("dirty analysis");
+    for file in &scored {
+        let expected = if file.relative_path == "src/main.rs" { "none" } else { "new" };
+        assert_eq!(file.supervision_status, expected, "{}", file.relative_path);
+    }
+    assert!(scored.iter().any(|file| file.relative_path == "src/dirty.rs"));
+}
+
+#[tokio::test]
+async fn hidden_source_files_are_not_scored() {
+    let (tmp, workspace_path, _file_path) = create_workspace_with_git_repo();
+    fs::write(tmp.path().join(".eslintrc.js"), "module.exports = { root: true };\n").expect("write eslintrc");
+    fs::write(tmp.path().join("src/.prettierrc.cjs"), "module.exports = {};\n").expect("write prettierrc");
+    let cache = Arc::new(Mutex::new(AnalysisCache::default()));
+
+    let result = run_full_analysis_internal(&workspace_path, &cache, |_| {}).expect("run full analysis");
+
+    let scored: Vec<&str> = result.files.iter().map(|f| f.relative_path.as_str()).collect();
+    assert_eq!(scored, vec!["src/main.rs"]);
+}
+
+#[tokio::test]
+async fn ast_analysis_classifies_imports_against_workspace_relative_roots() {
+    let (tmp, workspace_path, _file_path) = create_workspace_with_git_repo();
+    fs::write(tmp.path().join("src/app.py"), "import main\nimport tmp\n").expect("write app");
+
+    let data = run_ast_analysis(workspace_path.clone(), vec!["src/app.py".to_string()])
+        .await
+        .expect("ast analysis");
+
+    // `tmp` is a directory above the workspace, not workspace code
+    let imports = &data.imports["src/app.py"];
+    assert_eq!(imports.internal, vec!["main".to_string()]);
+    assert_eq!(imports.external, vec!["tmp".to_string()]);
+}
@@ -71,19 +71,13 @@ const App: React.FC = () => {
         const result = await runFullAnalysis(workspace.path);
         if (!cancelled) {
           setAnalysisResult(result);
-          // Auto-snapshot with top-10 file metadata for per-file history tracking
-          const top10 = [...result.files]
-            .sort((a, b) => b.composite_score - a.composite_score)
-            .slice(0, 10)
-            .map((f) => ({ path: f.relative_path, score: f.composite_score }));
-          const metadataJson = JSON.stringify(top10);
+          // Auto-snapshot; the backend embeds per-file scores for history tracking
           takeSnapshot(
             workspace.path,
             result.workspace_score,
             result.file_count,
             result.high_debt_count,
             0,
-            metadataJson,
           ).catch(() => { });
         }
       } finally {
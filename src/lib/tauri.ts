@@ -16,6 +16,7 @@ export interface ComponentScore {
   weight: number;
   contribution: number;
   details: string[];
+  confidence?: string | null;
 }
 
 export interface ScoreComponents {
@@ -38,7 +39,7 @@ export interface FileScore {
   loc: number;
   language: string;
   last_modified: number;
-  supervision_status: "none" | "acceptable" | "regressed";
+  supervision_status: "none" | "acceptable" | "regressed" | "new";
 }
 
 export interface HeatmapNode {
@@ -61,6 +62,7 @@ export interface ComponentDetail {
   weight: number;
   contribution: number;
   details: string[];
+  confidence?: string | null;
 }
 
 export interface AnalysisResult {
@@ -69,6 +71,8 @@ export interface AnalysisResult {
   high_debt_count: number;
   files: FileScore[];
   duration_ms: number;
+  partial?: boolean;
+  degraded?: string[];
 }
 
 export interface AnalysisProgress {
@@ -113,6 +117,7 @@ export interface DebtBudget {
   max_score: number;
   created_at: number;
   notify_on_breach: boolean;
+  warning_score?: number | null;
 }
 
 export interface FileChangedEvent {
@@ -120,6 +125,20 @@ export interface FileChangedEvent {
   event_type: string;
 }
 
+export interface RegressedFile {
+  path: string;
+  previous_score: number;
+  current_score: number;
+  delta: number;
+}
+
+export interface RegressionAlert {
+  previous_score: number;
+  current_score: number;
+  delta: number;
+  top_files: RegressedFile[];
+}
+
 export interface GitAnalysisData {
   churn: Record<string, number>;
   blame_summary: Record<string, [string, number][]>;
@@ -156,8 +175,10 @@ export async function runGitAnalysis(workspacePath: string, historyDays: number)
 
 export async function runFullAnalysis(
   workspacePath: string,
+  quickScan?: boolean,
+  weightsOverride?: Record<string, number>,
 ): Promise<AnalysisResult> {
-  return invoke<AnalysisResult>("run_full_analysis", { workspacePath });
+  return invoke<AnalysisResult>("run_full_analysis", { workspacePath, quickScan, weightsOverride });
 }
 
 export async function getHeatmapData(): Promise<HeatmapNode> {
@@ -205,8 +226,9 @@ export async function registerCrud(
   operation: string,
   item?: RegisterItem,
   id?: string,
+  filePath?: string,
 ): Promise<unknown> {
-  return invoke("register_crud", { workspacePath, operation, item, id });
+  return invoke("register_crud", { workspacePath, operation, item, id, filePath });
 }
 
 export async function budgetCrud(
@@ -273,3 +295,11 @@ export function onFileChanged(
     callback(event.payload),
   );
 }
+
+export function onDebtRegressionAlert(
+  callback: (alert: RegressionAlert) => void,
+): Promise<UnlistenFn> {
+  return listen<RegressionAlert>("debt_regression_alert", (event) =>
+    callback(event.payload),
+  );
+}